@@ -5,6 +5,8 @@ use roc_build::{
 };
 use roc_builtins::bitcode;
 use roc_collections::VecMap;
+use roc_error_macros::user_error;
+use roc_linker::LinkerError;
 use roc_load::{
     EntryPoint, ExecutionMode, Expectations, LoadConfig, LoadMonomorphizedError, LoadedModule,
     LoadingProblem, Threading,
@@ -62,6 +64,7 @@ pub fn build_file<'a>(
     app_module_path: PathBuf,
     opt_level: OptLevel,
     emit_debug_info: bool,
+    emit_backtrace: bool,
     emit_timings: bool,
     link_type: LinkType,
     linking_strategy: LinkingStrategy,
@@ -139,6 +142,13 @@ pub fn build_file<'a>(
         binary_path.set_extension(extension);
     }
 
+    if link_type == LinkType::Dylib
+        && target.operating_system == target_lexicon::OperatingSystem::Linux
+    {
+        // Both linkers name shared libraries like this, so this is the file they produce.
+        binary_path.set_extension("so");
+    }
+
     let host_input_path = if let EntryPoint::Executable { platform_path, .. } = &loaded.entry_point
     {
         cwd.join(platform_path)
@@ -153,14 +163,14 @@ pub fn build_file<'a>(
     // Also, we should no longer need to do this once we have platforms on
     // a package repository, as we can then get precompiled hosts from there.
 
-    let exposed_values = loaded
+    let exposed_values: Vec<String> = loaded
         .exposed_to_host
         .values
         .keys()
         .map(|x| x.as_str(&loaded.interns).to_string())
         .collect();
 
-    let exposed_closure_types = loaded
+    let exposed_closure_types: Vec<String> = loaded
         .exposed_to_host
         .closure_types
         .iter()
@@ -175,20 +185,23 @@ pub fn build_file<'a>(
 
     let preprocessed_host_path = if emit_wasm {
         host_input_path.with_file_name("preprocessedhost.o")
+    } else if link_type == LinkType::Dylib {
+        host_input_path.with_file_name("preprocessedhost.so")
     } else {
         host_input_path.with_file_name("preprocessedhost")
     };
 
     let rebuild_thread = spawn_rebuild_thread(
         opt_level,
+        link_type,
         linking_strategy,
         precompiled,
         host_input_path.clone(),
         preprocessed_host_path.clone(),
         binary_path.clone(),
         target,
-        exposed_values,
-        exposed_closure_types,
+        exposed_values.clone(),
+        exposed_closure_types.clone(),
     );
 
     // TODO try to move as much of this linking as possible to the precompiled
@@ -256,11 +269,11 @@ pub fn build_file<'a>(
 
     enum HostRebuildTiming {
         BeforeApp(u128),
-        ConcurrentWithApp(JoinHandle<u128>),
+        ConcurrentWithApp(JoinHandle<Result<u128, LinkerError>>),
     }
 
     let rebuild_timing = if linking_strategy == LinkingStrategy::Additive {
-        let rebuild_duration = rebuild_thread.join().unwrap();
+        let rebuild_duration = join_rebuild_thread(rebuild_thread);
         if emit_timings && !precompiled {
             println!(
                 "Finished rebuilding and preprocessing the host in {} ms\n",
@@ -280,6 +293,7 @@ pub fn build_file<'a>(
         app_o_file,
         opt_level,
         emit_debug_info,
+        emit_backtrace,
         &preprocessed_host_path,
         wasm_dev_stack_bytes,
     );
@@ -321,7 +335,7 @@ pub fn build_file<'a>(
     }
 
     if let HostRebuildTiming::ConcurrentWithApp(thread) = rebuild_timing {
-        let rebuild_duration = thread.join().unwrap();
+        let rebuild_duration = join_rebuild_thread(thread);
         if emit_timings && !precompiled {
             println!(
                 "Finished rebuilding and preprocessing the host in {} ms\n",
@@ -334,8 +348,29 @@ pub fn build_file<'a>(
     let link_start = Instant::now();
     let problems = match (linking_strategy, link_type) {
         (LinkingStrategy::Surgical, _) => {
-            roc_linker::link_preprocessed_host(target, &host_input_path, app_o_file, &binary_path);
-            problems
+            match roc_linker::link_preprocessed_host(
+                opt_level,
+                target,
+                &host_input_path,
+                &preprocessed_host_path,
+                exposed_values,
+                exposed_closure_types,
+                app_o_file,
+                &binary_path,
+                link_type,
+            ) {
+                Ok(()) => problems,
+                Err(err) => {
+                    eprintln!("\nSurgical linking failed:\n\n{}\n", err);
+
+                    let mut problems = problems;
+
+                    // Add an error for the surgical linker failing
+                    problems.errors += 1;
+
+                    problems
+                }
+            }
         }
         (LinkingStrategy::Additive, _) | (LinkingStrategy::Legacy, LinkType::None) => {
             // Just copy the object file to the output folder.
@@ -396,6 +431,7 @@ pub fn build_file<'a>(
 #[allow(clippy::too_many_arguments)]
 fn spawn_rebuild_thread(
     opt_level: OptLevel,
+    link_type: LinkType,
     linking_strategy: LinkingStrategy,
     precompiled: bool,
     host_input_path: PathBuf,
@@ -404,7 +440,7 @@ fn spawn_rebuild_thread(
     target: &Triple,
     exported_symbols: Vec<String>,
     exported_closure_types: Vec<String>,
-) -> std::thread::JoinHandle<u128> {
+) -> std::thread::JoinHandle<Result<u128, LinkerError>> {
     let thread_local_target = target.clone();
     std::thread::spawn(move || {
         if !precompiled {
@@ -433,7 +469,8 @@ fn spawn_rebuild_thread(
                         preprocessed_host_path.as_path(),
                         exported_symbols,
                         exported_closure_types,
-                    );
+                        link_type,
+                    )?;
                 }
                 LinkingStrategy::Legacy => {
                     rebuild_host(
@@ -451,10 +488,23 @@ fn spawn_rebuild_thread(
         }
         let rebuild_host_end = rebuild_host_start.elapsed();
 
-        rebuild_host_end.as_millis()
+        Ok(rebuild_host_end.as_millis())
     })
 }
 
+/// Waits for the host to be rebuilt, returning how long it took. A host which couldn't
+/// be preprocessed is reported from here rather than from the rebuild thread, so the
+/// error doesn't exit the process from underneath the app's code gen.
+fn join_rebuild_thread(thread: JoinHandle<Result<u128, LinkerError>>) -> u128 {
+    match thread.join().unwrap() {
+        Ok(rebuild_duration) => rebuild_duration,
+        Err(err) => user_error!(
+            "Preprocessing the host for surgical linking failed:\n\n{}",
+            err
+        ),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn check_file(
     arena: &Bump,
@@ -0,0 +1,56 @@
+use std::io::{self, BufRead, Write};
+
+/// Print the Roc name of each given procedure name, or if none were given, copy stdin to
+/// stdout with the procedure names in it demangled (e.g. to read `perf` output).
+pub fn demangle<'a, I: IntoIterator<Item = &'a str>>(names: Option<I>) -> io::Result<i32> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    match names {
+        Some(names) => {
+            for name in names {
+                let demangled = roc_mono::mangle::demangle(name);
+
+                writeln!(stdout, "{}", demangled.as_deref().unwrap_or(name))?;
+            }
+        }
+        None => {
+            let stdin = io::stdin();
+
+            for line in stdin.lock().lines() {
+                writeln!(stdout, "{}", demangle_line(&line?))?;
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+/// Whether a character can be part of a mangled name
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '#' | '$')
+}
+
+fn demangle_line(line: &str) -> String {
+    let mut buf = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(start) = rest.find(is_name_char) {
+        buf.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        let word = &rest[..end];
+
+        match roc_mono::mangle::demangle(word) {
+            Some(demangled) => buf.push_str(&demangled),
+            None => buf.push_str(word),
+        }
+
+        rest = &rest[end..];
+    }
+
+    buf.push_str(rest);
+
+    buf
+}
@@ -0,0 +1,763 @@
+use std::path::{Path, PathBuf};
+
+use crate::format::{format_src, parse_all};
+use bumpalo::Bump;
+use roc_can::exhaustive::{GUARD_CTOR, NONEXHAUSIVE_CTOR};
+use roc_collections::MutMap;
+use roc_error_macros::user_error;
+use roc_exhaustive::{Context, CtorName, RenderAs};
+use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
+use roc_module::ident::TagName;
+use roc_module::symbol::{Interns, ModuleId};
+use roc_parse::ast::{
+    AssignedField, Defs, Expr, Module, Pattern, StrLiteral, StrSegment, ValueDef, WhenBranch,
+};
+use roc_problem::can::{Problem, RuntimeError};
+use roc_region::all::{LineInfo, Loc, Position, Region};
+use roc_reporting::report::RenderTarget;
+use roc_solve_problem::TypeError;
+use roc_std::RocDec;
+use roc_target::TargetInfo;
+
+/// A machine-applicable fix for a problem: replace the code in `region` of a module
+/// with `replacement`. Insertions have an empty region, removals an empty replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub module_id: ModuleId,
+    pub region: Region,
+    pub replacement: String,
+}
+
+impl Edit {
+    fn insert(module_id: ModuleId, offset: usize, text: String) -> Self {
+        let position = Position::new(offset as u32);
+
+        Edit {
+            module_id,
+            region: Region::new(position, position),
+            replacement: text,
+        }
+    }
+
+    fn remove(module_id: ModuleId, start: usize, end: usize) -> Self {
+        Edit {
+            module_id,
+            region: Region::new(Position::new(start as u32), Position::new(end as u32)),
+            replacement: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedFile {
+    pub path: PathBuf,
+    pub edits: usize,
+}
+
+/// A `when` that doesn't cover every possible value. What the branches it's missing
+/// should do takes the programmer's judgement, so `roc fix` adds them with a body that
+/// doesn't typecheck, for the programmer to fill in.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MissingBranches {
+    pub path: PathBuf,
+    /// The line the `when` is on, starting from 1
+    pub line: u32,
+    pub patterns: Vec<String>,
+    /// The undefined name the added branches return, or `None` if they weren't added
+    pub placeholder: Option<String>,
+}
+
+pub struct Fixes {
+    /// The files that were rewritten, and how many edits were made to each
+    pub fixed: Vec<FixedFile>,
+    /// Files that had fixes, which were not applied because the result didn't parse
+    pub not_fixed: Vec<PathBuf>,
+    /// The `when`s which need more branches
+    pub missing_branches: Vec<MissingBranches>,
+}
+
+/// Load and typecheck a module and everything it imports, then rewrite the files
+/// (next to or below the given one) that have problems with machine-applicable fixes.
+/// Fixed files are re-formatted.
+pub fn fix(
+    arena: &Bump,
+    roc_file_path: PathBuf,
+    threading: Threading,
+) -> Result<Fixes, LoadingProblem> {
+    // only used for generating errors. We don't do code generation, so hardcoding should be fine
+    let target_info = TargetInfo::default_x86_64();
+
+    let root_dir = roc_file_path
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf));
+
+    let load_config = LoadConfig {
+        target_info,
+        render: RenderTarget::ColorTerminal,
+        threading,
+        exec_mode: ExecutionMode::Check,
+    };
+    let loaded =
+        roc_load::load_and_typecheck(arena, roc_file_path, Default::default(), load_config)?;
+
+    let is_fixable = |module_id: &ModuleId| match (loaded.sources.get(module_id), &root_dir) {
+        (Some((path, _)), Some(root_dir)) => {
+            // Never touch the builtins, or packages that live somewhere else (e.g. a platform
+            // downloaded into the cache)
+            !module_id.is_builtin()
+                && path
+                    .canonicalize()
+                    .map(|path| path.starts_with(root_dir))
+                    .unwrap_or(false)
+        }
+        _ => false,
+    };
+
+    let mut edits_by_module: MutMap<ModuleId, Vec<Edit>> = MutMap::default();
+    let mut fixes = Fixes {
+        fixed: Vec::new(),
+        not_fixed: Vec::new(),
+        missing_branches: Vec::new(),
+    };
+
+    for (module_id, problems) in loaded.can_problems.iter() {
+        for problem in problems {
+            for edit in can_problem_edits(&loaded, *module_id, problem) {
+                edits_by_module
+                    .entry(edit.module_id)
+                    .or_default()
+                    .push(edit);
+            }
+        }
+    }
+
+    for (module_id, problems) in loaded.type_problems.iter() {
+        if !is_fixable(module_id) {
+            continue;
+        }
+
+        for problem in problems {
+            if let Some((missing, edit)) = missing_branches(&loaded, *module_id, problem) {
+                fixes.missing_branches.push(missing);
+
+                if let Some(edit) = edit {
+                    edits_by_module
+                        .entry(edit.module_id)
+                        .or_default()
+                        .push(edit);
+                }
+            }
+        }
+    }
+
+    for (module_id, edits) in edits_by_module {
+        if !is_fixable(&module_id) {
+            continue;
+        }
+
+        let (path, src) = &loaded.sources[&module_id];
+        let (fixed_src, applied) = apply_edits(src, edits);
+
+        // Only write out code that still parses; fixes are made to the text of the
+        // module, so one that's off could leave it broken.
+        let fix_arena = Bump::new();
+        if parse_all(&fix_arena, &fixed_src).is_err() {
+            fixes.not_fixed.push(path.clone());
+            continue;
+        }
+
+        // If the formatter has a bug, keep the fixed code as it is rather than not fixing it.
+        let formatted_src = format_src(&fix_arena, &fixed_src, None).unwrap_or(fixed_src);
+
+        std::fs::write(path, formatted_src)
+            .unwrap_or_else(|err| user_error!("Could not write to {}: {}", path.display(), err));
+
+        fixes.fixed.push(FixedFile {
+            path: path.clone(),
+            edits: applied,
+        });
+    }
+
+    fixes.fixed.sort();
+    fixes.not_fixed.sort();
+    fixes.missing_branches.sort();
+
+    for missing in fixes.missing_branches.iter_mut() {
+        if fixes.not_fixed.contains(&missing.path) {
+            missing.placeholder = None;
+        }
+    }
+
+    Ok(fixes)
+}
+
+/// The edits that fix a canonicalization problem, if it can be fixed without
+/// the programmer's judgement
+pub fn can_problem_edits(
+    loaded: &LoadedModule,
+    module_id: ModuleId,
+    problem: &Problem,
+) -> Vec<Edit> {
+    let src = match loaded.sources.get(&module_id) {
+        Some((_, src)) => src,
+        None => return Vec::new(),
+    };
+
+    match problem {
+        Problem::UnusedImport(_, region) => {
+            vec![remove_list_item(module_id, src, *region)]
+        }
+        Problem::UnusedDef(_, region) => remove_unused_def(module_id, src, *region),
+        Problem::UnusedArgument(_, _, argument, region) => {
+            let start = region.start().offset as usize;
+            let end = region.end().offset as usize;
+            let name = argument.as_str(&loaded.interns);
+
+            // `{ x }` destructures the field `x`, so it can't be renamed to `{ _x }`
+            if src.get(start..end) == Some(name) && !is_record_field_shorthand(src, start) {
+                vec![Edit::insert(module_id, start, "_".to_string())]
+            } else {
+                Vec::new()
+            }
+        }
+        Problem::RuntimeError(RuntimeError::ValueNotExposed {
+            module_name, ident, ..
+        }) => loaded
+            .interns
+            .module_ids
+            .get_id(module_name)
+            .and_then(|exposing_id| {
+                let (_, exposing_src) = loaded.sources.get(&exposing_id)?;
+
+                add_exposed_value(exposing_id, exposing_src, ident.as_str())
+            })
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The branches a `when` is missing, if the type problem is that it doesn't cover
+/// every possible value, and the edit that adds them
+pub fn missing_branches(
+    loaded: &LoadedModule,
+    module_id: ModuleId,
+    problem: &TypeError,
+) -> Option<(MissingBranches, Option<Edit>)> {
+    let (path, src) = loaded.sources.get(&module_id)?;
+
+    let (condition, missing) = match problem {
+        TypeError::Exhaustive(roc_exhaustive::Error::Incomplete(
+            region,
+            Context::BadCase,
+            missing,
+        )) => (*region, missing),
+        _ => return None,
+    };
+
+    let patterns: Vec<String> = missing
+        .iter()
+        .map(|pattern| pattern_to_src(&loaded.interns, pattern.clone(), false))
+        .collect();
+    let placeholder = placeholder_name(src);
+    let edit = add_branches(module_id, src, condition, &patterns, &placeholder);
+
+    let missing_branches = MissingBranches {
+        path: path.clone(),
+        line: LineInfo::new(src).convert_pos(condition.start()).line + 1,
+        patterns,
+        placeholder: edit.as_ref().map(|_| placeholder),
+    };
+
+    Some((missing_branches, edit))
+}
+
+/// Add a branch for each of the patterns after the last branch of the `when` whose
+/// condition has the given region. They all return `placeholder`, so the module won't
+/// typecheck until the programmer has filled them in.
+fn add_branches(
+    module_id: ModuleId,
+    src: &str,
+    condition: Region,
+    patterns: &[String],
+    placeholder: &str,
+) -> Option<Edit> {
+    let arena = Bump::new();
+    let ast = parse_all(&arena, src).ok()?;
+    let last_branch = when_branches(&ast.defs, condition)?.last()?;
+
+    let branch_start = last_branch.patterns.first()?.region.start().offset as usize;
+    let line_start = src[..branch_start].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &src[line_start..branch_start];
+
+    // Only line the new branches up with the last one if it starts its own line
+    if !indent.trim().is_empty() {
+        return None;
+    }
+
+    // After the end of the line, so a comment there stays with the last branch
+    let value_end = last_branch.value.region.end().offset as usize;
+    let line_end = src[value_end..]
+        .find('\n')
+        .map_or(src.len(), |i| value_end + i);
+
+    let branches = patterns
+        .iter()
+        .map(|pattern| format!("\n{}{} -> {}", indent, pattern, placeholder))
+        .collect();
+
+    Some(Edit::insert(module_id, line_end, branches))
+}
+
+/// A name that appears nowhere in the source, so it can't be defined there
+fn placeholder_name(src: &str) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => "todo".to_string(),
+            _ => format!("todo{}", n),
+        })
+        .find(|name| !src.contains(name.as_str()))
+        .unwrap()
+}
+
+/// Apply the edits to a module's source, front to back. Edits overlapping an earlier
+/// one are skipped, apart from removals, which are merged; a later `roc fix` will pick
+/// up anything that's left. Returns the fixed source and how many edits were applied.
+fn apply_edits(src: &str, mut edits: Vec<Edit>) -> (String, usize) {
+    edits.sort_by_key(|edit| edit.region);
+    edits.dedup();
+
+    let mut fixed_src = String::with_capacity(src.len());
+    let mut copied_up_to = 0;
+    let mut applied = 0;
+
+    for edit in edits {
+        let start = edit.region.start().offset as usize;
+        let end = edit.region.end().offset as usize;
+
+        if start < copied_up_to {
+            if edit.replacement.is_empty() {
+                copied_up_to = copied_up_to.max(end);
+                applied += 1;
+            }
+
+            continue;
+        }
+
+        fixed_src.push_str(&src[copied_up_to..start]);
+        fixed_src.push_str(&edit.replacement);
+        copied_up_to = end;
+        applied += 1;
+    }
+
+    fixed_src.push_str(&src[copied_up_to..]);
+
+    (fixed_src, applied)
+}
+
+/// Remove an item from a comma-separated list, along with the comma after it
+/// (or before it, if it's the last item)
+fn remove_list_item(module_id: ModuleId, src: &str, region: Region) -> Edit {
+    let start = region.start().offset as usize;
+    let end = region.end().offset as usize;
+
+    let after = end + src[end..].len() - src[end..].trim_start().len();
+    let before = src[..start].trim_end().len();
+
+    if src[after..].starts_with(',') {
+        Edit::remove(module_id, start, after + 1)
+    } else if src[..before].ends_with(',') {
+        Edit::remove(module_id, before - 1, end)
+    } else {
+        Edit::remove(module_id, start, end)
+    }
+}
+
+/// Whether the identifier starting at `start` is a record field in a destructure like `{ x }`
+fn is_record_field_shorthand(src: &str, start: usize) -> bool {
+    // `{ x: y }` binds `y`, which can be renamed
+    if src[..start].trim_end().ends_with(':') {
+        return false;
+    }
+
+    // Find the innermost bracket (or closure) the identifier is in
+    let mut depth = 0;
+
+    for byte in src[..start].bytes().rev() {
+        match byte {
+            b')' | b']' | b'}' => depth += 1,
+            b'(' | b'[' if depth == 0 => return false,
+            b'{' if depth == 0 => return true,
+            b'(' | b'[' | b'{' => depth -= 1,
+            b'\\' if depth == 0 => return false,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Add a value to the `exposes` list of an interface module, if the module defines it
+fn add_exposed_value(module_id: ModuleId, src: &str, ident: &str) -> Option<Edit> {
+    let arena = Bump::new();
+    let ast = parse_all(&arena, src).ok()?;
+
+    let header = match ast.module {
+        Module::Interface { header } => header,
+        _ => return None,
+    };
+
+    let is_defined = ast.defs.value_defs.iter().any(|def| {
+        let pattern = match def {
+            ValueDef::Annotation(pattern, _) => pattern,
+            ValueDef::Body(pattern, _) => *pattern,
+            ValueDef::AnnotatedBody { body_pattern, .. } => *body_pattern,
+            ValueDef::Expect { .. } | ValueDef::ExpectFx { .. } => return false,
+        };
+
+        matches!(pattern.value, Pattern::Identifier(name) if name == ident)
+    });
+
+    if !is_defined {
+        return None;
+    }
+
+    match header.exposes.items.last() {
+        Some(last) => Some(Edit::insert(
+            module_id,
+            last.region.end().offset as usize,
+            format!(", {}", ident),
+        )),
+        None => {
+            // The list is empty, so add the value right after its opening bracket
+            let after_name = header.name.region.end().offset as usize;
+            let exposes = after_name + src[after_name..].find("exposes")?;
+            let bracket = exposes + src[exposes..].find('[')?;
+
+            Some(Edit::insert(module_id, bracket + 1, ident.to_string()))
+        }
+    }
+}
+
+/// Remove a definition that's never used, along with its separate annotation and its
+/// doc comment. Only definitions of a single name are removed; in a destructure like
+/// `{ x, y } = point`, the other names may be in use. Definitions which share their
+/// lines with other code are left alone, so nothing else gets removed with them.
+fn remove_unused_def(module_id: ModuleId, src: &str, region: Region) -> Vec<Edit> {
+    let arena = Bump::new();
+    let ast = match parse_all(&arena, src) {
+        Ok(ast) => ast,
+        Err(_) => return Vec::new(),
+    };
+
+    let defs = match defs_defining(&ast.defs, region) {
+        Some(defs) => defs,
+        None => return Vec::new(),
+    };
+
+    let name = &src[region.start().offset as usize..region.end().offset as usize];
+
+    defs.defs()
+        .zip(defs.regions.iter())
+        .filter(|(def, _)| {
+            let pattern = match def {
+                Err(ValueDef::Annotation(pattern, _)) => pattern,
+                Err(ValueDef::Body(pattern, _)) => *pattern,
+                Err(ValueDef::AnnotatedBody { body_pattern, .. }) => *body_pattern,
+                _ => return false,
+            };
+
+            matches!(pattern.value, Pattern::Identifier(ident) if ident == name)
+        })
+        .map(|(_, def_region)| remove_lines(module_id, src, *def_region))
+        .collect::<Option<Vec<Edit>>>()
+        .unwrap_or_default()
+}
+
+/// Remove the lines of a region, and the doc comment right above them, if nothing
+/// else is on those lines
+fn remove_lines(module_id: ModuleId, src: &str, region: Region) -> Option<Edit> {
+    let start = region.start().offset as usize;
+    let end = region.end().offset as usize;
+
+    let line_start = |offset: usize| src[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let first_line = line_start(start);
+    let after_last_line = src[end..].find('\n').map_or(src.len(), |i| end + i + 1);
+
+    if !src[first_line..start].trim().is_empty() || !src[end..after_last_line].trim().is_empty() {
+        return None;
+    }
+
+    let mut removed_from = first_line;
+
+    while removed_from > 0 {
+        let previous_line = line_start(removed_from - 1);
+
+        if !src[previous_line..removed_from]
+            .trim_start()
+            .starts_with("##")
+        {
+            break;
+        }
+
+        removed_from = previous_line;
+    }
+
+    Some(Edit::remove(module_id, removed_from, after_last_line))
+}
+
+/// The definitions, at the top level or nested in an expression, which include the
+/// one whose pattern has the given region
+fn defs_defining<'b, 'a: 'b>(defs: &'b Defs<'a>, region: Region) -> Option<&'b Defs<'a>> {
+    let defines = |defs: &Defs<'a>| {
+        defs.value_defs.iter().any(|def| match def {
+            ValueDef::Body(pattern, _)
+            | ValueDef::AnnotatedBody {
+                body_pattern: pattern,
+                ..
+            } => pattern.region == region,
+            _ => false,
+        })
+    };
+
+    if defines(defs) {
+        return Some(defs);
+    }
+
+    find_in_defs(defs, region, &|expr| match expr {
+        Expr::Defs(nested, _) if defines(nested) => Some(*nested),
+        _ => None,
+    })
+}
+
+/// The branches of the `when`, at the top level or nested in an expression, whose
+/// condition has the given region
+fn when_branches<'a>(defs: &Defs<'a>, condition: Region) -> Option<&'a [&'a WhenBranch<'a>]> {
+    find_in_defs(defs, condition, &|expr| match expr {
+        Expr::When(loc_condition, branches) if loc_condition.region == condition => Some(*branches),
+        _ => None,
+    })
+}
+
+/// Look through the bodies of definitions for an expression that `found` picks out.
+/// Only the parts of them that have `region` in them are searched.
+fn find_in_defs<'a, T>(
+    defs: &Defs<'a>,
+    region: Region,
+    found: &dyn Fn(&'a Expr<'a>) -> Option<T>,
+) -> Option<T> {
+    defs.value_defs.iter().find_map(|def| {
+        let body = match def {
+            ValueDef::Body(_, body)
+            | ValueDef::AnnotatedBody {
+                body_expr: body, ..
+            } => *body,
+            ValueDef::Expect { condition, .. } | ValueDef::ExpectFx { condition, .. } => *condition,
+            ValueDef::Annotation(_, _) => return None,
+        };
+
+        if body.region.contains(&region) {
+            find_in_expr(&body.value, region, found)
+        } else {
+            None
+        }
+    })
+}
+
+fn find_in_expr<'a, T>(
+    expr: &'a Expr<'a>,
+    region: Region,
+    found: &dyn Fn(&'a Expr<'a>) -> Option<T>,
+) -> Option<T> {
+    if let Some(value) = found(expr) {
+        return Some(value);
+    }
+
+    // Only the part of the expression that has the region in it needs to be searched
+    let mut sub_exprs: Vec<&'a Loc<Expr<'a>>> = Vec::new();
+
+    match expr {
+        Expr::Defs(defs, final_expr) => {
+            if let Some(value) = find_in_defs(defs, region, found) {
+                return Some(value);
+            }
+
+            sub_exprs.push(final_expr);
+        }
+        Expr::Access(record, _) => return find_in_expr(record, region, found),
+        Expr::SpaceBefore(nested, _) | Expr::SpaceAfter(nested, _) | Expr::ParensAround(nested) => {
+            return find_in_expr(nested, region, found)
+        }
+        Expr::Str(StrLiteral::Line(segments)) => return find_in_str(segments, region, found),
+        Expr::Str(StrLiteral::Block(lines)) => {
+            return lines
+                .iter()
+                .find_map(|segments| find_in_str(segments, region, found))
+        }
+        Expr::List(items) => sub_exprs.extend(items.iter().copied()),
+        Expr::Record(fields) => {
+            sub_exprs.extend(fields.iter().filter_map(|field| field_value(&field.value)))
+        }
+        Expr::RecordUpdate { update, fields } => {
+            sub_exprs.push(update);
+            sub_exprs.extend(fields.iter().filter_map(|field| field_value(&field.value)));
+        }
+        Expr::Closure(_, body) => sub_exprs.push(body),
+        Expr::Backpassing(_, first, second) | Expr::Expect(first, second) => {
+            sub_exprs.extend([*first, *second])
+        }
+        Expr::Apply(function, args, _) => {
+            sub_exprs.push(function);
+            sub_exprs.extend(args.iter().copied());
+        }
+        Expr::BinOps(lefts, right) => {
+            sub_exprs.extend(lefts.iter().map(|(left, _)| left));
+            sub_exprs.push(right);
+        }
+        Expr::UnaryOp(operand, _) => sub_exprs.push(operand),
+        Expr::If(branches, final_else) => {
+            for (condition, then) in branches.iter() {
+                sub_exprs.extend([condition, then]);
+            }
+
+            sub_exprs.push(final_else);
+        }
+        Expr::When(condition, branches) => {
+            sub_exprs.push(condition);
+
+            for branch in branches.iter().copied() {
+                sub_exprs.extend(branch.guard.as_ref());
+                sub_exprs.push(&branch.value);
+            }
+        }
+        _ => {}
+    }
+
+    sub_exprs
+        .into_iter()
+        .find(|sub_expr| sub_expr.region.contains(&region))
+        .and_then(|sub_expr| find_in_expr(&sub_expr.value, region, found))
+}
+
+fn find_in_str<'a, T>(
+    segments: &'a [StrSegment<'a>],
+    region: Region,
+    found: &dyn Fn(&'a Expr<'a>) -> Option<T>,
+) -> Option<T> {
+    segments.iter().find_map(|segment| match segment {
+        StrSegment::Interpolated(loc_expr) if loc_expr.region.contains(&region) => {
+            find_in_expr(loc_expr.value, region, found)
+        }
+        _ => None,
+    })
+}
+
+fn field_value<'a>(field: &'a AssignedField<'a, Expr<'a>>) -> Option<&'a Loc<Expr<'a>>> {
+    match field {
+        AssignedField::RequiredValue(_, _, value) | AssignedField::OptionalValue(_, _, value) => {
+            Some(*value)
+        }
+        AssignedField::SpaceBefore(nested, _) | AssignedField::SpaceAfter(nested, _) => {
+            field_value(nested)
+        }
+        AssignedField::LabelOnly(_) | AssignedField::Malformed(_) => None,
+    }
+}
+
+/// The source code of a pattern the exhaustiveness checker found to be missing
+fn pattern_to_src(
+    interns: &Interns,
+    pattern: roc_exhaustive::Pattern,
+    in_type_param: bool,
+) -> String {
+    use roc_exhaustive::Literal::*;
+    use roc_exhaustive::Pattern::*;
+
+    match pattern {
+        Anything => "_".to_string(),
+        Literal(literal) => match literal {
+            Int(i) => i128::from_ne_bytes(i).to_string(),
+            U128(i) => u128::from_ne_bytes(i).to_string(),
+            Bit(true) => "True".to_string(),
+            Bit(false) => "False".to_string(),
+            Byte(b) => b.to_string(),
+            Float(f) => f.to_string(),
+            Decimal(d) => RocDec::from_ne_bytes(d).to_string(),
+            Str(s) => str_literal(&s),
+        },
+        Ctor(union, tag_id, args) => match union.render_as {
+            RenderAs::Guard => {
+                // #Guard <fake-condition-tag> <unexhausted-pattern>
+                debug_assert!(union.alternatives[tag_id.0 as usize]
+                    .name
+                    .is_tag(&TagName(GUARD_CTOR.into())));
+                debug_assert!(args.len() == 2);
+
+                pattern_to_src(interns, args[1].clone(), in_type_param)
+            }
+            RenderAs::Record(field_names) => {
+                let fields: Vec<String> = field_names
+                    .into_iter()
+                    .zip(args.into_iter())
+                    .map(|(label, arg)| match arg {
+                        Anything => label.to_string(),
+                        Literal(_) | Ctor(_, _, _) => {
+                            format!("{}: {}", label, pattern_to_src(interns, arg, false))
+                        }
+                    })
+                    .collect();
+
+                format!("{{ {} }}", fields.join(", "))
+            }
+            RenderAs::Tag | RenderAs::Opaque => {
+                let ctor = &union.alternatives[tag_id.0 as usize];
+
+                let name = match &ctor.name {
+                    CtorName::Tag(TagName(name)) if name.as_str() == NONEXHAUSIVE_CTOR => {
+                        return "_".to_string();
+                    }
+                    CtorName::Tag(TagName(name)) => name.to_string(),
+                    CtorName::Opaque(opaque) => format!("@{}", opaque.as_str(interns)),
+                };
+
+                if args.is_empty() {
+                    return name;
+                }
+
+                let mut parts = vec![name];
+                parts.extend(
+                    args.into_iter()
+                        .map(|arg| pattern_to_src(interns, arg, true)),
+                );
+
+                if in_type_param {
+                    format!("({})", parts.join(" "))
+                } else {
+                    parts.join(" ")
+                }
+            }
+        },
+    }
+}
+
+fn str_literal(string: &str) -> String {
+    let mut literal = String::with_capacity(string.len() + 2);
+
+    literal.push('"');
+
+    for ch in string.chars() {
+        match ch {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            _ => literal.push(ch),
+        }
+    }
+
+    literal.push('"');
+
+    literal
+}
@@ -1,4 +1,6 @@
 use std::ffi::OsStr;
+use std::io::{Read, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use crate::FormatMode;
@@ -8,11 +10,13 @@ use roc_fmt::def::fmt_defs;
 use roc_fmt::module::fmt_module;
 use roc_fmt::spaces::RemoveSpaces;
 use roc_fmt::{Ast, Buf};
+use roc_parse::ast::Defs;
 use roc_parse::{
     module::{self, module_defs},
     parser::{Parser, SyntaxError},
     state::State,
 };
+use similar::TextDiff;
 
 fn flatten_directories(files: std::vec::Vec<PathBuf>) -> std::vec::Vec<PathBuf> {
     let mut to_flatten = files;
@@ -59,102 +63,377 @@ fn is_roc_file(path: &Path) -> bool {
     return matches!(ext, Some("roc"));
 }
 
-pub fn format(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), String> {
+pub fn format(
+    files: std::vec::Vec<PathBuf>,
+    mode: FormatMode,
+    max_width: Option<usize>,
+) -> Result<(), String> {
     let files = flatten_directories(files);
+    let mut unformatted_files = Vec::new();
 
     for file in files {
         let arena = Bump::new();
 
         let src = std::fs::read_to_string(&file).unwrap();
 
-        let ast = arena.alloc(parse_all(&arena, &src).unwrap_or_else(|e| {
-            user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
-        }));
-        let mut buf = Buf::new_in(&arena);
-        fmt_all(&mut buf, ast);
-
-        let reparsed_ast = arena.alloc(parse_all(&arena, buf.as_str()).unwrap_or_else(|e| {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-            internal_error!(
-                "Formatting bug; formatted code isn't valid\n\n\
-                I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
-                Parse error was: {:?}\n\n",
-                fail_file.display(),
-                e
-            );
-        }));
-
-        let ast_normalized = ast.remove_spaces(&arena);
-        let reparsed_ast_normalized = reparsed_ast.remove_spaces(&arena);
-
-        // HACK!
-        // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
-        // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
-        // I don't have the patience to debug this right now, so let's leave it for another day...
-        // TODO: fix PartialEq impl on ast types
-        if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-
-            let mut before_file = file.clone();
-            before_file.set_extension("roc-format-failed-ast-before");
-            std::fs::write(&before_file, &format!("{:#?}\n", ast)).unwrap();
-
-            let mut after_file = file.clone();
-            after_file.set_extension("roc-format-failed-ast-after");
-            std::fs::write(&after_file, &format!("{:#?}\n", reparsed_ast)).unwrap();
-
-            internal_error!(
-                "Formatting bug; formatting didn't reparse as the same tree\n\n\
-                I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
-                I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
-                fail_file.display(),
-                before_file.display(),
-                after_file.display());
-        }
+        let formatted_src = match format_src(&arena, &src, max_width) {
+            Ok(formatted_src) => formatted_src,
+            Err(FormatProblem::ParsingFailed {
+                formatted_src,
+                parse_err,
+            }) => {
+                let mut fail_file = file.clone();
+                fail_file.set_extension("roc-format-failed");
+                std::fs::write(&fail_file, formatted_src).unwrap();
+                internal_error!(
+                    "Formatting bug; formatted code isn't valid\n\n\
+                    I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
+                    Parse error was: {}\n\n",
+                    fail_file.display(),
+                    parse_err
+                );
+            }
+            Err(FormatProblem::ReformattingChangedAst {
+                formatted_src,
+                ast_before,
+                ast_after,
+            }) => {
+                let mut fail_file = file.clone();
+                fail_file.set_extension("roc-format-failed");
+                std::fs::write(&fail_file, formatted_src).unwrap();
 
-        // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
-        let mut reformatted_buf = Buf::new_in(&arena);
-        fmt_all(&mut reformatted_buf, reparsed_ast);
-        if buf.as_str() != reformatted_buf.as_str() {
-            let mut unstable_1_file = file.clone();
-            unstable_1_file.set_extension("roc-format-unstable-1");
-            std::fs::write(&unstable_1_file, buf.as_str()).unwrap();
-
-            let mut unstable_2_file = file.clone();
-            unstable_2_file.set_extension("roc-format-unstable-2");
-            std::fs::write(&unstable_2_file, reformatted_buf.as_str()).unwrap();
-
-            internal_error!(
-                "Formatting bug; formatting is not stable. Reformatting the formatted file changed it again.\n\n\
-                I wrote the result of formatting to this file for debugging purposes:\n{}\n\n\
-                I wrote the result of double-formatting here:\n{}\n\n",
-                unstable_1_file.display(),
-                unstable_2_file.display());
-        }
+                let mut before_file = file.clone();
+                before_file.set_extension("roc-format-failed-ast-before");
+                std::fs::write(&before_file, ast_before).unwrap();
+
+                let mut after_file = file.clone();
+                after_file.set_extension("roc-format-failed-ast-after");
+                std::fs::write(&after_file, ast_after).unwrap();
+
+                internal_error!(
+                    "Formatting bug; formatting didn't reparse as the same tree\n\n\
+                    I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
+                    I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
+                    fail_file.display(),
+                    before_file.display(),
+                    after_file.display());
+            }
+            Err(FormatProblem::ReformattingUnstable {
+                formatted_src,
+                reformatted_src,
+            }) => {
+                let mut unstable_1_file = file.clone();
+                unstable_1_file.set_extension("roc-format-unstable-1");
+                std::fs::write(&unstable_1_file, formatted_src).unwrap();
+
+                let mut unstable_2_file = file.clone();
+                unstable_2_file.set_extension("roc-format-unstable-2");
+                std::fs::write(&unstable_2_file, reformatted_src).unwrap();
+
+                internal_error!(
+                    "Formatting bug; formatting is not stable. Reformatting the formatted file changed it again.\n\n\
+                    I wrote the result of formatting to this file for debugging purposes:\n{}\n\n\
+                    I wrote the result of double-formatting here:\n{}\n\n",
+                    unstable_1_file.display(),
+                    unstable_2_file.display());
+            }
+        };
 
         match mode {
             FormatMode::CheckOnly => {
                 // If we notice that this file needs to be formatted, return early
-                if buf.as_str() != src {
+                if formatted_src != src {
                     return Err("One or more files need to be reformatted.".to_string());
                 }
             }
 
+            FormatMode::CheckWithDiff => {
+                if formatted_src != src {
+                    print!(
+                        "{}",
+                        unified_diff(&file.display().to_string(), &src, &formatted_src)
+                    );
+
+                    unformatted_files.push(file);
+                }
+            }
+
             FormatMode::Format => {
                 // If all the checks above passed, actually write out the new file.
-                std::fs::write(&file, buf.as_str()).unwrap();
+                std::fs::write(&file, formatted_src).unwrap();
+            }
+        }
+    }
+
+    if unformatted_files.is_empty() {
+        Ok(())
+    } else {
+        let mut message = match unformatted_files.len() {
+            1 => "This file needs to be reformatted:\n".to_string(),
+            count => format!("These {} files need to be reformatted:\n", count),
+        };
+
+        for file in unformatted_files {
+            message.push_str(&format!("\n    {}", file.display()));
+        }
+
+        Err(message)
+    }
+}
+
+/// A unified diff from the original source of a file to its formatted version
+fn unified_diff(file_name: &str, src: &str, formatted_src: &str) -> String {
+    TextDiff::from_lines(src, formatted_src)
+        .unified_diff()
+        .context_radius(3)
+        .header(file_name, &format!("{} (formatted)", file_name))
+        .to_string()
+}
+
+/// Format source code read from stdin, writing the result to stdout.
+///
+/// With a `range`, only the top-level defs overlapping that byte range are formatted,
+/// and the rest of the source is written out as it was.
+pub fn format_stdin(
+    mode: FormatMode,
+    range: Option<Range<u32>>,
+    max_width: Option<usize>,
+) -> Result<(), String> {
+    let mut src = String::new();
+
+    std::io::stdin()
+        .read_to_string(&mut src)
+        .map_err(|err| format!("Could not read from stdin: {}", err))?;
+
+    let arena = Bump::new();
+
+    let result = match range {
+        Some(range) => format_range(&arena, &src, range, max_width),
+        None => format_src(&arena, &src, max_width),
+    };
+
+    let formatted_src = match result {
+        Ok(formatted_src) => formatted_src,
+        Err(FormatProblem::ParsingFailed {
+            formatted_src,
+            parse_err,
+        }) => internal_error!(
+            "Formatting bug; formatted code isn't valid\n\n\
+            The incorrect result was:\n\n{}\n\n\
+            Parse error was: {}\n\n",
+            formatted_src,
+            parse_err
+        ),
+        Err(FormatProblem::ReformattingChangedAst {
+            formatted_src,
+            ast_before,
+            ast_after,
+        }) => internal_error!(
+            "Formatting bug; formatting didn't reparse as the same tree\n\n\
+            The incorrect result was:\n\n{}\n\n\
+            The tree before formatting was:\n\n{}\n\n\
+            The tree after formatting was:\n\n{}\n\n",
+            formatted_src,
+            ast_before,
+            ast_after
+        ),
+        Err(FormatProblem::ReformattingUnstable {
+            formatted_src,
+            reformatted_src,
+        }) => internal_error!(
+            "Formatting bug; formatting is not stable. Reformatting the formatted code changed it again.\n\n\
+            The result of formatting was:\n\n{}\n\n\
+            The result of double-formatting was:\n\n{}\n\n",
+            formatted_src,
+            reformatted_src
+        ),
+    };
+
+    match mode {
+        FormatMode::CheckOnly => {
+            if formatted_src != src {
+                return Err("The code from stdin needs to be reformatted.".to_string());
+            }
+        }
+
+        FormatMode::CheckWithDiff => {
+            if formatted_src != src {
+                print!("{}", unified_diff("<stdin>", &src, &formatted_src));
+
+                return Err("The code from stdin needs to be reformatted.".to_string());
             }
         }
+
+        FormatMode::Format => {
+            let mut stdout = std::io::stdout();
+
+            stdout
+                .write_all(formatted_src.as_bytes())
+                .and_then(|()| stdout.flush())
+                .map_err(|err| format!("Could not write to stdout: {}", err))?;
+        }
     }
 
     Ok(())
 }
 
-fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
+/// Parse a `start:end` byte range, as given to `roc format --range`
+pub fn parse_byte_range(range: &str) -> Result<Range<u32>, String> {
+    let invalid = || {
+        format!(
+            "`{}` is not a byte range; it should look like `start:end`, e.g. `120:240`",
+            range
+        )
+    };
+
+    let (start, end) = range.split_once(':').ok_or_else(invalid)?;
+    let start = start.trim().parse::<u32>().map_err(|_| invalid())?;
+    let end = end.trim().parse::<u32>().map_err(|_| invalid())?;
+
+    if start > end {
+        return Err(format!("The byte range `{}` ends before it starts", range));
+    }
+
+    Ok(start..end)
+}
+
+#[derive(Debug)]
+pub enum FormatProblem {
+    ParsingFailed {
+        formatted_src: String,
+        parse_err: String,
+    },
+    ReformattingChangedAst {
+        formatted_src: String,
+        ast_before: String,
+        ast_after: String,
+    },
+    ReformattingUnstable {
+        formatted_src: String,
+        reformatted_src: String,
+    },
+}
+
+fn new_buf(arena: &Bump, max_width: Option<usize>) -> Buf<'_> {
+    match max_width {
+        Some(max_width) => Buf::new_in(arena).with_max_width(max_width),
+        None => Buf::new_in(arena),
+    }
+}
+
+/// Format a whole module, checking that the result parses to the same tree as the
+/// original, and that formatting it again doesn't change it.
+pub fn format_src(
+    arena: &Bump,
+    src: &str,
+    max_width: Option<usize>,
+) -> Result<String, FormatProblem> {
+    let ast = arena.alloc(parse_all(arena, src).unwrap_or_else(|e| {
+        user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
+    }));
+    let mut buf = new_buf(arena, max_width);
+    fmt_all(&mut buf, ast);
+
+    let reparsed_ast = verify_reparse(arena, ast, buf.as_str())?;
+
+    // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
+    let mut reformatted_buf = new_buf(arena, max_width);
+    fmt_all(&mut reformatted_buf, reparsed_ast);
+    if buf.as_str() != reformatted_buf.as_str() {
+        return Err(FormatProblem::ReformattingUnstable {
+            formatted_src: buf.as_str().to_string(),
+            reformatted_src: reformatted_buf.as_str().to_string(),
+        });
+    }
+
+    Ok(buf.as_str().to_string())
+}
+
+/// Format only the top-level defs overlapping the byte range, keeping everything
+/// around them (the header, other defs, comments and blank lines between defs) as is.
+/// An empty range formats the def it's in.
+pub fn format_range(
+    arena: &Bump,
+    src: &str,
+    range: Range<u32>,
+    max_width: Option<usize>,
+) -> Result<String, FormatProblem> {
+    let ast = arena.alloc(parse_all(arena, src).unwrap_or_else(|e| {
+        user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
+    }));
+
+    let defs = &ast.defs;
+    let mut formatted_src = String::with_capacity(src.len());
+    let mut copied_up_to = 0;
+
+    for (index, def) in defs.defs().enumerate() {
+        let region = defs.regions[index];
+        let def_start = region.start().offset;
+        let def_end = region.end().offset;
+
+        let overlaps = def_start < range.end.max(range.start + 1) && range.start < def_end;
+
+        if !overlaps {
+            continue;
+        }
+
+        // Format the def on its own, so none of the code around it changes
+        let mut single_def = Defs::default();
+
+        match def {
+            Ok(type_def) => single_def.push_type_def(*type_def, region, &[], &[]),
+            Err(value_def) => single_def.push_value_def(*value_def, region, &[], &[]),
+        }
+
+        let mut buf = new_buf(arena, max_width);
+        fmt_defs(&mut buf, &single_def, 0);
+
+        formatted_src.push_str(&src[copied_up_to..def_start as usize]);
+        formatted_src.push_str(buf.as_str().trim_end());
+        copied_up_to = def_end as usize;
+    }
+
+    formatted_src.push_str(&src[copied_up_to..]);
+
+    verify_reparse(arena, ast, arena.alloc_str(&formatted_src))?;
+
+    Ok(formatted_src)
+}
+
+/// Check that formatted code parses to the same tree as the original did, ignoring spaces
+fn verify_reparse<'a>(
+    arena: &'a Bump,
+    ast: &Ast<'a>,
+    formatted_src: &'a str,
+) -> Result<&'a Ast<'a>, FormatProblem> {
+    let reparsed_ast =
+        arena.alloc(
+            parse_all(arena, formatted_src).map_err(|e| FormatProblem::ParsingFailed {
+                formatted_src: formatted_src.to_string(),
+                parse_err: format!("{:?}", e),
+            })?,
+        );
+
+    let ast_normalized = ast.remove_spaces(arena);
+    let reparsed_ast_normalized = reparsed_ast.remove_spaces(arena);
+
+    // HACK!
+    // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
+    // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
+    // I don't have the patience to debug this right now, so let's leave it for another day...
+    // TODO: fix PartialEq impl on ast types
+    if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
+        return Err(FormatProblem::ReformattingChangedAst {
+            formatted_src: formatted_src.to_string(),
+            ast_before: format!("{:#?}\n", ast),
+            ast_after: format!("{:#?}\n", reparsed_ast),
+        });
+    }
+
+    Ok(reparsed_ast)
+}
+
+pub(crate) fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
     let (module, state) = module::parse_header(arena, State::new(src.as_bytes()))
         .map_err(|e| SyntaxError::Header(e.problem))?;
 
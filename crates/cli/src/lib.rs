@@ -24,12 +24,19 @@ use target_lexicon::{
 use tempfile::TempDir;
 
 pub mod build;
+mod demangle;
+mod fix;
 mod format;
-pub use format::format;
+#[cfg(target_os = "linux")]
+mod profile;
+pub use demangle::demangle;
+pub use fix::fix;
+pub use format::{format, format_stdin, parse_byte_range};
 
 use crate::build::{BuildFileError, BuildOrdering};
 
 const DEFAULT_ROC_FILENAME: &str = "main.roc";
+const DEFAULT_PROFILE_FILENAME: &str = "profile.folded";
 
 pub const CMD_BUILD: &str = "build";
 pub const CMD_RUN: &str = "run";
@@ -40,10 +47,17 @@ pub const CMD_DOCS: &str = "docs";
 pub const CMD_CHECK: &str = "check";
 pub const CMD_VERSION: &str = "version";
 pub const CMD_FORMAT: &str = "format";
+pub const CMD_FIX: &str = "fix";
 pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
+pub const CMD_LINKER: &str = "linker";
+pub const CMD_INSPECT: &str = "inspect";
+pub const CMD_API_DIFF: &str = "api-diff";
+pub const CMD_DEMANGLE: &str = "demangle";
+pub const CMD_PROFILE: &str = "profile";
 
 pub const FLAG_DEBUG: &str = "debug";
+pub const FLAG_BACKTRACE: &str = "backtrace";
 pub const FLAG_DEV: &str = "dev";
 pub const FLAG_OPTIMIZE: &str = "optimize";
 pub const FLAG_MAX_THREADS: &str = "max-threads";
@@ -56,11 +70,23 @@ pub const FLAG_LINKER: &str = "linker";
 pub const FLAG_PRECOMPILED: &str = "precompiled-host";
 pub const FLAG_CHECK: &str = "check";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
+pub const FLAG_OUTPUT: &str = "output";
+pub const FLAG_DOC: &str = "doc";
+pub const FLAG_FORMAT: &str = "format";
+pub const FLAG_FREQUENCY: &str = "frequency";
+pub const FLAG_MAX_WIDTH: &str = "max-width";
+pub const FLAG_STDIN: &str = "stdin";
+pub const FLAG_RANGE: &str = "range";
+pub const FLAG_DIFF: &str = "diff";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_FILE: &str = "GLUE_FILE";
+pub const METADATA_PATH: &str = "METADATA_PATH";
 pub const DIRECTORY_OR_FILES: &str = "DIRECTORY_OR_FILES";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
+pub const OLD_PACKAGE: &str = "OLD_PACKAGE";
+pub const NEW_PACKAGE: &str = "NEW_PACKAGE";
+pub const MANGLED_NAMES: &str = "MANGLED_NAMES";
 
 const VERSION: &str = include_str!("../../../version.txt");
 
@@ -89,7 +115,12 @@ pub fn build_app<'a>() -> Command<'a> {
 
     let flag_debug = Arg::new(FLAG_DEBUG)
         .long(FLAG_DEBUG)
-        .help("Store LLVM debug information in the generated program.")
+        .help("Store debug information in the generated program, so debuggers can show the Roc source.")
+        .required(false);
+
+    let flag_backtrace = Arg::new(FLAG_BACKTRACE)
+        .long(FLAG_BACKTRACE)
+        .help("Record which Roc functions are running, so the platform's roc_panic can print a backtrace by calling roc__backtrace_frame. This slows down every call, so it's meant for development.")
         .required(false);
 
     let flag_time = Arg::new(FLAG_TIME)
@@ -140,6 +171,7 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_backtrace.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_precompiled.clone())
@@ -182,6 +214,12 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_precompiled.clone())
+            .arg(
+                Arg::new(FLAG_DOC)
+                    .long(FLAG_DOC)
+                    .help("Also run the code examples in doc comments. Examples ending in `actual == expected` run like `expect`s; the rest only need to typecheck.")
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file for the main module")
@@ -201,9 +239,40 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_backtrace.clone())
+            .arg(flag_time.clone())
+            .arg(flag_linker.clone())
+            .arg(flag_precompiled.clone())
+            .arg(roc_file_to_run.clone())
+            .arg(args_for_app.clone())
+        )
+        .subcommand(Command::new(CMD_PROFILE)
+            .about("Run a .roc file while sampling where it spends its time, and write the stacks in the folded format flame graph tools read. Only works on Linux.")
+            .arg(flag_optimize.clone())
+            .arg(flag_max_threads.clone())
+            .arg(flag_opt_size.clone())
+            .arg(flag_dev.clone())
+            .arg(flag_backtrace.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_precompiled.clone())
+            .arg(
+                Arg::new(FLAG_OUTPUT)
+                    .long(FLAG_OUTPUT)
+                    .help("The file to write the folded stacks to")
+                    .takes_value(true)
+                    .default_value(DEFAULT_PROFILE_FILENAME)
+                    .allow_invalid_utf8(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_FREQUENCY)
+                    .long(FLAG_FREQUENCY)
+                    .help("How many samples to take per second the app runs")
+                    .takes_value(true)
+                    .default_value("999")
+                    .required(false),
+            )
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone())
         )
@@ -214,6 +283,7 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_backtrace.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_precompiled.clone())
@@ -234,6 +304,48 @@ pub fn build_app<'a>() -> Command<'a> {
                     .help("Checks that specified files are formatted. If formatting is needed, it will return a non-zero exit code.")
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_DIFF)
+                    .long(FLAG_DIFF)
+                    .help("With --check, print a diff of the changes formatting would make")
+                    .requires(FLAG_CHECK)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_MAX_WIDTH)
+                    .long(FLAG_MAX_WIDTH)
+                    .help("Break up lines that would be longer than this many characters")
+                    .takes_value(true)
+                    .validator(|s| s.parse::<usize>())
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_STDIN)
+                    .long(FLAG_STDIN)
+                    .help("Format code read from stdin, and write the result to stdout")
+                    .conflicts_with(DIRECTORY_OR_FILES)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_RANGE)
+                    .long(FLAG_RANGE)
+                    .help("Only format the top-level definitions overlapping this byte range, given as start:end")
+                    .takes_value(true)
+                    .validator(parse_byte_range)
+                    .requires(FLAG_STDIN)
+                    .required(false),
+            )
+        )
+        .subcommand(Command::new(CMD_FIX)
+            .about("Fix the problems in a .roc file and the modules it imports that can be fixed automatically: unused imports, definitions and arguments, and values missing from `exposes`. Also lists the branches each incomplete `when` is missing")
+            .arg(flag_max_threads.clone())
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file of an app to fix")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME),
+            )
         )
         .subcommand(Command::new(CMD_VERSION)
             .about(concatcp!("Print the Roc compiler’s version, which is currently ", VERSION)))
@@ -258,6 +370,45 @@ pub fn build_app<'a>() -> Command<'a> {
                     .help("The directory or files to build documentation for")
                     .allow_invalid_utf8(true)
                 )
+                .arg(
+                    Arg::new(FLAG_OUTPUT)
+                        .long(FLAG_OUTPUT)
+                        .help("The directory to write the generated documentation to")
+                        .takes_value(true)
+                        .default_value(roc_docs::DEFAULT_BUILD_DIR)
+                        .allow_invalid_utf8(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new(FLAG_CHECK)
+                        .long(FLAG_CHECK)
+                        .help("Report broken doc links and exposed items without doc comments instead of generating docs. If there are any, it will return a non-zero exit code.")
+                        .required(false),
+                )
+                .arg(
+                    Arg::new(FLAG_FORMAT)
+                        .long(FLAG_FORMAT)
+                        .help("What to generate: a website (html), a file per module (markdown), or a description of the package's API for other tools (json)")
+                        .takes_value(true)
+                        .possible_values(roc_docs::DocsFormat::NAMES)
+                        .default_value("html")
+                        .required(false),
+                )
+        )
+        .subcommand(Command::new(CMD_API_DIFF)
+            .about("Compare the API of two versions of a package, and suggest which version number the new one should have.")
+            .arg(
+                Arg::new(OLD_PACKAGE)
+                    .help("The directory or package file of the old version")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
+            .arg(
+                Arg::new(NEW_PACKAGE)
+                    .help("The directory or package file of the new version")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
         )
         .subcommand(Command::new(CMD_GLUE)
             .about("Generate glue code between a platform's Roc API and its host language.")
@@ -274,12 +425,35 @@ pub fn build_app<'a>() -> Command<'a> {
                     .required(true)
             )
         )
+        .subcommand(Command::new(CMD_DEMANGLE)
+            .about("Turn the names of Roc procedures in a binary back into their Roc names and types, e.g. for perf output or backtraces.")
+            .arg(
+                Arg::new(MANGLED_NAMES)
+                    .help("The names to demangle. If there are none, demangle every name in stdin and print the result.")
+                    .multiple_values(true)
+                    .required(false)
+            )
+        )
+        .subcommand(Command::new(CMD_LINKER)
+            .about("Inspect how the surgical linker sees a platform's host.")
+            .subcommand_required(true)
+            .subcommand(Command::new(CMD_INSPECT)
+                .about("Print the metadata of a host preprocessed for surgical linking: application functions, plt slots, surgery sites and section offsets.")
+                .arg(
+                    Arg::new(METADATA_PATH)
+                        .help("The metadata file written while preprocessing the host, or the platform directory containing it")
+                        .allow_invalid_utf8(true)
+                        .required(true)
+                )
+            )
+        )
         .trailing_var_arg(true)
         .arg(flag_optimize)
             .arg(flag_max_threads.clone())
         .arg(flag_opt_size)
         .arg(flag_dev)
         .arg(flag_debug)
+        .arg(flag_backtrace)
         .arg(flag_time)
         .arg(flag_linker)
         .arg(flag_precompiled)
@@ -307,11 +481,14 @@ pub enum BuildConfig {
     BuildOnly,
     BuildAndRun,
     BuildAndRunIfNoErrors,
+    BuildAndProfile,
 }
 
 pub enum FormatMode {
     Format,
     CheckOnly,
+    /// Check, printing a diff for each file that needs to be reformatted
+    CheckWithDiff,
 }
 
 #[cfg(windows)]
@@ -384,7 +561,11 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         // TODO: expose this from CLI?
         render: roc_reporting::report::RenderTarget::ColorTerminal,
         threading,
-        exec_mode: ExecutionMode::Test,
+        exec_mode: if matches.is_present(FLAG_DOC) {
+            ExecutionMode::TestDocExamples
+        } else {
+            ExecutionMode::Test
+        },
     };
     let loaded =
         roc_load::load_and_monomorphize(arena, path.to_path_buf(), subs_by_module, load_config)
@@ -471,7 +652,25 @@ pub fn build(
         (false, false, false) => OptLevel::Normal,
         _ => user_error!("build can be only one of `--dev`, `--optimize`, or `--opt-size`"),
     };
-    let emit_debug_info = matches.is_present(FLAG_DEBUG);
+    // the profiler finds Roc source lines through the debug info
+    let emit_debug_info = config == BuildAndProfile || matches.is_present(FLAG_DEBUG);
+    let emit_backtrace = matches.is_present(FLAG_BACKTRACE);
+
+    if emit_backtrace && matches!(opt_level, OptLevel::Development) {
+        user_error!("`--backtrace` is not supported together with `--dev` yet");
+    }
+
+    // the dev backend doesn't emit debug info, so samples couldn't be traced back to Roc code
+    if config == BuildAndProfile && matches!(opt_level, OptLevel::Development) {
+        user_error!("`roc profile` is not supported together with `--dev` yet");
+    }
+
+    if config == BuildAndProfile && !cfg!(target_os = "linux") {
+        user_error!(
+            "`roc profile` samples with Linux's perf_event, so it only works on Linux for now"
+        );
+    }
+
     let emit_timings = matches.is_present(FLAG_TIME);
 
     let threading = match matches
@@ -491,6 +690,12 @@ pub fn build(
         LinkingStrategy::Additive
     } else if !roc_linker::supported(link_type, &triple)
         || matches.value_of(FLAG_LINKER) == Some("legacy")
+        // the profiler reads the app's symbols and debug info from the executable,
+        // and the surgical linker does not carry them over
+        || config == BuildAndProfile
+        // the host only reaches roc__backtrace_frame through a weak reference, which is
+        // already null by the time the surgical linker gets it, since the dummy app lacks it
+        || emit_backtrace
     {
         LinkingStrategy::Legacy
     } else {
@@ -533,7 +738,7 @@ pub fn build(
         .map(|x| x * 1024);
 
     let build_ordering = match config {
-        BuildAndRunIfNoErrors => BuildOrdering::BuildIfChecks,
+        BuildAndRunIfNoErrors | BuildAndProfile => BuildOrdering::BuildIfChecks,
         _ => BuildOrdering::AlwaysBuild,
     };
     let res_binary_path = build_file(
@@ -542,6 +747,7 @@ pub fn build(
         path.to_path_buf(),
         opt_level,
         emit_debug_info,
+        emit_backtrace,
         emit_timings,
         link_type,
         linking_strategy,
@@ -683,6 +889,51 @@ pub fn build(
                     std::mem::forget(bytes);
                     x
                 }
+                BuildAndProfile => {
+                    debug_assert!(
+                        problems.errors == 0,
+                        "if there are errors, they should have been returned as an error variant"
+                    );
+                    if problems.warnings > 0 {
+                        println!(
+                            "\x1B[32m0\x1B[39m errors and \x1B[33m{}\x1B[39m {} found in {} ms.\n\nProfiling program…\n\n\x1B[36m{}\x1B[39m",
+                            problems.warnings,
+                            if problems.warnings == 1 {
+                                "warning"
+                            } else {
+                                "warnings"
+                            },
+                            total_time.as_millis(),
+                            "─".repeat(80)
+                        );
+                    }
+
+                    let frequency = match matches
+                        .value_of(FLAG_FREQUENCY)
+                        .and_then(|s| s.parse::<u64>().ok())
+                    {
+                        None | Some(0) => {
+                            user_error!("the sampling frequency must be a positive whole number")
+                        }
+                        Some(n) => n,
+                    };
+                    let output_path = Path::new(matches.value_of_os(FLAG_OUTPUT).unwrap());
+                    let args = matches.values_of_os(ARGS_FOR_APP).unwrap_or_default();
+
+                    #[cfg(target_os = "linux")]
+                    {
+                        profile::profile(&binary_path, args, output_path, frequency)
+                    }
+
+                    #[cfg(not(target_os = "linux"))]
+                    {
+                        let _ = (args, output_path, frequency);
+
+                        internal_error!(
+                            "`roc profile` should have stopped before building on this OS"
+                        )
+                    }
+                }
             }
         }
         Err(BuildFileError::ErrorModule {
@@ -1,12 +1,14 @@
 use roc_build::link::LinkType;
 use roc_cli::build::check_file;
 use roc_cli::{
-    build_app, format, test, BuildConfig, FormatMode, Target, CMD_BUILD, CMD_CHECK, CMD_DEV,
-    CMD_DOCS, CMD_EDIT, CMD_FORMAT, CMD_GLUE, CMD_REPL, CMD_RUN, CMD_TEST, CMD_VERSION,
-    DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_LIB, FLAG_NO_LINK, FLAG_TARGET, FLAG_TIME, GLUE_FILE,
-    ROC_FILE,
+    build_app, demangle, fix, format, format_stdin, parse_byte_range, test, BuildConfig,
+    FormatMode, Target, CMD_API_DIFF, CMD_BUILD, CMD_CHECK, CMD_DEMANGLE, CMD_DEV, CMD_DOCS,
+    CMD_EDIT, CMD_FIX, CMD_FORMAT, CMD_GLUE, CMD_INSPECT, CMD_LINKER, CMD_PROFILE, CMD_REPL,
+    CMD_RUN, CMD_TEST, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DIFF, FLAG_FORMAT,
+    FLAG_LIB, FLAG_MAX_WIDTH, FLAG_NO_LINK, FLAG_OUTPUT, FLAG_RANGE, FLAG_STDIN, FLAG_TARGET,
+    FLAG_TIME, GLUE_FILE, MANGLED_NAMES, METADATA_PATH, NEW_PACKAGE, OLD_PACKAGE, ROC_FILE,
 };
-use roc_docs::generate_docs_html;
+use roc_docs::{api_diff, check_docs, generate_docs, DocsFormat};
 use roc_error_macros::user_error;
 use roc_load::{LoadingProblem, Threading};
 use std::fs::{self, FileType};
@@ -67,6 +69,20 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
+        Some((CMD_PROFILE, matches)) => {
+            if matches.is_present(ROC_FILE) {
+                build(
+                    matches,
+                    BuildConfig::BuildAndProfile,
+                    Triple::host(),
+                    LinkType::Executable,
+                )
+            } else {
+                eprintln!("What .roc file do you want to profile? Specify it at the end of the `roc profile` command.");
+
+                Ok(1)
+            }
+        }
         Some((CMD_DEV, matches)) => {
             if matches.is_present(ROC_FILE) {
                 build(
@@ -93,6 +109,26 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
+        Some((CMD_DEMANGLE, matches)) => demangle(matches.values_of(MANGLED_NAMES)),
+        Some((CMD_LINKER, matches)) => match matches.subcommand() {
+            Some((CMD_INSPECT, matches)) => {
+                let metadata_path = Path::new(matches.value_of_os(METADATA_PATH).unwrap());
+
+                match roc_linker::inspect_metadata(metadata_path) {
+                    Ok(description) => {
+                        println!("{}", description);
+
+                        Ok(0)
+                    }
+                    Err(err) => {
+                        eprintln!("{}", err);
+
+                        Ok(1)
+                    }
+                }
+            }
+            _ => unreachable!(),
+        },
         Some((CMD_BUILD, matches)) => {
             let target: Target = matches.value_of_t(FLAG_TARGET).unwrap_or_default();
 
@@ -171,6 +207,79 @@ fn main() -> io::Result<()> {
                 }
             }
         }
+        Some((CMD_FIX, matches)) => {
+            let arena = bumpalo::Bump::new();
+
+            let filename = matches.value_of_os(ROC_FILE).unwrap();
+            let roc_file_path = PathBuf::from(filename);
+            let threading = match matches
+                .value_of(roc_cli::FLAG_MAX_THREADS)
+                .and_then(|s| s.parse::<usize>().ok())
+            {
+                None => Threading::AllAvailable,
+                Some(0) => user_error!("cannot build with at most 0 threads"),
+                Some(1) => Threading::Single,
+                Some(n) => Threading::AtMost(n),
+            };
+
+            match fix(&arena, roc_file_path, threading) {
+                Ok(fixes) => {
+                    for fixed in fixes.fixed.iter() {
+                        println!(
+                            "Fixed {}: {} {}",
+                            fixed.path.display(),
+                            fixed.edits,
+                            if fixed.edits == 1 { "edit" } else { "edits" }
+                        );
+                    }
+
+                    if fixes.fixed.is_empty() {
+                        println!("There was nothing I could fix automatically.");
+                    }
+
+                    for missing in fixes.missing_branches.iter() {
+                        match &missing.placeholder {
+                            Some(placeholder) => println!(
+                                "\nThe `when` on line {} of {} had no branches for these patterns. \
+                                I added them, returning `{}`; replace that with what each one \
+                                should do:\n",
+                                missing.line,
+                                missing.path.display(),
+                                placeholder,
+                            ),
+                            None => println!(
+                                "\nThe `when` on line {} of {} has no branches for these patterns, \
+                                and I couldn't add them:\n",
+                                missing.line,
+                                missing.path.display(),
+                            ),
+                        }
+
+                        for pattern in missing.patterns.iter() {
+                            println!("    {}", pattern);
+                        }
+                    }
+
+                    for not_fixed in fixes.not_fixed.iter() {
+                        eprintln!(
+                            "I didn't fix {}, because the fixed code wouldn't parse.",
+                            not_fixed.display()
+                        );
+                    }
+
+                    Ok(if fixes.not_fixed.is_empty() { 0 } else { 1 })
+                }
+
+                Err(LoadingProblem::FormattedReport(report)) => {
+                    print!("{}", report);
+
+                    Ok(1)
+                }
+                Err(other) => {
+                    panic!("fix failed with error:\n{:?}", other);
+                }
+            }
+        }
         Some((CMD_REPL, _)) => {
             {
                 roc_repl_cli::main()?;
@@ -226,47 +335,91 @@ fn main() -> io::Result<()> {
                 roc_files_recursive(os_str.as_os_str(), metadata.file_type(), &mut roc_files)?;
             }
 
-            generate_docs_html(roc_files);
+            if matches.is_present(FLAG_CHECK) {
+                match check_docs(roc_files) {
+                    0 => Ok(0),
+                    problem_count => {
+                        eprintln!(
+                            "Found {} problem{} in the docs.",
+                            problem_count,
+                            if problem_count == 1 { "" } else { "s" }
+                        );
+
+                        Ok(1)
+                    }
+                }
+            } else {
+                let build_dir = PathBuf::from(matches.value_of_os(FLAG_OUTPUT).unwrap());
+                let format: DocsFormat = matches.value_of_t(FLAG_FORMAT).unwrap();
+
+                generate_docs(roc_files, &build_dir, format);
+
+                Ok(0)
+            }
+        }
+        Some((CMD_API_DIFF, matches)) => {
+            let old_files = package_roc_files(matches.value_of_os(OLD_PACKAGE).unwrap())?;
+            let new_files = package_roc_files(matches.value_of_os(NEW_PACKAGE).unwrap())?;
+
+            println!("{}", api_diff(old_files, new_files));
 
             Ok(0)
         }
         Some((CMD_FORMAT, matches)) => {
-            let maybe_values = matches.values_of_os(DIRECTORY_OR_FILES);
+            let format_mode = match (
+                matches.is_present(FLAG_CHECK),
+                matches.is_present(FLAG_DIFF),
+            ) {
+                (true, true) => FormatMode::CheckWithDiff,
+                (true, false) => FormatMode::CheckOnly,
+                (false, _) => FormatMode::Format,
+            };
 
-            let mut values: Vec<OsString> = Vec::new();
+            let max_width = matches
+                .value_of(FLAG_MAX_WIDTH)
+                .map(|s| s.parse::<usize>().unwrap());
 
-            match maybe_values {
-                None => {
-                    let mut os_string_values: Vec<OsString> = Vec::new();
-                    read_all_roc_files(
-                        &std::env::current_dir()?.as_os_str().to_os_string(),
-                        &mut os_string_values,
-                    )?;
-                    for os_string in os_string_values {
-                        values.push(os_string);
+            let result = if matches.is_present(FLAG_STDIN) {
+                let range = matches
+                    .value_of(FLAG_RANGE)
+                    .map(|s| parse_byte_range(s).unwrap());
+
+                format_stdin(format_mode, range, max_width)
+            } else {
+                let maybe_values = matches.values_of_os(DIRECTORY_OR_FILES);
+
+                let mut values: Vec<OsString> = Vec::new();
+
+                match maybe_values {
+                    None => {
+                        let mut os_string_values: Vec<OsString> = Vec::new();
+                        read_all_roc_files(
+                            &std::env::current_dir()?.as_os_str().to_os_string(),
+                            &mut os_string_values,
+                        )?;
+                        for os_string in os_string_values {
+                            values.push(os_string);
+                        }
                     }
-                }
-                Some(os_values) => {
-                    for os_str in os_values {
-                        values.push(os_str.to_os_string());
+                    Some(os_values) => {
+                        for os_str in os_values {
+                            values.push(os_str.to_os_string());
+                        }
                     }
                 }
-            }
 
-            let mut roc_files = Vec::new();
+                let mut roc_files = Vec::new();
 
-            // Populate roc_files
-            for os_str in values {
-                let metadata = fs::metadata(os_str.clone())?;
-                roc_files_recursive(os_str.as_os_str(), metadata.file_type(), &mut roc_files)?;
-            }
+                // Populate roc_files
+                for os_str in values {
+                    let metadata = fs::metadata(os_str.clone())?;
+                    roc_files_recursive(os_str.as_os_str(), metadata.file_type(), &mut roc_files)?;
+                }
 
-            let format_mode = match matches.is_present(FLAG_CHECK) {
-                true => FormatMode::CheckOnly,
-                false => FormatMode::Format,
+                format(roc_files, format_mode, max_width)
             };
 
-            let format_exit_code = match format(roc_files, format_mode) {
+            let format_exit_code = match result {
                 Ok(_) => 0,
                 Err(message) => {
                     eprintln!("{}", message);
@@ -310,6 +463,19 @@ fn read_all_roc_files(
     Ok(())
 }
 
+/// A package can be given as its directory, or as the file with its package header.
+fn package_roc_files(path: &OsStr) -> io::Result<Vec<PathBuf>> {
+    let mut roc_files = Vec::new();
+
+    if fs::metadata(path)?.is_dir() {
+        read_all_roc_files(&path.to_os_string(), &mut roc_files)?;
+    } else {
+        roc_files.push(path.to_os_string());
+    }
+
+    Ok(roc_files.into_iter().map(PathBuf::from).collect())
+}
+
 fn roc_files_recursive<P: AsRef<Path>>(
     path: P,
     file_type: FileType,
@@ -0,0 +1,824 @@
+//! `roc profile`: run an app under a sampling profiler, and attribute the samples to Roc
+//! procedures and source lines.
+//!
+//! The stacks are written in the folded format that `flamegraph.pl` and
+//! `inferno-flamegraph` read: one line per distinct stack, with its frames from outermost
+//! to innermost separated by `;`, followed by the number of samples taken in it.
+//!
+//! Sampling uses Linux's `perf_event_open`, which needs `kernel.perf_event_paranoid` to be
+//! at most 2 (the default on most distributions).
+
+use addr2line::gimli;
+use object::{Object, ObjectKind, ObjectSection, ObjectSegment};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{CString, OsStr};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::os::raw::{c_char, c_int, c_void};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::atomic::{fence, Ordering};
+
+const PERF_TYPE_SOFTWARE: u32 = 1;
+const PERF_COUNT_SW_TASK_CLOCK: u64 = 1;
+
+const PERF_SAMPLE_IP: u64 = 1 << 0;
+const PERF_SAMPLE_TID: u64 = 1 << 1;
+const PERF_SAMPLE_CALLCHAIN: u64 = 1 << 5;
+
+// bits of `PerfEventAttr::flags`
+const ATTR_DISABLED: u64 = 1 << 0;
+const ATTR_INHERIT: u64 = 1 << 1;
+const ATTR_EXCLUDE_KERNEL: u64 = 1 << 5;
+const ATTR_EXCLUDE_HV: u64 = 1 << 6;
+const ATTR_MMAP: u64 = 1 << 8;
+const ATTR_FREQ: u64 = 1 << 10;
+const ATTR_ENABLE_ON_EXEC: u64 = 1 << 12;
+const ATTR_WATERMARK: u64 = 1 << 14;
+const ATTR_EXCLUDE_CALLCHAIN_KERNEL: u64 = 1 << 21;
+
+const PERF_RECORD_MMAP: u32 = 1;
+const PERF_RECORD_LOST: u32 = 2;
+const PERF_RECORD_SAMPLE: u32 = 9;
+
+/// Callchain entries from here up mark where the kernel or user part of a stack begins
+const PERF_CONTEXT_MAX: u64 = -4095i64 as u64;
+
+/// Offsets of `data_head` and `data_tail` in `struct perf_event_mmap_page`
+const DATA_HEAD_OFFSET: usize = 1024;
+const DATA_TAIL_OFFSET: usize = 1032;
+
+/// Pages in the ring buffer the kernel writes samples to; must be a power of 2
+const RING_PAGES: usize = 256;
+
+/// How often to read the samples while the app runs
+const POLL_INTERVAL_MS: c_int = 100;
+
+const UNKNOWN_FRAME: &str = "[unknown]";
+
+/// How many procedures to list after profiling
+const SUMMARY_LENGTH: usize = 10;
+
+/// `struct perf_event_attr`, as of `PERF_ATTR_SIZE_VER5`
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+    kind: u32,
+    size: u32,
+    config: u64,
+    sample_freq: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_watermark: u32,
+    bp_type: u32,
+    config1: u64,
+    config2: u64,
+    branch_sample_type: u64,
+    sample_regs_user: u64,
+    sample_stack_user: u32,
+    clockid: i32,
+    sample_regs_intr: u64,
+    aux_watermark: u32,
+    sample_max_stack: u16,
+    reserved: u16,
+}
+
+/// Run the app at `binary_path`, sampling where it is `frequency` times per second of CPU
+/// time, and write the folded stacks to `output_path`. Returns the app's exit code.
+pub fn profile<I, S>(
+    binary_path: &Path,
+    args: I,
+    output_path: &Path,
+    frequency: u64,
+) -> io::Result<i32>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    // the kernel reports the mapped executable by its canonical path
+    let binary_path = binary_path.canonicalize()?;
+
+    let (pid, resume_fd) = spawn_paused(&binary_path, args)?;
+
+    let mut sampler = match Sampler::open(pid, frequency) {
+        Ok(sampler) => sampler,
+        Err(err) => {
+            // closing the pipe makes the child exit without running the app
+            unsafe {
+                libc::close(resume_fd);
+                libc::waitpid(pid, std::ptr::null_mut(), 0);
+            }
+
+            return Err(io::Error::new(
+                err.kind(),
+                format!(
+                    "I could not start sampling the app: {}. If sampling is not allowed, try `sudo sysctl kernel.perf_event_paranoid=2`.",
+                    err
+                ),
+            ));
+        }
+    };
+
+    unsafe {
+        libc::write(resume_fd, [1u8].as_ptr() as *const c_void, 1);
+        libc::close(resume_fd);
+    }
+
+    let mut recording = Recording::default();
+
+    let status = loop {
+        let mut poll_fd = libc::pollfd {
+            fd: sampler.fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        unsafe { libc::poll(&mut poll_fd, 1, POLL_INTERVAL_MS) };
+
+        sampler.drain(|kind, body| recording.record(&binary_path, kind, body));
+
+        let mut status = 0;
+
+        match unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } {
+            0 => continue,
+            -1 => {
+                let err = io::Error::last_os_error();
+
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            _ => break status,
+        }
+    };
+
+    sampler.drain(|kind, body| recording.record(&binary_path, kind, body));
+
+    let exit_code = if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else {
+        128 + libc::WTERMSIG(status)
+    };
+
+    let bytes = std::fs::read(&binary_path)?;
+    let file = object::File::parse(&*bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+    let mut symbolizer = Symbolizer::new(&file, std::mem::take(&mut recording.mappings));
+
+    let (folded, innermost) =
+        fold_stacks(&recording.stacks, |address| symbolizer.describe(address));
+
+    let mut out = BufWriter::new(File::create(output_path)?);
+
+    for (stack, count) in folded.iter() {
+        writeln!(out, "{} {}", stack, count)?;
+    }
+
+    out.flush()?;
+
+    print_summary(&recording, innermost, output_path);
+
+    Ok(exit_code)
+}
+
+/// Describes the frames of each stack, and counts how many samples were taken in each
+/// distinct stack, in the folded format, and how many had each frame innermost.
+fn fold_stacks(
+    stacks: &[Vec<u64>],
+    mut describe: impl FnMut(u64) -> String,
+) -> (BTreeMap<String, u64>, HashMap<String, u64>) {
+    let mut folded: BTreeMap<String, u64> = BTreeMap::new();
+    let mut innermost: HashMap<String, u64> = HashMap::new();
+
+    for stack in stacks.iter() {
+        let frames: Vec<String> = stack
+            .iter()
+            .enumerate()
+            .map(|(depth, address)| {
+                // return addresses point just past the call; look up the call itself
+                let address = if depth == 0 {
+                    *address
+                } else {
+                    address.saturating_sub(1)
+                };
+
+                describe(address)
+            })
+            .collect();
+
+        *innermost.entry(frames[0].clone()).or_default() += 1;
+
+        let mut folded_stack = String::new();
+
+        for frame in frames.iter().rev() {
+            if !folded_stack.is_empty() {
+                folded_stack.push(';');
+            }
+
+            folded_stack.push_str(frame);
+        }
+
+        *folded.entry(folded_stack).or_default() += 1;
+    }
+
+    (folded, innermost)
+}
+
+fn print_summary(recording: &Recording, innermost: HashMap<String, u64>, output_path: &Path) {
+    let total = recording.stacks.len();
+
+    println!(
+        "\n\x1B[36m{}\x1B[39m\n\nWrote {} {} to {}. To see them as a flame graph, run e.g.\n\n    inferno-flamegraph < {} > flamegraph.svg\n",
+        "─".repeat(80),
+        total,
+        if total == 1 { "sample" } else { "samples" },
+        output_path.display(),
+        output_path.display(),
+    );
+
+    if recording.lost > 0 {
+        println!(
+            "{} samples were lost, because the app produced them faster than they could be read.\n",
+            recording.lost
+        );
+    }
+
+    if total == 0 {
+        return;
+    }
+
+    let mut by_count: Vec<_> = innermost.into_iter().collect();
+    by_count.sort_by(|(name_a, count_a), (name_b, count_b)| {
+        count_b.cmp(count_a).then_with(|| name_a.cmp(name_b))
+    });
+
+    println!("Where the app spent its time:\n");
+
+    for (name, count) in by_count.into_iter().take(SUMMARY_LENGTH) {
+        println!(
+            "    {:>6.2}%  {}",
+            100.0 * count as f64 / total as f64,
+            name
+        );
+    }
+}
+
+/// Fork a child that waits until we write to the returned fd, and then execs the app.
+/// That gives us time to attach the sampler, which starts sampling at the exec.
+fn spawn_paused<I, S>(binary_path: &Path, args: I) -> io::Result<(libc::pid_t, c_int)>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let program = CString::new(binary_path.as_os_str().as_bytes())?;
+    let mut arg_strings = vec![program.clone()];
+
+    for arg in args {
+        arg_strings.push(CString::new(arg.as_ref().as_bytes())?);
+    }
+
+    let mut argv: Vec<*const c_char> = arg_strings.iter().map(|arg| arg.as_ptr()).collect();
+    argv.push(std::ptr::null());
+
+    let mut pipe_fds: [c_int; 2] = [0; 2];
+
+    if unsafe { libc::pipe(pipe_fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let [read_fd, write_fd] = pipe_fds;
+
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => unsafe {
+            // in the child, only async-signal-safe calls are allowed until the exec
+            libc::close(write_fd);
+
+            let mut byte = 0u8;
+
+            if libc::read(read_fd, &mut byte as *mut u8 as *mut c_void, 1) == 1 {
+                libc::execv(program.as_ptr(), argv.as_ptr());
+            }
+
+            libc::_exit(127)
+        },
+        pid => {
+            unsafe { libc::close(read_fd) };
+
+            Ok((pid, write_fd))
+        }
+    }
+}
+
+/// A perf event sampling one process (and the threads it starts), and the ring buffer the
+/// kernel writes its samples to.
+struct Sampler {
+    fd: c_int,
+    buffer: *mut u8,
+    page_size: usize,
+}
+
+impl Sampler {
+    fn open(pid: libc::pid_t, frequency: u64) -> io::Result<Self> {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let data_size = RING_PAGES * page_size;
+
+        let attr = PerfEventAttr {
+            kind: PERF_TYPE_SOFTWARE,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config: PERF_COUNT_SW_TASK_CLOCK,
+            sample_freq: frequency,
+            sample_type: PERF_SAMPLE_IP | PERF_SAMPLE_TID | PERF_SAMPLE_CALLCHAIN,
+            flags: ATTR_DISABLED
+                | ATTR_INHERIT
+                | ATTR_EXCLUDE_KERNEL
+                | ATTR_EXCLUDE_HV
+                | ATTR_MMAP
+                | ATTR_FREQ
+                | ATTR_ENABLE_ON_EXEC
+                | ATTR_WATERMARK
+                | ATTR_EXCLUDE_CALLCHAIN_KERNEL,
+            wakeup_watermark: (data_size / 2) as u32,
+            ..Default::default()
+        };
+
+        let any_cpu: c_int = -1;
+        let no_group: c_int = -1;
+        let no_flags: libc::c_ulong = 0;
+
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                pid,
+                any_cpu,
+                no_group,
+                no_flags,
+            )
+        };
+
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd = fd as c_int;
+
+        let buffer = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                page_size + data_size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+
+        if buffer == libc::MAP_FAILED {
+            let err = io::Error::last_os_error();
+            unsafe { libc::close(fd) };
+
+            return Err(err);
+        }
+
+        Ok(Sampler {
+            fd,
+            buffer: buffer as *mut u8,
+            page_size,
+        })
+    }
+
+    fn data_size(&self) -> usize {
+        RING_PAGES * self.page_size
+    }
+
+    /// Call `on_record` with the kind and body of each record the kernel has written since
+    /// the last drain, and hand their space back to the kernel.
+    fn drain(&mut self, mut on_record: impl FnMut(u32, &[u8])) {
+        unsafe {
+            let head_ptr = self.buffer.add(DATA_HEAD_OFFSET) as *const u64;
+            let tail_ptr = self.buffer.add(DATA_TAIL_OFFSET) as *mut u64;
+
+            let head = std::ptr::read_volatile(head_ptr);
+            fence(Ordering::Acquire);
+
+            let mut tail = std::ptr::read_volatile(tail_ptr);
+
+            while tail < head {
+                let header = self.read(tail, 8);
+                let kind = u32::from_ne_bytes([header[0], header[1], header[2], header[3]]);
+                let size = u16::from_ne_bytes([header[6], header[7]]) as u64;
+
+                if size < 8 {
+                    // a corrupt header; skip everything the kernel has written so far
+                    tail = head;
+                    break;
+                }
+
+                on_record(kind, &self.read(tail + 8, size as usize - 8));
+
+                tail += size;
+            }
+
+            fence(Ordering::SeqCst);
+            std::ptr::write_volatile(tail_ptr, tail);
+        }
+    }
+
+    /// Copy `len` bytes out of the ring buffer, starting at `position`
+    fn read(&self, position: u64, len: usize) -> Vec<u8> {
+        let data_size = self.data_size();
+        let start = position as usize % data_size;
+
+        unsafe {
+            let data = self.buffer.add(self.page_size);
+
+            (0..len)
+                .map(|i| *data.add((start + i) % data_size))
+                .collect()
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(
+                self.buffer as *mut c_void,
+                self.page_size + self.data_size(),
+            );
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Where a part of the app's executable was mapped into memory
+struct Mapping {
+    start: u64,
+    end: u64,
+    file_offset: u64,
+}
+
+#[derive(Default)]
+struct Recording {
+    mappings: Vec<Mapping>,
+    /// The addresses of each sample's stack, innermost first
+    stacks: Vec<Vec<u64>>,
+    lost: u64,
+}
+
+impl Recording {
+    fn record(&mut self, binary_path: &Path, kind: u32, body: &[u8]) {
+        let mut fields = Fields(body);
+
+        match kind {
+            PERF_RECORD_MMAP => {
+                let mapping = (|| {
+                    let _pid = fields.u32()?;
+                    let _tid = fields.u32()?;
+                    let start = fields.u64()?;
+                    let len = fields.u64()?;
+                    let file_offset = fields.u64()?;
+
+                    Some((start, len, file_offset))
+                })();
+
+                let filename = fields.0.split(|byte| *byte == 0).next().unwrap_or(&[]);
+
+                if let Some((start, len, file_offset)) = mapping {
+                    if Path::new(OsStr::from_bytes(filename)) == binary_path {
+                        self.mappings.push(Mapping {
+                            start,
+                            end: start + len,
+                            file_offset,
+                        });
+                    }
+                }
+            }
+            PERF_RECORD_SAMPLE => {
+                let stack = (|| {
+                    let ip = fields.u64()?;
+                    let _pid = fields.u32()?;
+                    let _tid = fields.u32()?;
+                    let len = fields.u64()?;
+
+                    let mut stack = Vec::with_capacity(len as usize);
+
+                    for _ in 0..len {
+                        let address = fields.u64()?;
+
+                        if address < PERF_CONTEXT_MAX {
+                            stack.push(address);
+                        }
+                    }
+
+                    if stack.is_empty() {
+                        stack.push(ip);
+                    }
+
+                    Some(stack)
+                })();
+
+                if let Some(stack) = stack {
+                    self.stacks.push(stack);
+                }
+            }
+            PERF_RECORD_LOST => {
+                let _id = fields.u64();
+
+                self.lost += fields.u64().unwrap_or(0);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads the fields of a record one after another
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Fields<'a> {
+    fn u32(&mut self) -> Option<u32> {
+        let (bytes, rest) = (self.0.get(..4)?, &self.0[4..]);
+        self.0 = rest;
+
+        Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let (bytes, rest) = (self.0.get(..8)?, &self.0[8..]);
+        self.0 = rest;
+
+        Some(u64::from_ne_bytes(bytes.try_into().ok()?))
+    }
+}
+
+/// Names the procedure and Roc source line at an address in the running app, using the
+/// executable's symbol table and debug info.
+struct Symbolizer<'data> {
+    mappings: Vec<Mapping>,
+    /// Whether the executable can be loaded anywhere, so addresses need translating
+    position_independent: bool,
+    /// The file range and address of each segment of the executable
+    segments: Vec<(u64, u64, u64)>,
+    symbols: object::SymbolMap<object::SymbolMapName<'data>>,
+    lines: Option<addr2line::Context<gimli::EndianSlice<'data, gimli::RunTimeEndian>>>,
+    cache: HashMap<u64, String>,
+}
+
+impl<'data> Symbolizer<'data> {
+    fn new(file: &object::File<'data>, mappings: Vec<Mapping>) -> Self {
+        let segments = file
+            .segments()
+            .map(|segment| {
+                let (offset, size) = segment.file_range();
+
+                (offset, offset + size, segment.address())
+            })
+            .collect();
+
+        Symbolizer {
+            mappings,
+            position_independent: file.kind() == ObjectKind::Dynamic,
+            segments,
+            symbols: file.symbol_map(),
+            lines: load_lines(file),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Where `address` in the running app is in the executable
+    fn file_address(&self, address: u64) -> Option<u64> {
+        if !self.position_independent {
+            return Some(address);
+        }
+
+        let mapping = self
+            .mappings
+            .iter()
+            .find(|mapping| mapping.start <= address && address < mapping.end)?;
+        let offset = address - mapping.start + mapping.file_offset;
+
+        self.segments
+            .iter()
+            .find(|(start, end, _)| *start <= offset && offset < *end)
+            .map(|(start, _, segment_address)| segment_address + (offset - start))
+    }
+
+    fn describe(&mut self, address: u64) -> String {
+        if let Some(description) = self.cache.get(&address) {
+            return description.clone();
+        }
+
+        let description = match self.file_address(address) {
+            Some(file_address) => self.describe_file_address(file_address),
+            None => UNKNOWN_FRAME.to_string(),
+        };
+
+        self.cache.insert(address, description.clone());
+
+        description
+    }
+
+    fn describe_file_address(&self, address: u64) -> String {
+        let name = match self.symbols.get(address) {
+            Some(symbol) => match roc_mono::mangle::demangle(symbol.name()) {
+                // flame graphs get wide enough without the types
+                Some(demangled) => match demangled.split_once(" : ") {
+                    Some((name, _)) => name.to_string(),
+                    None => demangled,
+                },
+                None => symbol.name().to_string(),
+            },
+            None => return UNKNOWN_FRAME.to_string(),
+        };
+
+        let location = self.lines.as_ref().and_then(|lines| {
+            let location = lines.find_location(address).ok()??;
+
+            Some((location.file?, location.line?))
+        });
+
+        match location {
+            Some((file, line)) => {
+                let file = Path::new(file)
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_else(|| file.into());
+
+                format!("{} ({}:{})", name, file, line)
+            }
+            None => name,
+        }
+    }
+}
+
+fn load_lines<'data>(
+    file: &object::File<'data>,
+) -> Option<addr2line::Context<gimli::EndianSlice<'data, gimli::RunTimeEndian>>> {
+    let endian = if file.is_little_endian() {
+        gimli::RunTimeEndian::Little
+    } else {
+        gimli::RunTimeEndian::Big
+    };
+
+    let load_section = |id: gimli::SectionId| -> Result<_, gimli::Error> {
+        let data = file
+            .section_by_name(id.name())
+            .and_then(|section| section.data().ok())
+            .unwrap_or(&[]);
+
+        Ok(gimli::EndianSlice::new(data, endian))
+    };
+
+    let dwarf = gimli::Dwarf::load(load_section).ok()?;
+
+    addr2line::Context::from_dwarf(dwarf).ok()
+}
+
+#[cfg(test)]
+mod test_profile {
+    use super::*;
+
+    fn u32_bytes(buf: &mut Vec<u8>, value: u32) {
+        buf.extend_from_slice(&value.to_ne_bytes());
+    }
+
+    fn u64_bytes(buf: &mut Vec<u8>, value: u64) {
+        buf.extend_from_slice(&value.to_ne_bytes());
+    }
+
+    fn mmap_record(start: u64, len: u64, file_offset: u64, filename: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+
+        u32_bytes(&mut body, 1);
+        u32_bytes(&mut body, 1);
+        u64_bytes(&mut body, start);
+        u64_bytes(&mut body, len);
+        u64_bytes(&mut body, file_offset);
+        body.extend_from_slice(filename.as_bytes());
+
+        // the kernel pads the name with zeroes to a multiple of 8 bytes
+        body.resize(body.len() + 8 - filename.len() % 8, 0);
+
+        body
+    }
+
+    fn sample_record(ip: u64, callchain: &[u64]) -> Vec<u8> {
+        let mut body = Vec::new();
+
+        u64_bytes(&mut body, ip);
+        u32_bytes(&mut body, 1);
+        u32_bytes(&mut body, 1);
+        u64_bytes(&mut body, callchain.len() as u64);
+
+        for address in callchain {
+            u64_bytes(&mut body, *address);
+        }
+
+        body
+    }
+
+    #[test]
+    fn fields() {
+        let mut bytes = Vec::new();
+
+        u32_bytes(&mut bytes, 7);
+        u64_bytes(&mut bytes, 1 << 40);
+        u32_bytes(&mut bytes, 9);
+
+        let mut fields = Fields(&bytes);
+
+        assert_eq!(fields.u32(), Some(7));
+        assert_eq!(fields.u64(), Some(1 << 40));
+        // only 4 bytes are left
+        assert_eq!(fields.u64(), None);
+        assert_eq!(fields.u32(), Some(9));
+        assert_eq!(fields.u32(), None);
+    }
+
+    #[test]
+    fn record_mappings_of_the_app() {
+        let binary_path = Path::new("/tmp/app");
+        let mut recording = Recording::default();
+
+        recording.record(
+            binary_path,
+            PERF_RECORD_MMAP,
+            &mmap_record(0x1000, 0x2000, 0x400, "/tmp/app"),
+        );
+        recording.record(
+            binary_path,
+            PERF_RECORD_MMAP,
+            &mmap_record(0x8000, 0x1000, 0, "/usr/lib/libc.so.6"),
+        );
+
+        assert_eq!(recording.mappings.len(), 1);
+
+        let mapping = &recording.mappings[0];
+
+        assert_eq!(
+            (mapping.start, mapping.end, mapping.file_offset),
+            (0x1000, 0x3000, 0x400)
+        );
+    }
+
+    #[test]
+    fn record_samples() {
+        let binary_path = Path::new("/tmp/app");
+        let mut recording = Recording::default();
+
+        // callchains start with a marker for the part of the stack they're in
+        let user_context = -512i64 as u64;
+
+        recording.record(
+            binary_path,
+            PERF_RECORD_SAMPLE,
+            &sample_record(0x10, &[user_context, 0x10, 0x20, 0x30]),
+        );
+        recording.record(binary_path, PERF_RECORD_SAMPLE, &sample_record(0x40, &[]));
+        // cut off partway through the callchain
+        recording.record(
+            binary_path,
+            PERF_RECORD_SAMPLE,
+            &sample_record(0x50, &[0x50, 0x60])[..32],
+        );
+
+        let mut lost = Vec::new();
+
+        u64_bytes(&mut lost, 0);
+        u64_bytes(&mut lost, 5);
+        recording.record(binary_path, PERF_RECORD_LOST, &lost);
+
+        assert_eq!(recording.stacks, vec![vec![0x10, 0x20, 0x30], vec![0x40]]);
+        assert_eq!(recording.lost, 5);
+    }
+
+    #[test]
+    fn folded_stacks() {
+        let stacks = vec![
+            vec![0x10, 0x21, 0x31],
+            vec![0x10, 0x21, 0x31],
+            vec![0x11, 0x31],
+            vec![0x99],
+        ];
+
+        let (folded, innermost) = fold_stacks(&stacks, |address| match address {
+            0x10 | 0x11 => "inner".to_string(),
+            0x20 => "middle".to_string(),
+            0x30 => "main".to_string(),
+            _ => UNKNOWN_FRAME.to_string(),
+        });
+
+        assert_eq!(
+            folded.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("[unknown]".to_string(), 1),
+                ("main;inner".to_string(), 1),
+                ("main;middle;inner".to_string(), 2),
+            ]
+        );
+        assert_eq!(innermost.get("inner"), Some(&3));
+        assert_eq!(innermost.get(UNKNOWN_FRAME), Some(&1));
+    }
+}
@@ -16,7 +16,7 @@ mod cli_run {
     };
     use const_format::concatcp;
     use indoc::indoc;
-    use roc_cli::{CMD_BUILD, CMD_CHECK, CMD_FORMAT, CMD_RUN};
+    use roc_cli::{CMD_BUILD, CMD_CHECK, CMD_FIX, CMD_FORMAT, CMD_RUN};
     use roc_test_utils::assert_multiline_str_eq;
     use serial_test::serial;
     use std::iter;
@@ -27,6 +27,9 @@ mod cli_run {
     const OPTIMIZE_FLAG: &str = concatcp!("--", roc_cli::FLAG_OPTIMIZE);
     const LINKER_FLAG: &str = concatcp!("--", roc_cli::FLAG_LINKER);
     const CHECK_FLAG: &str = concatcp!("--", roc_cli::FLAG_CHECK);
+    const STDIN_FLAG: &str = concatcp!("--", roc_cli::FLAG_STDIN);
+    const RANGE_FLAG: &str = concatcp!("--", roc_cli::FLAG_RANGE);
+    const DIFF_FLAG: &str = concatcp!("--", roc_cli::FLAG_DIFF);
     const PRECOMPILED_HOST: &str = concatcp!("--", roc_cli::FLAG_PRECOMPILED, "=true");
     #[allow(dead_code)]
     const TARGET_FLAG: &str = concatcp!("--", roc_cli::FLAG_TARGET);
@@ -948,6 +951,117 @@ mod cli_run {
         );
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn backtrace_on_panic() {
+        let file = fixture_file("backtrace", "Main.roc");
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!("--", roc_cli::FLAG_BACKTRACE),
+                file.to_str().unwrap(),
+            ],
+            &[],
+        );
+        assert!(out.status.success(), "bad status {:?}", out);
+
+        let binary = file.with_file_name("backtrace");
+        let out = run_cmd(binary.to_str().unwrap(), iter::empty(), &[]);
+        assert!(out.status.success(), "bad status {:?}", out);
+
+        // The c platform's roc_panic prints the frames from innermost to outermost.
+        let (message, frames) = out.stderr.split_once("Roc backtrace:\n\n").unwrap();
+        assert!(
+            message.contains("integer addition overflowed!"),
+            "{}",
+            message
+        );
+
+        let frames: Vec<&str> = frames.lines().map(str::trim).collect();
+        assert!(
+            frames[0].contains("increment (") && frames[0].contains("Main.roc:"),
+            "{:?}",
+            frames
+        );
+        assert!(
+            frames.iter().any(|frame| frame.contains("main (")),
+            "{:?}",
+            frames
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn surgical_shared_library() {
+        use object::read::elf::{Dyn, ElfFile64, FileHeader};
+        use object::{elf, LittleEndian};
+
+        /// A Roc `Str`, which keeps strings shorter than itself inline.
+        #[repr(C)]
+        struct RocStr([u8; 24]);
+
+        impl RocStr {
+            fn small(text: &str) -> Self {
+                let mut bytes = [0; 24];
+                bytes[..text.len()].copy_from_slice(text.as_bytes());
+                bytes[23] = text.len() as u8 | 0x80;
+                RocStr(bytes)
+            }
+
+            unsafe fn as_bytes(&self) -> &[u8] {
+                let last = self.0[23];
+                if last & 0x80 != 0 {
+                    &self.0[..(last & 0x7f) as usize]
+                } else {
+                    let word =
+                        |i: usize| usize::from_ne_bytes(self.0[i..i + 8].try_into().unwrap());
+                    std::slice::from_raw_parts(word(0) as *const u8, word(8))
+                }
+            }
+        }
+
+        let file = example_file("ruby-interop", "main.roc");
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!("--", roc_cli::FLAG_LIB),
+                LINKER_FLAG,
+                "surgical",
+                file.to_str().unwrap(),
+            ],
+            &[],
+        );
+        assert!(out.status.success(), "bad status {:?}", out);
+
+        // Anything linking against the library will look for it by its soname.
+        let lib_path = file.with_file_name("libhello.so");
+        let data = std::fs::read(&lib_path).unwrap();
+        let elf = ElfFile64::<LittleEndian>::parse(&*data).unwrap();
+        let sections = elf.raw_header().sections(LittleEndian, &*data).unwrap();
+        let (dynamic, link) = sections.dynamic(LittleEndian, &*data).unwrap().unwrap();
+        let strings = sections.strings(LittleEndian, &*data, link).unwrap();
+        let soname = dynamic
+            .iter()
+            .find(|d| d.d_tag(LittleEndian) == elf::DT_SONAME as u64)
+            .map(|d| d.string(LittleEndian, strings).unwrap())
+            .unwrap();
+        assert_eq!(soname, b"libhello.so");
+
+        let output = unsafe {
+            let lib = libloading::Library::new(&lib_path).unwrap();
+            let make_it_roc: libloading::Symbol<unsafe extern "C" fn(*mut RocStr, *const RocStr)> =
+                lib.get(b"roc__mainForHost_1_exposed_generic").unwrap();
+
+            let arg = RocStr::small("Hi");
+            let mut ret = RocStr([0; 24]);
+            make_it_roc(&mut ret, &arg);
+
+            // The string was allocated by the library's host, so leave it be.
+            String::from_utf8(ret.as_bytes().to_vec()).unwrap()
+        };
+        assert_eq!(output, "Hi, OH YEAH!!! 🤘🤘");
+    }
+
     #[test]
     fn known_type_error() {
         check_compile_error(
@@ -1062,6 +1176,159 @@ mod cli_run {
         // This doesn't fail, since only "Formatted.roc" and non-roc files are present in this folder
         check_format_check_as_expected(&fixtures_dir("format/formatted_directory"), true);
     }
+
+    #[test]
+    fn format_check_diff() {
+        let file = fixture_file("format", "NotFormatted.roc");
+        let out = run_roc(
+            [CMD_FORMAT, file.to_str().unwrap(), CHECK_FLAG, DIFF_FLAG],
+            &[],
+        );
+
+        assert!(!out.status.success());
+        assert!(out.stdout.contains("@@ "), "stdout was: {}", out.stdout);
+        assert!(out.stdout.contains("-  provides [main] to pf"));
+        assert!(out.stdout.contains("+    provides [main] to pf"));
+        assert!(out.stderr.contains("This file needs to be reformatted"));
+        assert!(out.stderr.contains("NotFormatted.roc"));
+
+        let formatted_file = fixture_file("format", "Formatted.roc");
+        let out = run_roc(
+            [
+                CMD_FORMAT,
+                formatted_file.to_str().unwrap(),
+                CHECK_FLAG,
+                DIFF_FLAG,
+            ],
+            &[],
+        );
+
+        assert!(out.status.success());
+        assert_eq!(out.stdout, "");
+    }
+
+    #[test]
+    fn format_stdin() {
+        let out = run_roc(
+            [CMD_FORMAT, STDIN_FLAG],
+            &["interface Foo exposes [] imports []\n\nx =   1\n\ny =   2\n"],
+        );
+
+        assert!(out.status.success(), "stderr was: {}", out.stderr);
+        assert_multiline_str_eq!(
+            out.stdout.as_str(),
+            "interface Foo exposes [] imports []\n\nx = 1\n\ny = 2\n"
+        );
+    }
+
+    #[test]
+    fn format_stdin_range() {
+        // Only `y` overlaps the range, so `x` is left as it was
+        let out = run_roc(
+            [CMD_FORMAT, STDIN_FLAG, RANGE_FLAG, "46:47"],
+            &["interface Foo exposes [] imports []\n\nx =   1\n\ny =   2\n"],
+        );
+
+        assert!(out.status.success(), "stderr was: {}", out.stderr);
+        assert_multiline_str_eq!(
+            out.stdout.as_str(),
+            "interface Foo exposes [] imports []\n\nx =   1\n\ny = 2\n"
+        );
+    }
+
+    /// `roc fix` rewrites files, so work on copies of the fixtures. Returns the path of
+    /// the copy of the module to fix.
+    fn copy_fix_fixtures(dir: &Path) -> PathBuf {
+        for name in ["NeedsFixes.roc", "Helper.roc", "Spare.roc"] {
+            std::fs::copy(fixture_file("fix", name), dir.join(name)).unwrap();
+        }
+
+        dir.join("NeedsFixes.roc")
+    }
+
+    #[test]
+    fn fix_unused_argument_and_defs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = copy_fix_fixtures(dir.path());
+
+        let out = run_roc([CMD_FIX, file.to_str().unwrap()], &[]);
+
+        assert!(out.status.success(), "stderr was: {}", out.stderr);
+        assert!(
+            out.stdout.contains("NeedsFixes.roc"),
+            "stdout was: {}",
+            out.stdout
+        );
+
+        let fixed = std::fs::read_to_string(&file).unwrap();
+
+        assert!(
+            fixed.contains("ignore = \\_x, y -> y"),
+            "fixed was: {}",
+            fixed
+        );
+        assert!(!fixed.contains("unused"), "fixed was: {}", fixed);
+        assert!(fixed.contains("n * 2"), "fixed was: {}", fixed);
+    }
+
+    #[test]
+    fn fix_adds_missing_branches() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = copy_fix_fixtures(dir.path());
+
+        let out = run_roc([CMD_FIX, file.to_str().unwrap()], &[]);
+
+        assert!(out.status.success(), "stderr was: {}", out.stderr);
+        assert!(
+            out.stdout.contains("The `when` on line 7 of"),
+            "stdout was: {}",
+            out.stdout
+        );
+        assert!(
+            out.stdout.contains("\n    Green\n    Blue\n"),
+            "stdout was: {}",
+            out.stdout
+        );
+
+        // What the missing branches should do is up to the programmer, so they're added
+        // returning a name that isn't defined
+        let fixed = std::fs::read_to_string(&file).unwrap();
+
+        assert!(
+            fixed.contains(
+                "        Red -> \"red\"\n        Green -> todo\n        Blue -> todo\n\n"
+            ),
+            "fixed was: {}",
+            fixed
+        );
+    }
+
+    #[test]
+    fn fix_unused_import_and_unexposed_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = copy_fix_fixtures(dir.path());
+
+        let out = run_roc([CMD_FIX, file.to_str().unwrap()], &[]);
+
+        assert!(out.status.success(), "stderr was: {}", out.stderr);
+
+        let fixed = std::fs::read_to_string(&file).unwrap();
+
+        assert!(fixed.contains("imports [Helper]\n"), "fixed was: {}", fixed);
+        assert!(
+            fixed.contains("Helper.shout (Helper.whisper name)"),
+            "fixed was: {}",
+            fixed
+        );
+
+        let helper = std::fs::read_to_string(dir.path().join("Helper.roc")).unwrap();
+
+        assert!(
+            helper.contains("exposes [shout, whisper]\n"),
+            "helper was: {}",
+            helper
+        );
+    }
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,10 @@
+app "backtrace"
+    packages { pf: "../../../../../examples/platform-switching/c-platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+main : Str
+main = Num.toStr (increment Num.maxI64)
+
+increment : I64 -> I64
+increment = \n -> n + 1
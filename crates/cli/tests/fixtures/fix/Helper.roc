@@ -0,0 +1,9 @@
+interface Helper
+    exposes [shout]
+    imports []
+
+shout : Str -> Str
+shout = \message -> "\(whisper message)!"
+
+whisper : Str -> Str
+whisper = \message -> message
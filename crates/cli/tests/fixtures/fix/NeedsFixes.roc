@@ -0,0 +1,22 @@
+interface NeedsFixes
+    exposes [describe, ignore, double, greet]
+    imports [Helper, Spare]
+
+describe : [Red, Green, Blue] -> Str
+describe = \color ->
+    when color is
+        Red -> "red"
+
+ignore = \x, y -> y
+
+double = \n ->
+    unusedLocal = n
+
+    n * 2
+
+## Helper doesn't expose `whisper` yet
+greet = \name -> Helper.shout (Helper.whisper name)
+
+## Not exposed, and not used by anything that is
+unused : Str
+unused = "unused"
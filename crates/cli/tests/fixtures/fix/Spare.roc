@@ -0,0 +1,7 @@
+interface Spare
+    exposes [spare]
+    imports []
+
+## Imported by NeedsFixes, which doesn't use it
+spare : Str
+spare = "spare"
@@ -12,7 +12,7 @@ use std::process::{self, Child, Command, Output};
 use target_lexicon::{Architecture, OperatingSystem, Triple};
 use wasi_libc_sys::{WASI_COMPILER_RT_PATH, WASI_LIBC_PATH};
 
-fn zig_executable() -> String {
+pub fn zig_executable() -> String {
     match std::env::var("ROC_ZIG") {
         Ok(path) => path,
         Err(_) => "zig".into(),
@@ -460,6 +460,14 @@ pub fn build_swift_host_native(
     command.output().unwrap()
 }
 
+/// Whether there's source code next to the host for `rebuild_host` to build it from,
+/// as opposed to only a host which was built ahead of time.
+pub fn host_source_exists(host_input_path: &Path) -> bool {
+    ["host.zig", "Cargo.toml", "host.rs", "host.c", "host.swift"]
+        .iter()
+        .any(|host_src| host_input_path.with_file_name(host_src).exists())
+}
+
 pub fn rebuild_host(
     opt_level: OptLevel,
     target: &Triple,
@@ -907,7 +915,6 @@ fn link_linux(
     let ld_linux = ld_linux.unwrap();
     let ld_linux = ld_linux.to_str().unwrap();
 
-    let mut soname;
     let (base_args, output_path) = match link_type {
         LinkType::Executable => (
             // Presumably this S stands for Static, since if we include Scrt1.o
@@ -916,27 +923,23 @@ fn link_linux(
             output_path,
         ),
         LinkType::Dylib => {
-            // TODO: do we actually need the version number on this?
-            // Do we even need the "-soname" argument?
-            //
-            // See https://software.intel.com/content/www/us/en/develop/articles/create-a-unix-including-linux-shared-library.html
-
-            soname = output_path.clone();
-            soname.set_extension("so.1");
-
             let mut output_path = output_path;
 
-            output_path.set_extension("so.1.0");
+            output_path.set_extension("so");
+
+            // Whatever links against the library records its soname, and looks for a file
+            // by that name at runtime, so it must be the file's name rather than its path.
+            let soname = output_path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned();
 
             (
                 // TODO: find a way to avoid using a vec! here - should theoretically be
                 // able to do this somehow using &[] but the borrow checker isn't having it.
                 // Also find a way to have these be string slices instead of Strings.
-                vec![
-                    "-shared".to_string(),
-                    "-soname".to_string(),
-                    soname.as_path().to_str().unwrap().to_string(),
-                ],
+                vec!["-shared".to_string(), "-soname".to_string(), soname],
                 output_path,
             )
         }
@@ -1215,6 +1218,17 @@ pub fn llvm_module_to_dylib(
     module: &inkwell::module::Module,
     target: &Triple,
     opt_level: OptLevel,
+) -> Result<Library, Error> {
+    llvm_module_to_dylib_with_host(module, target, opt_level, &[])
+}
+
+/// Like `llvm_module_to_dylib`, but also links in the given object files, e.g. a host
+/// which provides the `roc_fx_` functions the module's effects call.
+pub fn llvm_module_to_dylib_with_host(
+    module: &inkwell::module::Module,
+    target: &Triple,
+    opt_level: OptLevel,
+    host_objects: &[&Path],
 ) -> Result<Library, Error> {
     use crate::target::{self, convert_opt_level};
     use inkwell::targets::{FileType, RelocMode};
@@ -1235,11 +1249,15 @@ pub fn llvm_module_to_dylib(
         .write_to_file(module, FileType::Object, &app_o_file)
         .expect("Writing .o file failed");
 
+    let mut input_paths = vec![app_o_file.to_str().unwrap()];
+
+    input_paths.extend(host_objects.iter().map(|path| path.to_str().unwrap()));
+
     // Link app.o into a dylib - e.g. app.so or app.dylib
     let (mut child, dylib_path) = link(
         &Triple::host(),
         app_o_file.clone(),
-        &[app_o_file.to_str().unwrap()],
+        &input_paths,
         LinkType::Dylib,
     )
     .unwrap();
@@ -1,5 +1,7 @@
+use roc_gen_llvm::llvm::backtrace::add_backtrace_frame_fn;
 pub use roc_gen_llvm::llvm::build::FunctionIterator;
 use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
+use roc_gen_llvm::llvm::debug_info::{add_debug_info_flags, SourceInfo};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_load::{EntryPoint, LoadedModule, MonomorphizedModule};
 use roc_module::symbol::{Interns, ModuleId};
@@ -165,6 +167,7 @@ pub fn gen_from_mono_module(
     app_o_file: &Path,
     opt_level: OptLevel,
     emit_debug_info: bool,
+    emit_backtrace: bool,
     preprocessed_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
 ) -> CodeGenTiming {
@@ -177,6 +180,7 @@ pub fn gen_from_mono_module(
             app_o_file,
             opt_level,
             emit_debug_info,
+            emit_backtrace,
         ),
         OptLevel::Development => gen_from_mono_module_dev(
             arena,
@@ -200,6 +204,7 @@ pub fn gen_from_mono_module_llvm(
     app_o_file: &Path,
     opt_level: OptLevel,
     emit_debug_info: bool,
+    emit_backtrace: bool,
 ) -> CodeGenTiming {
     use crate::target::{self, convert_opt_level};
     use inkwell::attributes::{Attribute, AttributeLoc};
@@ -253,6 +258,17 @@ pub fn gen_from_mono_module_llvm(
     let (dibuilder, compile_unit) = roc_gen_llvm::llvm::build::Env::new_debug_info(module);
     let (mpm, _fpm) = roc_gen_llvm::llvm::build::construct_optimization_passes(module, opt_level);
 
+    let source_info = if emit_debug_info || emit_backtrace {
+        Some(SourceInfo::new(
+            &loaded.sources,
+            loaded.def_regions,
+            loaded.stmt_regions,
+            emit_debug_info,
+        ))
+    } else {
+        None
+    };
+
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
         arena,
@@ -265,6 +281,8 @@ pub fn gen_from_mono_module_llvm(
         target_info,
         mode: LlvmBackendMode::Binary,
         exposed_to_host: loaded.exposed_to_host.values.keys().copied().collect(),
+        source_info: source_info.as_ref(),
+        shadow_stack: emit_backtrace,
     };
 
     // does not add any externs for this mode (we have a host) but cleans up some functions around
@@ -286,10 +304,25 @@ pub fn gen_from_mono_module_llvm(
         Some(&app_ll_file),
     );
 
+    if emit_backtrace {
+        add_backtrace_frame_fn(&env);
+    }
+
     env.dibuilder.finalize();
 
-    // we don't use the debug info, and it causes weird errors.
-    module.strip_debug_info();
+    if emit_debug_info {
+        add_debug_info_flags(module, &env);
+
+        // keep frame pointers, so debuggers and profilers can walk the stack
+        let frame_pointer = context.create_string_attribute("frame-pointer", "all");
+
+        for function in FunctionIterator::from_module(module) {
+            function.add_attribute(AttributeLoc::Function, frame_pointer);
+        }
+    } else {
+        // we don't use the debug info, and it causes weird errors.
+        module.strip_debug_info();
+    }
 
     // Uncomment this to see the module's optimized LLVM instruction output:
     // env.module.print_to_stderr();
@@ -314,98 +347,27 @@ pub fn gen_from_mono_module_llvm(
     let code_gen = code_gen_start.elapsed();
     let emit_o_file_start = Instant::now();
 
-    // annotate the LLVM IR output with debug info
-    // so errors are reported with the line number of the LLVM source
-    if emit_debug_info {
-        module.strip_debug_info();
-
-        let mut app_ll_dbg_file = PathBuf::from(roc_file_path);
-        app_ll_dbg_file.set_extension("dbg.ll");
-
-        let mut app_bc_file = PathBuf::from(roc_file_path);
-        app_bc_file.set_extension("bc");
-
-        use std::process::Command;
-
-        // write the ll code to a file, so we can modify it
-        module.print_to_file(&app_ll_file).unwrap();
-
-        // run the debugir https://github.com/vaivaswatha/debugir tool
-        match Command::new("debugir")
-            .args(&["-instnamer", app_ll_file.to_str().unwrap()])
-            .output()
-        {
-            Ok(_) => {}
-            Err(error) => {
-                use std::io::ErrorKind;
-                match error.kind() {
-                    ErrorKind::NotFound => panic!(
-                        r"I could not find the `debugir` tool on the PATH, install it from https://github.com/vaivaswatha/debugir"
-                    ),
-                    _ => panic!("{:?}", error),
-                }
-            }
-        }
-
-        use target_lexicon::Architecture;
-        match target.architecture {
-            Architecture::X86_64
-            | Architecture::X86_32(_)
-            | Architecture::Aarch64(_)
-            | Architecture::Wasm32 => {
-                let ll_to_bc = Command::new("llvm-as")
-                    .args(&[
-                        app_ll_dbg_file.to_str().unwrap(),
-                        "-o",
-                        app_bc_file.to_str().unwrap(),
-                    ])
-                    .output()
-                    .unwrap();
-
-                assert!(ll_to_bc.stderr.is_empty(), "{:#?}", ll_to_bc);
-
-                let llc_args = &[
-                    "-relocation-model=pic",
-                    "-filetype=obj",
-                    app_bc_file.to_str().unwrap(),
-                    "-o",
-                    app_o_file.to_str().unwrap(),
-                ];
-
-                // write the .o file. Note that this builds the .o for the local machine,
-                // and ignores the `target_machine` entirely.
-                //
-                // different systems name this executable differently, so we shotgun for
-                // the most common ones and then give up.
-                let bc_to_object = Command::new("llc").args(llc_args).output().unwrap();
-
-                assert!(bc_to_object.stderr.is_empty(), "{:#?}", bc_to_object);
-            }
-            _ => unreachable!(),
+    // Emit the .o file
+    use target_lexicon::Architecture;
+    match target.architecture {
+        Architecture::X86_64 | Architecture::X86_32(_) | Architecture::Aarch64(_) => {
+            let reloc = RelocMode::PIC;
+            let target_machine =
+                target::target_machine(target, convert_opt_level(opt_level), reloc).unwrap();
+
+            target_machine
+                .write_to_file(env.module, FileType::Object, app_o_file)
+                .expect("Writing .o file failed");
         }
-    } else {
-        // Emit the .o file
-        use target_lexicon::Architecture;
-        match target.architecture {
-            Architecture::X86_64 | Architecture::X86_32(_) | Architecture::Aarch64(_) => {
-                let reloc = RelocMode::PIC;
-                let target_machine =
-                    target::target_machine(target, convert_opt_level(opt_level), reloc).unwrap();
-
-                target_machine
-                    .write_to_file(env.module, FileType::Object, app_o_file)
-                    .expect("Writing .o file failed");
-            }
-            Architecture::Wasm32 => {
-                // Useful for debugging
-                // module.print_to_file(app_ll_file);
-                module.write_bitcode_to_path(app_o_file);
-            }
-            _ => panic!(
-                "TODO gracefully handle unsupported architecture: {:?}",
-                target.architecture
-            ),
+        Architecture::Wasm32 => {
+            // Useful for debugging
+            // module.print_to_file(app_ll_file);
+            module.write_bitcode_to_path(app_o_file);
         }
+        _ => panic!(
+            "TODO gracefully handle unsupported architecture: {:?}",
+            target.architecture
+        ),
     }
 
     let emit_o_file = emit_o_file_start.elapsed();
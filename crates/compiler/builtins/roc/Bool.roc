@@ -23,10 +23,12 @@ Bool : [True, False]
 ## For example, in some languages, `enablePets && likesDogs user` would compile
 ## to the equivalent of:
 ##
-##     if enablePets then
-##         likesDogs user
-##     else
-##         False
+## ```roc ignore
+## if enablePets then
+##     likesDogs user
+## else
+##     False
+## ```
 ##
 ## In Roc, however, `&&` and `||` are not special. They work the same way as
 ## other functions. Conditionals like `if` and `when` have a performance cost,
@@ -52,11 +52,9 @@ interface Dict
 ##
 ## We can remove an element from the dictionary, like so:
 ##
-##     populationByCity
-##         |> Dict.remove "Philadelphia"
-##         |> Dict.keys
-##         ==
-##         ["London", "Amsterdam", "Shanghai", "Delhi"]
+##     withoutPhiladelphia = Dict.remove populationByCity "Philadelphia"
+##
+##     Dict.keys withoutPhiladelphia == ["London", "Amsterdam", "Shanghai", "Delhi"]
 ##
 ## Notice that the order changed! Philadelphia has been not only removed from the list, but Amsterdam - the last
 ## entry we inserted - has been moved into the spot where Philadelphia was previously. This is exactly what
@@ -115,8 +115,8 @@ interface List
 ##
 ## Let's turn this example into a function.
 ##
-##     getRatings = \first ->
-##         ratings = [first, 4, 3]
+##     getRatings = \firstRating ->
+##         ratings = [firstRating, 4, 3]
 ##
 ##         { foo: ratings, bar: ratings }
 ##
@@ -137,8 +137,8 @@ interface List
 ##
 ## Let's change the last line to be `(getRatings 5).bar` instead of `getRatings 5`:
 ##
-##     getRatings = \first ->
-##         ratings = [first, 4, 3]
+##     getRatings = \firstRating ->
+##         ratings = [firstRating, 4, 3]
 ##
 ##         { foo: ratings, bar: ratings }
 ##
@@ -151,7 +151,9 @@ interface List
 ##
 ## Finally let's suppose the final line were changed to this:
 ##
-##     List.first (getRatings 5).bar
+## ```roc ignore
+## List.first (getRatings 5).bar
+## ```
 ##
 ## This call to [List.first] means that even the list in the `bar` field has become
 ## inaccessible. As such, this line will cause the list's refcount to get
@@ -164,23 +166,27 @@ interface List
 ##
 ## Here's the example using a list of numbers.
 ##
-##     nums = [1, 2, 3, 4, 5, 6, 7]
+## ```roc ignore
+## nums = [1, 2, 3, 4, 5, 6, 7]
 ##
-##     first = List.first nums
-##     last = List.last nums
+## first = List.first nums
+## last = List.last nums
 ##
-##     first
+## first
+## ```
 ##
 ## It makes a list, calls [List.first] and [List.last] on it, and then returns `first`.
 ##
 ## Here's the equivalent code with a list of lists:
 ##
-##     lists = [[1], [2, 3], [], [4, 5, 6, 7]]
+## ```roc ignore
+## lists = [[1], [2, 3], [], [4, 5, 6, 7]]
 ##
-##     first = List.first lists
-##     last = List.last lists
+## first = List.first lists
+## last = List.last lists
 ##
-##     first
+## first
+## ```
 ##
 ## TODO explain how in the former example, when we go to free `nums` at the end,
 ## we can free it immediately because there are no other refcounts. However,
@@ -303,9 +309,11 @@ last = \list ->
 ##
 ## This is useful in pipelines, like so:
 ##
-##     websites =
-##         Str.concat domain ".com"
-##             |> List.single
+## ```roc ignore
+## websites =
+##     Str.concat domain ".com"
+##         |> List.single
+## ```
 ##
 single : a -> List a
 single = \x -> [x]
@@ -364,10 +372,10 @@ contains = \list, needle ->
 ## You can use it in a pipeline:
 ##
 ##     [2, 4, 8]
-##         |> List.walk { start: 0, step: Num.add }
+##         |> List.walk 0 Num.add
 ##
 ## This returns 14 because:
-## * `state` starts at 0 (because of `start: 0`)
+## * `state` starts at 0 (the second argument to [List.walk])
 ## * Each `step` runs `Num.add state elem`, and the return value becomes the new `state`.
 ##
 ## Here is a table of how `state` changes as [List.walk] walks over the elements
@@ -381,11 +389,11 @@ contains = \list, needle ->
 ## 6       | 8      | 14
 ##
 ## So `state` goes through these changes:
-## 1. `0` (because of `start: 0`)
+## 1. `0` (the second argument to [List.walk])
 ## 2. `1` (because of `Num.add state elem` with `state` = 0 and `elem` = 1
 ##
 ##     [1, 2, 3]
-##         |> List.walk { start: 0, step: Num.sub }
+##         |> List.walk 0 Num.sub
 ##
 ## This returns -6 because
 ##
@@ -921,7 +929,7 @@ split = \elements, userSplitIndex ->
 ## Returns the elements before the first occurrence of a delimiter, as well as the
 ## remaining elements after that occurrence. If the delimiter is not found, returns `Err`.
 ##
-##     List.splitFirst [Foo, Z, Bar, Z, Baz] Z == Ok { before: [Foo], after: [Bar, Baz] }
+##     List.splitFirst [Foo, Z, Bar, Z, Baz] Z == Ok { before: [Foo], after: [Bar, Z, Baz] }
 splitFirst : List elem, elem -> Result { before : List elem, after : List elem } [NotFound]*
 splitFirst = \list, delimiter ->
     when List.findFirstIndex list (\elem -> elem == delimiter) is
@@ -936,7 +944,7 @@ splitFirst = \list, delimiter ->
 ## Returns the elements before the last occurrence of a delimiter, as well as the
 ## remaining elements after that occurrence. If the delimiter is not found, returns `Err`.
 ##
-##     List.splitLast [Foo, Z, Bar, Z, Baz] Z == Ok { before: [Foo, Bar], after: [Baz] }
+##     List.splitLast [Foo, Z, Bar, Z, Baz] Z == Ok { before: [Foo, Z, Bar], after: [Baz] }
 splitLast : List elem, elem -> Result { before : List elem, after : List elem } [NotFound]*
 splitLast = \list, delimiter ->
     when List.findLastIndex list (\elem -> elem == delimiter) is
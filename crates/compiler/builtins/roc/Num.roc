@@ -151,7 +151,7 @@ interface Num
 ##
 ## This is useful for functions that can work on either, for example #Num.add, whose type is:
 ##
-## ```
+## ```roc ignore
 ## add : Num a, Num a -> Num a
 ## ```
 ##
@@ -351,9 +351,9 @@ Int range : Num (Integer range)
 ##
 ## In contrast, suppose we use `f32` or `f64` for one of these numbers:
 ##
-##     wasItPrecise = 0.1f64 + 0.2 == 0.3
+##     wasItPreciseF64 = 0.1f64 + 0.2 == 0.3
 ##
-## Here, `wasItPrecise` will be `False` because the entire calculation will have
+## Here, `wasItPreciseF64` will be `False` because the entire calculation will have
 ## been done in a base-2 floating point calculation, which causes noticeable
 ## precision loss in this case.
 ##
@@ -66,9 +66,17 @@ interface Str
 ##
 ## You can get the number of graphemes in a string by calling [Str.countGraphemes] on it:
 ##
-##     Str.countGraphemes "Roc!"
-##     Str.countGraphemes "折り紙"
-##     Str.countGraphemes "🕊"
+## ```
+## Str.countGraphemes "Roc!" == 4
+## ```
+##
+## ```
+## Str.countGraphemes "折り紙" == 3
+## ```
+##
+## ```
+## Str.countGraphemes "🕊" == 1
+## ```
 ##
 ## > The `countGraphemes` function walks through the entire string to get its answer,
 ## > so if you want to check whether a string is empty, you'll get much better performance
@@ -158,9 +166,17 @@ repeat : Str, Nat -> Str
 ## Count the number of [extended grapheme clusters](http://www.unicode.org/glossary/#extended_grapheme_cluster)
 ## in the string.
 ##
-##     Str.countGraphemes "Roc!"   # 4
-##     Str.countGraphemes "‰∏ÉÂ∑ßÊùø" # 3
-##     Str.countGraphemes "üïä"     # 1
+## ```
+## Str.countGraphemes "Roc!" == 4
+## ```
+##
+## ```
+## Str.countGraphemes "七巧板" == 3
+## ```
+##
+## ```
+## Str.countGraphemes "🕊" == 1
+## ```
 countGraphemes : Str -> Nat
 
 ## If the string begins with a [Unicode code point](http://www.unicode.org/glossary/#code_point)
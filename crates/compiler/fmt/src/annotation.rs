@@ -226,12 +226,17 @@ impl<'a> Formattable for TypeAnnotation<'a> {
                         buf.newline();
                     }
 
-                    (&argument.value).format_with_options(
-                        buf,
-                        Parens::InFunctionType,
-                        Newlines::No,
-                        indent,
-                    );
+                    // Apart from functions in annotations, only records and tag unions are
+                    // broken up when they don't fit on the line. Everything else keeps its
+                    // parts on one line, since its layout depends on whether they're multiline.
+                    buf.without_breaks(|buf| {
+                        (&argument.value).format_with_options(
+                            buf,
+                            Parens::InFunctionType,
+                            Newlines::No,
+                            indent,
+                        )
+                    });
 
                     if it.peek().is_some() {
                         buf.push_str(",");
@@ -251,12 +256,14 @@ impl<'a> Formattable for TypeAnnotation<'a> {
                 buf.push_str("->");
                 buf.spaces(1);
 
-                (&result.value).format_with_options(
-                    buf,
-                    Parens::InFunctionType,
-                    Newlines::No,
-                    indent,
-                );
+                buf.without_breaks(|buf| {
+                    (&result.value).format_with_options(
+                        buf,
+                        Parens::InFunctionType,
+                        Newlines::No,
+                        indent,
+                    )
+                });
 
                 if needs_parens {
                     buf.push(')')
@@ -278,15 +285,17 @@ impl<'a> Formattable for TypeAnnotation<'a> {
 
                 buf.push_str(name);
 
-                for argument in *arguments {
-                    buf.spaces(1);
-                    (&argument.value).format_with_options(
-                        buf,
-                        Parens::InApply,
-                        Newlines::No,
-                        indent,
-                    );
-                }
+                buf.without_breaks(|buf| {
+                    for argument in *arguments {
+                        buf.spaces(1);
+                        (&argument.value).format_with_options(
+                            buf,
+                            Parens::InApply,
+                            Newlines::No,
+                            indent,
+                        );
+                    }
+                });
 
                 if write_parens {
                     buf.push(')')
@@ -314,8 +323,10 @@ impl<'a> Formattable for TypeAnnotation<'a> {
 
             As(lhs, _spaces, TypeHeader { name, vars }) => {
                 // TODO use _spaces?
-                lhs.value
-                    .format_with_options(buf, Parens::InFunctionType, Newlines::No, indent);
+                buf.without_breaks(|buf| {
+                    lhs.value
+                        .format_with_options(buf, Parens::InFunctionType, Newlines::No, indent)
+                });
                 buf.spaces(1);
                 buf.push_str("as");
                 buf.spaces(1);
@@ -327,7 +338,7 @@ impl<'a> Formattable for TypeAnnotation<'a> {
                 }
             }
 
-            Where(annot, has_clauses) => {
+            Where(annot, has_clauses) => buf.without_breaks(|buf| {
                 annot.format_with_options(buf, parens, newlines, indent);
                 buf.spaces(1);
                 for (i, has) in has_clauses.iter().enumerate() {
@@ -335,7 +346,7 @@ impl<'a> Formattable for TypeAnnotation<'a> {
                     buf.spaces(1);
                     has.format_with_options(buf, parens, newlines, indent);
                 }
-            }
+            }),
 
             SpaceBefore(ann, spaces) => {
                 let is_function = matches!(ann, TypeAnnotation::Function(..));
@@ -527,10 +538,12 @@ impl<'a> Formattable for Tag<'a> {
                         arg.format_with_options(buf, Parens::InApply, Newlines::No, arg_indent);
                     }
                 } else {
-                    for arg in *args {
-                        buf.spaces(1);
-                        arg.format_with_options(buf, Parens::InApply, Newlines::No, indent);
-                    }
+                    buf.without_breaks(|buf| {
+                        for arg in *args {
+                            buf.spaces(1);
+                            arg.format_with_options(buf, Parens::InApply, Newlines::No, indent);
+                        }
+                    });
                 }
             }
             Tag::SpaceBefore(_, _) | Tag::SpaceAfter(_, _) => unreachable!(),
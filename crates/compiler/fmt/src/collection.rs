@@ -31,7 +31,16 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
         Braces::Square => ']',
     };
 
-    if items.is_multiline() {
+    // A collection that doesn't fit on the line is broken up with one item per line,
+    // the same way as one that was written over several lines.
+    let is_multiline = items.is_multiline()
+        || (newline == Newlines::No
+            && !items.is_empty()
+            && !buf.fits_on_line(indent, |buf| {
+                fmt_collection(buf, indent, braces, items, newline)
+            }));
+
+    if is_multiline {
         let braces_indent = indent;
         let item_indent = braces_indent + INDENT;
         if newline == Newlines::Yes {
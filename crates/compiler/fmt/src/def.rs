@@ -103,10 +103,15 @@ impl<'a> Formattable for TypeDef<'a> {
                     false
                 };
 
-                let make_multiline = ann.is_multiline() || has_abilities_multiline;
+                let ann_start = buf.position();
 
                 ann.format(buf, indent);
 
+                // The annotation may have been broken up for being too long
+                let make_multiline = ann.is_multiline()
+                    || has_abilities_multiline
+                    || buf.has_newline_since(ann_start);
+
                 if let Some(has_abilities) = has_abilities {
                     buf.spaces(1);
 
@@ -175,14 +180,32 @@ impl<'a> Formattable for ValueDef<'a> {
             Annotation(loc_pattern, loc_annotation) => {
                 loc_pattern.format(buf, indent);
 
-                if loc_annotation.is_multiline() {
+                if is_too_long_function(buf, loc_annotation, indent) {
+                    buf.push_str(" :");
+                    buf.newline();
+                    loc_annotation.format_with_options(
+                        buf,
+                        Parens::NotNeeded,
+                        Newlines::Yes,
+                        indent + INDENT,
+                    );
+                } else if loc_annotation.is_multiline() {
                     buf.push_str(" :");
 
                     let should_outdent = match loc_annotation.value {
                         TypeAnnotation::SpaceBefore(sub_def, spaces) => match sub_def {
                             TypeAnnotation::Record { .. } | TypeAnnotation::TagUnion { .. } => {
                                 let is_only_newlines = spaces.iter().all(|s| s.is_newline());
-                                is_only_newlines && sub_def.is_multiline()
+                                is_only_newlines
+                                    && (sub_def.is_multiline()
+                                        || !buf.fits_on_own_line(indent + INDENT, |buf| {
+                                            sub_def.format_with_options(
+                                                buf,
+                                                Parens::NotNeeded,
+                                                Newlines::No,
+                                                indent + INDENT,
+                                            )
+                                        }))
                             }
                             _ => false,
                         },
@@ -259,9 +282,18 @@ impl<'a> Formattable for ValueDef<'a> {
                 };
 
                 ann_pattern.format(buf, indent);
-                buf.push_str(" :");
 
-                if is_type_multiline && is_type_function {
+                if is_too_long_function(buf, ann_type, indent) {
+                    buf.push_str(" :");
+                    buf.newline();
+                    ann_type.format_with_options(
+                        buf,
+                        Parens::NotNeeded,
+                        Newlines::Yes,
+                        indent + INDENT,
+                    );
+                } else if is_type_multiline && is_type_function {
+                    buf.push_str(" :");
                     ann_type.format_with_options(
                         buf,
                         Parens::NotNeeded,
@@ -269,6 +301,7 @@ impl<'a> Formattable for ValueDef<'a> {
                         next_indent,
                     );
                 } else {
+                    buf.push_str(" :");
                     buf.spaces(1);
                     ann_type.format(buf, indent);
                 }
@@ -286,6 +319,18 @@ impl<'a> Formattable for ValueDef<'a> {
     }
 }
 
+/// Whether an annotation is a function type that doesn't fit on the line after
+/// its ` : `, in which case it gets an argument per line.
+fn is_too_long_function(buf: &Buf<'_>, annotation: &Loc<TypeAnnotation<'_>>, indent: u16) -> bool {
+    matches!(annotation.value, TypeAnnotation::Function(..))
+        && !annotation.is_multiline()
+        && !buf.fits_on_line(indent, |buf| {
+            buf.push_str(" :");
+            buf.spaces(1);
+            annotation.format_with_options(buf, Parens::NotNeeded, Newlines::No, indent);
+        })
+}
+
 fn fmt_expect<'a, 'buf>(
     buf: &mut Buf<'buf>,
     condition: &'a Loc<Expr<'a>>,
@@ -296,6 +341,12 @@ fn fmt_expect<'a, 'buf>(
     buf.indent(indent);
     buf.push_str("expect");
 
+    let is_multiline = is_multiline
+        || !buf.fits_on_line(indent, |buf| {
+            buf.spaces(1);
+            condition.format(buf, indent);
+        });
+
     let return_indent = if is_multiline {
         buf.newline();
         indent + INDENT
@@ -317,6 +368,12 @@ fn fmt_expect_fx<'a, 'buf>(
     buf.indent(indent);
     buf.push_str("expect-fx");
 
+    let is_multiline = is_multiline
+        || !buf.fits_on_line(indent, |buf| {
+            buf.spaces(1);
+            condition.format(buf, indent);
+        });
+
     let return_indent = if is_multiline {
         buf.newline();
         indent + INDENT
@@ -353,13 +410,30 @@ pub fn fmt_body<'a, 'buf>(
     pattern.format_with_options(buf, Parens::InApply, Newlines::No, indent);
     buf.push_str(" =");
 
-    if body.is_multiline() {
+    // A binop chain that doesn't fit on the line goes on its own line, like a multiline one
+    let is_too_long_binops = matches!(body, Expr::BinOps(..))
+        && !body.is_multiline()
+        && !buf.fits_on_line(indent, |buf| {
+            buf.spaces(1);
+            body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+        });
+
+    if body.is_multiline() || is_too_long_binops {
         match body {
             Expr::SpaceBefore(sub_def, spaces) => {
                 let should_outdent = match sub_def {
                     Expr::Record { .. } | Expr::List { .. } => {
                         let is_only_newlines = spaces.iter().all(|s| s.is_newline());
-                        is_only_newlines && sub_def.is_multiline()
+                        is_only_newlines
+                            && (sub_def.is_multiline()
+                                || !buf.fits_on_own_line(indent + INDENT, |buf| {
+                                    sub_def.format_with_options(
+                                        buf,
+                                        Parens::NotNeeded,
+                                        Newlines::Yes,
+                                        indent + INDENT,
+                                    )
+                                }))
                     }
                     _ => false,
                 };
@@ -413,6 +487,6 @@ impl<'a> Formattable for AbilityMember<'a> {
         buf.spaces(1);
         buf.push(':');
         buf.spaces(1);
-        self.typ.value.format(buf, indent + INDENT);
+        buf.without_breaks(|buf| self.typ.value.format(buf, indent + INDENT));
     }
 }
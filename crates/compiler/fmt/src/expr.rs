@@ -138,7 +138,19 @@ impl<'a> Formattable for Expr<'a> {
                         Expr::Closure(..)
                         | Expr::SpaceBefore(..)
                         | Expr::SpaceAfter(Closure(..), ..) => false,
-                        _ => sub_expr.is_multiline(),
+                        _ => {
+                            sub_expr.is_multiline()
+                                || !buf.fits_on_line(indent, |buf| {
+                                    buf.push('(');
+                                    sub_expr.format_with_options(
+                                        buf,
+                                        Parens::NotNeeded,
+                                        Newlines::Yes,
+                                        indent,
+                                    );
+                                    buf.push(')');
+                                })
+                        }
                     };
 
                     buf.indent(indent);
@@ -188,6 +200,55 @@ impl<'a> Formattable for Expr<'a> {
                 buf.push_str(name);
             }
             Apply(loc_expr, loc_args, _) => {
+                // An application that doesn't fit on the line gets an argument per line,
+                // unless its last argument is a record, list or closure that can be broken
+                // up instead.
+                let is_too_long = !loc_args.is_empty()
+                    && !self.is_multiline()
+                    && !buf.fits_on_line(indent, |buf| {
+                        self.format_with_options(buf, parens, newlines, indent)
+                    });
+
+                let outdent_too_long_last_arg = is_too_long
+                    && match loc_args.split_last() {
+                        Some((last_arg, first_args)) => {
+                            let opening = match last_arg.value {
+                                Record { .. } => "{",
+                                List { .. } => "[",
+                                Closure { .. } => "\\",
+                                _ => "",
+                            };
+
+                            !opening.is_empty()
+                                && buf.fits_on_line(indent, |buf| {
+                                    if apply_needs_parens {
+                                        buf.push('(');
+                                    }
+
+                                    loc_expr.format_with_options(
+                                        buf,
+                                        Parens::InApply,
+                                        Newlines::Yes,
+                                        indent,
+                                    );
+
+                                    for loc_arg in first_args {
+                                        buf.spaces(1);
+                                        loc_arg.format_with_options(
+                                            buf,
+                                            Parens::InApply,
+                                            Newlines::Yes,
+                                            indent,
+                                        );
+                                    }
+
+                                    buf.spaces(1);
+                                    buf.push_str(opening);
+                                })
+                        }
+                        None => false,
+                    };
+
                 buf.indent(indent);
                 if apply_needs_parens && !loc_args.is_empty() {
                     buf.push('(');
@@ -195,7 +256,8 @@ impl<'a> Formattable for Expr<'a> {
 
                 loc_expr.format_with_options(buf, Parens::InApply, Newlines::Yes, indent);
 
-                let multiline_args = loc_args.iter().any(|loc_arg| loc_arg.is_multiline());
+                let multiline_args =
+                    is_too_long || loc_args.iter().any(|loc_arg| loc_arg.is_multiline());
 
                 let mut found_multiline_expr = false;
                 let mut iter = loc_args.iter().peekable();
@@ -208,7 +270,15 @@ impl<'a> Formattable for Expr<'a> {
                                     let is_only_newlines = spaces.iter().all(|s| s.is_newline());
                                     is_only_newlines
                                         && !found_multiline_expr
-                                        && sub_expr.is_multiline()
+                                        && (sub_expr.is_multiline()
+                                            || !buf.fits_on_own_line(indent + INDENT, |buf| {
+                                                sub_expr.format_with_options(
+                                                    buf,
+                                                    Parens::InApply,
+                                                    Newlines::No,
+                                                    indent + INDENT,
+                                                )
+                                            }))
                                 }
                                 _ => false,
                             },
@@ -222,7 +292,7 @@ impl<'a> Formattable for Expr<'a> {
                     }
                 }
 
-                let should_outdent_last_arg = found_multiline_expr;
+                let should_outdent_last_arg = found_multiline_expr || outdent_too_long_last_arg;
 
                 if multiline_args && !should_outdent_last_arg {
                     let arg_indent = indent + INDENT;
@@ -368,7 +438,12 @@ impl<'a> Formattable for Expr<'a> {
                 fmt_expect(buf, condition, continuation, self.is_multiline(), indent);
             }
             If(branches, final_else) => {
-                fmt_if(buf, branches, final_else, self.is_multiline(), indent);
+                let is_multiline = self.is_multiline()
+                    || !buf.fits_on_line(indent, |buf| {
+                        self.format_with_options(buf, parens, newlines, indent)
+                    });
+
+                fmt_if(buf, branches, final_else, is_multiline, indent);
             }
             When(loc_condition, branches) => fmt_when(buf, loc_condition, branches, indent),
             List(items) => fmt_collection(buf, indent, Braces::Square, *items, Newlines::No),
@@ -433,12 +508,14 @@ fn format_str_segment<'a, 'buf>(seg: &StrSegment<'a>, buf: &mut Buf<'buf>, inden
         Interpolated(loc_expr) => {
             buf.push_str("\\(");
             // e.g. (name) in "Hi, \(name)!"
-            loc_expr.value.format_with_options(
-                buf,
-                Parens::NotNeeded, // We already printed parens!
-                Newlines::No,      // Interpolations can never have newlines
-                indent,
-            );
+            buf.without_breaks(|buf| {
+                loc_expr.value.format_with_options(
+                    buf,
+                    Parens::NotNeeded, // We already printed parens!
+                    Newlines::No,      // Interpolations can never have newlines
+                    indent,
+                )
+            });
             buf.push(')');
         }
     }
@@ -549,6 +626,38 @@ fn fmt_binops<'a, 'buf>(
         || (&loc_right_side.value).is_multiline()
         || lefts.iter().any(|(expr, _)| expr.value.is_multiline());
 
+    // A chain of operators that doesn't fit on the line is broken before each operator,
+    // as long as it starts its own line. Otherwise it stays on one line, so that it is
+    // laid out the same way when it's formatted again.
+    let is_too_long = !is_multiline
+        && !buf.fits_on_line(indent, |buf| {
+            fmt_binops(
+                buf,
+                lefts,
+                loc_right_side,
+                false,
+                apply_needs_parens,
+                indent,
+            )
+        });
+
+    if is_too_long && !buf.is_at_line_start() {
+        buf.without_breaks(|buf| {
+            fmt_binops(
+                buf,
+                lefts,
+                loc_right_side,
+                false,
+                apply_needs_parens,
+                indent,
+            )
+        });
+
+        return;
+    }
+
+    let is_multiline = is_multiline || is_too_long;
+
     for (loc_left_side, loc_binop) in lefts {
         let binop = loc_binop.value;
 
@@ -647,7 +756,13 @@ fn fmt_when<'a, 'buf>(
     branches: &[&'a WhenBranch<'a>],
     indent: u16,
 ) {
-    let is_multiline_condition = loc_condition.is_multiline();
+    let is_multiline_condition = loc_condition.is_multiline()
+        || !buf.fits_on_line(indent, |buf| {
+            buf.push_str("when");
+            buf.spaces(1);
+            loc_condition.format(buf, indent);
+            buf.push_str(" is");
+        });
     buf.indent(indent);
     buf.push_str(
         "\
@@ -712,7 +827,6 @@ fn fmt_when<'a, 'buf>(
     for (branch_index, branch) in branches.iter().enumerate() {
         let expr = &branch.value;
         let patterns = &branch.patterns;
-        let is_multiline_expr = expr.is_multiline();
         let is_multiline_patterns = is_when_patterns_multiline(branch);
 
         for (pattern_index, pattern) in patterns.iter().enumerate() {
@@ -783,11 +897,29 @@ fn fmt_when<'a, 'buf>(
         if let Some(guard_expr) = &branch.guard {
             buf.push_str(" if");
             buf.spaces(1);
-            guard_expr.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent + INDENT);
+            buf.without_breaks(|buf| {
+                guard_expr.format_with_options(
+                    buf,
+                    Parens::NotNeeded,
+                    Newlines::Yes,
+                    indent + INDENT,
+                )
+            });
         }
 
         buf.push_str(" ->");
 
+        let is_multiline_expr = expr.is_multiline()
+            || !buf.fits_on_line(indent + 2 * INDENT, |buf| {
+                buf.spaces(1);
+                expr.format_with_options(
+                    buf,
+                    Parens::NotNeeded,
+                    Newlines::Yes,
+                    indent + 2 * INDENT,
+                );
+            });
+
         match expr.value {
             Expr::SpaceBefore(nested, spaces) => {
                 fmt_spaces_no_blank_lines(buf, spaces.iter(), indent + (INDENT * 2));
@@ -836,6 +968,12 @@ fn fmt_expect<'a, 'buf>(
     buf.indent(indent);
     buf.push_str("expect");
 
+    let is_multiline = is_multiline
+        || !buf.fits_on_line(indent, |buf| {
+            buf.spaces(1);
+            condition.format(buf, indent);
+        });
+
     let return_indent = if is_multiline {
         buf.newline();
         indent + INDENT
@@ -871,7 +1009,18 @@ fn fmt_if<'a, 'buf>(
     };
 
     for (i, (loc_condition, loc_then)) in branches.iter().enumerate() {
-        let is_multiline_condition = loc_condition.is_multiline();
+        let is_multiline_condition = loc_condition.is_multiline()
+            || !buf.fits_on_line(indent, |buf| {
+                if i > 0 {
+                    buf.push_str("else");
+                    buf.spaces(1);
+                }
+
+                buf.push_str("if");
+                buf.spaces(1);
+                loc_condition.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+                buf.push_str(" then");
+            });
 
         buf.indent(indent);
 
@@ -1049,7 +1198,13 @@ fn fmt_closure<'a, 'buf>(
 
     buf.push_str("->");
 
-    let is_multiline = (&loc_ret.value).is_multiline();
+    let is_too_long = !(&loc_ret.value).is_multiline()
+        && !buf.fits_on_line(indent, |buf| {
+            buf.spaces(1);
+            loc_ret.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+        });
+
+    let is_multiline = is_too_long || (&loc_ret.value).is_multiline();
 
     // If the body is multiline, go down a line and indent.
     let body_indent = if is_multiline {
@@ -1066,6 +1221,14 @@ fn fmt_closure<'a, 'buf>(
             // the body starts with (first comment and then) a newline
             // do nothing
         }
+        Record { .. } | List { .. } => {
+            // add a space after the `->`
+            buf.spaces(1);
+        }
+        _ if is_too_long => {
+            // a body that doesn't fit after the `->` goes on the next line
+            buf.newline();
+        }
         _ => {
             // add a space after the `->`
             buf.spaces(1);
@@ -1078,7 +1241,16 @@ fn fmt_closure<'a, 'buf>(
                 let should_outdent = match sub_expr {
                     Record { .. } | List { .. } => {
                         let is_only_newlines = spaces.iter().all(|s| s.is_newline());
-                        is_only_newlines && sub_expr.is_multiline()
+                        is_only_newlines
+                            && (sub_expr.is_multiline()
+                                || !buf.fits_on_own_line(body_indent, |buf| {
+                                    sub_expr.format_with_options(
+                                        buf,
+                                        Parens::NotNeeded,
+                                        Newlines::Yes,
+                                        body_indent,
+                                    )
+                                }))
                     }
                     _ => false,
                 };
@@ -1205,6 +1377,13 @@ fn fmt_record<'a, 'buf>(
 ) {
     let loc_fields = fields.items;
     let final_comments = fields.final_comments();
+
+    // A record that doesn't fit on the line gets a field per line
+    let is_multiline = loc_fields.iter().any(|loc_field| loc_field.is_multiline())
+        || !final_comments.is_empty()
+        || (!loc_fields.is_empty()
+            && !buf.fits_on_line(indent, |buf| fmt_record(buf, update, fields, indent)));
+
     buf.indent(indent);
     if loc_fields.is_empty() && final_comments.iter().all(|c| c.is_newline()) {
         buf.push_str("{}");
@@ -1224,9 +1403,6 @@ fn fmt_record<'a, 'buf>(
             }
         }
 
-        let is_multiline = loc_fields.iter().any(|loc_field| loc_field.is_multiline())
-            || !final_comments.is_empty();
-
         if is_multiline {
             let field_indent = indent + INDENT;
             for (index, field) in loc_fields.iter().enumerate() {
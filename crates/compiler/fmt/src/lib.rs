@@ -23,6 +23,9 @@ pub struct Buf<'a> {
     text: String<'a>,
     spaces_to_flush: usize,
     beginning_of_line: bool,
+    /// Applications, collections, type annotations and operator chains that would go past
+    /// this column are broken over several lines. `None` means lines can be any length.
+    max_width: Option<usize>,
 }
 
 impl<'a> Buf<'a> {
@@ -31,9 +34,16 @@ impl<'a> Buf<'a> {
             text: String::new_in(arena),
             spaces_to_flush: 0,
             beginning_of_line: true,
+            max_width: None,
         }
     }
 
+    pub fn with_max_width(mut self, max_width: usize) -> Buf<'a> {
+        self.max_width = Some(max_width);
+
+        self
+    }
+
     pub fn as_str(&'a self) -> &'a str {
         self.text.as_str()
     }
@@ -145,6 +155,87 @@ impl<'a> Buf<'a> {
     fn is_empty(&self) -> bool {
         self.spaces_to_flush == 0 && self.text.is_empty()
     }
+
+    /// Whether nothing has been written on the current line yet
+    pub fn is_at_line_start(&self) -> bool {
+        self.beginning_of_line
+    }
+
+    /// Where the next character will be written, to pass to [Buf::has_newline_since]
+    pub fn position(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn has_newline_since(&self, position: usize) -> bool {
+        self.text[position..].contains('\n')
+    }
+
+    /// The column the next character will be written at, if it is indented by `indent`
+    fn column(&self, indent: u16) -> usize {
+        if self.beginning_of_line {
+            indent as usize
+        } else {
+            let line_start = self.text.rfind('\n').map_or(0, |index| index + 1);
+
+            self.text[line_start..].chars().count() + self.spaces_to_flush
+        }
+    }
+
+    /// Whether what `format` writes fits before the max width, if it is all put on the
+    /// current line (indented by `indent`, if nothing has been written on it yet).
+    ///
+    /// Everything `format` writes is laid out on one line, unless it was multiline to
+    /// begin with. The decision to break something up can then be made the same way
+    /// when formatting it again, which keeps formatting idempotent.
+    pub fn fits_on_line(&self, indent: u16, format: impl FnOnce(&mut Buf<'_>)) -> bool {
+        self.fits_from_column(self.column(indent), format)
+    }
+
+    /// Like [Buf::fits_on_line], for something that would start on a line of its own,
+    /// indented by `indent`.
+    pub fn fits_on_own_line(&self, indent: u16, format: impl FnOnce(&mut Buf<'_>)) -> bool {
+        self.fits_from_column(indent as usize, format)
+    }
+
+    fn fits_from_column(&self, column: usize, format: impl FnOnce(&mut Buf<'_>)) -> bool {
+        let max_width = match self.max_width {
+            Some(max_width) => max_width,
+            None => return true,
+        };
+
+        let arena = Bump::new();
+        let mut line = Buf {
+            text: String::new_in(&arena),
+            spaces_to_flush: 0,
+            beginning_of_line: false,
+            max_width: None,
+        };
+
+        format(&mut line);
+
+        let width = line
+            .text
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count();
+
+        column + width <= max_width
+    }
+
+    /// Format something on one line, however long it gets.
+    ///
+    /// This is for things whose layout depends on whether their parts are multiline,
+    /// and that don't break themselves up when they're too long: if one of their parts
+    /// broke up instead, formatting them again would lay them out differently.
+    pub fn without_breaks(&mut self, format: impl FnOnce(&mut Buf<'a>)) {
+        let max_width = self.max_width.take();
+
+        format(self);
+
+        self.max_width = max_width;
+    }
 }
 
 /// Ensures the text ends in a newline with no whitespace preceding it.
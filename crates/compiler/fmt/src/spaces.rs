@@ -9,7 +9,7 @@ use roc_parse::{
     },
     header::{
         AppHeader, ExposedName, HostedHeader, ImportsEntry, InterfaceHeader, ModuleName,
-        PackageEntry, PackageName, PlatformHeader, PlatformRequires, To, TypedIdent,
+        PackageEntry, PackageHeader, PackageName, PlatformHeader, PlatformRequires, To, TypedIdent,
     },
     ident::UppercaseIdent,
 };
@@ -278,6 +278,22 @@ impl<'a> RemoveSpaces<'a> for Module<'a> {
                     after_to: &[],
                 },
             },
+            Module::Package { header } => Module::Package {
+                header: PackageHeader {
+                    name: header.name.remove_spaces(arena),
+                    exposes: header.exposes.remove_spaces(arena),
+                    packages: header.packages.remove_spaces(arena),
+                    imports: header.imports.remove_spaces(arena),
+                    before_header: &[],
+                    after_package_keyword: &[],
+                    before_exposes: &[],
+                    after_exposes: &[],
+                    before_packages: &[],
+                    after_packages: &[],
+                    before_imports: &[],
+                    after_imports: &[],
+                },
+            },
             Module::Platform { header } => Module::Platform {
                 header: PlatformHeader {
                     name: header.name.remove_spaces(arena),
@@ -16,8 +16,19 @@ mod test_fmt {
     use roc_parse::state::State;
     use roc_test_utils::{assert_multiline_str_eq, workspace_root};
 
+    fn new_buf(arena: &Bump, max_width: Option<usize>) -> Buf<'_> {
+        match max_width {
+            Some(max_width) => Buf::new_in(arena).with_max_width(max_width),
+            None => Buf::new_in(arena),
+        }
+    }
+
     // Not intended to be used directly in tests; please use expr_formats_to or expr_formats_same
     fn expr_formats_to(input: &str, expected: &str) {
+        expr_formats_to_with_max_width(input, expected, None);
+    }
+
+    fn expr_formats_to_with_max_width(input: &str, expected: &str, max_width: Option<usize>) {
         let arena = Bump::new();
         let input = input.trim();
         let expected = expected.trim();
@@ -26,7 +37,7 @@ mod test_fmt {
             Ok(actual) => {
                 use roc_fmt::spaces::RemoveSpaces;
 
-                let mut buf = Buf::new_in(&arena);
+                let mut buf = new_buf(&arena, max_width);
 
                 actual.format_with_options(&mut buf, Parens::NotNeeded, Newlines::Yes, 0);
 
@@ -60,7 +71,7 @@ mod test_fmt {
                 }
 
                 // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
-                let mut reformatted_buf = Buf::new_in(&arena);
+                let mut reformatted_buf = new_buf(&arena, max_width);
                 reparsed_ast.format_with_options(&mut reformatted_buf, Parens::NotNeeded, Newlines::Yes, 0);
 
                 if output != reformatted_buf.as_str() {
@@ -77,6 +88,14 @@ mod test_fmt {
         expr_formats_to(input, input);
     }
 
+    fn expr_formats_to_within(max_width: usize, input: &str, expected: &str) {
+        expr_formats_to_with_max_width(input, expected, Some(max_width));
+    }
+
+    fn expr_formats_same_within(max_width: usize, input: &str) {
+        expr_formats_to_with_max_width(input, input, Some(max_width));
+    }
+
     fn fmt_module_and_defs<'a>(
         arena: &Bump,
         src: &str,
@@ -4602,6 +4621,14 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn single_line_package() {
+        module_formats_same(indoc!(
+            r#"
+                package "roc/json 1.0.0" exposes [Json] packages { util: "roc/util" } imports [util.Str]"#
+        ));
+    }
+
     #[test]
     fn module_defs_with_comments() {
         module_formats_to(
@@ -5457,6 +5484,192 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn short_lines_stay_within_max_width() {
+        expr_formats_same_within(
+            40,
+            indoc!(
+                r#"
+                Num.add 1 2
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_apply_breaks_args() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                Num.add someLongVariableName anotherLongVariableName
+                "#
+            ),
+            indoc!(
+                r#"
+                Num.add
+                    someLongVariableName
+                    anotherLongVariableName
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_apply_breaks_last_record_arg() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                Http.get { url: "https://example.com", timeout: 30 }
+                "#
+            ),
+            indoc!(
+                r#"
+                Http.get {
+                    url: "https://example.com",
+                    timeout: 30,
+                }
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_apply_breaks_last_closure_arg() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                List.map numbers \number -> Num.toStr (number * 2)
+                "#
+            ),
+            indoc!(
+                r#"
+                List.map numbers \number ->
+                    Num.toStr (number * 2)
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_list_breaks_items() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                ["first item", "second item", "third item"]
+                "#
+            ),
+            indoc!(
+                r#"
+                [
+                    "first item",
+                    "second item",
+                    "third item",
+                ]
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_pipeline_breaks_before_operators() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                result = items |> List.map Num.toStr |> Str.joinWith ", "
+
+                result
+                "#
+            ),
+            indoc!(
+                r#"
+                result =
+                    items
+                    |> List.map Num.toStr
+                    |> Str.joinWith ", "
+
+                result
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_if_breaks_branches() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                if someCondition then someLongResultValue else anotherLongResultValue
+                "#
+            ),
+            indoc!(
+                r#"
+                if someCondition then
+                    someLongResultValue
+                else
+                    anotherLongResultValue
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_record_annotation_breaks_fields() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                config : { name : Str, verbose : Bool, retries : U64 }
+
+                config
+                "#
+            ),
+            indoc!(
+                r#"
+                config : {
+                    name : Str,
+                    verbose : Bool,
+                    retries : U64,
+                }
+
+                config
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn long_function_annotation_breaks_args() {
+        expr_formats_to_within(
+            40,
+            indoc!(
+                r#"
+                transform : List Str, Dict Str U64, Bool -> Result (List U64) Str
+                transform = \a, b, c -> a
+
+                transform
+                "#
+            ),
+            indoc!(
+                r#"
+                transform :
+                    List Str,
+                    Dict Str U64,
+                    Bool
+                    -> Result (List U64) Str
+                transform = \a, b, c -> a
+
+                transform
+                "#
+            ),
+        );
+    }
+
     // this is a parse error atm
     //    #[test]
     //    fn multiline_apply() {
@@ -14,12 +14,15 @@ use roc_mono::ir::{
     BranchInfo, CallType, Expr, JoinPointId, ListLiteralElement, Literal, Param, Proc, ProcLayout,
     SelfRecursive, Stmt,
 };
-use roc_mono::layout::{Builtin, Layout, LayoutId, LayoutIds, TagIdIntType, UnionLayout};
+use roc_mono::layout::{Builtin, CapturesNiche, Layout, TagIdIntType, UnionLayout};
+use roc_mono::mangle::mangle_proc_name;
 
 mod generic64;
 mod object_builder;
 pub use object_builder::build_module;
 mod run_roc;
+mod supported;
+pub use supported::{check_supported, Unsupported};
 
 pub struct Env<'a> {
     pub arena: &'a Bump,
@@ -64,8 +67,14 @@ trait Backend<'a> {
     // rust understands that they are part of a single use of mutable self.
     fn env_interns_helpers_mut(&mut self) -> (&Env<'a>, &mut Interns, &mut CodeGenHelp<'a>);
 
-    fn symbol_to_string(&self, symbol: Symbol, layout_id: LayoutId) -> String {
-        layout_id.to_symbol_string(symbol, self.interns())
+    fn proc_name(&self, symbol: Symbol, layout: &ProcLayout<'a>) -> String {
+        mangle_proc_name(
+            self.interns(),
+            symbol,
+            layout.arguments,
+            &layout.result,
+            layout.captures_niche,
+        )
     }
 
     fn defined_in_app_module(&self, symbol: Symbol) -> bool {
@@ -102,10 +111,15 @@ trait Backend<'a> {
     fn build_proc(
         &mut self,
         proc: Proc<'a>,
-        layout_ids: &mut LayoutIds<'a>,
     ) -> (Vec<u8>, Vec<Relocation>, Vec<'a, (Symbol, String)>) {
-        let layout_id = layout_ids.get(proc.name.name(), &proc.ret_layout);
-        let proc_name = self.symbol_to_string(proc.name.name(), layout_id);
+        let arena = self.env().arena;
+        let proc_layout = ProcLayout {
+            arguments: Vec::from_iter_in(proc.args.iter().map(|(layout, _)| *layout), arena)
+                .into_bump_slice(),
+            result: proc.ret_layout,
+            captures_niche: proc.name.captures_niche(),
+        };
+        let proc_name = self.proc_name(proc.name.name(), &proc_layout);
         self.reset(proc_name, proc.is_self_recursive);
         self.load_args(proc.args, &proc.ret_layout);
         for (layout, sym) in proc.args {
@@ -117,9 +131,7 @@ trait Backend<'a> {
         let mut helper_proc_names = bumpalo::vec![in self.env().arena];
         helper_proc_names.reserve(self.helper_proc_symbols().len());
         for (rc_proc_sym, rc_proc_layout) in self.helper_proc_symbols() {
-            let name = layout_ids
-                .get_toplevel(*rc_proc_sym, rc_proc_layout)
-                .to_symbol_string(*rc_proc_sym, self.interns());
+            let name = self.proc_name(*rc_proc_sym, rc_proc_layout);
 
             helper_proc_names.push((*rc_proc_sym, name));
         }
@@ -271,8 +283,12 @@ trait Backend<'a> {
                                 ret_layout,
                             )
                         } else if self.defined_in_app_module(func_sym.name()) {
-                            let layout_id = LayoutIds::default().get(func_sym.name(), layout);
-                            let fn_name = self.symbol_to_string(func_sym.name(), layout_id);
+                            let proc_layout = ProcLayout {
+                                arguments: *arg_layouts,
+                                result: **ret_layout,
+                                captures_niche: func_sym.captures_niche(),
+                            };
+                            let fn_name = self.proc_name(func_sym.name(), &proc_layout);
                             // Now that the arguments are needed, load them if they are literals.
                             self.load_literal_symbols(arguments);
                             self.build_fn_call(sym, fn_name, arguments, arg_layouts, ret_layout)
@@ -688,8 +704,12 @@ trait Backend<'a> {
             }
             Symbol::LIST_GET | Symbol::LIST_SET | Symbol::LIST_REPLACE => {
                 // TODO: This is probably simple enough to be worth inlining.
-                let layout_id = LayoutIds::default().get(func_sym, ret_layout);
-                let fn_name = self.symbol_to_string(func_sym, layout_id);
+                let proc_layout = ProcLayout {
+                    arguments: self.env().arena.alloc_slice_copy(arg_layouts),
+                    result: *ret_layout,
+                    captures_niche: CapturesNiche::no_niche(),
+                };
+                let fn_name = self.proc_name(func_sym, &proc_layout);
                 // Now that the arguments are needed, load them if they are literals.
                 self.load_literal_symbols(args);
                 self.build_fn_call(sym, fn_name, args, arg_layouts, ret_layout)
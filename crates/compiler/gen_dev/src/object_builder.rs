@@ -208,7 +208,7 @@ fn build_object<'a, B: Backend<'a>>(
         );
     }
 
-    // Setup layout_ids for procedure calls.
+    // Setup layout_ids for the names of exposed procedures.
     let mut layout_ids = LayoutIds::default();
     let mut procs = Vec::with_capacity_in(procedures.len(), arena);
 
@@ -232,7 +232,6 @@ fn build_object<'a, B: Backend<'a>>(
             &mut output,
             &mut backend,
             &mut relocations,
-            &mut layout_ids,
             data_section,
             fn_name,
             section_id,
@@ -260,8 +259,7 @@ fn build_object<'a, B: Backend<'a>>(
 
     // Names and linker data for helpers
     for ((sym, layout), proc) in helper_symbols_and_layouts.into_iter().zip(helper_procs) {
-        let layout_id = layout_ids.get_toplevel(sym, &layout);
-        let fn_name = backend.symbol_to_string(sym, layout_id);
+        let fn_name = backend.proc_name(sym, &layout);
         if let Some(proc_id) = output.symbol_id(fn_name.as_bytes()) {
             if let SymbolSection::Section(section_id) = output.symbol(proc_id).section {
                 helper_names_symbols_procs.push((fn_name, section_id, proc_id, proc));
@@ -298,7 +296,6 @@ fn build_object<'a, B: Backend<'a>>(
             &mut output,
             &mut backend,
             &mut relocations,
-            &mut layout_ids,
             data_section,
             fn_name,
             section_id,
@@ -326,8 +323,7 @@ fn build_proc_symbol<'a, B: Backend<'a>>(
     layout: ProcLayout<'a>,
     proc: Proc<'a>,
 ) {
-    let layout_id = layout_ids.get_toplevel(sym, &layout);
-    let base_name = backend.symbol_to_string(sym, layout_id);
+    let base_name = backend.proc_name(sym, &layout);
 
     let fn_name = if backend.env().exposed_to_host.contains(&sym) {
         layout_ids
@@ -368,7 +364,6 @@ fn build_proc<'a, B: Backend<'a>>(
     output: &mut Object,
     backend: &mut B,
     relocations: &mut Vec<'a, (SectionId, object::write::Relocation)>,
-    layout_ids: &mut LayoutIds<'a>,
     data_section: SectionId,
     fn_name: String,
     section_id: SectionId,
@@ -376,7 +371,7 @@ fn build_proc<'a, B: Backend<'a>>(
     proc: Proc<'a>,
 ) {
     let mut local_data_index = 0;
-    let (proc_data, relocs, rc_proc_names) = backend.build_proc(proc, layout_ids);
+    let (proc_data, relocs, rc_proc_names) = backend.build_proc(proc);
     let proc_offset = output.add_symbol_data(proc_id, section_id, &proc_data, 16);
     for reloc in relocs.iter() {
         let elfreloc = match reloc {
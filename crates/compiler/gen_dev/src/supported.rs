@@ -0,0 +1,242 @@
+//! Whether the dev backend can compile a module. It doesn't support everything yet,
+//! and reaching something it doesn't support panics partway through code gen, so a
+//! caller with another backend to fall back on (like the REPL) checks here first.
+//!
+//! This errs on the side of saying no: it only accepts the layouts, expressions and
+//! low-level operations the x86_64 backend is known to handle, rather than trying to
+//! keep up with everything it can do.
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::all::MutMap;
+use roc_module::ident::ModuleName;
+use roc_module::low_level::{LowLevel, LowLevelWrapperType};
+use roc_module::symbol::{Interns, Symbol};
+use roc_mono::ir::{CallType, Expr, Literal, Proc, ProcLayout, Stmt};
+use roc_mono::layout::{Builtin, Layout};
+use std::fmt;
+use target_lexicon::{Architecture as TargetArch, BinaryFormat as TargetBF, Triple};
+
+/// Something in a module the dev backend can't compile yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported(String);
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the dev backend doesn't support {} yet", self.0)
+    }
+}
+
+fn unsupported<T>(what: impl fmt::Display) -> Result<T, Unsupported> {
+    Err(Unsupported(what.to_string()))
+}
+
+/// Checks that `build_module` can compile these procedures for this target without
+/// reaching anything it doesn't support.
+pub fn check_supported<'a>(
+    interns: &Interns,
+    target: &Triple,
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> Result<(), Unsupported> {
+    match target {
+        Triple {
+            architecture: TargetArch::X86_64,
+            binary_format: TargetBF::Elf | TargetBF::Macho,
+            ..
+        } if cfg!(feature = "target-x86_64") => {}
+        _ => return unsupported(format_args!("the target {}", target)),
+    }
+
+    for proc in procedures.values() {
+        let mut checker = Checker {
+            interns,
+            layouts: MutMap::default(),
+        };
+
+        checker.check_proc(proc)?;
+    }
+
+    Ok(())
+}
+
+struct Checker<'i, 'a> {
+    interns: &'i Interns,
+    layouts: MutMap<Symbol, Layout<'a>>,
+}
+
+impl<'i, 'a> Checker<'i, 'a> {
+    fn check_proc(&mut self, proc: &Proc<'a>) -> Result<(), Unsupported> {
+        check_layout(&proc.ret_layout)?;
+
+        for (layout, symbol) in proc.args {
+            self.define(*symbol, layout)?;
+        }
+
+        self.check_stmt(&proc.body)
+    }
+
+    fn define(&mut self, symbol: Symbol, layout: &Layout<'a>) -> Result<(), Unsupported> {
+        check_layout(layout)?;
+        self.layouts.insert(symbol, *layout);
+
+        Ok(())
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt<'a>) -> Result<(), Unsupported> {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, following) => {
+                self.define(*symbol, layout)?;
+                self.check_expr(expr, layout)?;
+                self.check_stmt(following)
+            }
+            Stmt::Ret(_) => Ok(()),
+            Stmt::Refcounting(_, following) => self.check_stmt(following),
+            Stmt::Switch {
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+                ..
+            } => {
+                check_layout(cond_layout)?;
+                check_layout(ret_layout)?;
+
+                for (_, _, branch) in branches.iter() {
+                    self.check_stmt(branch)?;
+                }
+
+                self.check_stmt(default_branch.1)
+            }
+            Stmt::Join {
+                parameters,
+                body,
+                remainder,
+                ..
+            } => {
+                for param in parameters.iter() {
+                    self.define(param.symbol, &param.layout)?;
+                }
+
+                self.check_stmt(body)?;
+                self.check_stmt(remainder)
+            }
+            Stmt::Jump(_, _) => Ok(()),
+            Stmt::Expect { .. } | Stmt::ExpectFx { .. } => unsupported("expect"),
+            Stmt::RuntimeError(_) => unsupported("runtime errors"),
+        }
+    }
+
+    fn check_expr(&self, expr: &Expr<'a>, layout: &Layout<'a>) -> Result<(), Unsupported> {
+        match expr {
+            Expr::Literal(literal) => check_literal(literal, layout),
+            Expr::Call(call) => match &call.call_type {
+                CallType::ByName {
+                    name,
+                    arg_layouts,
+                    ret_layout,
+                    ..
+                } => {
+                    if let LowLevelWrapperType::CanBeReplacedBy(op) =
+                        LowLevelWrapperType::from_symbol(name.name())
+                    {
+                        check_low_level(op, arg_layouts, ret_layout)
+                    } else if name
+                        .name()
+                        .module_string(self.interns)
+                        .starts_with(ModuleName::APP)
+                        || name.name() == Symbol::NUM_IS_ZERO
+                    {
+                        Ok(())
+                    } else {
+                        unsupported(format_args!("calling {:?}", name.name()))
+                    }
+                }
+                CallType::LowLevel { op, .. } => {
+                    let arg_layouts = call
+                        .arguments
+                        .iter()
+                        .map(|arg| match self.layouts.get(arg) {
+                            Some(layout) => Ok(*layout),
+                            None => unsupported(format_args!("the argument {:?}", arg)),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    check_low_level(*op, &arg_layouts, layout)
+                }
+                other => unsupported(format_args!("the call type {:?}", other)),
+            },
+            other => unsupported(format_args!("the expression {:?}", other)),
+        }
+    }
+}
+
+/// Values with these layouts fit in a register, except for strings, which the calling
+/// convention passes and returns in memory.
+fn check_layout(layout: &Layout) -> Result<(), Unsupported> {
+    match layout {
+        Layout::Builtin(
+            Builtin::Int(IntWidth::I64 | IntWidth::U64)
+            | Builtin::Float(FloatWidth::F64)
+            | Builtin::Bool
+            | Builtin::Str,
+        ) => Ok(()),
+        other => unsupported(format_args!("the layout {:?}", other)),
+    }
+}
+
+fn check_literal(literal: &Literal, layout: &Layout) -> Result<(), Unsupported> {
+    match (literal, layout) {
+        (Literal::Int(_), Layout::Builtin(Builtin::Int(_)))
+        | (Literal::Float(_), Layout::Builtin(Builtin::Float(_)))
+        | (Literal::Bool(_), Layout::Builtin(Builtin::Bool)) => Ok(()),
+        // Longer strings would have to be allocated.
+        (Literal::Str(string), Layout::Builtin(Builtin::Str)) if string.len() < 24 => Ok(()),
+        (other, _) => unsupported(format_args!("the literal {:?}", other)),
+    }
+}
+
+fn is_int(layout: &Layout) -> bool {
+    matches!(layout, Layout::Builtin(Builtin::Int(_)))
+}
+
+fn is_f64(layout: &Layout) -> bool {
+    matches!(layout, Layout::Builtin(Builtin::Float(FloatWidth::F64)))
+}
+
+fn check_low_level(
+    op: LowLevel,
+    arg_layouts: &[Layout],
+    ret_layout: &Layout,
+) -> Result<(), Unsupported> {
+    let ints = arg_layouts.iter().all(is_int);
+    let f64s = arg_layouts.iter().all(is_f64);
+
+    let supported = match op {
+        LowLevel::NumAdd | LowLevel::NumMul | LowLevel::NumAbs | LowLevel::NumDivTruncUnchecked => {
+            ints || f64s
+        }
+        LowLevel::NumSub
+        | LowLevel::NumNeg
+        | LowLevel::Eq
+        | LowLevel::NotEq
+        | LowLevel::NumLt
+        | LowLevel::NumLte
+        | LowLevel::NumGte => ints,
+        LowLevel::NumDivFrac | LowLevel::NumAcos | LowLevel::NumAsin | LowLevel::NumAtan => f64s,
+        LowLevel::NumToFrac => (ints || f64s) && is_f64(ret_layout),
+        // These call the I64 versions of the builtins.
+        LowLevel::NumPowInt => arg_layouts
+            .iter()
+            .all(|layout| *layout == Layout::Builtin(Builtin::Int(IntWidth::I64))),
+        LowLevel::NumRound => f64s && *ret_layout == Layout::Builtin(Builtin::Int(IntWidth::I64)),
+        LowLevel::StrConcat => arg_layouts
+            .iter()
+            .all(|layout| *layout == Layout::Builtin(Builtin::Str)),
+        _ => false,
+    };
+
+    if supported {
+        Ok(())
+    } else {
+        unsupported(format_args!("{:?} on {:?}", op, arg_layouts))
+    }
+}
@@ -0,0 +1,205 @@
+//! A shadow call stack, recording which Roc procedures are running so the platform's
+//! `roc_panic` can print a Roc backtrace. It costs a few loads and stores per call, so it
+//! is only built when asked for.
+//!
+//! The platform reads it with `const char* roc__backtrace_frame(size_t index)`, which
+//! describes the `index`th innermost procedure (e.g. `List.get (/path/to/List.roc:12)`),
+//! or returns NULL once `index` is past the outermost one.
+
+use crate::debug_info_init;
+use crate::llvm::build::{Env, C_CALL_CONV};
+use crate::llvm::debug_info::readable_name;
+use inkwell::module::Linkage;
+use inkwell::types::PointerType;
+use inkwell::values::{FunctionValue, GlobalValue, InstructionOpcode, PointerValue};
+use inkwell::{AddressSpace, IntPredicate};
+use roc_module::symbol::Symbol;
+use roc_target::Architecture;
+
+pub const BACKTRACE_FRAME_FN: &str = "roc__backtrace_frame";
+
+/// Deeper frames are counted, but not recorded
+const CAPACITY: u32 = 1024;
+
+const FRAMES: &str = "roc_shadow_stack";
+const DEPTH: &str = "roc_shadow_stack_depth";
+
+fn str_ptr_type<'ctx>(env: &Env<'_, 'ctx, '_>) -> PointerType<'ctx> {
+    env.context.i8_type().ptr_type(AddressSpace::Generic)
+}
+
+fn set_thread_local(env: &Env<'_, '_, '_>, global: GlobalValue<'_>) {
+    // wasm has no threads to keep apart
+    if env.target_info.architecture != Architecture::Wasm32 {
+        global.set_thread_local(true);
+    }
+}
+
+fn frames_ptr<'ctx>(env: &Env<'_, 'ctx, '_>) -> PointerValue<'ctx> {
+    let frames_type = str_ptr_type(env).array_type(CAPACITY);
+
+    let global = env.module.get_global(FRAMES).unwrap_or_else(|| {
+        let global = env.module.add_global(frames_type, None, FRAMES);
+        global.set_initializer(&frames_type.const_zero());
+        global.set_linkage(Linkage::Internal);
+        set_thread_local(env, global);
+        global
+    });
+
+    global.as_pointer_value()
+}
+
+fn depth_ptr<'ctx>(env: &Env<'_, 'ctx, '_>) -> PointerValue<'ctx> {
+    let global = env.module.get_global(DEPTH).unwrap_or_else(|| {
+        let global = env.module.add_global(env.ptr_int(), None, DEPTH);
+        global.set_initializer(&env.ptr_int().const_zero());
+        global.set_linkage(Linkage::Internal);
+        set_thread_local(env, global);
+        global
+    });
+
+    global.as_pointer_value()
+}
+
+fn frame_description(env: &Env<'_, '_, '_>, symbol: Symbol) -> String {
+    let name = readable_name(&env.interns, symbol);
+    let location = env.source_info.and_then(|source_info| {
+        let path = source_info.path(symbol.module_id())?;
+        let line = source_info.def_line(symbol)?;
+
+        Some((path, line))
+    });
+
+    match location {
+        Some((path, line)) => format!("{} ({}:{})", name, path.display(), line),
+        None => name,
+    }
+}
+
+/// Record that a procedure is running. Called at the start of its entry block; leaves
+/// the builder at the end of a new block to continue the procedure in.
+pub(crate) fn push_frame<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    fn_val: FunctionValue<'ctx>,
+    symbol: Symbol,
+) {
+    let builder = env.builder;
+    let ptr_int = env.ptr_int();
+
+    let description = frame_description(env, symbol);
+    let frame = builder
+        .build_global_string_ptr(&description, "backtrace_frame")
+        .as_pointer_value();
+
+    let depth_ptr = depth_ptr(env);
+    let depth = builder.build_load(depth_ptr, "depth").into_int_value();
+    let capacity = ptr_int.const_int(CAPACITY as u64, false);
+    let fits = builder.build_int_compare(IntPredicate::ULT, depth, capacity, "frame_fits");
+
+    let record_block = env.context.append_basic_block(fn_val, "record_frame");
+    let cont_block = env.context.append_basic_block(fn_val, "frame_recorded");
+
+    builder.build_conditional_branch(fits, record_block, cont_block);
+
+    builder.position_at_end(record_block);
+    let slot = unsafe {
+        builder.build_in_bounds_gep(
+            frames_ptr(env),
+            &[ptr_int.const_zero(), depth],
+            "frame_slot",
+        )
+    };
+    builder.build_store(slot, frame);
+    builder.build_unconditional_branch(cont_block);
+
+    builder.position_at_end(cont_block);
+    let new_depth = builder.build_int_add(depth, ptr_int.const_int(1, false), "new_depth");
+    builder.build_store(depth_ptr, new_depth);
+}
+
+/// Record that a procedure is done, at each place it returns from. Called once the
+/// procedure's body is built.
+pub(crate) fn pop_frame<'ctx>(env: &Env<'_, 'ctx, '_>, fn_val: FunctionValue<'ctx>) {
+    let builder = env.builder;
+    let ptr_int = env.ptr_int();
+    let depth_ptr = depth_ptr(env);
+
+    let returns: Vec<_> = fn_val
+        .get_basic_blocks()
+        .iter()
+        .filter_map(|block| block.get_terminator())
+        .filter(|terminator| terminator.get_opcode() == InstructionOpcode::Return)
+        .collect();
+
+    for ret in returns {
+        builder.position_before(&ret);
+
+        let depth = builder.build_load(depth_ptr, "depth").into_int_value();
+        let new_depth = builder.build_int_sub(depth, ptr_int.const_int(1, false), "new_depth");
+        builder.build_store(depth_ptr, new_depth);
+    }
+}
+
+/// Define `roc__backtrace_frame`, for the platform to read the shadow stack with.
+pub fn add_backtrace_frame_fn(env: &Env<'_, '_, '_>) {
+    let context = env.context;
+    let builder = env.builder;
+    let ptr_int = env.ptr_int();
+    let str_ptr_type = str_ptr_type(env);
+
+    let fn_type = str_ptr_type.fn_type(&[ptr_int.into()], false);
+    let fn_val = env
+        .module
+        .add_function(BACKTRACE_FRAME_FN, fn_type, Some(Linkage::External));
+    fn_val.set_call_conventions(C_CALL_CONV);
+
+    let subprogram = env.new_subprogram(BACKTRACE_FRAME_FN);
+    fn_val.set_subprogram(subprogram);
+
+    let entry = context.append_basic_block(fn_val, "entry");
+    let in_stack_block = context.append_basic_block(fn_val, "in_stack");
+    let recorded_block = context.append_basic_block(fn_val, "recorded");
+    let not_recorded_block = context.append_basic_block(fn_val, "not_recorded");
+    let past_end_block = context.append_basic_block(fn_val, "past_end");
+
+    builder.position_at_end(entry);
+
+    debug_info_init!(env, fn_val);
+
+    let index = fn_val.get_nth_param(0).unwrap().into_int_value();
+    let depth = builder.build_load(depth_ptr(env), "depth").into_int_value();
+    let in_stack = builder.build_int_compare(IntPredicate::ULT, index, depth, "in_stack");
+    builder.build_conditional_branch(in_stack, in_stack_block, past_end_block);
+
+    // the index counts from the innermost frame
+    builder.position_at_end(in_stack_block);
+    let top = builder.build_int_sub(depth, ptr_int.const_int(1, false), "top");
+    let position = builder.build_int_sub(top, index, "position");
+    let capacity = ptr_int.const_int(CAPACITY as u64, false);
+    let recorded = builder.build_int_compare(IntPredicate::ULT, position, capacity, "recorded");
+    builder.build_conditional_branch(recorded, recorded_block, not_recorded_block);
+
+    builder.position_at_end(recorded_block);
+    let slot = unsafe {
+        builder.build_in_bounds_gep(
+            frames_ptr(env),
+            &[ptr_int.const_zero(), position],
+            "frame_slot",
+        )
+    };
+    let frame = builder.build_load(slot, "frame");
+    builder.build_return(Some(&frame));
+
+    builder.position_at_end(not_recorded_block);
+    let placeholder = builder
+        .build_global_string_ptr("(frame too deep to record)", "backtrace_placeholder")
+        .as_pointer_value();
+    builder.build_return(Some(&placeholder));
+
+    builder.position_at_end(past_end_block);
+    builder.build_return(Some(&str_ptr_type.const_null()));
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(fn_val);
+    }
+}
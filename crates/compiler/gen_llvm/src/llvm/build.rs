@@ -1,3 +1,4 @@
+use crate::llvm::backtrace::{pop_frame, push_frame};
 use crate::llvm::bitcode::{
     call_bitcode_fn, call_bitcode_fn_fixing_for_convention, call_list_bitcode_fn,
     call_str_bitcode_fn, call_void_bitcode_fn, pass_list_or_string_to_zig_32bit, BitcodeReturns,
@@ -13,6 +14,9 @@ use crate::llvm::compare::{generic_eq, generic_neq};
 use crate::llvm::convert::{
     self, argument_type_from_layout, basic_type_from_builtin, basic_type_from_layout, zig_str_type,
 };
+use crate::llvm::debug_info::{
+    declare_variable, enter_proc, enter_stmt, proc_subprogram, SourceInfo,
+};
 use crate::llvm::expect::clone_to_shared_memory;
 use crate::llvm::refcounting::{
     build_reset, decrement_refcount_layout, increment_refcount_layout, PointerToRefcount,
@@ -59,6 +63,7 @@ use roc_mono::layout::{
     Builtin, CapturesNiche, LambdaName, LambdaSet, Layout, LayoutIds, RawFunctionLayout,
     TagIdIntType, UnionLayout,
 };
+use roc_mono::mangle::{mangle_proc_name, mangle_proc_name_in_full};
 use roc_std::RocDec;
 use roc_target::{PtrWidth, TargetInfo};
 use std::convert::TryInto;
@@ -212,6 +217,10 @@ pub struct Env<'a, 'ctx, 'env> {
     pub target_info: TargetInfo,
     pub mode: LlvmBackendMode,
     pub exposed_to_host: MutSet<Symbol>,
+    /// Where procedures come from in the Roc source, for debug info and backtraces
+    pub source_info: Option<&'env SourceInfo>,
+    /// Whether procedures record themselves on a shadow call stack, for Roc backtraces
+    pub shadow_stack: bool,
 }
 
 #[repr(u32)]
@@ -244,6 +253,11 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         }
     }
 
+    /// Where procedures come from, if we're emitting debug info for them
+    pub fn debug_source_info(&self) -> Option<&'env SourceInfo> {
+        self.source_info.filter(|info| info.emits_debug_info())
+    }
+
     /// The integer type representing twice the width of a pointer
     ///
     /// on 64-bit systems, this is i128
@@ -2451,6 +2465,10 @@ pub fn build_exp_stmt<'a, 'ctx, 'env>(
             for (symbol, expr, layout) in queue {
                 debug_assert!(layout != &Layout::RecursivePointer);
 
+                if let Some(source_info) = env.debug_source_info() {
+                    enter_stmt(env, source_info, parent, *symbol);
+                }
+
                 let val = build_exp_expr(
                     env,
                     layout_ids,
@@ -2468,6 +2486,10 @@ pub fn build_exp_stmt<'a, 'ctx, 'env>(
                 // access itself!
                 // scope = scope.clone();
 
+                if let Some(source_info) = env.debug_source_info() {
+                    declare_variable(env, source_info, parent, *symbol, layout, val, None);
+                }
+
                 scope.insert(*symbol, (*layout, val));
                 stack.push(*symbol);
             }
@@ -4479,25 +4501,27 @@ fn build_procedures_help<'a, 'ctx, 'env>(
     mod_solutions
 }
 
+/// The name of a specialization. Names only have a hash of the `FuncSpec` in them, so if
+/// one is already taken when a specialization is declared, it gets the name with the
+/// whole `FuncSpec` instead; see `func_spec_name_in_full`.
 fn func_spec_name<'a>(
-    arena: &'a Bump,
     interns: &Interns,
     symbol: Symbol,
+    arguments: &[Layout<'a>],
+    result: &Layout<'a>,
     func_spec: FuncSpec,
-) -> bumpalo::collections::String<'a> {
-    use std::fmt::Write;
-
-    let mut buf = bumpalo::collections::String::with_capacity_in(1, arena);
-
-    let ident_string = symbol.as_str(interns);
-    let module_string = interns.module_ids.get_name(symbol.module_id()).unwrap();
-    write!(buf, "{}_{}_", module_string, ident_string).unwrap();
-
-    for byte in func_spec.0.iter() {
-        write!(buf, "{:x?}", byte).unwrap();
-    }
+) -> String {
+    mangle_proc_name(interns, symbol, arguments, result, func_spec.0)
+}
 
-    buf
+fn func_spec_name_in_full<'a>(
+    interns: &Interns,
+    symbol: Symbol,
+    arguments: &[Layout<'a>],
+    result: &Layout<'a>,
+    func_spec: FuncSpec,
+) -> String {
+    mangle_proc_name_in_full(interns, symbol, arguments, result, &func_spec.0)
 }
 
 fn build_proc_header<'a, 'ctx, 'env>(
@@ -4510,7 +4534,26 @@ fn build_proc_header<'a, 'ctx, 'env>(
     let args = proc.args;
     let arena = env.arena;
 
-    let fn_name = func_spec_name(env.arena, &env.interns, symbol, func_spec);
+    let arg_layouts = Vec::from_iter_in(args.iter().map(|(layout, _)| *layout), arena);
+    let mut fn_name = func_spec_name(
+        &env.interns,
+        symbol,
+        &arg_layouts,
+        &proc.ret_layout,
+        func_spec,
+    );
+
+    // Another specialization's `FuncSpec` hashed the same. LLVM would give this one a
+    // different name, which calls to it by this name would not find.
+    if env.module.get_function(&fn_name).is_some() {
+        fn_name = func_spec_name_in_full(
+            &env.interns,
+            symbol,
+            &arg_layouts,
+            &proc.ret_layout,
+            func_spec,
+        );
+    }
 
     let ret_type = basic_type_from_layout(env, &proc.ret_layout);
     let mut arg_basic_types = Vec::with_capacity_in(args.len(), arena);
@@ -4532,7 +4575,17 @@ fn build_proc_header<'a, 'ctx, 'env>(
         Linkage::Internal,
     );
 
-    let subprogram = env.new_subprogram(&fn_name);
+    let subprogram = match env.debug_source_info() {
+        Some(source_info) => proc_subprogram(
+            env,
+            source_info,
+            symbol,
+            &fn_name,
+            proc.args,
+            &proc.ret_layout,
+        ),
+        None => env.new_subprogram(&fn_name),
+    };
     fn_val.set_subprogram(subprogram);
 
     if env.exposed_to_host.contains(&symbol) {
@@ -4890,6 +4943,14 @@ pub fn build_proc<'a, 'ctx, 'env>(
         scope.insert(*arg_symbol, (*layout, arg_val));
     }
 
+    if let Some(source_info) = env.debug_source_info() {
+        enter_proc(env, source_info, fn_val, proc.name.name(), args);
+    }
+
+    if env.shadow_stack {
+        push_frame(env, fn_val, proc.name.name());
+    }
+
     let body = build_exp_stmt(
         env,
         layout_ids,
@@ -4905,6 +4966,10 @@ pub fn build_proc<'a, 'ctx, 'env>(
             builder.build_return(Some(&body));
         }
     }
+
+    if env.shadow_stack {
+        pop_frame(env, fn_val);
+    }
 }
 
 pub fn verify_fn(fn_val: FunctionValue<'_>) {
@@ -4925,7 +4990,14 @@ fn function_value_by_func_spec<'a, 'ctx, 'env>(
     captures_niche: CapturesNiche<'a>,
     result: &Layout<'a>,
 ) -> FunctionValue<'ctx> {
-    let fn_name = func_spec_name(env.arena, &env.interns, symbol, func_spec);
+    // Only a specialization whose name was taken is declared with the whole `FuncSpec`
+    let name_in_full = func_spec_name_in_full(&env.interns, symbol, arguments, result, func_spec);
+
+    if let Some(fn_val) = env.module.get_function(&name_in_full) {
+        return fn_val;
+    }
+
+    let fn_name = func_spec_name(&env.interns, symbol, arguments, result, func_spec);
     let fn_name = fn_name.as_str();
 
     function_value_by_name_help(env, arguments, captures_niche, result, symbol, fn_name)
@@ -0,0 +1,433 @@
+//! Debug info which points at the Roc source procedures and their statements come
+//! from, so debuggers can show it, step through it line by line, break on Roc
+//! functions by name (e.g. `break 'List.map'`) and show the values of Roc variables.
+
+use crate::llvm::build::{create_entry_block_alloca, Env};
+use inkwell::debug_info::{
+    AsDIScope, DIFile, DIFlags, DIFlagsConstants, DISubprogram, DIType, DebugInfoBuilder,
+};
+use inkwell::module::{FlagBehavior, Module};
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue};
+use inkwell::AddressSpace;
+use roc_builtins::bitcode::IntWidth;
+use roc_collections::all::MutMap;
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::layout::{Builtin, Layout};
+use roc_region::all::{LineInfo, Region};
+use std::path::{Path, PathBuf};
+
+// DWARF base type encodings
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x08;
+
+/// The source of each module, where its top-level definitions are, and where the
+/// statements of its procedures come from
+pub struct SourceInfo {
+    files: MutMap<ModuleId, SourceFile>,
+    def_regions: MutMap<Symbol, Region>,
+    stmt_regions: MutMap<Symbol, Region>,
+    emit_debug_info: bool,
+}
+
+struct SourceFile {
+    path: PathBuf,
+    line_info: LineInfo,
+}
+
+impl SourceInfo {
+    pub fn new(
+        sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+        def_regions: MutMap<Symbol, Region>,
+        stmt_regions: MutMap<Symbol, Region>,
+        emit_debug_info: bool,
+    ) -> Self {
+        let files = sources
+            .iter()
+            .map(|(module_id, (path, src))| {
+                // Debuggers look for the file relative to where they run, so make it absolute.
+                let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+
+                let file = SourceFile {
+                    path,
+                    line_info: LineInfo::new(src),
+                };
+
+                (*module_id, file)
+            })
+            .collect();
+
+        SourceInfo {
+            files,
+            def_regions,
+            stmt_regions,
+            emit_debug_info,
+        }
+    }
+
+    /// Backtraces use the source info too, but only debuggers need debug info.
+    pub fn emits_debug_info(&self) -> bool {
+        self.emit_debug_info
+    }
+
+    /// The line a top-level definition's name is on, counting from 1
+    pub(crate) fn def_line(&self, symbol: Symbol) -> Option<u32> {
+        let region = self.def_regions.get(&symbol)?;
+        let file = self.files.get(&symbol.module_id())?;
+
+        Some(file.line_info.convert_pos(region.start()).line + 1)
+    }
+
+    /// The line and column the statement binding a symbol starts at, counting from 1.
+    /// Statements are specialized in the module whose source they come from, so the
+    /// symbol's module is also the region's.
+    fn stmt_position(&self, symbol: Symbol) -> Option<(u32, u32)> {
+        let region = self.stmt_regions.get(&symbol)?;
+        let file = self.files.get(&symbol.module_id())?;
+        let position = file.line_info.convert_pos(region.start());
+
+        Some((position.line + 1, position.column + 1))
+    }
+
+    pub(crate) fn path(&self, module_id: ModuleId) -> Option<&Path> {
+        self.files.get(&module_id).map(|file| file.path.as_path())
+    }
+
+    fn di_file<'ctx>(&self, env: &Env<'_, 'ctx, '_>, module_id: ModuleId) -> DIFile<'ctx> {
+        match self.files.get(&module_id) {
+            Some(file) => {
+                let filename = file.path.file_name().unwrap_or_default();
+                let directory = file.path.parent().unwrap_or_else(|| "".as_ref());
+
+                env.dibuilder
+                    .create_file(&filename.to_string_lossy(), &directory.to_string_lossy())
+            }
+            None => env.compile_unit.get_file(),
+        }
+    }
+}
+
+/// Without these flags, LLVM drops the debug info rather than emitting it.
+pub fn add_debug_info_flags<'ctx>(module: &Module<'ctx>, env: &Env<'_, 'ctx, '_>) {
+    let i32_type = env.context.i32_type();
+
+    if module.get_flag("Debug Info Version").is_none() {
+        let version =
+            i32_type.const_int(inkwell::debug_info::debug_metadata_version() as u64, false);
+
+        module.add_basic_value_flag("Debug Info Version", FlagBehavior::Warning, version);
+    }
+
+    if module.get_flag("Dwarf Version").is_none() {
+        let version = i32_type.const_int(4, false);
+
+        module.add_basic_value_flag("Dwarf Version", FlagBehavior::Warning, version);
+    }
+}
+
+/// Roc's name for a procedure, e.g. `List.map`. Specializations share it, but their
+/// argument types tell them apart.
+pub(crate) fn readable_name(interns: &Interns, symbol: Symbol) -> String {
+    match interns.module_ids.get_name(symbol.module_id()) {
+        Some(module_name) => format!("{}.{}", module_name, symbol.as_str(interns)),
+        None => symbol.as_str(interns).to_string(),
+    }
+}
+
+/// A subprogram for a procedure, with its Roc name, the line it's defined on, and the
+/// types of its arguments and return value.
+pub(crate) fn proc_subprogram<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    source_info: &SourceInfo,
+    symbol: Symbol,
+    linkage_name: &str,
+    args: &[(Layout<'a>, Symbol)],
+    ret_layout: &Layout<'a>,
+) -> DISubprogram<'ctx> {
+    let file = source_info.di_file(env, symbol.module_id());
+    let line = source_info.def_line(symbol).unwrap_or(0);
+
+    let return_type = ditype_from_layout(env, file, ret_layout);
+    let parameter_types: Vec<DIType<'ctx>> = args
+        .iter()
+        .map(|(layout, _)| ditype_from_layout(env, file, layout))
+        .collect();
+
+    let subroutine_type = env.dibuilder.create_subroutine_type(
+        file,
+        Some(return_type),
+        &parameter_types,
+        DIFlags::PUBLIC,
+    );
+
+    env.dibuilder.create_function(
+        /* scope */ file.as_debug_info_scope(),
+        /* func name */ &readable_name(&env.interns, symbol),
+        /* linkage_name */ Some(linkage_name),
+        /* file */ file,
+        /* line_no */ line,
+        /* DIType */ subroutine_type,
+        /* is_local_to_unit */ true,
+        /* is_definition */ true,
+        /* scope_line */ line,
+        /* flags */ DIFlags::PUBLIC,
+        /* is_optimized */ false,
+    )
+}
+
+/// Point the instructions which follow at the line the procedure is defined on, and
+/// make its arguments visible to debuggers.
+pub(crate) fn enter_proc<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    source_info: &SourceInfo,
+    fn_val: FunctionValue<'ctx>,
+    symbol: Symbol,
+    args: &[(Layout<'a>, Symbol)],
+) {
+    let subprogram = match fn_val.get_subprogram() {
+        Some(subprogram) => subprogram,
+        None => return,
+    };
+
+    let line = source_info.def_line(symbol).unwrap_or(0);
+    let loc = env.dibuilder.create_debug_location(
+        env.context,
+        line,
+        0,
+        subprogram.as_debug_info_scope(),
+        None,
+    );
+
+    env.builder.set_current_debug_location(env.context, loc);
+
+    for (index, (arg_val, (layout, arg_symbol))) in fn_val.get_param_iter().zip(args).enumerate() {
+        declare_variable(
+            env,
+            source_info,
+            fn_val,
+            *arg_symbol,
+            layout,
+            arg_val,
+            Some(index as u32 + 1),
+        );
+    }
+}
+
+/// Point the instructions which follow at the source of the statement which binds the
+/// symbol. Statements the compiler generates keep the location of the one before them.
+pub(crate) fn enter_stmt<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    source_info: &SourceInfo,
+    fn_val: FunctionValue<'ctx>,
+    symbol: Symbol,
+) {
+    let (subprogram, (line, column)) =
+        match (fn_val.get_subprogram(), source_info.stmt_position(symbol)) {
+            (Some(subprogram), Some(position)) => (subprogram, position),
+            _ => return,
+        };
+
+    let loc = env.dibuilder.create_debug_location(
+        env.context,
+        line,
+        column,
+        subprogram.as_debug_info_scope(),
+        None,
+    );
+
+    env.builder.set_current_debug_location(env.context, loc);
+}
+
+/// Make a variable visible to debuggers, by storing its value in a stack slot which
+/// the debug info points at. Only variables named in the source are declared, since
+/// the ones the compiler introduces would just be noise.
+pub(crate) fn declare_variable<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    source_info: &SourceInfo,
+    fn_val: FunctionValue<'ctx>,
+    symbol: Symbol,
+    layout: &Layout<'a>,
+    value: BasicValueEnum<'ctx>,
+    arg_no: Option<u32>,
+) {
+    let name = symbol.as_str(&env.interns);
+
+    // Values passed by reference would be shown as the pointer's bytes.
+    if !name.starts_with(|c: char| c.is_ascii_lowercase()) || value.is_pointer_value() {
+        return;
+    }
+
+    let (subprogram, loc, block) = match (
+        fn_val.get_subprogram(),
+        env.builder.get_current_debug_location(),
+        env.builder.get_insert_block(),
+    ) {
+        (Some(subprogram), Some(loc), Some(block)) if block.get_terminator().is_none() => {
+            (subprogram, loc, block)
+        }
+        _ => return,
+    };
+
+    let dibuilder: &DebugInfoBuilder<'ctx> = env.dibuilder;
+    let file = source_info.di_file(env, symbol.module_id());
+    let ty = ditype_from_layout(env, file, layout);
+    let scope = subprogram.as_debug_info_scope();
+
+    let var_info = match arg_no {
+        Some(arg_no) => dibuilder.create_parameter_variable(
+            scope,
+            name,
+            arg_no,
+            file,
+            loc.get_line(),
+            ty,
+            true,
+            DIFlags::ZERO,
+        ),
+        None => dibuilder.create_auto_variable(
+            scope,
+            name,
+            file,
+            loc.get_line(),
+            ty,
+            true,
+            DIFlags::ZERO,
+            0,
+        ),
+    };
+
+    let storage = create_entry_block_alloca(env, fn_val, value.get_type(), name);
+
+    env.builder
+        .build_store(storage, value.as_basic_value_enum());
+    dibuilder.insert_declare_at_end(storage, Some(var_info), None, loc, block);
+}
+
+fn basic_ditype<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    name: &str,
+    size_in_bytes: u32,
+    encoding: u32,
+) -> DIType<'ctx> {
+    env.dibuilder
+        .create_basic_type(name, size_in_bytes as u64 * 8, encoding, DIFlags::PUBLIC)
+        .expect("basic types have a name")
+        .as_type()
+}
+
+/// The layout of a Str or List: a pointer to the elements, the length and the capacity
+fn seamless_ditype<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    file: DIFile<'ctx>,
+    name: &str,
+    element_type: DIType<'ctx>,
+) -> DIType<'ctx> {
+    let ptr_bytes = env.target_info.ptr_size() as u32;
+    let ptr_bits = ptr_bytes as u64 * 8;
+    let nat_type = basic_ditype(env, "Nat", ptr_bytes, DW_ATE_UNSIGNED);
+    let bytes_type = env
+        .dibuilder
+        .create_pointer_type(
+            "bytes",
+            element_type,
+            ptr_bits,
+            ptr_bits as u32,
+            AddressSpace::Generic,
+        )
+        .as_type();
+
+    let members: Vec<DIType<'ctx>> = [
+        ("bytes", bytes_type),
+        ("length", nat_type),
+        ("capacity", nat_type),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(index, (member_name, member_type))| {
+        env.dibuilder
+            .create_member_type(
+                file.as_debug_info_scope(),
+                member_name,
+                file,
+                0,
+                ptr_bits,
+                ptr_bits as u32,
+                index as u64 * ptr_bits,
+                DIFlags::PUBLIC,
+                *member_type,
+            )
+            .as_type()
+    })
+    .collect();
+
+    env.dibuilder
+        .create_struct_type(
+            file.as_debug_info_scope(),
+            name,
+            file,
+            0,
+            3 * ptr_bits,
+            ptr_bits as u32,
+            DIFlags::PUBLIC,
+            None,
+            &members,
+            0,
+            None,
+            "",
+        )
+        .as_type()
+}
+
+fn ditype_from_layout<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    file: DIFile<'ctx>,
+    layout: &Layout<'_>,
+) -> DIType<'ctx> {
+    match layout {
+        Layout::Builtin(builtin) => match builtin {
+            Builtin::Bool => basic_ditype(env, "Bool", 1, DW_ATE_BOOLEAN),
+            Builtin::Int(width) => {
+                let encoding = if width.is_signed() {
+                    DW_ATE_SIGNED
+                } else {
+                    DW_ATE_UNSIGNED
+                };
+
+                basic_ditype(env, &format!("{:?}", width), width.stack_size(), encoding)
+            }
+            Builtin::Float(width) => basic_ditype(
+                env,
+                &format!("{:?}", width),
+                width.stack_size(),
+                DW_ATE_FLOAT,
+            ),
+            Builtin::Decimal => basic_ditype(env, "Dec", 16, DW_ATE_SIGNED),
+            Builtin::Str => {
+                let byte_type = basic_ditype(env, "U8", IntWidth::U8.stack_size(), DW_ATE_UNSIGNED);
+
+                seamless_ditype(env, file, "Str", byte_type)
+            }
+            Builtin::List(element_layout) => {
+                let element_type = ditype_from_layout(env, file, element_layout);
+
+                seamless_ditype(env, file, "List", element_type)
+            }
+        },
+        // These get shown as their bytes, with a name saying what kind of value they are.
+        Layout::Struct { .. } => opaque_ditype(env, "Record", layout),
+        Layout::Union(_) => opaque_ditype(env, "Union", layout),
+        Layout::Boxed(_) => opaque_ditype(env, "Box", layout),
+        Layout::LambdaSet(_) => opaque_ditype(env, "Closure", layout),
+        Layout::RecursivePointer => opaque_ditype(env, "Recursive", layout),
+    }
+}
+
+fn opaque_ditype<'ctx>(env: &Env<'_, 'ctx, '_>, name: &str, layout: &Layout<'_>) -> DIType<'ctx> {
+    basic_ditype(
+        env,
+        name,
+        layout.stack_size(env.target_info),
+        DW_ATE_UNSIGNED,
+    )
+}
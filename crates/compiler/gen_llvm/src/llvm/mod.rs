@@ -1,9 +1,11 @@
+pub mod backtrace;
 pub mod bitcode;
 pub mod build;
 pub mod build_list;
 pub mod build_str;
 pub mod compare;
 pub mod convert;
+pub mod debug_info;
 mod expect;
 pub mod externs;
 pub mod refcounting;
@@ -1,20 +1,27 @@
 use crate::docs::DocEntry::DetachedDoc;
 use crate::docs::TypeAnnotation::{Apply, BoundVariable, Function, NoTypeAnn, Record, TagUnion};
 use crate::file::LoadedModule;
+use bumpalo::Bump;
 use roc_can::scope::Scope;
+use roc_module::called_via::{BinOp, CalledVia};
 use roc_module::ident::ModuleName;
 use roc_module::symbol::IdentIds;
 use roc_parse::ast::AssignedField;
 use roc_parse::ast::{self, ExtractSpaces, TypeHeader};
-use roc_parse::ast::{CommentOrNewline, TypeDef, ValueDef};
+use roc_parse::ast::{Collection, CommentOrNewline, TypeDef, ValueDef};
+use roc_parse::module::module_defs;
+use roc_parse::parser::{EExpr, Parser, SyntaxError};
+use roc_parse::state::State;
+use roc_region::all::{Loc, Position, Region};
+use serde::{Serialize, Serializer};
 
 // Documentation generation requirements
 
 #[derive(Debug)]
 pub struct Documentation {
     pub name: String,
-    pub version: String,
-    pub docs: String,
+    pub version: Option<String>,
+    pub docs: Option<String>,
     pub modules: Vec<LoadedModule>,
 }
 
@@ -31,15 +38,22 @@ pub enum DocEntry {
     DetachedDoc(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DocDef {
     pub name: String,
+    /// Where the name is defined
+    #[serde(skip)]
+    pub region: Region,
     pub type_vars: Vec<String>,
+    #[serde(rename = "type")]
     pub type_annotation: TypeAnnotation,
     pub docs: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// Serializes (e.g. into `docs.json`) as an object whose `kind` says which variant it is.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
 pub enum TypeAnnotation {
     TagUnion {
         tags: Vec<Tag>,
@@ -51,6 +65,7 @@ pub enum TypeAnnotation {
     },
     ObscuredTagUnion,
     ObscuredRecord,
+    #[serde(serialize_with = "serialize_bound_variable")]
     BoundVariable(String),
     Apply {
         name: String,
@@ -63,39 +78,96 @@ pub enum TypeAnnotation {
     Ability {
         members: Vec<AbilityMember>,
     },
+    /// An opaque type. Its underlying type is hidden, but the abilities it has are not.
+    Opaque {
+        abilities: Vec<HasAbility>,
+    },
+    /// A type with `has` clauses, e.g. `a -> U64 | a has Hash`
+    #[serde(rename_all = "camelCase")]
+    Where {
+        #[serde(rename = "type")]
+        ann: Box<TypeAnnotation>,
+        #[serde(serialize_with = "serialize_has_clauses")]
+        has_clauses: Vec<(String, TypeAnnotation)>,
+    },
     Wildcard,
     NoTypeAnn,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
 pub enum RecordField {
+    #[serde(rename = "required")]
     RecordField {
         name: String,
+        #[serde(rename = "type")]
         type_annotation: TypeAnnotation,
     },
+    #[serde(rename = "optional")]
     OptionalField {
         name: String,
+        #[serde(rename = "type")]
         type_annotation: TypeAnnotation,
     },
-    LabelOnly {
-        name: String,
-    },
+    #[serde(rename = "labelOnly")]
+    LabelOnly { name: String },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AbilityMember {
     pub name: String,
+    #[serde(skip)]
+    pub region: Region,
+    #[serde(rename = "type")]
     pub type_annotation: TypeAnnotation,
+    #[serde(rename = "hasClauses", serialize_with = "serialize_has_clauses")]
     pub able_variables: Vec<(String, TypeAnnotation)>,
     pub docs: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// An ability in an opaque type's `has` list, e.g. `Eq` in `Age := U32 has [Eq]`
+#[derive(Debug, Clone, Serialize)]
+pub struct HasAbility {
+    /// The name as written, so it may be qualified, e.g. `Encode.EncoderFormatting`
+    pub name: String,
+    /// False if the opaque type gives its own implementations, as in `Eq { isEq: myIsEq }`
+    pub derived: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Tag {
     pub name: String,
     pub values: Vec<TypeAnnotation>,
 }
 
+/// A bound variable's name goes in a field, since only objects can have a `kind`.
+fn serialize_bound_variable<S: Serializer>(name: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct BoundVariable<'a> {
+        name: &'a str,
+    }
+
+    BoundVariable { name }.serialize(serializer)
+}
+
+/// `has` clauses like `a has Hash`, as objects rather than pairs
+fn serialize_has_clauses<S: Serializer>(
+    has_clauses: &[(String, TypeAnnotation)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct HasClause<'a> {
+        variable: &'a str,
+        ability: &'a TypeAnnotation,
+    }
+
+    serializer.collect_seq(
+        has_clauses
+            .iter()
+            .map(|(variable, ability)| HasClause { variable, ability }),
+    )
+}
+
 pub fn generate_module_docs(
     scope: Scope,
     module_name: ModuleName,
@@ -170,6 +242,7 @@ fn generate_entry_docs<'a>(
                             let name = identifier.to_string();
                             let doc_def = DocDef {
                                 name,
+                                region: loc_pattern.region,
                                 type_annotation: type_to_docs(false, loc_ann.value),
                                 type_vars: Vec::new(),
                                 docs,
@@ -189,6 +262,7 @@ fn generate_entry_docs<'a>(
                         if ident_ids.get_id(identifier).is_some() {
                             let doc_def = DocDef {
                                 name: identifier.to_string(),
+                                region: ann_pattern.region,
                                 type_annotation: type_to_docs(false, ann_type.value),
                                 type_vars: Vec::new(),
                                 docs,
@@ -223,6 +297,7 @@ fn generate_entry_docs<'a>(
 
                     let doc_def = DocDef {
                         name: name.value.to_string(),
+                        region: name.region,
                         type_annotation: type_to_docs(false, ann.value),
                         type_vars,
                         docs,
@@ -232,6 +307,7 @@ fn generate_entry_docs<'a>(
 
                 TypeDef::Opaque {
                     header: TypeHeader { name, vars },
+                    derived,
                     ..
                 } => {
                     let mut type_vars = Vec::new();
@@ -242,9 +318,20 @@ fn generate_entry_docs<'a>(
                         }
                     }
 
+                    let abilities = match derived {
+                        Some(has_abilities) => has_abilities
+                            .value
+                            .collection()
+                            .iter()
+                            .filter_map(|has_ability| has_ability_to_doc(has_ability.value))
+                            .collect(),
+                        None => Vec::new(),
+                    };
+
                     let doc_def = DocDef {
                         name: name.value.to_string(),
-                        type_annotation: TypeAnnotation::NoTypeAnn,
+                        region: name.region,
+                        type_annotation: TypeAnnotation::Opaque { abilities },
                         type_vars,
                         docs,
                     };
@@ -273,6 +360,7 @@ fn generate_entry_docs<'a>(
 
                             AbilityMember {
                                 name: extracted.item.to_string(),
+                                region: mem.name.region,
                                 type_annotation,
                                 able_variables,
                                 docs: comments_or_new_lines_to_docs(extracted.before),
@@ -282,6 +370,7 @@ fn generate_entry_docs<'a>(
 
                     let doc_def = DocDef {
                         name: name.value.to_string(),
+                        region: name.region,
                         type_annotation: TypeAnnotation::Ability { members },
                         type_vars,
                         docs,
@@ -380,6 +469,10 @@ fn type_to_docs(in_func_type_ann: bool, type_annotation: ast::TypeAnnotation) ->
                 output: Box::new(type_to_docs(true, output_ann.value)),
             }
         }
+        ast::TypeAnnotation::Where(ta, has_clauses) => TypeAnnotation::Where {
+            ann: Box::new(type_to_docs(in_func_type_ann, ta.value)),
+            has_clauses: has_clauses_to_docs(has_clauses),
+        },
         ast::TypeAnnotation::Wildcard => TypeAnnotation::Wildcard,
         _ => NoTypeAnn,
     }
@@ -389,25 +482,48 @@ fn ability_member_type_to_docs(
     type_annotation: ast::TypeAnnotation,
 ) -> (TypeAnnotation, Vec<(String, TypeAnnotation)>) {
     match type_annotation {
-        ast::TypeAnnotation::Where(ta, has_clauses) => {
-            let ta = type_to_docs(false, ta.value);
-            let has_clauses = has_clauses
-                .iter()
-                .map(|hc| {
-                    let ast::HasClause { var, ability } = hc.value;
-                    (
-                        var.value.extract_spaces().item.to_string(),
-                        type_to_docs(false, ability.value),
-                    )
-                })
-                .collect();
-
-            (ta, has_clauses)
+        ast::TypeAnnotation::Where(ta, has_clauses) => (
+            type_to_docs(false, ta.value),
+            has_clauses_to_docs(has_clauses),
+        ),
+        ast::TypeAnnotation::SpaceBefore(&sub_type_ann, _)
+        | ast::TypeAnnotation::SpaceAfter(&sub_type_ann, _) => {
+            ability_member_type_to_docs(sub_type_ann)
         }
         _ => (type_to_docs(false, type_annotation), vec![]),
     }
 }
 
+fn has_clauses_to_docs(has_clauses: &[Loc<ast::HasClause>]) -> Vec<(String, TypeAnnotation)> {
+    has_clauses
+        .iter()
+        .map(|hc| {
+            let ast::HasClause { var, ability } = hc.value;
+            (
+                var.value.extract_spaces().item.to_string(),
+                type_to_docs(false, ability.value),
+            )
+        })
+        .collect()
+}
+
+// The Option here represents if it is malformed.
+fn has_ability_to_doc(has_ability: ast::HasAbility) -> Option<HasAbility> {
+    match has_ability {
+        ast::HasAbility::HasAbility { ability, impls } => {
+            match type_to_docs(false, ability.value) {
+                Apply { name, parts } if parts.is_empty() => Some(HasAbility {
+                    name,
+                    derived: impls.is_none(),
+                }),
+                _ => None,
+            }
+        }
+        ast::HasAbility::SpaceBefore(&sub_has_ability, _)
+        | ast::HasAbility::SpaceAfter(&sub_has_ability, _) => has_ability_to_doc(sub_has_ability),
+    }
+}
+
 fn record_field_to_doc(
     in_func_ann: bool,
     field: ast::AssignedField<'_, ast::TypeAnnotation>,
@@ -474,3 +590,290 @@ fn comments_or_new_lines_to_docs<'a>(
         Some(docs)
     }
 }
+
+/// Top-level `expect`s that check the code examples in a module's doc comments.
+///
+/// Each example is parsed right where it is in the module's source, with the `##` in front of
+/// its lines blanked out, so problems with it get reported at the doc comment's lines.
+/// Examples ending in an `expected == actual` comparison become expects which run under
+/// `roc test --doc`; other examples only need to typecheck. Examples that only contain defs
+/// (like setting up a `Dict` to use in later examples) are put in front of the examples after
+/// them in the same doc comment.
+pub fn doc_example_expects<'a>(
+    arena: &'a Bump,
+    src: &'a [u8],
+) -> Result<Vec<Loc<ValueDef<'a>>>, (SyntaxError<'a>, State<'a>)> {
+    let mut expects = Vec::new();
+
+    for doc_comment in doc_comments(src) {
+        let mut setup = ast::Defs::default();
+        let mut setup_region = None;
+        let mut setup_used = true;
+
+        for example in doc_examples(src, &doc_comment) {
+            match parse_doc_example(arena, src, example)? {
+                DocExample::Setup(defs) => {
+                    for (def, region) in defs.defs().zip(defs.regions.iter().copied()) {
+                        match def {
+                            Ok(type_def) => setup.push_type_def(*type_def, region, &[], &[]),
+                            Err(value_def) => setup.push_value_def(*value_def, region, &[], &[]),
+                        }
+
+                        setup_region = Some(match setup_region {
+                            Some(setup_region) => Region::span_across(&setup_region, &region),
+                            None => region,
+                        });
+                    }
+
+                    setup_used = false;
+                }
+                DocExample::Expression(loc_expr) => {
+                    let is_assertion = is_assertion(&loc_expr.value);
+                    let body = with_setup(arena, &setup, loc_expr);
+
+                    if is_assertion {
+                        expects.push(expect(arena, body));
+                    } else {
+                        expects.push(expect(arena, typecheck_only(arena, body)));
+                    }
+
+                    setup_used = true;
+                }
+            }
+        }
+
+        if let (false, Some(region)) = (setup_used, setup_region) {
+            let empty_record = Loc::at(region, ast::Expr::Record(Collection::empty()));
+            let body = with_setup(arena, &setup, empty_record);
+
+            expects.push(expect(arena, typecheck_only(arena, body)));
+        }
+    }
+
+    Ok(expects)
+}
+
+/// A line of a doc comment: where it starts and ends in the module's source, and where its `##` is.
+#[derive(Debug, Clone, Copy)]
+struct DocLine {
+    start: usize,
+    hashes: usize,
+    end: usize,
+}
+
+impl DocLine {
+    /// The text of the line after the `##`. Doc comments are conventionally written as
+    /// `## text`, so this drops the space after the `##`.
+    fn text<'a>(&self, src: &'a [u8]) -> &'a str {
+        let text = std::str::from_utf8(&src[self.hashes + 2..self.end]).unwrap_or("");
+
+        text.strip_prefix(' ').unwrap_or(text)
+    }
+}
+
+/// The runs of consecutive lines in the source which start with `##`.
+fn doc_comments(src: &[u8]) -> Vec<Vec<DocLine>> {
+    let mut doc_comments = Vec::new();
+    let mut current = Vec::new();
+    let mut start = 0;
+
+    for line in src.split(|byte| *byte == b'\n') {
+        let indent = line.iter().take_while(|byte| **byte == b' ').count();
+
+        if line[indent..].starts_with(b"##") {
+            let end = start + line.strip_suffix(b"\r").unwrap_or(line).len();
+
+            current.push(DocLine {
+                start,
+                hashes: start + indent,
+                end,
+            });
+        } else if !current.is_empty() {
+            doc_comments.push(std::mem::take(&mut current));
+        }
+
+        start += line.len() + 1;
+    }
+
+    if !current.is_empty() {
+        doc_comments.push(current);
+    }
+
+    doc_comments
+}
+
+/// The Roc code blocks in a doc comment: indented code blocks, and fenced ones tagged `roc`
+/// (or not tagged at all). Fenced blocks tagged anything else, like `roc ignore`, are left alone.
+fn doc_examples<'c>(src: &[u8], doc_comment: &'c [DocLine]) -> Vec<&'c [DocLine]> {
+    let mut examples = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut fence: Option<(&str, bool)> = None;
+    let mut after_blank_line = true;
+
+    for (index, line) in doc_comment.iter().enumerate() {
+        let text = line.text(src);
+        let trimmed = text.trim_start();
+
+        if let Some((marker, is_roc)) = fence {
+            if trimmed.starts_with(marker) {
+                if let Some(start) = current.take() {
+                    if is_roc {
+                        examples.push(&doc_comment[start..index]);
+                    }
+                }
+
+                fence = None;
+            }
+
+            continue;
+        }
+
+        let is_indented = text.starts_with("    ");
+
+        if let Some(start) = current {
+            if is_indented || trimmed.is_empty() {
+                continue;
+            }
+
+            examples.push(&doc_comment[start..index]);
+            current = None;
+        }
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let info = trimmed[3..].trim();
+
+            fence = Some((&trimmed[..3], info.is_empty() || info == "roc"));
+            current = Some(index + 1);
+        } else if is_indented && after_blank_line {
+            current = Some(index);
+        }
+
+        after_blank_line = trimmed.is_empty();
+    }
+
+    if let (Some(start), None) = (current, fence) {
+        examples.push(&doc_comment[start..]);
+    }
+
+    examples
+        .into_iter()
+        .filter_map(|lines| {
+            let is_code = |line: &DocLine| !line.text(src).trim().is_empty();
+            let first = lines.iter().position(is_code)?;
+            let last = lines.iter().rposition(is_code)?;
+
+            Some(&lines[first..=last])
+        })
+        .collect()
+}
+
+enum DocExample<'a> {
+    /// Nothing but defs, for the examples after it to use
+    Setup(ast::Defs<'a>),
+    /// Ends in an expression, which is either an assertion or only needs to typecheck
+    Expression(Loc<ast::Expr<'a>>),
+}
+
+/// Parses the example in place: in a copy of the source which ends where the example does,
+/// and has the `##`s in front of the example's lines replaced by spaces.
+fn parse_doc_example<'a>(
+    arena: &'a Bump,
+    src: &'a [u8],
+    lines: &[DocLine],
+) -> Result<DocExample<'a>, (SyntaxError<'a>, State<'a>)> {
+    let first = lines[0];
+    let last = lines[lines.len() - 1];
+    let bytes = arena.alloc_slice_copy(&src[..last.end]);
+
+    for line in lines {
+        bytes[line.hashes..line.hashes + 2].copy_from_slice(b"  ");
+    }
+
+    let state = State::new_at_line_start(bytes, first.start);
+
+    match roc_parse::expr::test_parse_expr(0, arena, state.clone()) {
+        Ok(loc_expr) => Ok(DocExample::Expression(loc_expr)),
+        Err(expr_fail) => match module_defs().parse(arena, state.clone()) {
+            Ok((_, defs, _)) => Ok(DocExample::Setup(defs)),
+            Err((_, defs_fail, _)) => {
+                let fail = match expr_fail {
+                    // The example was meant to be nothing but defs.
+                    EExpr::DefMissingFinalExpr(_) | EExpr::DefMissingFinalExpr2(_, _) => defs_fail,
+                    _ => SyntaxError::Expr(expr_fail, Position::new(first.start as u32)),
+                };
+
+                Err((fail, state))
+            }
+        },
+    }
+}
+
+/// Whether the example ends in an `expected == actual` comparison. This goes by how the
+/// example parses, so `a |> f == b` (which means `a |> (f == b)`) is not an assertion.
+fn is_assertion(expr: &ast::Expr) -> bool {
+    use ast::Expr::*;
+
+    match expr {
+        SpaceBefore(expr, _) | SpaceAfter(expr, _) | ParensAround(expr) => is_assertion(expr),
+        Defs(_, final_expr) => is_assertion(&final_expr.value),
+        BinOps(lefts, _) => {
+            let ops = || lefts.iter().map(|(_, loc_op)| loc_op.value);
+
+            match ops().min() {
+                Some(lowest) => ops()
+                    .filter(|op| op.cmp(&lowest).is_eq())
+                    .all(|op| op == BinOp::Equals),
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn with_setup<'a>(
+    arena: &'a Bump,
+    setup: &ast::Defs<'a>,
+    loc_expr: Loc<ast::Expr<'a>>,
+) -> Loc<ast::Expr<'a>> {
+    if setup.is_empty() {
+        loc_expr
+    } else {
+        let region = loc_expr.region;
+        let defs = ast::Expr::Defs(arena.alloc(setup.clone()), arena.alloc(loc_expr));
+
+        Loc::at(region, defs)
+    }
+}
+
+/// `(\_ -> True) (\{} -> body)`, which gets the body typechecked without the expect
+/// depending on what it evaluates to.
+fn typecheck_only<'a>(arena: &'a Bump, body: Loc<ast::Expr<'a>>) -> Loc<ast::Expr<'a>> {
+    use ast::{Expr, Pattern};
+
+    let region = body.region;
+    let ignore = Expr::Closure(
+        arena.alloc([Loc::at(region, Pattern::Underscore(""))]),
+        arena.alloc(Loc::at(region, Expr::Tag("True"))),
+    );
+    let empty_record = Pattern::RecordDestructure(Collection::empty());
+    let example = Expr::Closure(
+        arena.alloc([Loc::at(region, empty_record)]),
+        arena.alloc(body),
+    );
+    let args: &[&Loc<Expr>] = arena.alloc([&*arena.alloc(Loc::at(region, example))]);
+
+    Loc::at(
+        region,
+        Expr::Apply(arena.alloc(Loc::at(region, ignore)), args, CalledVia::Space),
+    )
+}
+
+fn expect<'a>(arena: &'a Bump, condition: Loc<ast::Expr<'a>>) -> Loc<ValueDef<'a>> {
+    let region = condition.region;
+    let expect = ValueDef::Expect {
+        condition: arena.alloc(condition),
+        preceding_comment: Region::new(region.start(), region.start()),
+    };
+
+    Loc::at(region, expect)
+}
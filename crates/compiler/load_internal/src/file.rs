@@ -128,7 +128,11 @@ pub struct LoadConfig {
 #[derive(Debug, Clone, Copy)]
 pub enum ExecutionMode {
     Test,
+    /// Like [`ExecutionMode::Test`], but also runs the code examples in doc comments as expects.
+    TestDocExamples,
     Check,
+    /// Like [`ExecutionMode::Check`], but also typechecks the code examples in doc comments.
+    CheckDocExamples,
     Executable,
     /// Like [`ExecutionMode::Executable`], but stops in the presence of type errors.
     ExecutableIfCheck,
@@ -137,10 +141,25 @@ pub enum ExecutionMode {
 impl ExecutionMode {
     fn goal_phase(&self) -> Phase {
         match self {
-            ExecutionMode::Test | ExecutionMode::Executable => Phase::MakeSpecializations,
-            ExecutionMode::Check | ExecutionMode::ExecutableIfCheck => Phase::SolveTypes,
+            ExecutionMode::Test | ExecutionMode::TestDocExamples | ExecutionMode::Executable => {
+                Phase::MakeSpecializations
+            }
+            ExecutionMode::Check
+            | ExecutionMode::CheckDocExamples
+            | ExecutionMode::ExecutableIfCheck => Phase::SolveTypes,
         }
     }
+
+    fn includes_doc_examples(&self) -> bool {
+        matches!(
+            self,
+            ExecutionMode::TestDocExamples | ExecutionMode::CheckDocExamples
+        )
+    }
+
+    fn runs_expects(&self) -> bool {
+        matches!(self, ExecutionMode::Test | ExecutionMode::TestDocExamples)
+    }
 }
 
 /// Struct storing various intermediate stages by their ModuleId
@@ -289,7 +308,17 @@ fn start_phase<'a>(
                 // parse the file
                 let header = state.module_cache.headers.remove(&module_id).unwrap();
 
-                BuildTask::Parse { header }
+                let include_doc_examples = state.exec_mode.includes_doc_examples();
+
+                if include_doc_examples {
+                    // The cached builtins were typechecked without their doc examples.
+                    state.cached_subs.lock().remove(&module_id);
+                }
+
+                BuildTask::Parse {
+                    header,
+                    include_doc_examples,
+                }
             }
             Phase::CanonicalizeAndConstrain => {
                 // canonicalize the file
@@ -723,6 +752,10 @@ pub struct MonomorphizedModule<'a> {
     pub type_problems: MutMap<ModuleId, Vec<TypeError>>,
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub toplevel_expects: ToplevelExpects,
+    /// Where each top-level definition's name is, for debug info
+    pub def_regions: MutMap<Symbol, Region>,
+    /// Where the statement binding each symbol in `procedures` comes from, for debug info
+    pub stmt_regions: MutMap<Symbol, Region>,
     pub entry_point: EntryPoint<'a>,
     pub exposed_to_host: ExposedToHost,
     pub sources: MutMap<ModuleId, (PathBuf, Box<str>)>,
@@ -744,6 +777,9 @@ pub enum EntryPoint<'a> {
 pub struct Expectations {
     pub subs: roc_types::subs::Subs,
     pub path: PathBuf,
+    /// The module's source, for rendering expect failures. This is not always on disk at
+    /// `path`, as with the builtins, whose doc examples can have expects too.
+    pub source: Box<str>,
     pub expectations: VecMap<Region, Vec<(Symbol, Variable)>>,
     pub ident_ids: IdentIds,
 }
@@ -828,6 +864,7 @@ enum Msg<'a> {
         module_timing: ModuleTiming,
         abilities_store: AbilitiesStore,
         toplevel_expects: ToplevelExpects,
+        def_regions: MutMap<Symbol, Region>,
     },
     MadeSpecializations {
         module_id: ModuleId,
@@ -839,6 +876,7 @@ enum Msg<'a> {
         update_mode_ids: UpdateModeIds,
         module_timing: ModuleTiming,
         subs: Subs,
+        stmt_regions: MutMap<Symbol, Region>,
     },
 
     /// The task is to only typecheck AND monomorphize modules
@@ -915,6 +953,8 @@ struct State<'a> {
     pub dependencies: Dependencies<'a>,
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub toplevel_expects: ToplevelExpects,
+    pub def_regions: MutMap<Symbol, Region>,
+    pub stmt_regions: MutMap<Symbol, Region>,
     pub exposed_to_host: ExposedToHost,
 
     /// This is the "final" list of IdentIds, after canonicalization and constraint gen
@@ -988,6 +1028,8 @@ impl<'a> State<'a> {
             dependencies,
             procedures: MutMap::default(),
             toplevel_expects: ToplevelExpects::default(),
+            def_regions: MutMap::default(),
+            stmt_regions: MutMap::default(),
             exposed_to_host: ExposedToHost::default(),
             exposed_types,
             arc_modules,
@@ -1090,6 +1132,7 @@ enum BuildTask<'a> {
     },
     Parse {
         header: ModuleHeader<'a>,
+        include_doc_examples: bool,
     },
     CanonicalizeAndConstrain {
         parsed: ParsedModule<'a>,
@@ -2359,12 +2402,13 @@ fn update<'a>(
                 .insert(module_id, solved_module.problems);
 
             if !loc_expects.is_empty() {
-                let (path, _) = state.module_cache.sources.get(&module_id).unwrap();
+                let (path, source) = state.module_cache.sources.get(&module_id).unwrap();
 
                 let expectations = Expectations {
                     expectations: loc_expects,
                     subs: solved_subs.clone().into_inner(),
                     path: path.to_owned(),
+                    source: (*source).into(),
                     ident_ids: ident_ids.clone(),
                 };
 
@@ -2528,6 +2572,7 @@ fn update<'a>(
             module_timing,
             abilities_store,
             toplevel_expects,
+            def_regions,
         } => {
             log!("found specializations for {:?}", module_id);
 
@@ -2535,6 +2580,7 @@ fn update<'a>(
 
             state.toplevel_expects.pure.extend(toplevel_expects.pure);
             state.toplevel_expects.fx.extend(toplevel_expects.fx);
+            state.def_regions.extend(def_regions);
 
             state
                 .module_cache
@@ -2575,6 +2621,7 @@ fn update<'a>(
             external_specializations_requested,
             module_timing,
             layout_cache,
+            stmt_regions,
             ..
         } => {
             debug_assert!(state.goal_phase() == Phase::MakeSpecializations);
@@ -2585,6 +2632,7 @@ fn update<'a>(
             let _ = layout_cache;
 
             state.procedures.extend(procedures);
+            state.stmt_regions.extend(stmt_regions);
             state.module_cache.late_specializations.insert(
                 module_id,
                 LateSpecializationsModule {
@@ -2847,6 +2895,8 @@ fn finish_specialization(
 
     let State {
         toplevel_expects,
+        def_regions,
+        stmt_regions,
         procedures,
         module_cache,
         output_path,
@@ -2871,7 +2921,7 @@ fn finish_specialization(
 
     let entry_point = {
         match exec_mode {
-            ExecutionMode::Test => EntryPoint::Test,
+            ExecutionMode::Test | ExecutionMode::TestDocExamples => EntryPoint::Test,
             ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck => {
                 let path_to_platform = {
                     use PlatformPath::*;
@@ -2922,7 +2972,7 @@ fn finish_specialization(
                     }
                 }
             }
-            ExecutionMode::Check => unreachable!(),
+            ExecutionMode::Check | ExecutionMode::CheckDocExamples => unreachable!(),
         }
     };
 
@@ -2945,6 +2995,8 @@ fn finish_specialization(
         sources,
         timings: state.timings,
         toplevel_expects,
+        def_regions,
+        stmt_regions,
     })
 }
 
@@ -3057,6 +3109,12 @@ fn load_platform_module<'a>(
                         header
                     )))
                 }
+                Ok((ast::Module::Package { header }, _parse_state)) => {
+                    Err(LoadingProblem::UnexpectedHeader(format!(
+                        "loading package modules is not supported yet, got Package with header\n{:?}",
+                        header
+                    )))
+                }
                 Ok((ast::Module::Platform { header }, parser_state)) => {
                     // make a `platform` module that ultimately exposes `main` to the host
                     let platform_module_msg = fabricate_platform_module(
@@ -3471,6 +3529,12 @@ fn parse_header<'a>(
             &header,
             module_timing,
         )),
+        Ok((ast::Module::Package { header }, _parse_state)) => {
+            Err(LoadingProblem::UnexpectedHeader(format!(
+                "loading package modules is not supported yet, got Package with header\n{:?}",
+                header
+            )))
+        }
 
         Err(fail) => Err(LoadingProblem::ParsingFailed(
             fail.map_problem(SyntaxError::Header)
@@ -4631,12 +4695,16 @@ fn canonicalize_and_constrain<'a>(
     }
 }
 
-fn parse<'a>(arena: &'a Bump, header: ModuleHeader<'a>) -> Result<Msg<'a>, LoadingProblem<'a>> {
+fn parse<'a>(
+    arena: &'a Bump,
+    header: ModuleHeader<'a>,
+    include_doc_examples: bool,
+) -> Result<Msg<'a>, LoadingProblem<'a>> {
     let mut module_timing = header.module_timing;
     let parse_start = Instant::now();
     let source = header.parse_state.original_bytes();
     let parse_state = header.parse_state;
-    let parsed_defs = match module_defs().parse(arena, parse_state) {
+    let mut parsed_defs = match module_defs().parse(arena, parse_state) {
         Ok((_, success, _state)) => success,
         Err((_, fail, state)) => {
             return Err(LoadingProblem::ParsingFailed(
@@ -4645,6 +4713,21 @@ fn parse<'a>(arena: &'a Bump, header: ModuleHeader<'a>) -> Result<Msg<'a>, Loadi
         }
     };
 
+    if include_doc_examples {
+        let expects = match crate::docs::doc_example_expects(arena, source) {
+            Ok(expects) => expects,
+            Err((fail, state)) => {
+                return Err(LoadingProblem::ParsingFailed(
+                    fail.into_file_error(header.module_path, &state),
+                ));
+            }
+        };
+
+        for Loc { value, region } in expects {
+            parsed_defs.push_value_def(value, region, &[], &[]);
+        }
+    }
+
     // Record the parse end time once, to avoid checking the time a second time
     // immediately afterward (for the beginning of canonicalization).
     let parse_end = Instant::now();
@@ -4758,6 +4841,7 @@ fn make_specializations<'a>(
         abilities: AbilitiesView::World(&world_abilities),
         exposed_by_module,
         derived_module: &derived_module,
+        stmt_regions: MutMap::default(),
     };
 
     let mut procs = Procs::new_in(arena);
@@ -4787,6 +4871,8 @@ fn make_specializations<'a>(
     // Turn `Bytes.Decode.IdentId(238)` into `Bytes.Decode.238`, we rely on this in mono tests
     mono_env.home.register_debug_idents(mono_env.ident_ids);
 
+    let stmt_regions = mono_env.stmt_regions;
+
     let make_specializations_end = Instant::now();
     module_timing
         .make_specializations
@@ -4802,6 +4888,7 @@ fn make_specializations<'a>(
         subs,
         external_specializations_requested,
         module_timing,
+        stmt_regions,
     }
 }
 
@@ -4826,6 +4913,7 @@ fn build_pending_specializations<'a>(
 
     let mut module_thunks = bumpalo::collections::Vec::new_in(arena);
     let mut toplevel_expects = ToplevelExpects::default();
+    let mut def_regions = MutMap::default();
 
     let mut procs_base = ProcsBase {
         partial_procs: BumpMap::default(),
@@ -4852,6 +4940,7 @@ fn build_pending_specializations<'a>(
         abilities: AbilitiesView::Module(&abilities_store),
         exposed_by_module,
         derived_module: &derived_module,
+        stmt_regions: MutMap::default(),
     };
 
     // Add modules' decls to Procs
@@ -4861,6 +4950,8 @@ fn build_pending_specializations<'a>(
         let symbol = declarations.symbols[index].value;
         let expr_var = declarations.variables[index];
 
+        def_regions.insert(symbol, declarations.symbols[index].region);
+
         let is_host_exposed = exposed_to_host.values.contains_key(&symbol);
 
         // TODO remove clones (with drain)
@@ -5067,11 +5158,8 @@ fn build_pending_specializations<'a>(
             }
             Expectation => {
                 // skip expectations if we're not going to run them
-                match execution_mode {
-                    ExecutionMode::Test => { /* fall through */ }
-                    ExecutionMode::Check
-                    | ExecutionMode::Executable
-                    | ExecutionMode::ExecutableIfCheck => continue,
+                if !execution_mode.runs_expects() {
+                    continue;
                 }
 
                 // mark this symbol as a top-level thunk before any other work on the procs
@@ -5143,11 +5231,8 @@ fn build_pending_specializations<'a>(
             }
             ExpectationFx => {
                 // skip expectations if we're not going to run them
-                match execution_mode {
-                    ExecutionMode::Test => { /* fall through */ }
-                    ExecutionMode::Check
-                    | ExecutionMode::Executable
-                    | ExecutionMode::ExecutableIfCheck => continue,
+                if !execution_mode.runs_expects() {
+                    continue;
                 }
 
                 // mark this symbol as a top-level thunk before any other work on the procs
@@ -5235,6 +5320,7 @@ fn build_pending_specializations<'a>(
         module_timing,
         abilities_store,
         toplevel_expects,
+        def_regions,
     }
 }
 
@@ -5289,6 +5375,7 @@ fn load_derived_partial_procs<'a>(
             abilities: AbilitiesView::World(world_abilities),
             exposed_by_module,
             derived_module,
+            stmt_regions: MutMap::default(),
         };
 
         let partial_proc = match derived_expr {
@@ -5370,7 +5457,10 @@ fn run_task<'a>(
             ident_ids_by_module,
         )
         .map(|(_, msg)| msg),
-        Parse { header } => parse(arena, header),
+        Parse {
+            header,
+            include_doc_examples,
+        } => parse(arena, header, include_doc_examples),
         CanonicalizeAndConstrain {
             parsed,
             module_ids,
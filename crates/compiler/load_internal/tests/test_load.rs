@@ -17,6 +17,7 @@ mod helpers;
 use crate::helpers::fixtures_dir;
 use bumpalo::Bump;
 use roc_can::module::ExposedByModule;
+use roc_load_internal::docs::{DocEntry, TypeAnnotation};
 use roc_load_internal::file::{ExecutionMode, LoadConfig, Threading};
 use roc_load_internal::file::{LoadResult, LoadStart, LoadedModule, LoadingProblem};
 use roc_module::ident::ModuleName;
@@ -26,6 +27,8 @@ use roc_region::all::LineInfo;
 use roc_reporting::report::can_problem;
 use roc_reporting::report::RenderTarget;
 use roc_reporting::report::RocDocAllocator;
+use roc_reporting::report::Severity;
+use roc_solve_problem::TypeError;
 use roc_target::TargetInfo;
 use roc_types::pretty_print::name_and_print_var;
 use roc_types::pretty_print::DebugPrint;
@@ -37,6 +40,7 @@ fn load_and_typecheck(
     filename: PathBuf,
     exposed_types: ExposedByModule,
     target_info: TargetInfo,
+    exec_mode: ExecutionMode,
 ) -> Result<LoadedModule, LoadingProblem> {
     use LoadResult::*;
 
@@ -45,7 +49,7 @@ fn load_and_typecheck(
         target_info,
         render: RenderTarget::Generic,
         threading: Threading::Single,
-        exec_mode: ExecutionMode::Check,
+        exec_mode,
     };
 
     match roc_load_internal::file::load(
@@ -91,10 +95,18 @@ fn format_can_problems(
 }
 
 fn multiple_modules(subdir: &str, files: Vec<(&str, &str)>) -> Result<LoadedModule, String> {
+    multiple_modules_with_mode(subdir, ExecutionMode::Check, files)
+}
+
+fn multiple_modules_with_mode(
+    subdir: &str,
+    exec_mode: ExecutionMode,
+    files: Vec<(&str, &str)>,
+) -> Result<LoadedModule, String> {
     let arena = Bump::new();
     let arena = &arena;
 
-    match multiple_modules_help(subdir, arena, files) {
+    match multiple_modules_help(subdir, arena, exec_mode, files) {
         Err(io_error) => panic!("IO trouble: {:?}", io_error),
         Ok(Err(LoadingProblem::FormattedReport(buf))) => Err(buf),
         Ok(Err(loading_problem)) => Err(format!("{:?}", loading_problem)),
@@ -127,6 +139,7 @@ fn multiple_modules(subdir: &str, files: Vec<(&str, &str)>) -> Result<LoadedModu
 fn multiple_modules_help<'a>(
     subdir: &str,
     arena: &'a Bump,
+    exec_mode: ExecutionMode,
     mut files: Vec<(&str, &str)>,
 ) -> Result<Result<LoadedModule, roc_load_internal::file::LoadingProblem<'a>>, std::io::Error> {
     use std::fs::{self, File};
@@ -165,7 +178,13 @@ fn multiple_modules_help<'a>(
         writeln!(file, "{}", source)?;
         file_handles.push(file);
 
-        load_and_typecheck(arena, full_file_path, Default::default(), TARGET_INFO)
+        load_and_typecheck(
+            arena,
+            full_file_path,
+            Default::default(),
+            TARGET_INFO,
+            exec_mode,
+        )
     };
 
     Ok(result)
@@ -179,7 +198,13 @@ fn load_fixture(
     let src_dir = fixtures_dir().join(dir_name);
     let filename = src_dir.join(format!("{}.roc", module_name));
     let arena = Bump::new();
-    let loaded = load_and_typecheck(&arena, filename, subs_by_module, TARGET_INFO);
+    let loaded = load_and_typecheck(
+        &arena,
+        filename,
+        subs_by_module,
+        TARGET_INFO,
+        ExecutionMode::Check,
+    );
     let mut loaded_module = match loaded {
         Ok(x) => x,
         Err(roc_load_internal::file::LoadingProblem::FormattedReport(report)) => {
@@ -339,7 +364,13 @@ fn interface_with_deps() {
     let src_dir = fixtures_dir().join("interface_with_deps");
     let filename = src_dir.join("Primary.roc");
     let arena = Bump::new();
-    let loaded = load_and_typecheck(&arena, filename, subs_by_module, TARGET_INFO);
+    let loaded = load_and_typecheck(
+        &arena,
+        filename,
+        subs_by_module,
+        TARGET_INFO,
+        ExecutionMode::Check,
+    );
 
     let mut loaded_module = loaded.expect("Test module failed to load");
     let home = loaded_module.module_id;
@@ -872,3 +903,299 @@ fn issue_2863_module_type_does_not_exist() {
         Ok(_) => unreachable!("we expect failure here"),
     }
 }
+
+const DOC_EXAMPLES_PLATFORM: (&str, &str) = (
+    "platform/main.roc",
+    indoc!(
+        r#"
+            platform "testplatform"
+                requires {} { main : Str }
+                exposes []
+                packages {}
+                imports []
+                provides [mainForHost]
+
+            mainForHost : Str
+            mainForHost = main
+            "#
+    ),
+);
+
+#[test]
+fn doc_examples_are_typechecked() {
+    let modules = vec![
+        DOC_EXAMPLES_PLATFORM,
+        (
+            "Main",
+            indoc!(
+                r#"
+                    app "test"
+                        packages { pf: "platform/main.roc" }
+                        provides [main] to pf
+
+                    ## The greeting.
+                    ##
+                    ##     greeting = Str.concat main "!"
+                    ##
+                    ## It ends up like this:
+                    ##
+                    ##     greeting == "Hello, World!!"
+                    ##
+                    ## And it has a length:
+                    ##
+                    ##     Str.countGraphemes main
+                    main : Str
+                    main = "Hello, World!"
+                    "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules_with_mode(
+        "doc_examples_are_typechecked",
+        ExecutionMode::CheckDocExamples,
+        modules,
+    )
+    .unwrap();
+
+    // The examples are parsed where they are, rather than appended to the module's source.
+    let (_, src) = loaded_module.sources.get(&loaded_module.module_id).unwrap();
+
+    assert!(!src.contains("expect"));
+}
+
+#[test]
+fn doc_examples_with_type_errors() {
+    let modules = vec![
+        DOC_EXAMPLES_PLATFORM,
+        (
+            "Main",
+            indoc!(
+                r#"
+                    app "test"
+                        packages { pf: "platform/main.roc" }
+                        provides [main] to pf
+
+                    ## The greeting.
+                    ##
+                    ##     main == 42
+                    main : Str
+                    main = "Hello, World!"
+                    "#
+            ),
+        ),
+    ];
+
+    let arena = Bump::new();
+    let mut loaded_module = multiple_modules_help(
+        "doc_examples_with_type_errors",
+        &arena,
+        ExecutionMode::CheckDocExamples,
+        modules,
+    )
+    .unwrap()
+    .unwrap();
+
+    let home = loaded_module.module_id;
+    let (_, src) = loaded_module.sources.get(&home).unwrap();
+    let line_info = LineInfo::new(src);
+    let example_line = src
+        .lines()
+        .position(|line| line.ends_with("main == 42"))
+        .unwrap() as u32;
+
+    let type_problems = loaded_module
+        .type_problems
+        .remove(&home)
+        .unwrap_or_default();
+
+    // The mismatch is reported at the example's line in the doc comment.
+    assert!(type_problems.iter().any(|problem| match problem {
+        TypeError::BadExpr(region, ..) => {
+            line_info.convert_region(*region).start().line == example_line
+        }
+        _ => false,
+    }));
+}
+
+#[test]
+fn doc_examples_with_syntax_errors() {
+    let modules = vec![
+        DOC_EXAMPLES_PLATFORM,
+        (
+            "Main",
+            indoc!(
+                r#"
+                    app "test"
+                        packages { pf: "platform/main.roc" }
+                        provides [main] to pf
+
+                    ## The greeting.
+                    ##
+                    ##     Str.concat main )
+                    ##
+                    ## Illustrations don't get checked:
+                    ##
+                    ## ```roc ignore
+                    ## main ==
+                    ## ```
+                    main : Str
+                    main = "Hello, World!"
+                    "#
+            ),
+        ),
+    ];
+
+    let arena = Bump::new();
+    let loaded = multiple_modules_help(
+        "doc_examples_with_syntax_errors",
+        &arena,
+        ExecutionMode::CheckDocExamples,
+        modules,
+    )
+    .unwrap();
+
+    match loaded {
+        Err(LoadingProblem::FormattedReport(report)) => {
+            assert!(report.contains("Str.concat main )"), "{}", report);
+            assert!(!report.contains("main =="), "{}", report);
+        }
+        Err(problem) => panic!("Expected a syntax error, but got {:?}", problem),
+        Ok(_) => panic!("Expected a syntax error"),
+    }
+}
+
+#[test]
+fn builtin_doc_examples_typecheck() {
+    let modules = vec![(
+        "Main",
+        indoc!(
+            r#"
+                interface Main exposes [greeting] imports []
+
+                greeting = Str.concat "Hello, " "World!"
+                "#
+        ),
+    )];
+
+    let arena = Bump::new();
+    let mut loaded_module = multiple_modules_help(
+        "builtin_doc_examples_typecheck",
+        &arena,
+        ExecutionMode::CheckDocExamples,
+        modules,
+    )
+    .unwrap()
+    .unwrap();
+
+    let builtins: Vec<ModuleId> = loaded_module
+        .sources
+        .keys()
+        .copied()
+        .filter(|module_id| module_id.is_builtin())
+        .collect();
+
+    assert!(!builtins.is_empty());
+
+    for module_id in builtins {
+        let (path, src) = loaded_module.sources.get(&module_id).unwrap();
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, module_id, &loaded_module.interns);
+
+        // Examples which only set things up can leave unused defs behind, which is fine.
+        let errors: Vec<Problem> = loaded_module
+            .can_problems
+            .remove(&module_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|problem| {
+                let report = can_problem(&alloc, &lines, path.clone(), problem.clone());
+
+                report.severity == Severity::RuntimeError
+            })
+            .collect();
+
+        assert!(
+            errors.is_empty(),
+            "{}",
+            format_can_problems(errors, module_id, &loaded_module.interns, path.clone(), src)
+        );
+
+        let type_problems = loaded_module
+            .type_problems
+            .remove(&module_id)
+            .unwrap_or_default();
+
+        assert!(type_problems.is_empty(), "{:?}", type_problems);
+    }
+}
+
+#[test]
+fn docs_for_abilities_and_opaque_types() {
+    let modules = vec![(
+        "Main",
+        indoc!(
+            r#"
+                interface Hashing exposes [MyHash, Id, hashTwice] imports []
+
+                MyHash has
+                    ## Hashes the value.
+                    myHash : a -> U64 | a has MyHash
+
+                Id := U64 has [MyHash { myHash: hashId }]
+
+                hashId = \@Id n -> n
+
+                hashTwice : a -> U64 | a has MyHash
+                hashTwice = \value -> myHash value + myHash value
+                "#
+        ),
+    )];
+
+    let loaded_module = multiple_modules("docs_for_abilities_and_opaque_types", modules).unwrap();
+    let module_docs = &loaded_module.documentation[&loaded_module.module_id];
+
+    let doc_def = |name: &str| {
+        module_docs
+            .entries
+            .iter()
+            .find_map(|entry| match entry {
+                DocEntry::DocDef(doc_def) if doc_def.name == name => Some(doc_def),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    match &doc_def("MyHash").type_annotation {
+        TypeAnnotation::Ability { members } => {
+            assert_eq!(members.len(), 1);
+            assert_eq!(members[0].name, "myHash");
+            assert!(members[0]
+                .docs
+                .as_deref()
+                .unwrap()
+                .contains("Hashes the value."));
+            assert_eq!(members[0].able_variables.len(), 1);
+        }
+        other => panic!("expected an ability, got {:?}", other),
+    }
+
+    match &doc_def("Id").type_annotation {
+        TypeAnnotation::Opaque { abilities } => {
+            assert_eq!(abilities.len(), 1);
+            assert_eq!(abilities[0].name, "MyHash");
+            assert!(!abilities[0].derived);
+        }
+        other => panic!("expected an opaque type, got {:?}", other),
+    }
+
+    match &doc_def("hashTwice").type_annotation {
+        TypeAnnotation::Where { ann, has_clauses } => {
+            assert!(matches!(**ann, TypeAnnotation::Function { .. }));
+            assert_eq!(has_clauses.len(), 1);
+            assert_eq!(has_clauses[0].0, "a");
+        }
+        other => panic!("expected `has` clauses, got {:?}", other),
+    }
+}
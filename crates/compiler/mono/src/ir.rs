@@ -1375,6 +1375,10 @@ pub struct Env<'a, 'i> {
     pub abilities: AbilitiesView<'i>,
     pub exposed_by_module: &'i ExposedByModule,
     pub derived_module: &'i SharedDerivedModule,
+    /// Where the statement binding each symbol comes from in the source, for debug info.
+    /// A symbol is bound by exactly one `Stmt::Let` in a procedure, and by the same code
+    /// in every specialization of it, so this identifies the statement.
+    pub stmt_regions: MutMap<Symbol, Region>,
 }
 
 impl<'a, 'i> Env<'a, 'i> {
@@ -1384,6 +1388,14 @@ impl<'a, 'i> Env<'a, 'i> {
         Symbol::new(self.home, ident_id)
     }
 
+    /// Remember which source code the statement binding this symbol comes from
+    fn record_stmt_region(&mut self, symbol: Symbol, region: Region) {
+        // Code the compiler generates has no region of its own
+        if region != Region::zero() {
+            self.stmt_regions.insert(symbol, region);
+        }
+    }
+
     pub fn next_update_mode_id(&mut self) -> UpdateModeId {
         self.update_mode_ids.next_id()
     }
@@ -2436,6 +2448,8 @@ fn from_can_let<'a>(
                     0 => {
                         // We don't need any specializations, that means this symbol is never
                         // referenced.
+                        env.record_stmt_region(*symbol, def.loc_expr.region);
+
                         with_hole(
                             env,
                             def.loc_expr.value,
@@ -2457,6 +2471,8 @@ fn from_can_let<'a>(
                         // Unify the expr_var with the requested specialization once.
                         let _res = env.unify(var, def.expr_var);
 
+                        env.record_stmt_region(specialized_symbol, def.loc_expr.region);
+
                         with_hole(
                             env,
                             def.loc_expr.value,
@@ -2491,6 +2507,8 @@ fn from_can_let<'a>(
 
                             let _res = env.unify(var, new_def_expr_var);
 
+                            env.record_stmt_region(specialized_symbol, def.loc_expr.region);
+
                             stmt = with_hole(
                                 env,
                                 specialized_expr,
@@ -2546,6 +2564,8 @@ fn from_can_let<'a>(
         let outer_symbol = env.unique_symbol();
         stmt = store_pattern(env, procs, layout_cache, &mono_pattern, outer_symbol, stmt);
 
+        env.record_stmt_region(outer_symbol, def.loc_expr.region);
+
         // convert the def body, store in outer_symbol
         with_hole(
             env,
@@ -7665,15 +7685,19 @@ fn assign_to_symbol<'a>(
             )
         }
         Value(_symbol) => result,
-        NotASymbol => with_hole(
-            env,
-            loc_arg.value,
-            arg_var,
-            procs,
-            layout_cache,
-            symbol,
-            env.arena.alloc(result),
-        ),
+        NotASymbol => {
+            env.record_stmt_region(symbol, loc_arg.region);
+
+            with_hole(
+                env,
+                loc_arg.value,
+                arg_var,
+                procs,
+                layout_cache,
+                symbol,
+                env.arena.alloc(result),
+            )
+        }
     }
 }
 
@@ -0,0 +1,369 @@
+//! The names procedures get in generated code, so that profilers, debuggers and crash
+//! backtraces show which Roc function is running.
+//!
+//! A name looks like `List.map$LxC_Lx$1f3a9c02`:
+//!
+//! - the module and the name of the function, as written in Roc
+//! - the layouts of the arguments, then `_`, then the layout of the result
+//! - a hash of what else tells specializations with the same layouts apart
+//!   (e.g. their captures, or the specialization chosen by alias analysis)
+//!
+//! Should two specializations' hashes be the same, [mangle_proc_name_in_full] names one
+//! of them with all of the bytes that were hashed instead.
+//!
+//! Layouts are encoded with one letter per builtin, following the Itanium C++ ABI
+//! for the numbers: `a` I8, `s` I16, `l` I32, `x` I64, `n` I128, `h` U8, `t` U16,
+//! `m` U32, `y` U64, `o` U128, `f` F32, `d` F64, `e` F128, `b` Bool, `D` Dec, `S` Str.
+//! `L` and `B` are followed by the layout of the List's element or the Box's content,
+//! and `R` by the layouts of a struct's fields, ending in `E`. Unions, lambda sets and
+//! recursive pointers are just `U`, `C` and `P`.
+//!
+//! Since the layouts say nothing about field or tag names, demangling gives back
+//! something like `List.map : List I64, <closure> -> List I64`.
+
+use crate::layout::{Builtin, Layout};
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::all::WyHash;
+use roc_module::symbol::{Interns, Symbol};
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+
+const SEPARATOR: char = '$';
+const RESULT_SEPARATOR: char = '_';
+const HASH_LENGTH: usize = 8;
+/// Names need to stay the same from one build to the next, so the hash can't be seeded
+/// randomly, or use a hasher like `DefaultHasher` whose algorithm may change.
+const HASH_SEED: u64 = 0;
+
+pub fn mangle_proc_name<H: Hash>(
+    interns: &Interns,
+    symbol: Symbol,
+    arguments: &[Layout<'_>],
+    result: &Layout<'_>,
+    disambiguator: H,
+) -> String {
+    let mut buf = name_and_signature(interns, symbol, arguments, result);
+
+    write!(buf, "{:08x}", disambiguator_hash(disambiguator)).unwrap();
+
+    buf
+}
+
+/// Like [mangle_proc_name], but with every byte of the disambiguator rather than a hash
+/// of them, for a specialization whose name would otherwise be the same as another's.
+pub fn mangle_proc_name_in_full(
+    interns: &Interns,
+    symbol: Symbol,
+    arguments: &[Layout<'_>],
+    result: &Layout<'_>,
+    disambiguator: &[u8],
+) -> String {
+    let mut buf = name_and_signature(interns, symbol, arguments, result);
+
+    write_in_full(&mut buf, disambiguator);
+
+    buf
+}
+
+fn name_and_signature(
+    interns: &Interns,
+    symbol: Symbol,
+    arguments: &[Layout<'_>],
+    result: &Layout<'_>,
+) -> String {
+    let mut buf = String::new();
+
+    match interns.module_ids.get_name(symbol.module_id()) {
+        Some(module_name) => write!(buf, "{}.{}", module_name, symbol.as_str(interns)).unwrap(),
+        None => buf.push_str(symbol.as_str(interns)),
+    }
+
+    buf.push(SEPARATOR);
+    encode_signature(&mut buf, arguments, result);
+    buf.push(SEPARATOR);
+
+    buf
+}
+
+fn write_in_full(buf: &mut String, bytes: &[u8]) {
+    for byte in bytes {
+        write!(buf, "{:02x}", byte).unwrap();
+    }
+}
+
+fn disambiguator_hash<H: Hash>(disambiguator: H) -> u32 {
+    let mut hasher = WyHash::with_seed(HASH_SEED);
+    disambiguator.hash(&mut hasher);
+
+    hasher.finish() as u32
+}
+
+fn encode_signature(buf: &mut String, arguments: &[Layout<'_>], result: &Layout<'_>) {
+    for argument in arguments {
+        encode_layout(buf, argument);
+    }
+
+    buf.push(RESULT_SEPARATOR);
+    encode_layout(buf, result);
+}
+
+fn encode_layout(buf: &mut String, layout: &Layout<'_>) {
+    match layout {
+        Layout::Builtin(builtin) => match builtin {
+            Builtin::Int(width) => buf.push(match width {
+                IntWidth::I8 => 'a',
+                IntWidth::I16 => 's',
+                IntWidth::I32 => 'l',
+                IntWidth::I64 => 'x',
+                IntWidth::I128 => 'n',
+                IntWidth::U8 => 'h',
+                IntWidth::U16 => 't',
+                IntWidth::U32 => 'm',
+                IntWidth::U64 => 'y',
+                IntWidth::U128 => 'o',
+            }),
+            Builtin::Float(width) => buf.push(match width {
+                FloatWidth::F32 => 'f',
+                FloatWidth::F64 => 'd',
+                FloatWidth::F128 => 'e',
+            }),
+            Builtin::Bool => buf.push('b'),
+            Builtin::Decimal => buf.push('D'),
+            Builtin::Str => buf.push('S'),
+            Builtin::List(element) => {
+                buf.push('L');
+                encode_layout(buf, element);
+            }
+        },
+        Layout::Struct { field_layouts, .. } => {
+            buf.push('R');
+
+            for field in field_layouts.iter() {
+                encode_layout(buf, field);
+            }
+
+            buf.push('E');
+        }
+        Layout::Boxed(inner) => {
+            buf.push('B');
+            encode_layout(buf, inner);
+        }
+        Layout::Union(_) => buf.push('U'),
+        Layout::LambdaSet(_) => buf.push('C'),
+        Layout::RecursivePointer => buf.push('P'),
+    }
+}
+
+/// Turn a name made by [mangle_proc_name] back into the Roc name and the types of the
+/// specialization, or `None` if it isn't one.
+pub fn demangle(mangled: &str) -> Option<String> {
+    let mut parts = mangled.rsplitn(3, SEPARATOR);
+
+    let hash = parts.next()?;
+    let signature = parts.next()?;
+    let name = parts.next()?;
+
+    if name.is_empty()
+        || hash.len() < HASH_LENGTH
+        || !hash.bytes().all(|byte| byte.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    let (arguments, result) = signature.split_once(RESULT_SEPARATOR)?;
+
+    let mut decoder = Decoder {
+        bytes: arguments.as_bytes(),
+    };
+    let mut argument_types = Vec::new();
+
+    while !decoder.bytes.is_empty() {
+        argument_types.push(decoder.decode(false)?);
+    }
+
+    let mut decoder = Decoder {
+        bytes: result.as_bytes(),
+    };
+    let result_type = decoder.decode(false)?;
+
+    if !decoder.bytes.is_empty() {
+        return None;
+    }
+
+    if argument_types.is_empty() {
+        Some(format!("{} : {}", name, result_type))
+    } else {
+        Some(format!(
+            "{} : {} -> {}",
+            name,
+            argument_types.join(", "),
+            result_type
+        ))
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn next(&mut self) -> Option<u8> {
+        let (first, rest) = self.bytes.split_first()?;
+
+        self.bytes = rest;
+
+        Some(*first)
+    }
+
+    /// Decode one layout into the type it's shown as. Applied types get parentheses
+    /// when they are themselves a type argument.
+    fn decode(&mut self, is_type_argument: bool) -> Option<String> {
+        let name = match self.next()? {
+            b'a' => "I8",
+            b's' => "I16",
+            b'l' => "I32",
+            b'x' => "I64",
+            b'n' => "I128",
+            b'h' => "U8",
+            b't' => "U16",
+            b'm' => "U32",
+            b'y' => "U64",
+            b'o' => "U128",
+            b'f' => "F32",
+            b'd' => "F64",
+            b'e' => "F128",
+            b'b' => "Bool",
+            b'D' => "Dec",
+            b'S' => "Str",
+            b'U' => "[..]",
+            b'C' => "<closure>",
+            b'P' => "<recursive>",
+            b'L' => return self.decode_applied("List", is_type_argument),
+            b'B' => return self.decode_applied("Box", is_type_argument),
+            b'R' => {
+                let mut fields = Vec::new();
+
+                loop {
+                    match self.bytes.first()? {
+                        b'E' => {
+                            self.next();
+
+                            break;
+                        }
+                        _ => fields.push(self.decode(false)?),
+                    }
+                }
+
+                return if fields.is_empty() {
+                    Some("{}".to_string())
+                } else {
+                    Some(format!("{{ {} }}", fields.join(", ")))
+                };
+            }
+            _ => return None,
+        };
+
+        Some(name.to_string())
+    }
+
+    fn decode_applied(&mut self, name: &str, is_type_argument: bool) -> Option<String> {
+        let argument = self.decode(true)?;
+
+        if is_type_argument {
+            Some(format!("({} {})", name, argument))
+        } else {
+            Some(format!("{} {}", name, argument))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_mangle {
+    use super::{demangle, disambiguator_hash, encode_signature, write_in_full};
+    use crate::layout::{Builtin, Layout};
+    use roc_builtins::bitcode::IntWidth;
+    use roc_collections::all::MutMap;
+
+    #[test]
+    fn demangle_list_map() {
+        assert_eq!(
+            demangle("List.map$LxC_LLS$1f3a9c02"),
+            Some("List.map : List I64, <closure> -> List (List Str)".to_string())
+        );
+    }
+
+    #[test]
+    fn demangle_thunk() {
+        assert_eq!(
+            demangle("#UserApp.main$_RbDE$00000000"),
+            Some("#UserApp.main : { Bool, Dec }".to_string())
+        );
+    }
+
+    #[test]
+    fn demangle_in_full() {
+        let mut buf = "List.map$LxC_Lx$".to_string();
+        write_in_full(&mut buf, &[0x1f; 32]);
+
+        assert_eq!(
+            demangle(&buf),
+            Some("List.map : List I64, <closure> -> List I64".to_string())
+        );
+    }
+
+    #[test]
+    fn demangle_rejects_other_names() {
+        assert_eq!(demangle("roc_builtins.list.map"), None);
+        assert_eq!(demangle("List.map$LxC_Lx$1f3a"), None);
+        assert_eq!(demangle("List.map$Lx_LxLx$1f3a9c02"), None);
+    }
+
+    #[test]
+    fn disambiguator_hashes() {
+        assert_eq!(disambiguator_hash(42u32), disambiguator_hash(42u32));
+        assert_ne!(disambiguator_hash(42u32), disambiguator_hash(43u32));
+    }
+
+    #[test]
+    fn colliding_hashes_in_full() {
+        // With only 32 bits of hash, some of the first few hundred thousand disambiguators
+        // are bound to share one
+        let mut by_hash = MutMap::default();
+        let (first, second) = (0u64..)
+            .find_map(|n| {
+                let mut bytes = [0u8; 32];
+                bytes[..8].copy_from_slice(&n.to_le_bytes());
+
+                by_hash
+                    .insert(disambiguator_hash(bytes), bytes)
+                    .map(|earlier| (earlier, bytes))
+            })
+            .unwrap();
+
+        let in_full = |bytes: [u8; 32]| {
+            let mut buf = String::new();
+            write_in_full(&mut buf, &bytes);
+
+            buf
+        };
+
+        assert_eq!(disambiguator_hash(first), disambiguator_hash(second));
+        assert_ne!(in_full(first), in_full(second));
+    }
+
+    #[test]
+    fn encode_then_demangle() {
+        let element = Layout::Builtin(Builtin::Int(IntWidth::U8));
+        let list = Layout::Builtin(Builtin::List(&element));
+        let arguments = [list, Layout::Boxed(&list), Layout::UNIT];
+
+        let mut buf = "Str.fromUtf8$".to_string();
+        encode_signature(&mut buf, &arguments, &Layout::Builtin(Builtin::Str));
+        buf.push_str("$0badf00d");
+
+        assert_eq!(
+            demangle(&buf),
+            Some("Str.fromUtf8 : List U8, Box (List U8), {} -> Str".to_string())
+        );
+    }
+}
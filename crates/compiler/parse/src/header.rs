@@ -5,7 +5,6 @@ use crate::parser::Progress::*;
 use crate::parser::{specialize, word1, EPackageEntry, EPackageName, Parser};
 use crate::state::State;
 use crate::string_literal;
-use bumpalo::collections::Vec;
 use roc_module::symbol::Symbol;
 use roc_region::all::Loc;
 
@@ -191,9 +190,9 @@ pub struct AppHeader<'a> {
 #[derive(Clone, Debug, PartialEq)]
 pub struct PackageHeader<'a> {
     pub name: Loc<PackageName<'a>>,
-    pub exposes: Vec<'a, Loc<Spaced<'a, ExposedName<'a>>>>,
-    pub packages: Vec<'a, (Loc<&'a str>, Loc<PackageName<'a>>)>,
-    pub imports: Vec<'a, Loc<ImportsEntry<'a>>>,
+    pub exposes: Collection<'a, Loc<Spaced<'a, ModuleName<'a>>>>,
+    pub packages: Collection<'a, Loc<Spaced<'a, PackageEntry<'a>>>>,
+    pub imports: Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>,
 
     // Potential comments and newlines - these will typically all be empty.
     pub before_header: &'a [CommentOrNewline<'a>],
@@ -29,6 +29,18 @@ impl<'a> State<'a> {
         self.original_bytes
     }
 
+    /// A state for parsing `bytes` from `offset`, which must be the start of a line.
+    /// This lets the loader parse code examples right where they are in doc comments.
+    pub fn new_at_line_start(bytes: &'a [u8], offset: usize) -> State<'a> {
+        debug_assert!(offset == 0 || bytes[offset - 1] == b'\n');
+
+        State {
+            original_bytes: bytes,
+            offset,
+            line_start: Position::new(offset as u32),
+        }
+    }
+
     pub(crate) fn bytes(&self) -> &'a [u8] {
         &self.original_bytes[self.offset..]
     }
@@ -0,0 +1 @@
+package "rtfeldman/blah" exposes [] packages {} imports []
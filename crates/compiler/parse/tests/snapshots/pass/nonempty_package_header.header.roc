@@ -0,0 +1,4 @@
+package "foo/barbaz"
+    exposes [Foo, Bar]
+    packages { json: "roc/json" }
+    imports [json.Json]
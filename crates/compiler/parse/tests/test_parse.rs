@@ -152,6 +152,7 @@ mod test_parse {
         pass/empty_hosted_header.header,
         pass/empty_interface_header.header,
         pass/empty_list.expr,
+        pass/empty_package_header.header,
         pass/empty_platform_header.header,
         pass/empty_record.expr,
         pass/empty_string.expr,
@@ -200,6 +201,7 @@ mod test_parse {
         pass/newline_inside_empty_list.expr,
         pass/newline_singleton_list.expr,
         pass/nonempty_hosted_header.header,
+        pass/nonempty_package_header.header,
         pass/nonempty_platform_header.header,
         pass/not_docs.expr,
         pass/number_literal_suffixes.expr,
@@ -1954,6 +1954,15 @@ pub(crate) fn type_to_var(
     }
 }
 
+/// Adds a type to `Subs` once solving is done, for example to print a type alias's
+/// definition the way solved types get printed. Every alias the type refers to has to be
+/// in `aliases`.
+pub fn type_to_var_after_solving(subs: &mut Subs, aliases: &mut Aliases, typ: &Type) -> Variable {
+    let mut pools = Pools::default();
+
+    type_to_var(subs, Rank::toplevel(), &mut pools, aliases, typ)
+}
+
 enum RegisterVariable {
     /// Based on the Type, we already know what variable this will be
     Direct(Variable),
@@ -0,0 +1,26 @@
+#![cfg(feature = "gen-llvm")]
+
+use crate::helpers::llvm::debug_line_numbers;
+
+use indoc::indoc;
+
+#[test]
+fn line_table_has_each_statement() {
+    let lines = debug_line_numbers(indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        double = \n ->
+            x = Num.add n 1
+            y = Num.mul x 3
+            y
+
+        main = double 4
+        "#
+    ));
+
+    // the line `double` is defined on, and then each of its statements
+    assert!(lines.contains(&3), "{:?}", lines);
+    assert!(lines.contains(&4), "{:?}", lines);
+    assert!(lines.contains(&5), "{:?}", lines);
+}
@@ -6,6 +6,7 @@ use roc_build::link::llvm_module_to_dylib;
 use roc_build::program::FunctionIterator;
 use roc_collections::all::MutSet;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
+use roc_gen_llvm::llvm::debug_info::{add_debug_info_flags, SourceInfo};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_load::{EntryPoint, ExecutionMode, LoadConfig, Threading};
 use roc_mono::ir::OptLevel;
@@ -49,6 +50,7 @@ fn create_llvm_module<'a>(
     config: HelperConfig,
     context: &'a inkwell::context::Context,
     target: &Triple,
+    emit_debug_info: bool,
 ) -> (&'static str, String, &'a Module<'a>) {
     let target_info = roc_target::TargetInfo::from(target);
 
@@ -98,6 +100,17 @@ fn create_llvm_module<'a>(
         ..
     } = loaded;
 
+    let source_info = if emit_debug_info {
+        Some(SourceInfo::new(
+            &loaded.sources,
+            std::mem::take(&mut loaded.def_regions),
+            std::mem::take(&mut loaded.stmt_regions),
+            true,
+        ))
+    } else {
+        None
+    };
+
     let mut lines = Vec::new();
     // errors whose reporting we delay (so we can see that code gen generates runtime errors)
     let mut delayed_errors = Vec::new();
@@ -221,6 +234,8 @@ fn create_llvm_module<'a>(
         mode: config.mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        source_info: source_info.as_ref(),
+        shadow_stack: false,
     };
 
     // strip Zig debug stuff
@@ -257,8 +272,12 @@ fn create_llvm_module<'a>(
 
     env.dibuilder.finalize();
 
-    // strip all debug info: we don't use it at the moment and causes weird validation issues
-    module.strip_debug_info();
+    if emit_debug_info {
+        add_debug_info_flags(module, &env);
+    } else {
+        // strip all debug info: we don't use it at the moment and causes weird validation issues
+        module.strip_debug_info();
+    }
 
     // Uncomment this to see the module's un-optimized LLVM instruction output:
     // env.module.print_to_stderr();
@@ -312,7 +331,7 @@ pub fn helper<'a>(
     let target = target_lexicon::Triple::host();
 
     let (main_fn_name, delayed_errors, module) =
-        create_llvm_module(arena, src, config, context, &target);
+        create_llvm_module(arena, src, config, context, &target, false);
 
     let res_lib = if config.add_debug_info {
         let module = annotate_with_debug_info(module, context);
@@ -326,6 +345,71 @@ pub fn helper<'a>(
     (main_fn_name, delayed_errors, lib)
 }
 
+/// The lines of `Test.roc` in the line table of the debug info emitted for `src`
+#[allow(dead_code)]
+pub fn debug_line_numbers(src: &str) -> Vec<u64> {
+    use inkwell::targets::{FileType, RelocMode};
+    use object::{Object, ObjectSection};
+    use roc_build::target::{convert_opt_level, target_machine};
+    use std::borrow::Cow;
+
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+    let target = target_lexicon::Triple::host();
+
+    let config = HelperConfig {
+        mode: LlvmBackendMode::GenTest,
+        ignore_problems: false,
+        add_debug_info: false,
+        opt_level: OptLevel::Normal,
+    };
+
+    let (_, _, module) = create_llvm_module(&arena, src, config, &context, &target, true);
+
+    let machine =
+        target_machine(&target, convert_opt_level(config.opt_level), RelocMode::PIC).unwrap();
+    let buffer = machine
+        .write_to_memory_buffer(module, FileType::Object)
+        .unwrap();
+    let object = object::File::parse(buffer.as_slice()).unwrap();
+
+    let load_section = |id: gimli::SectionId| -> Result<Cow<[u8]>, gimli::Error> {
+        let data = object
+            .section_by_name(id.name())
+            .and_then(|section| section.uncompressed_data().ok());
+
+        Ok(data.unwrap_or(Cow::Borrowed(&[])))
+    };
+    let dwarf = gimli::Dwarf::load(load_section).unwrap();
+    let dwarf = dwarf.borrow(|section| gimli::EndianSlice::new(section, gimli::LittleEndian));
+
+    let mut lines = Vec::new();
+    let mut units = dwarf.units();
+
+    while let Some(header) = units.next().unwrap() {
+        let unit = dwarf.unit(header).unwrap();
+        let program = match unit.line_program.clone() {
+            Some(program) => program,
+            None => continue,
+        };
+        let mut rows = program.rows();
+
+        while let Some((header, row)) = rows.next_row().unwrap() {
+            let file = match row.file(header) {
+                Some(file) => file,
+                None => continue,
+            };
+            let path = dwarf.attr_string(&unit, file.path_name()).unwrap();
+
+            if path.to_string_lossy().ends_with("Test.roc") {
+                lines.extend(row.line().map(|line| line.get()));
+            }
+        }
+    }
+
+    lines
+}
+
 fn annotate_with_debug_info<'ctx>(
     module: &Module<'ctx>,
     context: &'ctx inkwell::context::Context,
@@ -402,7 +486,7 @@ fn compile_to_wasm_bytes<'a>(
     let target = wasm32_target_tripple();
 
     let (_main_fn_name, _delayed_errors, llvm_module) =
-        create_llvm_module(arena, src, config, context, &target);
+        create_llvm_module(arena, src, config, context, &target, false);
 
     let content_hash = crate::helpers::src_hash(src);
     let wasm_file = llvm_module_to_wasm_file(&TEMP_DIR, content_hash, llvm_module);
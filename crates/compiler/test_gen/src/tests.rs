@@ -6,6 +6,7 @@
 
 pub mod gen_abilities;
 pub mod gen_compare;
+pub mod gen_debug_info;
 pub mod gen_dict;
 pub mod gen_list;
 pub mod gen_num;
@@ -5303,6 +5303,136 @@ fn instantiate_rigids_help(subs: &mut Subs, max_rank: Rank, initial: Variable) {
     }
 }
 
+/// Turns every named type variable in a type into an unnamed flex variable, so printing it
+/// names them `a`, `b`, ... in order of appearance, no matter what they were called.
+/// This is used to compare types which only differ in the names of their type variables.
+pub fn forget_type_var_names(subs: &mut Subs, var: Variable) {
+    let mut visited = MutSet::default();
+    let mut stack = vec![var];
+
+    macro_rules! var_slice {
+        ($variable_subs_slice:expr) => {{
+            let slice = $variable_subs_slice;
+            &subs.variables[slice.indices()]
+        }};
+    }
+
+    while let Some(var) = stack.pop() {
+        if !visited.insert(subs.get_root_key_without_compacting(var)) {
+            continue;
+        }
+
+        use Content::*;
+        use FlatType::*;
+
+        match subs.get_content_without_compacting(var) {
+            FlexVar(Some(_)) | RigidVar(_) => subs.set_content(var, FlexVar(None)),
+            &FlexAbleVar(Some(_), ability) | &RigidAbleVar(_, ability) => {
+                subs.set_content(var, FlexAbleVar(None, ability))
+            }
+            FlexVar(None) | FlexAbleVar(None, _) | Error => (),
+
+            &RecursionVar { structure, .. } => {
+                subs.set_content(
+                    var,
+                    RecursionVar {
+                        structure,
+                        opt_name: None,
+                    },
+                );
+
+                stack.push(structure);
+            }
+
+            Structure(flat_type) => match flat_type {
+                Apply(_, args) => {
+                    stack.extend(var_slice!(*args));
+                }
+
+                Func(arg_vars, closure_var, ret_var) => {
+                    let arg_vars = *arg_vars;
+                    let ret_var = *ret_var;
+                    let closure_var = *closure_var;
+
+                    stack.extend(var_slice!(arg_vars));
+
+                    stack.push(ret_var);
+                    stack.push(closure_var);
+                }
+
+                EmptyRecord => (),
+                EmptyTagUnion => (),
+
+                Record(fields, ext_var) => {
+                    let fields = *fields;
+                    let ext_var = *ext_var;
+                    stack.extend(var_slice!(fields.variables()));
+
+                    stack.push(ext_var);
+                }
+                TagUnion(tags, ext_var) => {
+                    let tags = *tags;
+                    let ext_var = *ext_var;
+
+                    for slice_index in tags.variables() {
+                        let slice = subs.variable_slices[slice_index.index as usize];
+                        stack.extend(var_slice!(slice));
+                    }
+
+                    stack.push(ext_var);
+                }
+                FunctionOrTagUnion(_, _, ext_var) => {
+                    stack.push(*ext_var);
+                }
+
+                RecursiveTagUnion(rec_var, tags, ext_var) => {
+                    let tags = *tags;
+                    let ext_var = *ext_var;
+                    let rec_var = *rec_var;
+
+                    for slice_index in tags.variables() {
+                        let slice = subs.variable_slices[slice_index.index as usize];
+                        stack.extend(var_slice!(slice));
+                    }
+
+                    stack.push(ext_var);
+                    stack.push(rec_var);
+                }
+
+                Erroneous(_) => (),
+            },
+            Alias(_, args, var, _) => {
+                let var = *var;
+                let args = *args;
+
+                stack.extend(var_slice!(args.all_variables()));
+
+                stack.push(var);
+            }
+            LambdaSet(self::LambdaSet {
+                solved,
+                recursion_var,
+                unspecialized,
+                ambient_function: _,
+            }) => {
+                for slice_index in solved.variables() {
+                    let slice = subs.variable_slices[slice_index.index as usize];
+                    stack.extend(var_slice!(slice));
+                }
+
+                if let Some(rec_var) = recursion_var.into_variable() {
+                    stack.push(rec_var);
+                }
+
+                for Uls(var, _, _) in subs.get_subs_slice(*unspecialized) {
+                    stack.push(*var);
+                }
+            }
+            &RangedNumber(_) => {}
+        }
+    }
+}
+
 /// Finds the lambda set of the ability member type (not specialization) at the region `r`,
 /// or all lambda sets if no region is specified.
 ///
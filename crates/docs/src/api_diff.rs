@@ -0,0 +1,761 @@
+use crate::load_package;
+use roc_can::expr::DeclarationTag;
+use roc_load::docs::{DocDef, DocEntry, Documentation, TypeAnnotation};
+use roc_load::{default_aliases, LoadedModule};
+use roc_module::ident::Lowercase;
+use roc_module::symbol::Symbol;
+use roc_solve::solve::{type_to_var_after_solving, Aliases};
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_types::subs::{forget_type_var_names, Variable};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// What each exposed module of a package exposes, by module name
+type PackageApi = BTreeMap<String, ModuleApi>;
+
+#[derive(Debug, Default)]
+struct ModuleApi {
+    /// Exposed values, and their solved types
+    values: BTreeMap<String, String>,
+    types: BTreeMap<String, TypeApi>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TypeApi {
+    /// A type alias, and how it's defined
+    Alias(String),
+    /// An opaque type's underlying type is hidden, so only how many type variables
+    /// it takes and the abilities it has can break anyone.
+    Opaque {
+        type_vars: usize,
+        abilities: Vec<String>,
+    },
+    /// An ability, and the signatures of its members
+    Ability { members: BTreeMap<String, String> },
+}
+
+impl TypeApi {
+    fn description(&self) -> &'static str {
+        match self {
+            TypeApi::Alias(_) => "a type alias",
+            TypeApi::Opaque { .. } => "an opaque type",
+            TypeApi::Ability { .. } => "an ability",
+        }
+    }
+}
+
+/// The semantic versioning bump a change needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VersionBump {
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ApiChange {
+    /// Whether code using the old version might not compile against the new one
+    pub breaking: bool,
+    pub description: String,
+}
+
+impl ApiChange {
+    fn breaking(description: String) -> Self {
+        ApiChange {
+            breaking: true,
+            description,
+        }
+    }
+
+    fn addition(description: String) -> Self {
+        ApiChange {
+            breaking: false,
+            description,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiDiff {
+    /// The version in the old package's header, if it has one
+    pub old_version: Option<String>,
+    pub changes: Vec<ApiChange>,
+}
+
+impl ApiDiff {
+    pub fn required_bump(&self) -> VersionBump {
+        if self.changes.iter().any(|change| change.breaking) {
+            VersionBump::Major
+        } else if !self.changes.is_empty() {
+            VersionBump::Minor
+        } else {
+            VersionBump::Patch
+        }
+    }
+
+    /// The version to publish the new package as, if the old version is known.
+    pub fn suggested_version(&self) -> Option<String> {
+        bump_version(self.old_version.as_deref()?, self.required_bump())
+    }
+}
+
+impl fmt::Display for ApiDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (breaking, additions): (Vec<&ApiChange>, Vec<&ApiChange>) =
+            self.changes.iter().partition(|change| change.breaking);
+
+        for (heading, changes) in [("Breaking changes", breaking), ("Additions", additions)] {
+            if !changes.is_empty() {
+                writeln!(f, "{}:\n", heading)?;
+
+                for change in changes {
+                    writeln!(f, "    - {}", change.description)?;
+                }
+
+                writeln!(f)?;
+            }
+        }
+
+        let summary = match self.required_bump() {
+            VersionBump::Major => "These changes can break code which uses the package",
+            VersionBump::Minor => "These changes only add to the package's API",
+            VersionBump::Patch => "The package's API hasn't changed",
+        };
+
+        match self.suggested_version() {
+            Some(version) => write!(f, "{}, so the next version should be {}.", summary, version),
+            None => {
+                let bump = match self.required_bump() {
+                    VersionBump::Major => "major",
+                    VersionBump::Minor => "minor",
+                    VersionBump::Patch => "patch",
+                };
+
+                write!(f, "{}, so it needs a {} version bump.", summary, bump)
+            }
+        }
+    }
+}
+
+/// Loads two versions of a package and compares what they expose: values, type aliases
+/// and ability members by their solved types, and opaque types by their type variables
+/// and abilities.
+pub fn api_diff(old_filenames: Vec<PathBuf>, new_filenames: Vec<PathBuf>) -> ApiDiff {
+    let (old_package_info, old_package) = load_package(old_filenames);
+    let (_, new_package) = load_package(new_filenames);
+
+    ApiDiff {
+        old_version: old_package_info.version,
+        changes: diff_packages(&package_api(old_package), &package_api(new_package)),
+    }
+}
+
+fn package_api(package: Documentation) -> PackageApi {
+    let mut package_api = PackageApi::new();
+
+    for mut loaded_module in package.modules {
+        let module_docs = match loaded_module.documentation.remove(&loaded_module.module_id) {
+            Some(module_docs) => module_docs,
+            None => continue,
+        };
+
+        let mut module_api = ModuleApi::default();
+
+        // The aliases the module was solved with, so that its own aliases can be added
+        // to its Subs too.
+        let mut aliases = default_aliases();
+
+        for (symbol, alias) in loaded_module.exposed_aliases.iter() {
+            aliases.insert(*symbol, alias.clone());
+        }
+
+        for entry in &module_docs.entries {
+            if let DocEntry::DocDef(doc_def) = entry {
+                if !doc_def.name.starts_with(char::is_uppercase) {
+                    continue;
+                }
+
+                let symbol = match loaded_module
+                    .exposed_values
+                    .iter()
+                    .find(|symbol| symbol.as_str(&loaded_module.interns) == doc_def.name)
+                {
+                    Some(symbol) => *symbol,
+                    None => continue,
+                };
+
+                if let Some(type_api) = type_api(&mut loaded_module, &mut aliases, symbol, doc_def)
+                {
+                    module_api.types.insert(doc_def.name.clone(), type_api);
+                }
+            }
+        }
+
+        module_api.values = solved_value_types(&mut loaded_module);
+
+        package_api.insert(module_docs.name, module_api);
+    }
+
+    package_api
+}
+
+fn type_api(
+    loaded_module: &mut LoadedModule,
+    aliases: &mut Aliases,
+    symbol: Symbol,
+    doc_def: &DocDef,
+) -> Option<TypeApi> {
+    match &doc_def.type_annotation {
+        TypeAnnotation::Opaque { abilities } => {
+            let mut abilities: Vec<String> = abilities
+                .iter()
+                .map(|ability| ability.name.clone())
+                .collect();
+
+            abilities.sort();
+
+            Some(TypeApi::Opaque {
+                type_vars: loaded_module
+                    .exposed_aliases
+                    .get(&symbol)?
+                    .type_variables
+                    .len(),
+                abilities,
+            })
+        }
+        TypeAnnotation::Ability { .. } => {
+            let member_symbols = loaded_module
+                .abilities_store
+                .members_of_ability(symbol)?
+                .to_vec();
+            let mut members = BTreeMap::new();
+
+            for member in member_symbols {
+                let var = loaded_module
+                    .abilities_store
+                    .member_def(member)?
+                    .signature_var();
+                let name = member.as_str(&loaded_module.interns).to_string();
+                let signature = format!("{} : {}", name, print_solved_type(loaded_module, var));
+
+                members.insert(name, signature);
+            }
+
+            Some(TypeApi::Ability { members })
+        }
+        _ => {
+            let alias = loaded_module.exposed_aliases.get(&symbol)?.clone();
+            let subs = loaded_module.solved.inner_mut();
+            let var = type_to_var_after_solving(subs, aliases, &alias.typ);
+
+            forget_type_var_names(subs, var);
+
+            // Name the alias's type variables by their position, so renaming them isn't a change.
+            let mut definition = symbol.as_str(&loaded_module.interns).to_string();
+
+            for (index, type_var) in alias.type_variables.iter().enumerate() {
+                let name = Lowercase::from(type_var_name(index));
+
+                definition.push(' ');
+                definition.push_str(name.as_str());
+
+                match type_var.value.opt_bound_ability {
+                    Some(ability) => subs.rigid_able_var(type_var.value.var, name, ability),
+                    None => subs.rigid_var(type_var.value.var, name),
+                }
+            }
+
+            let type_str = name_and_print_var(
+                var,
+                subs,
+                loaded_module.module_id,
+                &loaded_module.interns,
+                DebugPrint::NOTHING,
+            );
+
+            Some(TypeApi::Alias(format!("{} : {}", definition, type_str)))
+        }
+    }
+}
+
+/// `a`, `b`, ..., `z`, `a1`, `b1`, ...
+fn type_var_name(index: usize) -> String {
+    let letter = (b'a' + (index % 26) as u8) as char;
+
+    match index / 26 {
+        0 => letter.to_string(),
+        round => format!("{}{}", letter, round),
+    }
+}
+
+/// Prints a solved type with its type variables named in order of appearance,
+/// so types which only differ in what their type variables are called come out the same.
+fn print_solved_type(loaded_module: &mut LoadedModule, var: Variable) -> String {
+    let subs = loaded_module.solved.inner_mut();
+
+    forget_type_var_names(subs, var);
+
+    name_and_print_var(
+        var,
+        subs,
+        loaded_module.module_id,
+        &loaded_module.interns,
+        DebugPrint::NOTHING,
+    )
+}
+
+fn solved_value_types(loaded_module: &mut LoadedModule) -> BTreeMap<String, String> {
+    let home = loaded_module.module_id;
+    let interns = &loaded_module.interns;
+    let exposed_values = &loaded_module.exposed_values;
+    let subs = loaded_module.solved.inner_mut();
+    let mut types = BTreeMap::new();
+
+    let declarations = match loaded_module.declarations_by_id.get(&home) {
+        Some(declarations) => declarations,
+        None => return types,
+    };
+
+    let mut add_type = |symbol: Symbol, var: Variable| {
+        if exposed_values.contains(&symbol) {
+            forget_type_var_names(subs, var);
+
+            let type_str = name_and_print_var(var, subs, home, interns, DebugPrint::NOTHING);
+
+            types.insert(symbol.as_str(interns).to_string(), type_str);
+        }
+    };
+
+    for index in 0..declarations.len() {
+        use DeclarationTag::*;
+
+        match declarations.declarations[index] {
+            Value | Function(_) | Recursive(_) | TailRecursive(_) => {
+                add_type(
+                    declarations.symbols[index].value,
+                    declarations.variables[index],
+                );
+            }
+            Destructure(d_index) => {
+                let pattern_vars = &declarations.destructs[d_index.index()].pattern_vars;
+
+                for (symbol, var) in pattern_vars.iter() {
+                    add_type(*symbol, *var);
+                }
+            }
+            MutualRecursion { .. } | Expectation | ExpectationFx => {}
+        }
+    }
+
+    types
+}
+
+fn diff_packages(old: &PackageApi, new: &PackageApi) -> Vec<ApiChange> {
+    let mut changes = Vec::new();
+
+    for (module_name, old_module) in old {
+        match new.get(module_name) {
+            Some(new_module) => diff_modules(module_name, old_module, new_module, &mut changes),
+            None => changes.push(ApiChange::breaking(format!(
+                "The `{}` module is no longer exposed.",
+                module_name
+            ))),
+        }
+    }
+
+    for module_name in new.keys().filter(|name| !old.contains_key(*name)) {
+        changes.push(ApiChange::addition(format!(
+            "The `{}` module is now exposed.",
+            module_name
+        )));
+    }
+
+    changes
+}
+
+fn diff_modules(module_name: &str, old: &ModuleApi, new: &ModuleApi, changes: &mut Vec<ApiChange>) {
+    for (name, old_type) in &old.values {
+        match new.values.get(name) {
+            Some(new_type) if new_type != old_type => changes.push(ApiChange::breaking(format!(
+                "`{}.{}` changed from `{}` to `{}`.",
+                module_name, name, old_type, new_type
+            ))),
+            Some(_) => {}
+            None => changes.push(ApiChange::breaking(format!(
+                "`{}.{}` was removed.",
+                module_name, name
+            ))),
+        }
+    }
+
+    for (name, new_type) in new.values.iter() {
+        if !old.values.contains_key(name) {
+            changes.push(ApiChange::addition(format!(
+                "`{}.{} : {}` was added.",
+                module_name, name, new_type
+            )));
+        }
+    }
+
+    for (name, old_type) in &old.types {
+        let qualified_name = format!("{}.{}", module_name, name);
+
+        match new.types.get(name) {
+            Some(new_type) => diff_types(&qualified_name, old_type, new_type, changes),
+            None => changes.push(ApiChange::breaking(format!(
+                "`{}` was removed.",
+                qualified_name
+            ))),
+        }
+    }
+
+    for (name, new_type) in new.types.iter() {
+        if !old.types.contains_key(name) {
+            changes.push(ApiChange::addition(format!(
+                "`{}.{}`, {}, was added.",
+                module_name,
+                name,
+                new_type.description()
+            )));
+        }
+    }
+}
+
+fn diff_types(qualified_name: &str, old: &TypeApi, new: &TypeApi, changes: &mut Vec<ApiChange>) {
+    match (old, new) {
+        (TypeApi::Alias(old_def), TypeApi::Alias(new_def)) => {
+            if old_def != new_def {
+                changes.push(ApiChange::breaking(format!(
+                    "`{}` changed from `{}` to `{}`.",
+                    qualified_name, old_def, new_def
+                )));
+            }
+        }
+        (
+            TypeApi::Opaque {
+                type_vars: old_vars,
+                abilities: old_abilities,
+            },
+            TypeApi::Opaque {
+                type_vars: new_vars,
+                abilities: new_abilities,
+            },
+        ) => {
+            if old_vars != new_vars {
+                changes.push(ApiChange::breaking(format!(
+                    "`{}` now has {} type variables instead of {}.",
+                    qualified_name, new_vars, old_vars
+                )));
+            }
+
+            for ability in old_abilities.iter().filter(|a| !new_abilities.contains(a)) {
+                changes.push(ApiChange::breaking(format!(
+                    "`{}` no longer has `{}`.",
+                    qualified_name, ability
+                )));
+            }
+
+            for ability in new_abilities.iter().filter(|a| !old_abilities.contains(a)) {
+                changes.push(ApiChange::addition(format!(
+                    "`{}` now has `{}`.",
+                    qualified_name, ability
+                )));
+            }
+        }
+        (
+            TypeApi::Ability {
+                members: old_members,
+            },
+            TypeApi::Ability {
+                members: new_members,
+            },
+        ) => {
+            // Every type which has an ability implements all of its members,
+            // so adding a member breaks them too.
+            for (member, old_signature) in old_members {
+                match new_members.get(member) {
+                    Some(new_signature) if new_signature != old_signature => {
+                        changes.push(ApiChange::breaking(format!(
+                            "`{}` member `{}` changed to `{}`.",
+                            qualified_name, old_signature, new_signature
+                        )))
+                    }
+                    Some(_) => {}
+                    None => changes.push(ApiChange::breaking(format!(
+                        "`{}` no longer has the member `{}`.",
+                        qualified_name, member
+                    ))),
+                }
+            }
+
+            for (member, new_signature) in new_members {
+                if !old_members.contains_key(member) {
+                    changes.push(ApiChange::breaking(format!(
+                        "`{}` has a new member `{}`, which types that have it must implement.",
+                        qualified_name, new_signature
+                    )));
+                }
+            }
+        }
+        _ => changes.push(ApiChange::breaking(format!(
+            "`{}` was {}, but now it's {}.",
+            qualified_name,
+            old.description(),
+            new.description()
+        ))),
+    }
+}
+
+/// Bumps a `major.minor.patch` version. Before 1.0.0, breaking changes only need
+/// a minor bump and additions a patch bump, like Cargo does it.
+fn bump_version(version: &str, bump: VersionBump) -> Option<String> {
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+
+    let (major, minor, patch) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) => (major, minor, patch),
+        _ => return None,
+    };
+
+    let (major, minor, patch) = match (bump, major) {
+        (VersionBump::Major, 0) => (0, minor + 1, 0),
+        (VersionBump::Major, _) => (major + 1, 0, 0),
+        (VersionBump::Minor, 0) => (0, minor, patch + 1),
+        (VersionBump::Minor, _) => (major, minor + 1, 0),
+        (VersionBump::Patch, _) => (major, minor, patch + 1),
+    };
+
+    Some(format!("{}.{}.{}", major, minor, patch))
+}
+
+#[cfg(test)]
+mod test_api_diff {
+    use super::*;
+
+    fn module_api(values: &[(&str, &str)], types: Vec<(&str, TypeApi)>) -> ModuleApi {
+        ModuleApi {
+            values: values
+                .iter()
+                .map(|(name, type_str)| (name.to_string(), type_str.to_string()))
+                .collect(),
+            types: types
+                .into_iter()
+                .map(|(name, type_api)| (name.to_string(), type_api))
+                .collect(),
+        }
+    }
+
+    fn package(modules: Vec<(&str, ModuleApi)>) -> PackageApi {
+        modules
+            .into_iter()
+            .map(|(name, module)| (name.to_string(), module))
+            .collect()
+    }
+
+    fn opaque(abilities: &[&str]) -> TypeApi {
+        TypeApi::Opaque {
+            type_vars: 0,
+            abilities: abilities.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn unchanged() {
+        let old = package(vec![("Foo", module_api(&[("foo", "Str")], vec![]))]);
+        let new = package(vec![("Foo", module_api(&[("foo", "Str")], vec![]))]);
+
+        assert!(diff_packages(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn additions() {
+        let old = package(vec![(
+            "Foo",
+            module_api(&[("foo", "Str")], vec![("Id", opaque(&[]))]),
+        )]);
+        let new = package(vec![
+            (
+                "Foo",
+                module_api(
+                    &[("foo", "Str"), ("bar", "U8")],
+                    vec![("Id", opaque(&["Eq"]))],
+                ),
+            ),
+            ("Bar", module_api(&[], vec![])),
+        ]);
+
+        let changes = diff_packages(&old, &new);
+
+        assert!(changes.iter().all(|change| !change.breaking));
+        assert_eq!(changes.len(), 3);
+    }
+
+    #[test]
+    fn breaking_changes() {
+        let old = package(vec![
+            (
+                "Foo",
+                module_api(
+                    &[("foo", "Str"), ("bar", "U8")],
+                    vec![
+                        ("Id", opaque(&["Eq"])),
+                        ("Name", TypeApi::Alias("Name : Str".to_string())),
+                    ],
+                ),
+            ),
+            ("Bar", module_api(&[], vec![])),
+        ]);
+        let new = package(vec![(
+            "Foo",
+            module_api(
+                &[("foo", "List U8")],
+                vec![("Id", opaque(&[])), ("Name", opaque(&[]))],
+            ),
+        )]);
+
+        let changes = diff_packages(&old, &new);
+
+        assert!(changes.iter().all(|change| change.breaking));
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.description.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "The `Bar` module is no longer exposed.",
+                "`Foo.bar` was removed.",
+                "`Foo.foo` changed from `Str` to `List U8`.",
+                "`Foo.Id` no longer has `Eq`.",
+                "`Foo.Name` was a type alias, but now it's an opaque type.",
+            ]
+        );
+    }
+
+    #[test]
+    fn new_ability_member_is_breaking() {
+        let ability = |members: &[&str]| TypeApi::Ability {
+            members: members
+                .iter()
+                .map(|name| {
+                    (
+                        name.to_string(),
+                        format!("{} : a -> U64 | a has Hash", name),
+                    )
+                })
+                .collect(),
+        };
+
+        let old = package(vec![(
+            "Foo",
+            module_api(&[], vec![("Hash", ability(&["hash"]))]),
+        )]);
+        let new = package(vec![(
+            "Foo",
+            module_api(&[], vec![("Hash", ability(&["hash", "hash32"]))]),
+        )]);
+
+        let changes = diff_packages(&old, &new);
+
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].breaking);
+    }
+
+    fn write_module(dir: &tempfile::TempDir, source: &str) -> Vec<PathBuf> {
+        let path = dir.path().join("Pair.roc");
+
+        std::fs::write(&path, source).unwrap();
+
+        vec![path]
+    }
+
+    #[test]
+    fn renamed_type_variables() {
+        let header = r#"interface Pair
+    exposes [Pair, Mappable, swap]
+    imports []
+"#;
+        let old_dir = tempfile::tempdir().unwrap();
+        let old = write_module(
+            &old_dir,
+            &format!(
+                r#"{}
+Pair a b : {{ first : a, second : b }}
+
+Mappable has
+    mapFirst : m, (Str -> Str) -> m | m has Mappable
+
+swap : Pair a b -> Pair b a
+swap = \{{ first, second }} -> {{ first: second, second: first }}
+"#,
+                header
+            ),
+        );
+
+        let renamed_dir = tempfile::tempdir().unwrap();
+        let renamed = write_module(
+            &renamed_dir,
+            &format!(
+                r#"{}
+Pair x y : {{ first : x, second : y }}
+
+Mappable has
+    mapFirst : t, (Str -> Str) -> t | t has Mappable
+
+swap : Pair x y -> Pair y x
+swap = \{{ first, second }} -> {{ first: second, second: first }}
+"#,
+                header
+            ),
+        );
+
+        let swapped_dir = tempfile::tempdir().unwrap();
+        let swapped = write_module(
+            &swapped_dir,
+            &format!(
+                r#"{}
+Pair a b : {{ first : b, second : a }}
+
+Mappable has
+    mapFirst : m, (Str -> Str) -> m | m has Mappable
+
+swap : Pair a b -> Pair b a
+swap = \{{ first, second }} -> {{ first: second, second: first }}
+"#,
+                header
+            ),
+        );
+
+        assert!(api_diff(old.clone(), renamed).changes.is_empty());
+        assert_eq!(
+            api_diff(old, swapped).changes,
+            vec![ApiChange::breaking(
+                "`Pair.Pair` changed from `Pair a b : { first : a, second : b }` to `Pair a b : { first : b, second : a }`.".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn version_bumps() {
+        assert_eq!(
+            bump_version("1.2.3", VersionBump::Major).as_deref(),
+            Some("2.0.0")
+        );
+        assert_eq!(
+            bump_version("1.2.3", VersionBump::Minor).as_deref(),
+            Some("1.3.0")
+        );
+        assert_eq!(
+            bump_version("1.2.3", VersionBump::Patch).as_deref(),
+            Some("1.2.4")
+        );
+        assert_eq!(
+            bump_version("0.2.3", VersionBump::Major).as_deref(),
+            Some("0.3.0")
+        );
+        assert_eq!(
+            bump_version("0.2.3", VersionBump::Minor).as_deref(),
+            Some("0.2.4")
+        );
+        assert_eq!(bump_version("1.2", VersionBump::Patch), None);
+    }
+}
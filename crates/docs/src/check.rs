@@ -0,0 +1,271 @@
+use crate::package::read_package_info;
+use crate::{load_modules_for_files, report_errors, resolve_doc_link, shortcut_links};
+use bumpalo::Bump;
+use roc_collections::all::MutMap;
+use roc_load::docs::{DocEntry, ModuleDocumentation, TypeAnnotation};
+use roc_load::LoadedModule;
+use roc_region::all::{LineInfo, Position, Region};
+use roc_reporting::report::{Report, RocDocAllocator, Severity, DEFAULT_PALETTE};
+use std::path::PathBuf;
+use ven_pretty::DocAllocator;
+
+const BROKEN_DOC_LINK: &str = "BROKEN DOC LINK";
+const MISSING_DOCS: &str = "MISSING DOCS";
+
+enum DocProblem {
+    /// A link like `[Dict.insert]` which doesn't resolve to anything
+    BrokenLink { region: Region, reason: String },
+    /// An exposed value, type or ability member without a doc comment
+    Undocumented { region: Region, name: String },
+}
+
+/// Checks the docs for the given files without generating them, reporting every doc
+/// link which doesn't resolve, every exposed item which doesn't have a doc comment,
+/// and every compile error, including ones in doc examples. Returns how many problems
+/// were reported.
+pub fn check_docs(filenames: Vec<PathBuf>) -> usize {
+    let package_info = read_package_info(filenames);
+    let mut loaded_modules = load_modules_for_files(package_info.module_files.clone());
+    let mut problem_count = 0;
+
+    for loaded_module in loaded_modules.iter_mut() {
+        problem_count += report_errors(loaded_module);
+
+        let loaded_module = &*loaded_module;
+        let home = loaded_module.module_id;
+
+        let (module_docs, (module_path, src)) = match (
+            loaded_module.documentation.get(&home),
+            loaded_module.sources.get(&home),
+        ) {
+            (Some(module_docs), Some(source)) => (module_docs, source),
+            _ => continue,
+        };
+
+        let mut problems = broken_links(
+            loaded_module,
+            module_docs,
+            src,
+            &package_info.dependency_modules,
+        );
+
+        problems.extend(undocumented_items(loaded_module, module_docs));
+        problems.sort_by_key(|problem| match problem {
+            DocProblem::BrokenLink { region, .. } | DocProblem::Undocumented { region, .. } => {
+                region.start()
+            }
+        });
+
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, home, &loaded_module.interns);
+        let palette = DEFAULT_PALETTE;
+
+        for problem in problems {
+            let report = doc_problem(&alloc, &lines, module_path.clone(), problem);
+            let mut buf = String::new();
+
+            report.render_color_terminal(&mut buf, &alloc, &palette);
+            eprintln!("\n{}\n", buf);
+
+            problem_count += 1;
+        }
+    }
+
+    problem_count
+}
+
+fn broken_links(
+    loaded_module: &LoadedModule,
+    module_docs: &ModuleDocumentation,
+    src: &str,
+    dependency_modules: &MutMap<String, String>,
+) -> Vec<DocProblem> {
+    let arena = Bump::new();
+    let exposed_values = loaded_module.exposed_values_str();
+    let mut problems = Vec::new();
+
+    for doc_comment in doc_comments(src) {
+        for (span, reference) in shortcut_links(&doc_comment.markdown) {
+            let resolved = resolve_doc_link(
+                &arena,
+                loaded_module,
+                &exposed_values,
+                &module_docs.scope,
+                dependency_modules,
+                &reference,
+            );
+
+            if let Some(Err(reason)) = resolved {
+                let start = doc_comment.source_offset(span.start);
+                let end = doc_comment.source_offset(span.end);
+
+                problems.push(DocProblem::BrokenLink {
+                    region: Region::new(Position::new(start as u32), Position::new(end as u32)),
+                    reason,
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+fn undocumented_items(
+    loaded_module: &LoadedModule,
+    module_docs: &ModuleDocumentation,
+) -> Vec<DocProblem> {
+    let exposed_values = loaded_module.exposed_values_str();
+    let mut problems = Vec::new();
+
+    for entry in &module_docs.entries {
+        let doc_def = match entry {
+            DocEntry::DocDef(doc_def) if exposed_values.contains(&doc_def.name.as_str()) => doc_def,
+            _ => continue,
+        };
+
+        if doc_def.docs.is_none() {
+            problems.push(DocProblem::Undocumented {
+                region: doc_def.region,
+                name: doc_def.name.clone(),
+            });
+        }
+
+        if let TypeAnnotation::Ability { members } = &doc_def.type_annotation {
+            for member in members.iter().filter(|member| member.docs.is_none()) {
+                problems.push(DocProblem::Undocumented {
+                    region: member.region,
+                    name: member.name.clone(),
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+/// The markdown in a block of consecutive `##` lines
+struct DocComment {
+    markdown: String,
+    /// Where each line starts, as (offset in the markdown, offset in the source)
+    line_starts: Vec<(usize, usize)>,
+}
+
+impl DocComment {
+    fn source_offset(&self, markdown_offset: usize) -> usize {
+        let line = self
+            .line_starts
+            .partition_point(|(line_start, _)| *line_start <= markdown_offset)
+            .saturating_sub(1);
+        let (markdown_start, source_start) = self.line_starts[line];
+
+        source_start + (markdown_offset - markdown_start)
+    }
+}
+
+fn doc_comments(src: &str) -> Vec<DocComment> {
+    let mut doc_comments = Vec::new();
+    let mut current: Option<DocComment> = None;
+    let mut line_offset = 0;
+
+    for line in src.split('\n') {
+        match line.trim_start().strip_prefix("##") {
+            Some(content) => {
+                let doc_comment = current.get_or_insert_with(|| DocComment {
+                    markdown: String::new(),
+                    line_starts: Vec::new(),
+                });
+
+                doc_comment.line_starts.push((
+                    doc_comment.markdown.len(),
+                    line_offset + line.len() - content.len(),
+                ));
+                doc_comment.markdown.push_str(content);
+                doc_comment.markdown.push('\n');
+            }
+            None => doc_comments.extend(current.take()),
+        }
+
+        line_offset += line.len() + 1;
+    }
+
+    doc_comments.extend(current);
+
+    doc_comments
+}
+
+fn doc_problem<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    problem: DocProblem,
+) -> Report<'b> {
+    let doc;
+    let title;
+
+    match problem {
+        DocProblem::BrokenLink { region, reason } => {
+            doc = alloc.stack([
+                alloc.reflow("This doc link doesn't lead anywhere:"),
+                alloc.region(lines.convert_region(region)),
+                alloc.string(reason),
+                alloc.concat([
+                    alloc.reflow("Links like "),
+                    alloc.parser_suggestion("[Str.join]"),
+                    alloc.reflow(" have to name something which is in scope, or exposed by an imported module."),
+                ]),
+            ]);
+
+            title = BROKEN_DOC_LINK.to_string();
+        }
+        DocProblem::Undocumented { region, name } => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.string(name),
+                    alloc.reflow(" is exposed, but it doesn't have a doc comment:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("Add a comment starting with "),
+                    alloc.parser_suggestion("##"),
+                    alloc.reflow(" right above it to say what it's for."),
+                ]),
+            ]);
+
+            title = MISSING_DOCS.to_string();
+        }
+    }
+
+    Report {
+        title,
+        filename,
+        doc,
+        severity: Severity::Warning,
+    }
+}
+
+#[cfg(test)]
+mod test_check {
+    use super::doc_comments;
+
+    #[test]
+    fn doc_comment_offsets_map_to_source() {
+        let src = "x = 1\n\n## Adds [One].\n##\n## See [Num.add]\nadd = 2\n";
+        let comments = doc_comments(src);
+
+        assert_eq!(comments.len(), 1);
+
+        let comment = &comments[0];
+
+        assert_eq!(comment.markdown, " Adds [One].\n\n See [Num.add]\n");
+
+        for link in ["[One]", "[Num.add]"] {
+            let markdown_offset = comment.markdown.find(link).unwrap();
+
+            assert_eq!(
+                comment.source_offset(markdown_offset),
+                src.find(link).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{create_build_dir, entry_signature, home_module_docs, load_package};
+use roc_load::docs::{DocDef, DocEntry, Documentation, ModuleDocumentation};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+struct PackageJson<'a> {
+    name: &'a str,
+    version: Option<&'a str>,
+    readme: Option<&'a str>,
+    modules: Vec<ModuleJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ModuleJson<'a> {
+    name: &'a str,
+    entries: Vec<EntryJson<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum EntryJson<'a> {
+    Def {
+        #[serde(flatten)]
+        doc_def: &'a DocDef,
+        signature: String,
+    },
+    DetachedDoc {
+        docs: &'a str,
+    },
+}
+
+/// Writes `docs.json`, which has the package's name, version and README, and for each
+/// exposed module, the docs and types of everything it exposes, in source order.
+pub fn generate_docs_json(filenames: Vec<PathBuf>, build_dir: &Path) {
+    let (_, package) = load_package(filenames);
+
+    create_build_dir(build_dir);
+
+    let path = build_dir.join("docs.json");
+
+    fs::write(&path, docs_json(&package))
+        .expect("TODO gracefully handle failing to write docs.json");
+
+    println!("🎉 Docs generated in {}", path.display());
+}
+
+fn docs_json(package: &Documentation) -> String {
+    let modules = package
+        .modules
+        .iter()
+        .filter_map(|loaded_module| {
+            home_module_docs(loaded_module)
+                .map(|module| module_json(module, &loaded_module.exposed_values_str()))
+        })
+        .collect();
+
+    let package_json = PackageJson {
+        name: package.name.as_str(),
+        version: package.version.as_deref(),
+        readme: package.docs.as_deref(),
+        modules,
+    };
+
+    // Indented, so diffs of the output are readable.
+    let mut json = serde_json::to_string_pretty(&package_json)
+        .expect("TODO gracefully handle failing to serialize docs.json");
+
+    json.push('\n');
+
+    json
+}
+
+fn module_json<'a>(module: &'a ModuleDocumentation, exposed_values: &[&str]) -> ModuleJson<'a> {
+    let entries = module
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            DocEntry::DocDef(doc_def) if exposed_values.contains(&doc_def.name.as_str()) => {
+                Some(EntryJson::Def {
+                    doc_def,
+                    signature: entry_signature(doc_def),
+                })
+            }
+            DocEntry::DocDef(_) => None,
+            DocEntry::DetachedDoc(docs) => Some(EntryJson::DetachedDoc {
+                docs: docs.as_str(),
+            }),
+        })
+        .collect();
+
+    ModuleJson {
+        name: module.name.as_str(),
+        entries,
+    }
+}
+
+#[cfg(test)]
+mod test_json {
+    use super::docs_json;
+    use crate::load_package;
+    use serde_json::{json, Value};
+    use std::fs;
+
+    #[test]
+    fn module_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Greeting.roc");
+
+        fs::write(
+            &path,
+            r#"interface Greeting
+    exposes [greet, first]
+    imports []
+
+## Says hi
+greet : Str -> Str
+greet = \name -> Str.concat "Hi, " name
+
+first : List a, a -> a
+first = \list, default -> List.first list |> Result.withDefault default
+
+notExposed = 1
+"#,
+        )
+        .unwrap();
+
+        let (_, package) = load_package(vec![path]);
+        let docs: Value = serde_json::from_str(docs_json(&package).as_str()).unwrap();
+        let module = &docs["modules"][0];
+        let str_type = json!({ "kind": "apply", "name": "Str", "parts": [] });
+        let var_a = json!({ "kind": "boundVariable", "name": "a" });
+
+        assert_eq!(module["name"], "Greeting");
+        assert_eq!(module["entries"].as_array().unwrap().len(), 2);
+
+        let greet = &module["entries"][0];
+
+        assert_eq!(greet["kind"], "def");
+        assert_eq!(greet["name"], "greet");
+        assert_eq!(greet["signature"], "greet : Str -> Str");
+        assert_eq!(greet["docs"].as_str().unwrap().trim(), "Says hi");
+        assert_eq!(
+            greet["type"],
+            json!({ "kind": "function", "args": [str_type], "output": str_type })
+        );
+
+        let first = &module["entries"][1];
+
+        assert_eq!(first["name"], "first");
+        assert_eq!(first["docs"], Value::Null);
+        assert_eq!(
+            first["type"],
+            json!({
+                "kind": "function",
+                "args": [{ "kind": "apply", "name": "List", "parts": [var_a] }, var_a],
+                "output": var_a,
+            })
+        );
+    }
+}
@@ -3,41 +3,133 @@ extern crate roc_load;
 use bumpalo::Bump;
 use docs_error::{DocsError, DocsResult};
 use html::mark_node_to_html;
+use package::{read_package_info, PackageInfo};
 use roc_can::scope::Scope;
 use roc_code_markup::markup::nodes::MarkupNode;
 use roc_code_markup::slow_pool::SlowPool;
+use roc_collections::all::MutMap;
 use roc_highlight::highlight_parser::{highlight_defs, highlight_expr};
 use roc_load::docs::DocEntry::DocDef;
-use roc_load::docs::{DocEntry, TypeAnnotation};
+use roc_load::docs::{AbilityMember, DocEntry, Documentation, TypeAnnotation};
 use roc_load::docs::{ModuleDocumentation, RecordField};
 use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
-use roc_module::symbol::{IdentIdsByModule, Interns, ModuleId};
+use roc_module::symbol::Interns;
 use roc_parse::ident::{parse_ident, Ident};
 use roc_parse::state::State;
-use roc_region::all::Region;
+use roc_region::all::{LineInfo, Region};
+use search_index::render_search_index;
 use std::fs;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
+mod api_diff;
+mod check;
 mod docs_error;
 mod html;
+mod json;
+mod markdown;
+mod package;
+mod search_index;
+
+pub use api_diff::{api_diff, ApiChange, ApiDiff, VersionBump};
+pub use check::check_docs;
+
+/// Where `roc docs` writes its output unless told otherwise.
+pub const DEFAULT_BUILD_DIR: &str = "./generated-docs";
+
+/// What `roc docs` generates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsFormat {
+    /// A website, with search
+    Html,
+    /// An `index.md` for the package, plus one `.md` file per module
+    Markdown,
+    /// A `docs.json` describing the package's API, for other tools to render or compare
+    Json,
+}
+
+impl DocsFormat {
+    pub const NAMES: [&'static str; 3] = ["html", "markdown", "json"];
+}
+
+impl std::str::FromStr for DocsFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "html" => Ok(DocsFormat::Html),
+            "markdown" => Ok(DocsFormat::Markdown),
+            "json" => Ok(DocsFormat::Json),
+            _ => Err(format!(
+                "`{}` is not a docs format. The formats are: {}",
+                name,
+                DocsFormat::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+pub fn generate_docs(filenames: Vec<PathBuf>, build_dir: &Path, format: DocsFormat) {
+    match format {
+        DocsFormat::Html => generate_docs_html(filenames, build_dir),
+        DocsFormat::Markdown => markdown::generate_docs_markdown(filenames, build_dir),
+        DocsFormat::Json => json::generate_docs_json(filenames, build_dir),
+    }
+}
+
+/// Loads the modules the package exposes, in the order its header exposes them.
+fn load_package(filenames: Vec<PathBuf>) -> (PackageInfo, Documentation) {
+    let package_info = read_package_info(filenames);
+    let mut loaded_modules = load_modules_for_files(package_info.module_files.clone());
+
+    // Report code (and doc examples) that doesn't compile, but still document the rest.
+    for loaded_module in loaded_modules.iter_mut() {
+        report_errors(loaded_module);
+    }
 
-const BUILD_DIR: &str = "./generated-docs";
+    // List modules in the order the package header exposes them.
+    if let Some(exposed_modules) = &package_info.exposed_modules {
+        loaded_modules.sort_by_key(|loaded_module| {
+            let module_name = loaded_module
+                .interns
+                .module_ids
+                .get_name(loaded_module.module_id)
+                .map(|name| name.as_str());
 
-pub fn generate_docs_html(filenames: Vec<PathBuf>) {
-    let build_dir = Path::new(BUILD_DIR);
-    let loaded_modules = load_modules_for_files(filenames);
+            exposed_modules
+                .iter()
+                .position(|exposed| Some(exposed.as_str()) == module_name)
+        });
+    }
 
-    // TODO: get info from a package module; this is all hardcoded for now.
-    let mut package = roc_load::docs::Documentation {
-        name: "roc/builtins".to_string(),
-        version: "1.0.0".to_string(),
-        docs: "Package introduction or README.".to_string(),
+    let package = Documentation {
+        name: package_info.name.clone(),
+        version: package_info.version.clone(),
+        docs: package_info.readme.clone(),
         modules: loaded_modules,
     };
 
+    (package_info, package)
+}
+
+/// The documentation of a loaded module's own definitions
+fn home_module_docs(loaded_module: &LoadedModule) -> Option<&ModuleDocumentation> {
+    // The `documentation` dictionary has entries for every module the
+    // loaded module depends on, but only the loaded module's has any info in it.
+    loaded_module.documentation.get(&loaded_module.module_id)
+}
+
+/// Creates the directory docs get written to, if it doesn't exist yet.
+fn create_build_dir(build_dir: &Path) {
     if !build_dir.exists() {
         fs::create_dir_all(build_dir).expect("TODO gracefully handle unable to create build dir");
     }
+}
+
+pub fn generate_docs_html(filenames: Vec<PathBuf>, build_dir: &Path) {
+    let (package_info, mut package) = load_package(filenames);
+
+    create_build_dir(build_dir);
 
     // Copy over the assets
     fs::write(
@@ -58,38 +150,71 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
     )
     .expect("TODO gracefully handle failing to make the favicon");
 
+    let exposed_modules = package
+        .modules
+        .iter()
+        .flat_map(|loaded_module| {
+            loaded_module
+                .documentation
+                .iter()
+                .filter_map(move |(module_id, module)| {
+                    // TODO it seems this `documentation` dictionary has entries for
+                    // every module, but only the current module has any info in it.
+                    // We disregard the others, but probably this shouldn't bother
+                    // being a hash map in the first place if only one of its entries
+                    // actually has interesting information in it?
+                    if *module_id == loaded_module.module_id {
+                        let exposed_values = loaded_module
+                            .exposed_values
+                            .iter()
+                            .map(|symbol| symbol.as_str(&loaded_module.interns).to_string())
+                            .collect::<Vec<String>>();
+
+                        Some((module, exposed_values))
+                    } else {
+                        None
+                    }
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let module_names = exposed_modules
+        .iter()
+        .map(|(module, _)| module.name.clone())
+        .collect::<Vec<_>>();
+
+    fs::write(
+        build_dir.join("search-index.js"),
+        render_search_index(exposed_modules.iter().cloned()),
+    )
+    .expect("TODO gracefully handle failing to make the search index");
+
     let template_html = include_str!("./static/index.html")
         .replace("<!-- search.js -->", "/search.js")
+        .replace("<!-- search-index.js -->", "/search-index.js")
         .replace("<!-- styles.css -->", "/styles.css")
         .replace("<!-- favicon.svg -->", "/favicon.svg")
         .replace(
             "<!-- Module links -->",
-            render_sidebar(package.modules.iter().flat_map(|loaded_module| {
-                loaded_module
-                    .documentation
-                    .iter()
-                    .filter_map(move |(module_id, module)| {
-                        // TODO it seems this `documentation` dictionary has entries for
-                        // every module, but only the current module has any info in it.
-                        // We disregard the others, but probably this shouldn't bother
-                        // being a hash map in the first place if only one of its entries
-                        // actually has interesting information in it?
-                        if *module_id == loaded_module.module_id {
-                            let exposed_values = loaded_module
-                                .exposed_values
-                                .iter()
-                                .map(|symbol| symbol.as_str(&loaded_module.interns).to_string())
-                                .collect::<Vec<String>>();
-
-                            Some((module, exposed_values))
-                        } else {
-                            None
-                        }
-                    })
-            }))
-            .as_str(),
+            render_sidebar(exposed_modules.into_iter()).as_str(),
+        )
+        .replace(
+            "<!-- Package Name and Version -->",
+            render_name_and_version(package.name.as_str(), package.version.as_deref()).as_str(),
+        );
+
+    if package_info.has_landing_page() {
+        let rendered_package = template_html.replace(
+            "<!-- Module Docs -->",
+            render_package_index(&package, &module_names).as_str(),
         );
 
+        fs::write(build_dir.join("index.html"), rendered_package)
+            .expect("TODO gracefully handle failing to write the package's index.html");
+    }
+
+    let implementations = ability_implementations(&package.modules);
+
     // Write each package's module docs html file
     for loaded_module in package.modules.iter_mut() {
         for (module_id, module_docs) in loaded_module.documentation.iter() {
@@ -99,16 +224,16 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
                 fs::create_dir_all(&module_dir)
                     .expect("TODO gracefully handle not being able to create the module dir");
 
-                let rendered_module = template_html
-                    .replace(
-                        "<!-- Package Name and Version -->",
-                        render_name_and_version(package.name.as_str(), package.version.as_str())
-                            .as_str(),
+                let rendered_module = template_html.replace(
+                    "<!-- Module Docs -->",
+                    render_module_documentation(
+                        module_docs,
+                        loaded_module,
+                        &package_info.dependency_modules,
+                        &implementations,
                     )
-                    .replace(
-                        "<!-- Module Docs -->",
-                        render_module_documentation(module_docs, loaded_module).as_str(),
-                    );
+                    .as_str(),
+                );
 
                 fs::write(module_dir.join("index.html"), rendered_module).expect(
                     "TODO gracefully handle failing to write index.html inside module's dir",
@@ -163,9 +288,84 @@ pub fn syntax_highlight_top_level_defs(code_str: &str) -> DocsResult<String> {
     }
 }
 
+/// An exposed opaque type which has an ability.
+struct AbilityImplementation {
+    module_name: String,
+    type_name: String,
+    derived: bool,
+}
+
+/// Finds the exposed opaque types which have each ability, keyed by the ability's
+/// fully qualified name, so that the ability's docs can say what implements it.
+fn ability_implementations(
+    loaded_modules: &[LoadedModule],
+) -> MutMap<String, Vec<AbilityImplementation>> {
+    let mut implementations: MutMap<String, Vec<AbilityImplementation>> = MutMap::default();
+
+    for loaded_module in loaded_modules {
+        let module_docs = match loaded_module.documentation.get(&loaded_module.module_id) {
+            Some(module_docs) => module_docs,
+            None => continue,
+        };
+
+        let exposed_values = loaded_module.exposed_values_str();
+
+        for entry in &module_docs.entries {
+            if let DocDef(doc_def) = entry {
+                let abilities = match &doc_def.type_annotation {
+                    TypeAnnotation::Opaque { abilities }
+                        if exposed_values.contains(&doc_def.name.as_str()) =>
+                    {
+                        abilities
+                    }
+                    _ => continue,
+                };
+
+                for ability in abilities {
+                    let qualified_name = qualified_ability_name(
+                        &module_docs.scope,
+                        &loaded_module.interns,
+                        ability.name.as_str(),
+                    );
+
+                    if let Some(qualified_name) = qualified_name {
+                        implementations.entry(qualified_name).or_default().push(
+                            AbilityImplementation {
+                                module_name: module_docs.name.clone(),
+                                type_name: doc_def.name.clone(),
+                                derived: ability.derived,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    implementations
+}
+
+/// Resolves an ability name as written in a module to its fully qualified name,
+/// e.g. `Eq` to `Bool.Eq`.
+fn qualified_ability_name(scope: &Scope, interns: &Interns, name: &str) -> Option<String> {
+    if name.contains('.') {
+        return Some(name.to_string());
+    }
+
+    let symbol = scope.lookup_str(name, Region::zero()).ok()?;
+
+    Some(format!(
+        "{}.{}",
+        symbol.module_string(interns).as_str(),
+        symbol.as_str(interns)
+    ))
+}
+
 fn render_module_documentation(
     module: &ModuleDocumentation,
     loaded_module: &LoadedModule,
+    dependency_modules: &MutMap<String, String>,
+    implementations: &MutMap<String, Vec<AbilityImplementation>>,
 ) -> String {
     let mut buf = String::new();
 
@@ -214,6 +414,43 @@ fn render_module_documentation(
 
                     match type_ann {
                         TypeAnnotation::NoTypeAnn => {}
+                        TypeAnnotation::Ability { .. } => {
+                            content.push_str(" has");
+                        }
+                        TypeAnnotation::Opaque { abilities } => {
+                            if !abilities.is_empty() {
+                                content.push_str(" has [");
+
+                                for (index, ability) in abilities.iter().enumerate() {
+                                    if index > 0 {
+                                        content.push_str(", ");
+                                    }
+
+                                    let url = qualified_ability_name(
+                                        &module.scope,
+                                        &loaded_module.interns,
+                                        ability.name.as_str(),
+                                    )
+                                    .map(|qualified_name| {
+                                        qualified_url(dependency_modules, qualified_name.as_str())
+                                    });
+
+                                    match url {
+                                        Some(url) => content.push_str(
+                                            html_to_string(
+                                                "a",
+                                                vec![("href", url.as_str())],
+                                                ability.name.as_str(),
+                                            )
+                                            .as_str(),
+                                        ),
+                                        None => content.push_str(ability.name.as_str()),
+                                    }
+                                }
+
+                                content.push(']');
+                            }
+                        }
                         _ => {
                             content.push_str(" : ");
                         }
@@ -237,11 +474,29 @@ fn render_module_documentation(
                                 &module.scope,
                                 docs.to_string(),
                                 loaded_module,
+                                dependency_modules,
                             )
                             .as_str(),
                         );
                     }
 
+                    if let TypeAnnotation::Ability { members } = type_ann {
+                        render_ability_members(
+                            &mut buf,
+                            members,
+                            &exposed_values,
+                            module,
+                            loaded_module,
+                            dependency_modules,
+                        );
+
+                        let qualified_name = format!("{}.{}", module.name, name);
+
+                        if let Some(implementors) = implementations.get(&qualified_name) {
+                            render_implementations(&mut buf, implementors, dependency_modules);
+                        }
+                    }
+
                     buf.push_str("</section>");
                 }
                 DocEntry::DetachedDoc(docs) => {
@@ -250,6 +505,7 @@ fn render_module_documentation(
                         &module.scope,
                         docs.to_string(),
                         loaded_module,
+                        dependency_modules,
                     );
                     buf.push_str(markdown.as_str());
                 }
@@ -260,6 +516,84 @@ fn render_module_documentation(
     buf
 }
 
+fn render_ability_members(
+    buf: &mut String,
+    members: &[AbilityMember],
+    exposed_values: &[&str],
+    module: &ModuleDocumentation,
+    loaded_module: &LoadedModule,
+    dependency_modules: &MutMap<String, String>,
+) {
+    buf.push_str("<ul class=\"ability-members\">");
+
+    for member in members {
+        let name = member.name.as_str();
+        let href = format!("#{}", name);
+
+        let mut content = html_to_string("a", vec![("href", href.as_str())], name);
+
+        content.push_str(" : ");
+        type_annotation_to_html(0, &mut content, &member.type_annotation);
+        push_has_clauses(&mut content, &member.able_variables);
+
+        buf.push_str("<li>");
+        buf.push_str(
+            html_to_string(
+                "h4",
+                vec![("id", name), ("class", "entry-name")],
+                content.as_str(),
+            )
+            .as_str(),
+        );
+
+        if let Some(docs) = &member.docs {
+            buf.push_str(
+                markdown_to_html(
+                    exposed_values,
+                    &module.scope,
+                    docs.to_string(),
+                    loaded_module,
+                    dependency_modules,
+                )
+                .as_str(),
+            );
+        }
+
+        buf.push_str("</li>");
+    }
+
+    buf.push_str("</ul>");
+}
+
+fn render_implementations(
+    buf: &mut String,
+    implementors: &[AbilityImplementation],
+    dependency_modules: &MutMap<String, String>,
+) {
+    buf.push_str(
+        html_to_string("h4", vec![("class", "implemented-by")], "Implemented by").as_str(),
+    );
+    buf.push_str("<ul class=\"ability-implementations\">");
+
+    for implementor in implementors {
+        let qualified_name = format!("{}.{}", implementor.module_name, implementor.type_name);
+        let url = qualified_url(dependency_modules, qualified_name.as_str());
+
+        buf.push_str("<li>");
+        buf.push_str(
+            html_to_string("a", vec![("href", url.as_str())], qualified_name.as_str()).as_str(),
+        );
+
+        if implementor.derived {
+            buf.push_str(" (derived)");
+        }
+
+        buf.push_str("</li>");
+    }
+
+    buf.push_str("</ul>");
+}
+
 fn html_to_string(tag_name: &str, attrs: Vec<(&str, &str)>, content: &str) -> String {
     let mut buf = String::new();
 
@@ -319,7 +653,7 @@ fn base_url() -> String {
     }
 }
 
-fn render_name_and_version(name: &str, version: &str) -> String {
+fn render_name_and_version(name: &str, version: Option<&str>) -> String {
     let mut buf = String::new();
     let mut url_str = base_url();
 
@@ -334,6 +668,11 @@ fn render_name_and_version(name: &str, version: &str) -> String {
         .as_str(),
     );
 
+    let version = match version {
+        Some(version) => version,
+        None => return buf,
+    };
+
     let mut versions_url_str = base_url();
 
     versions_url_str.push('/');
@@ -353,6 +692,61 @@ fn render_name_and_version(name: &str, version: &str) -> String {
     buf
 }
 
+fn render_package_index(
+    package: &roc_load::docs::Documentation,
+    module_names: &[String],
+) -> String {
+    let mut buf = String::new();
+
+    buf.push_str(
+        html_to_string(
+            "h2",
+            vec![("class", "module-name")],
+            html_to_string("a", vec![("href", "/#")], package.name.as_str()).as_str(),
+        )
+        .as_str(),
+    );
+
+    if let Some(readme) = &package.docs {
+        let mut readme_html = String::new();
+
+        pulldown_cmark::html::push_html(
+            &mut readme_html,
+            pulldown_cmark::Parser::new_ext(readme, pulldown_cmark::Options::empty()),
+        );
+
+        buf.push_str(readme_html.as_str());
+    }
+
+    let mut module_links = String::new();
+
+    for module_name in module_names {
+        let mut href = base_url();
+        href.push_str(module_name);
+
+        module_links.push_str(
+            html_to_string(
+                "li",
+                vec![],
+                html_to_string("a", vec![("href", href.as_str())], module_name).as_str(),
+            )
+            .as_str(),
+        );
+    }
+
+    buf.push_str(html_to_string("h3", vec![], "Exposed Modules").as_str());
+    buf.push_str(
+        html_to_string(
+            "ul",
+            vec![("class", "package-modules")],
+            module_links.as_str(),
+        )
+        .as_str(),
+    );
+
+    buf
+}
+
 fn render_sidebar<'a, I: Iterator<Item = (&'a ModuleDocumentation, Vec<String>)>>(
     modules: I,
 ) -> String {
@@ -436,7 +830,8 @@ pub fn load_modules_for_files(filenames: Vec<PathBuf>) -> Vec<LoadedModule> {
             target_info: roc_target::TargetInfo::default_x86_64(), // This is just type-checking for docs, so "target" doesn't matter
             render: roc_reporting::report::RenderTarget::ColorTerminal,
             threading: Threading::AllAvailable,
-            exec_mode: ExecutionMode::Check,
+            // Typecheck the code examples in doc comments too, so they can't drift from the code.
+            exec_mode: ExecutionMode::CheckDocExamples,
         };
         match roc_load::load_and_typecheck(&arena, filename, Default::default(), load_config) {
             Ok(loaded) => modules.push(loaded),
@@ -451,6 +846,52 @@ pub fn load_modules_for_files(filenames: Vec<PathBuf>) -> Vec<LoadedModule> {
     modules
 }
 
+/// Prints the errors (but not warnings) in the loaded module, including ones in its
+/// doc examples, and returns how many there were. Errors in the modules it depends on
+/// get reported when those are loaded, if they are part of the package.
+pub(crate) fn report_errors(loaded: &mut LoadedModule) -> usize {
+    use roc_reporting::report::{can_problem, type_problem, RocDocAllocator, Severity};
+
+    let palette = roc_reporting::report::DEFAULT_PALETTE;
+    let home = loaded.module_id;
+    let mut errors = Vec::new();
+
+    if let Some((module_path, src)) = loaded.sources.get(&home) {
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, home, &loaded.interns);
+
+        let can_reports = loaded
+            .can_problems
+            .remove(&home)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|problem| can_problem(&alloc, &lines, module_path.clone(), problem));
+
+        let type_reports = loaded
+            .type_problems
+            .remove(&home)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|problem| type_problem(&alloc, &lines, module_path.clone(), problem));
+
+        for report in can_reports.chain(type_reports) {
+            if report.severity == Severity::RuntimeError {
+                let mut buf = String::new();
+
+                report.render_color_terminal(&mut buf, &alloc, &palette);
+                errors.push(buf);
+            }
+        }
+    }
+
+    for error in errors.iter() {
+        eprintln!("\n{}\n", error);
+    }
+
+    errors.len()
+}
+
 const INDENT: &str = "    ";
 
 fn indent(buf: &mut String, times: usize) {
@@ -635,8 +1076,13 @@ fn type_annotation_to_html(indent_level: usize, buf: &mut String, type_ann: &Typ
 
             type_annotation_to_html(next_indent_level, buf, output);
         }
-        TypeAnnotation::Ability { members: _ } => {
-            // TODO(abilities): fill me in
+        TypeAnnotation::Where { ann, has_clauses } => {
+            type_annotation_to_html(indent_level, buf, ann);
+            push_has_clauses(buf, has_clauses);
+        }
+        TypeAnnotation::Ability { .. } | TypeAnnotation::Opaque { .. } => {
+            // These are rendered by render_module_documentation, since their
+            // members and abilities get links and docs of their own.
         }
         TypeAnnotation::ObscuredTagUnion => {
             buf.push_str("[@..]");
@@ -649,6 +1095,66 @@ fn type_annotation_to_html(indent_level: usize, buf: &mut String, type_ann: &Typ
     }
 }
 
+/// The plain-text signature of an entry, the way it would be written in Roc,
+/// e.g. `join : List Str, Str -> Str` or `Id has [Eq]`
+fn entry_signature(doc_def: &roc_load::docs::DocDef) -> String {
+    let mut buf = doc_def.name.clone();
+
+    for type_var in &doc_def.type_vars {
+        buf.push(' ');
+        buf.push_str(type_var.as_str());
+    }
+
+    match &doc_def.type_annotation {
+        TypeAnnotation::NoTypeAnn => {}
+        TypeAnnotation::Ability { members } => {
+            buf.push_str(" has");
+
+            for member in members {
+                new_line(&mut buf);
+                indent(&mut buf, 1);
+                buf.push_str(member.name.as_str());
+                buf.push_str(" : ");
+                type_annotation_to_html(1, &mut buf, &member.type_annotation);
+                push_has_clauses(&mut buf, &member.able_variables);
+            }
+        }
+        TypeAnnotation::Opaque { abilities } => {
+            if !abilities.is_empty() {
+                let names: Vec<&str> = abilities
+                    .iter()
+                    .map(|ability| ability.name.as_str())
+                    .collect();
+
+                buf.push_str(" has [");
+                buf.push_str(names.join(", ").as_str());
+                buf.push(']');
+            }
+        }
+        type_ann => {
+            buf.push_str(" : ");
+            type_annotation_to_html(0, &mut buf, type_ann);
+        }
+    }
+
+    buf
+}
+
+/// e.g. ` | a has Hash, b has Eq`
+fn push_has_clauses(buf: &mut String, has_clauses: &[(String, TypeAnnotation)]) {
+    for (index, (var, ability)) in has_clauses.iter().enumerate() {
+        if index == 0 {
+            buf.push_str(" | ");
+        } else {
+            buf.push_str(", ");
+        }
+
+        buf.push_str(var.as_str());
+        buf.push_str(" has ");
+        type_annotation_to_html(0, buf, ability);
+    }
+}
+
 fn should_be_multiline(type_ann: &TypeAnnotation) -> bool {
     match type_ann {
         TypeAnnotation::TagUnion { tags, extension } => {
@@ -714,7 +1220,9 @@ fn should_be_multiline(type_ann: &TypeAnnotation) -> bool {
 
             is_multiline
         }
+        TypeAnnotation::Where { ann, .. } => should_be_multiline(ann),
         TypeAnnotation::Ability { .. } => true,
+        TypeAnnotation::Opaque { .. } => false,
         TypeAnnotation::Wildcard => false,
         TypeAnnotation::NoTypeAnn => false,
     }
@@ -723,17 +1231,22 @@ fn should_be_multiline(type_ann: &TypeAnnotation) -> bool {
 struct DocUrl {
     url: String,
     title: String,
+    module_name: String,
+    ident: String,
 }
 
 fn doc_url<'a>(
-    home: ModuleId,
+    loaded_module: &'a LoadedModule,
     exposed_values: &[&str],
-    dep_idents: &IdentIdsByModule,
     scope: &Scope,
-    interns: &'a Interns,
+    dependency_modules: &MutMap<String, String>,
     mut module_name: &'a str,
     ident: &str,
-) -> DocUrl {
+) -> Result<DocUrl, String> {
+    let home = loaded_module.module_id;
+    let dep_idents = &loaded_module.dep_idents;
+    let interns = &loaded_module.interns;
+
     if module_name.is_empty() {
         // This is an unqualified lookup, so look for the ident
         // in scope!
@@ -746,11 +1259,10 @@ fn doc_url<'a>(
                 module_name = symbol.module_string(interns);
             }
             Err(_) => {
-                // TODO return Err here
-                panic!(
-                "Tried to generate an automatic link in docs for symbol `{}`, but that symbol was not in scope in this module.",
-                ident
-            );
+                return Err(format!(
+                    "`{}` is not in scope in this module, so there is nothing to link to.",
+                    ident
+                ));
             }
         }
     } else {
@@ -762,10 +1274,7 @@ fn doc_url<'a>(
                     // Check to see if the value is exposed in this module.
                     // If it's not exposed, then we can't link to it!
                     if !exposed_values.contains(&ident) {
-                        // TODO return Err here
-                        panic!(
-                            "Tried to generate an automatic link in docs for `{}.{}`, but `{}` does not expose `{}`.",
-                            module_name, ident, module_name, ident);
+                        return Err(format!("`{}` does not expose `{}`.", module_name, ident));
                     }
                 } else {
                     // This is not the home module
@@ -776,28 +1285,50 @@ fn doc_url<'a>(
                         Some(_) => {
                             // This is a valid symbol for this dependency,
                             // so proceed using the current module's name.
-                            //
-                            // TODO: In the future, this is where we'll
-                            // incorporate the package name into the link.
+                            // If the module comes from another package,
+                            // the link goes to that package's docs below.
                         }
                         _ => {
-                            // TODO return Err here
-                            panic!(
-                                "Tried to generate an automatic link in docs for `{}.{}`, but `{}` is not exposed in `{}`.",
-                                module_name, ident, ident, module_name);
+                            return Err(format!(
+                                "`{}` is not exposed in `{}`.",
+                                ident, module_name
+                            ));
                         }
                     }
                 }
             }
             None => {
-                // TODO return Err here
-                panic!("Tried to generate a doc link for `{}.{}` but the `{}` module was not imported!", module_name, ident, module_name);
+                return Err(format!("The `{}` module is not imported.", module_name));
             }
         }
     }
 
+    let qualified_name = format!("{}.{}", module_name, ident);
+
+    Ok(DocUrl {
+        url: qualified_url(dependency_modules, qualified_name.as_str()),
+        title: format!("Docs for {}", qualified_name),
+        module_name: module_name.to_string(),
+        ident: ident.to_string(),
+    })
+}
+
+/// The url of the docs for a qualified name like `Str.join`
+fn qualified_url(dependency_modules: &MutMap<String, String>, qualified_name: &str) -> String {
+    let (module_name, ident) = qualified_name
+        .rsplit_once('.')
+        .unwrap_or(("", qualified_name));
+
     let mut url = base_url();
 
+    // Modules from other packages live in those packages' docs, e.g.
+    //
+    // module_name: "Json", ident: "toUtf8" => "/roc/json/Json#toUtf8"
+    if let Some(package_name) = dependency_modules.get(module_name) {
+        url.push_str(package_name);
+        url.push('/');
+    }
+
     // Example:
     //
     // module_name: "Str", ident: "join" => "/Str#join"
@@ -805,9 +1336,82 @@ fn doc_url<'a>(
     url.push('#');
     url.push_str(ident);
 
-    DocUrl {
-        url,
-        title: format!("Docs for {}.{}", module_name, ident),
+    url
+}
+
+/// Finds the shortcut links - see https://spec.commonmark.org/0.30/#shortcut-reference-link -
+/// like `[Str.join]` which have no matching `[Str.join]: https://...` entry, along with
+/// where they are in the markdown. These are the ones which get resolved as doc links.
+fn shortcut_links(markdown: &str) -> Vec<(Range<usize>, String)> {
+    use pulldown_cmark::{BrokenLink, LinkType};
+
+    let mut links = Vec::new();
+
+    let mut broken_link_callback = |link: BrokenLink| {
+        if let LinkType::Shortcut = link.link_type {
+            links.push((link.span, link.reference.to_string()));
+        }
+
+        None
+    };
+
+    pulldown_cmark::Parser::new_with_broken_link_callback(
+        markdown,
+        pulldown_cmark::Options::empty(),
+        Some(&mut broken_link_callback),
+    )
+    .for_each(drop);
+
+    links
+}
+
+/// Resolves the text of a shortcut link like `[Str.join]` or `[I64]` to the docs
+/// for what it names. Returns `None` if the text doesn't look like a name at all,
+/// e.g. `[foo.bar]` (a record field access) - those aren't meant to be doc links.
+fn resolve_doc_link(
+    arena: &Bump,
+    loaded_module: &LoadedModule,
+    exposed_values: &[&str],
+    scope: &Scope,
+    dependency_modules: &MutMap<String, String>,
+    reference: &str,
+) -> Option<Result<DocUrl, String>> {
+    let state = State::new(reference.as_bytes());
+
+    match parse_ident(arena, state) {
+        Ok((_, Ident::Access { module_name, parts }, _)) => {
+            let mut iter = parts.iter();
+
+            match iter.next() {
+                Some(symbol_name) if iter.next().is_none() => Some(doc_url(
+                    loaded_module,
+                    exposed_values,
+                    scope,
+                    dependency_modules,
+                    module_name,
+                    symbol_name,
+                )),
+                _ => {
+                    // This had record field access,
+                    // e.g. [foo.bar] - which we
+                    // can't create a doc link to!
+                    None
+                }
+            }
+        }
+        Ok((_, Ident::Tag(type_name), _)) => {
+            // This looks like a tag name, but it could
+            // be a type alias that's in scope, e.g. [I64]
+            Some(doc_url(
+                loaded_module,
+                exposed_values,
+                scope,
+                dependency_modules,
+                "",
+                type_name,
+            ))
+        }
+        _ => None,
     }
 }
 
@@ -816,6 +1420,7 @@ fn markdown_to_html(
     scope: &Scope,
     markdown: String,
     loaded_module: &LoadedModule,
+    dependency_modules: &MutMap<String, String>,
 ) -> String {
     use pulldown_cmark::{BrokenLink, CodeBlockKind, CowStr, Event, LinkType, Tag::*};
 
@@ -827,56 +1432,25 @@ fn markdown_to_html(
         // at the end of the document, we resolve it as an identifier based on
         // what's currently in scope, so you write things like [Str.join] or
         // [myFunction] and have them resolve to the docs for what you wrote.
+        //
+        // Links which don't resolve are left as plain text here; `roc docs --check`
+        // reports them.
         match link.link_type {
             LinkType::Shortcut => {
-                let state = State::new(link.reference.as_bytes());
-
                 // Reset the bump arena so we aren't constantly reallocating
                 // more memory as we iterate through these.
                 arena.reset();
 
-                match parse_ident(&arena, state) {
-                    Ok((_, Ident::Access { module_name, parts }, _)) => {
-                        let mut iter = parts.iter();
-
-                        match iter.next() {
-                            Some(symbol_name) if iter.next().is_none() => {
-                                let DocUrl { url, title } = doc_url(
-                                    loaded_module.module_id,
-                                    exposed_values,
-                                    &loaded_module.dep_idents,
-                                    scope,
-                                    &loaded_module.interns,
-                                    module_name,
-                                    symbol_name,
-                                );
-
-                                Some((url.into(), title.into()))
-                            }
-                            _ => {
-                                // This had record field access,
-                                // e.g. [foo.bar] - which we
-                                // can't create a doc link to!
-                                None
-                            }
-                        }
-                    }
-                    Ok((_, Ident::Tag(type_name), _)) => {
-                        // This looks like a tag name, but it could
-                        // be a type alias that's in scope, e.g. [I64]
-                        let DocUrl { url, title } = doc_url(
-                            loaded_module.module_id,
-                            exposed_values,
-                            &loaded_module.dep_idents,
-                            scope,
-                            &loaded_module.interns,
-                            "",
-                            type_name,
-                        );
-
-                        Some((url.into(), title.into()))
-                    }
-                    _ => None,
+                match resolve_doc_link(
+                    &arena,
+                    loaded_module,
+                    exposed_values,
+                    scope,
+                    dependency_modules,
+                    &link.reference,
+                ) {
+                    Some(Ok(DocUrl { url, title, .. })) => Some((url.into(), title.into())),
+                    Some(Err(_)) | None => None,
                 }
             }
             _ => None,
@@ -960,8 +1534,10 @@ fn markdown_to_html(
 
                 (start_quote_count, end_quote_count)
             }
-            Event::Start(CodeBlock(CodeBlockKind::Fenced(_))) => {
-                expecting_code_block = true;
+            Event::Start(CodeBlock(CodeBlockKind::Fenced(info))) => {
+                // Blocks tagged something else, like `roc ignore`, aren't checked by the compiler,
+                // so they might not parse.
+                expecting_code_block = matches!(&**info, "" | "roc");
                 docs_parser.push(event);
                 (0, 0)
             }
@@ -0,0 +1,219 @@
+use crate::{
+    ability_implementations, create_build_dir, entry_signature, home_module_docs, load_package,
+    member_signature, resolve_doc_link, shortcut_links, AbilityImplementation, DocUrl,
+};
+use bumpalo::Bump;
+use roc_collections::all::MutMap;
+use roc_load::docs::{AbilityMember, DocEntry, ModuleDocumentation, TypeAnnotation};
+use roc_load::LoadedModule;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes an `index.md` for the package, with its README and a list of its modules,
+/// plus a `<Module>.md` for each exposed module. Doc links like `[Str.join]` become
+/// links between these files.
+pub fn generate_docs_markdown(filenames: Vec<PathBuf>, build_dir: &Path) {
+    let (package_info, package) = load_package(filenames);
+    let implementations = ability_implementations(&package.modules);
+
+    create_build_dir(build_dir);
+
+    let mut index = format!("# {}", package.name);
+
+    if let Some(version) = &package.version {
+        index.push(' ');
+        index.push_str(version.as_str());
+    }
+
+    index.push_str("\n\n");
+
+    if let Some(readme) = &package.docs {
+        index.push_str(readme.trim_end());
+        index.push_str("\n\n");
+    }
+
+    index.push_str("## Modules\n\n");
+
+    for loaded_module in package.modules.iter() {
+        if let Some(module) = home_module_docs(loaded_module) {
+            let filename = format!("{}.md", module.name);
+
+            index.push_str(format!("- [{}]({})\n", module.name, filename).as_str());
+
+            let rendered_module = ModuleRenderer {
+                module,
+                loaded_module,
+                exposed_values: loaded_module.exposed_values_str(),
+                dependency_modules: &package_info.dependency_modules,
+                link_definitions: Vec::new(),
+                arena: Bump::new(),
+            }
+            .render(&implementations);
+
+            fs::write(build_dir.join(filename), rendered_module)
+                .expect("TODO gracefully handle failing to write a module's markdown");
+        }
+    }
+
+    fs::write(build_dir.join("index.md"), index)
+        .expect("TODO gracefully handle failing to write index.md");
+
+    println!("🎉 Docs generated in {}", build_dir.display());
+}
+
+struct ModuleRenderer<'a> {
+    module: &'a ModuleDocumentation,
+    loaded_module: &'a LoadedModule,
+    exposed_values: Vec<&'a str>,
+    dependency_modules: &'a MutMap<String, String>,
+    /// Doc links which resolved, as (link text, url). These get written at the end of
+    /// the file as link reference definitions, which turns every `[Str.join]` into a link.
+    link_definitions: Vec<(String, String)>,
+    arena: Bump,
+}
+
+impl<'a> ModuleRenderer<'a> {
+    fn render(mut self, implementations: &MutMap<String, Vec<AbilityImplementation>>) -> String {
+        let module = self.module;
+        let mut buf = format!("# {}\n", module.name);
+
+        for entry in &module.entries {
+            match entry {
+                DocEntry::DocDef(doc_def)
+                    if self.exposed_values.contains(&doc_def.name.as_str()) =>
+                {
+                    push_heading(&mut buf, "##", doc_def.name.as_str());
+                    push_code_block(&mut buf, entry_signature(doc_def).as_str());
+
+                    if let Some(docs) = &doc_def.docs {
+                        self.push_docs(&mut buf, docs);
+                    }
+
+                    if let TypeAnnotation::Ability { members } = &doc_def.type_annotation {
+                        for member in members {
+                            self.push_member(&mut buf, member);
+                        }
+
+                        let qualified_name = format!("{}.{}", module.name, doc_def.name);
+
+                        if let Some(implementors) = implementations.get(&qualified_name) {
+                            push_implementations(&mut buf, implementors);
+                        }
+                    }
+                }
+                DocEntry::DocDef(_) => {}
+                DocEntry::DetachedDoc(docs) => {
+                    self.push_docs(&mut buf, docs);
+                }
+            }
+        }
+
+        if !self.link_definitions.is_empty() {
+            buf.push('\n');
+
+            for (reference, url) in self.link_definitions.iter() {
+                buf.push_str(format!("[{}]: {}\n", reference, url).as_str());
+            }
+        }
+
+        buf
+    }
+
+    fn push_member(&mut self, buf: &mut String, member: &AbilityMember) {
+        push_heading(buf, "###", member.name.as_str());
+        push_code_block(buf, member_signature(member).as_str());
+
+        if let Some(docs) = &member.docs {
+            self.push_docs(buf, docs);
+        }
+    }
+
+    fn push_docs(&mut self, buf: &mut String, docs: &str) {
+        // Doc comments are written `## Like this`, so every line starts with a space.
+        let docs = docs
+            .lines()
+            .map(|line| line.strip_prefix(' ').unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for (_, reference) in shortcut_links(docs.as_str()) {
+            if self
+                .link_definitions
+                .iter()
+                .any(|(defined, _)| *defined == reference)
+            {
+                continue;
+            }
+
+            // Reset the bump arena so we aren't constantly reallocating
+            // more memory as we iterate through these.
+            self.arena.reset();
+
+            let resolved = resolve_doc_link(
+                &self.arena,
+                self.loaded_module,
+                &self.exposed_values,
+                &self.module.scope,
+                self.dependency_modules,
+                reference.as_str(),
+            );
+
+            // Links which don't resolve are left as plain text; `roc docs --check` reports them.
+            if let Some(Ok(doc_url)) = resolved {
+                let url = self.markdown_url(&doc_url);
+
+                self.link_definitions.push((reference, url));
+            }
+        }
+
+        buf.push('\n');
+        buf.push_str(docs.trim_end());
+        buf.push('\n');
+    }
+
+    fn markdown_url(&self, doc_url: &DocUrl) -> String {
+        let DocUrl {
+            module_name, ident, ..
+        } = doc_url;
+
+        match self.dependency_modules.get(module_name) {
+            // e.g. module_name: "Json", ident: "toUtf8" => "../roc/json/Json.md#toUtf8"
+            Some(package_name) => format!("../{}/{}.md#{}", package_name, module_name, ident),
+            None => format!("{}.md#{}", module_name, ident),
+        }
+    }
+}
+
+/// Headings get an explicit anchor, since markdown renderers disagree on how to make them.
+fn push_heading(buf: &mut String, level: &str, name: &str) {
+    buf.push_str(format!("\n<a id=\"{}\"></a>\n\n{} {}\n", name, level, name).as_str());
+}
+
+fn push_code_block(buf: &mut String, code: &str) {
+    buf.push_str("\n```roc\n");
+    buf.push_str(code);
+    buf.push_str("\n```\n");
+}
+
+fn push_implementations(buf: &mut String, implementors: &[AbilityImplementation]) {
+    buf.push_str("\nImplemented by:\n\n");
+
+    for implementor in implementors {
+        buf.push_str(
+            format!(
+                "- [{}.{}]({}.md#{})",
+                implementor.module_name,
+                implementor.type_name,
+                implementor.module_name,
+                implementor.type_name
+            )
+            .as_str(),
+        );
+
+        if implementor.derived {
+            buf.push_str(" (derived)");
+        }
+
+        buf.push('\n');
+    }
+}
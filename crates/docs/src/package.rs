@@ -0,0 +1,271 @@
+use bumpalo::Bump;
+use roc_collections::all::MutMap;
+use roc_parse::ast::{Collection, ExtractSpaces, Module, Spaced};
+use roc_parse::header::ImportsEntry;
+use roc_parse::module::parse_header;
+use roc_parse::state::State;
+use roc_region::all::Loc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What `roc docs` knows about the package it's documenting.
+///
+/// This comes from the `package` or `platform` header among the given files, if
+/// there is one. Without a header, every module given gets documented as part of
+/// the builtins, which is what `www/build.sh` relies on.
+#[derive(Debug)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: Option<String>,
+    /// The contents of the README next to the package header, if any.
+    pub readme: Option<String>,
+    /// The modules listed in the header's `exposes`, in order. `None` means there
+    /// was no header, so everything we were given is fair game.
+    pub exposed_modules: Option<Vec<String>>,
+    /// The files which should be loaded and documented.
+    pub module_files: Vec<PathBuf>,
+    /// Maps module names imported from another package (e.g. `Json` in `imports [json.Json]`)
+    /// to the name of the package they come from (e.g. `roc/json`).
+    pub dependency_modules: MutMap<String, String>,
+}
+
+const DEFAULT_PACKAGE_NAME: &str = "roc/builtins";
+const DEFAULT_PACKAGE_VERSION: &str = "1.0.0";
+const README_NAMES: [&str; 3] = ["README.md", "Readme.md", "readme.md"];
+
+impl PackageInfo {
+    /// Whether there is anything to put on a landing page for the package.
+    pub fn has_landing_page(&self) -> bool {
+        self.exposed_modules.is_some() || self.readme.is_some()
+    }
+}
+
+/// Reads the header of each file to find the package header, which modules it
+/// exposes, and which packages those modules import from.
+pub fn read_package_info(filenames: Vec<PathBuf>) -> PackageInfo {
+    let arena = Bump::new();
+    let mut headers = Vec::with_capacity(filenames.len());
+
+    for filename in filenames {
+        let src = match fs::read_to_string(&filename) {
+            Ok(src) => src,
+            Err(_) => {
+                // Let the loader report unreadable files, like it always has.
+                headers.push((filename, None));
+                continue;
+            }
+        };
+
+        let src: &str = arena.alloc_str(&src);
+
+        match parse_header(&arena, State::new(src.as_bytes())) {
+            Ok((module, _)) => headers.push((filename, Some(module))),
+            Err(_) => headers.push((filename, None)),
+        }
+    }
+
+    let package_header = headers.iter().find_map(|(filename, module)| match module {
+        Some(Module::Package { header }) => {
+            Some((filename, header.name, header.exposes, header.packages))
+        }
+        Some(Module::Platform { header }) => {
+            Some((filename, header.name, header.exposes, header.packages))
+        }
+        _ => None,
+    });
+
+    let (header_path, package_name, exposes, packages) = match package_header {
+        Some(found) => found,
+        None => {
+            return PackageInfo {
+                name: DEFAULT_PACKAGE_NAME.to_string(),
+                version: Some(DEFAULT_PACKAGE_VERSION.to_string()),
+                readme: None,
+                exposed_modules: None,
+                module_files: headers.into_iter().map(|(filename, _)| filename).collect(),
+                dependency_modules: MutMap::default(),
+            };
+        }
+    };
+
+    let (name, version) = split_name_and_version(package_name.value.as_str());
+
+    let exposed_modules: Vec<String> = exposes
+        .iter()
+        .map(|exposed| exposed.value.extract_spaces().item.as_str().to_string())
+        .collect();
+
+    let mut packages_by_shorthand = MutMap::default();
+
+    for entry in packages.iter() {
+        let entry = entry.value.extract_spaces().item;
+
+        packages_by_shorthand.insert(
+            entry.shorthand,
+            split_name_and_version(entry.package_name.value.as_str()).0,
+        );
+    }
+
+    let mut dependency_modules = MutMap::default();
+    let mut module_files = Vec::new();
+
+    for (filename, module) in headers.iter() {
+        let (module_name, imports) = match module {
+            Some(Module::Interface { header }) => (header.name.value.as_str(), header.imports),
+            Some(Module::Hosted { header }) => (header.name.value.as_str(), header.imports),
+            Some(Module::Package { header }) => ("", header.imports),
+            Some(Module::Platform { header }) => ("", header.imports),
+            Some(Module::App { .. }) | None => continue,
+        };
+
+        add_dependency_modules(imports, &packages_by_shorthand, &mut dependency_modules);
+
+        if exposed_modules.iter().any(|exposed| exposed == module_name) {
+            module_files.push(filename.clone());
+        }
+    }
+
+    PackageInfo {
+        name,
+        version,
+        readme: read_readme(header_path.parent().unwrap_or_else(|| Path::new("."))),
+        exposed_modules: Some(exposed_modules),
+        module_files,
+        dependency_modules,
+    }
+}
+
+/// Records which modules come from a dependency. A shorthand that isn't in the
+/// header's `packages` (like a platform's own `pf`) refers to this package.
+fn add_dependency_modules<'a>(
+    imports: Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>,
+    packages_by_shorthand: &MutMap<&'a str, String>,
+    dependency_modules: &mut MutMap<String, String>,
+) {
+    for import in imports.iter() {
+        if let ImportsEntry::Package(shorthand, module_name, _) = import.value.extract_spaces().item
+        {
+            if let Some(package_name) = packages_by_shorthand.get(shorthand) {
+                dependency_modules.insert(module_name.as_str().to_string(), package_name.clone());
+            }
+        }
+    }
+}
+
+/// Package names may carry a version after the name, as in `roc/unicode 1.0.0`.
+fn split_name_and_version(package_name: &str) -> (String, Option<String>) {
+    let mut words = package_name.split_whitespace();
+    let name = words.next().unwrap_or_default().to_string();
+
+    (name, words.next().map(|version| version.to_string()))
+}
+
+fn read_readme(package_dir: &Path) -> Option<String> {
+    README_NAMES
+        .iter()
+        .find_map(|readme_name| fs::read_to_string(package_dir.join(readme_name)).ok())
+}
+
+#[cfg(test)]
+mod test_package {
+    use super::{read_package_info, split_name_and_version};
+    use std::fs;
+    use std::path::Path;
+
+    fn write(dir: &Path, filename: &str, src: &str) -> std::path::PathBuf {
+        let path = dir.join(filename);
+
+        fs::write(&path, src).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn names_and_versions() {
+        assert_eq!(
+            split_name_and_version("roc/unicode 1.0.0"),
+            ("roc/unicode".to_string(), Some("1.0.0".to_string()))
+        );
+        assert_eq!(
+            split_name_and_version("roc/unicode"),
+            ("roc/unicode".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn package_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = write(
+            dir.path(),
+            "main.roc",
+            r#"package "roc/json 1.2.0"
+    exposes [Json]
+    packages { util: "roc/util" }
+    imports []
+"#,
+        );
+        let json = write(
+            dir.path(),
+            "Json.roc",
+            "interface Json\n    exposes [null]\n    imports [util.Text]\n\nnull = \"null\"\n",
+        );
+        let hidden = write(
+            dir.path(),
+            "Hidden.roc",
+            "interface Hidden\n    exposes []\n    imports []\n",
+        );
+        write(dir.path(), "README.md", "# JSON\n");
+
+        let info = read_package_info(vec![package, json.clone(), hidden]);
+
+        assert_eq!(info.name, "roc/json");
+        assert_eq!(info.version.as_deref(), Some("1.2.0"));
+        assert_eq!(info.readme.as_deref(), Some("# JSON\n"));
+        assert_eq!(info.exposed_modules, Some(vec!["Json".to_string()]));
+        assert_eq!(info.module_files, vec![json]);
+        assert_eq!(
+            info.dependency_modules.get("Text").map(String::as_str),
+            Some("roc/util")
+        );
+    }
+
+    #[test]
+    fn platform_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let platform = write(
+            dir.path(),
+            "main.roc",
+            r#"platform "roc/cli"
+    requires {} { main : Str }
+    exposes [Stdout]
+    packages {}
+    imports []
+    provides [mainForHost]
+"#,
+        );
+
+        let info = read_package_info(vec![platform]);
+
+        assert_eq!(info.name, "roc/cli");
+        assert_eq!(info.version, None);
+        assert_eq!(info.readme, None);
+        assert_eq!(info.exposed_modules, Some(vec!["Stdout".to_string()]));
+    }
+
+    #[test]
+    fn no_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let module = write(
+            dir.path(),
+            "Str.roc",
+            "interface Str\n    exposes []\n    imports []\n",
+        );
+
+        let info = read_package_info(vec![module.clone()]);
+
+        assert_eq!(info.name, "roc/builtins");
+        assert_eq!(info.version.as_deref(), Some("1.0.0"));
+        assert_eq!(info.exposed_modules, None);
+        assert_eq!(info.module_files, vec![module]);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{base_url, type_annotation_to_html};
+use roc_load::docs::{DocEntry, ModuleDocumentation, TypeAnnotation};
+use serde::Serialize;
+
+/// One searchable item in the generated docs.
+#[derive(Serialize)]
+struct SearchEntry {
+    kind: &'static str,
+    module: String,
+    name: String,
+    /// The type signature, rendered on a single line.
+    signature: String,
+    /// For functions, each argument type in normalized form (see `normalize_type`).
+    args: Vec<String>,
+    /// The normalized return type for functions, or the whole type otherwise.
+    output: String,
+    url: String,
+}
+
+/// Builds the contents of `search-index.js`, which assigns every exposed value,
+/// type, ability, ability member and tag in the given modules to `window.rocSearchIndex`.
+pub fn render_search_index<'a, I: Iterator<Item = (&'a ModuleDocumentation, Vec<String>)>>(
+    modules: I,
+) -> String {
+    let mut entries = Vec::new();
+
+    for (module, exposed_values) in modules {
+        for entry in &module.entries {
+            if let DocEntry::DocDef(doc_def) = entry {
+                if !exposed_values.contains(&doc_def.name) {
+                    continue;
+                }
+
+                let url = entry_url(module.name.as_str(), doc_def.name.as_str());
+                let type_ann = &doc_def.type_annotation;
+
+                match type_ann {
+                    TypeAnnotation::Ability { members } => {
+                        entries.push(SearchEntry {
+                            kind: "ability",
+                            module: module.name.clone(),
+                            name: doc_def.name.clone(),
+                            signature: String::new(),
+                            args: Vec::new(),
+                            output: String::new(),
+                            url,
+                        });
+
+                        for member in members {
+                            entries.push(value_entry(
+                                "ability member",
+                                module.name.as_str(),
+                                member.name.as_str(),
+                                &member.type_annotation,
+                                entry_url(module.name.as_str(), member.name.as_str()),
+                            ));
+                        }
+                    }
+                    _ if is_type_name(doc_def.name.as_str()) => {
+                        let mut name = doc_def.name.clone();
+
+                        for type_var in &doc_def.type_vars {
+                            name.push(' ');
+                            name.push_str(type_var.as_str());
+                        }
+
+                        entries.push(SearchEntry {
+                            kind: "type",
+                            module: module.name.clone(),
+                            name: doc_def.name.clone(),
+                            signature: single_line(type_ann),
+                            args: Vec::new(),
+                            output: normalize_type(name.as_str()),
+                            url: url.clone(),
+                        });
+
+                        if let TypeAnnotation::TagUnion { tags, .. } = type_ann {
+                            for tag in tags {
+                                let mut signature = String::new();
+
+                                for value in &tag.values {
+                                    if !signature.is_empty() {
+                                        signature.push_str(", ");
+                                    }
+                                    type_annotation_to_html(0, &mut signature, value);
+                                }
+
+                                let args = tag
+                                    .values
+                                    .iter()
+                                    .map(|value| normalize_type(&single_line(value)))
+                                    .collect::<Vec<_>>();
+
+                                // Tags are searchable like constructor functions,
+                                // e.g. `Str -> Result Str err` finds `Ok`.
+                                let signature = if args.is_empty() {
+                                    name.clone()
+                                } else {
+                                    format!("{} -> {}", collapse_whitespace(&signature), name)
+                                };
+
+                                entries.push(SearchEntry {
+                                    kind: "tag",
+                                    module: module.name.clone(),
+                                    name: tag.name.clone(),
+                                    signature,
+                                    args,
+                                    output: normalize_type(name.as_str()),
+                                    url: url.clone(),
+                                });
+                            }
+                        }
+                    }
+                    _ => {
+                        entries.push(value_entry(
+                            "value",
+                            module.name.as_str(),
+                            doc_def.name.as_str(),
+                            type_ann,
+                            url,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&entries).expect("the search index is valid JSON");
+
+    // Keep `</script>` and friends from ever ending up in the output verbatim. `<` can only
+    // appear inside strings, where this escape means the same thing.
+    format!(
+        "window.rocSearchIndex = {};\n",
+        json.replace('<', "\\u003c")
+    )
+}
+
+fn value_entry(
+    kind: &'static str,
+    module_name: &str,
+    name: &str,
+    type_ann: &TypeAnnotation,
+    url: String,
+) -> SearchEntry {
+    // `has` clauses don't take part in type search.
+    let unconstrained = match type_ann {
+        TypeAnnotation::Where { ann, .. } => ann,
+        _ => type_ann,
+    };
+
+    let (args, output) = match unconstrained {
+        TypeAnnotation::Function { args, output } => (
+            args.iter()
+                .map(|arg| normalize_type(&single_line(arg)))
+                .collect(),
+            normalize_type(&single_line(output)),
+        ),
+        _ => (Vec::new(), normalize_type(&single_line(unconstrained))),
+    };
+
+    SearchEntry {
+        kind,
+        module: module_name.to_string(),
+        name: name.to_string(),
+        signature: single_line(type_ann),
+        args,
+        output,
+        url,
+    }
+}
+
+fn entry_url(module_name: &str, name: &str) -> String {
+    let mut url = base_url();
+
+    url.push_str(module_name);
+    url.push('#');
+    url.push_str(name);
+
+    url
+}
+
+fn is_type_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_uppercase())
+}
+
+/// Renders a type annotation the same way the docs pages do, but on one line.
+fn single_line(type_ann: &TypeAnnotation) -> String {
+    let mut buf = String::new();
+
+    type_annotation_to_html(0, &mut buf, type_ann);
+
+    collapse_whitespace(buf.as_str())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Drops the parentheses around type applications, so that `(List U8)` and
+/// `List U8` compare equal. `search.js` normalizes queries the same way.
+fn normalize_type(rendered: &str) -> String {
+    collapse_whitespace(&rendered.replace('(', " ").replace(')', " "))
+}
+
+#[cfg(test)]
+mod test_search_index {
+    use super::render_search_index;
+    use crate::{home_module_docs, load_package};
+    use serde_json::Value;
+    use std::fs;
+
+    #[test]
+    fn signature_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Bytes.roc");
+
+        fs::write(
+            &path,
+            r#"interface Bytes
+    exposes [toBytes, first, count]
+    imports []
+
+toBytes : Str -> List U8
+toBytes = \str -> Str.toUtf8 str
+
+first : List a, a -> a
+first = \list, default -> List.first list |> Result.withDefault default
+
+count : Str -> Nat
+count = \str -> Str.countUtf8Bytes str
+"#,
+        )
+        .unwrap();
+
+        let (_, package) = load_package(vec![path]);
+        let loaded_module = &package.modules[0];
+        let module_docs = home_module_docs(loaded_module).unwrap();
+        let exposed_values = loaded_module
+            .exposed_values_str()
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let index = render_search_index(std::iter::once((module_docs, exposed_values)));
+        let json = index
+            .strip_prefix("window.rocSearchIndex = ")
+            .and_then(|json| json.strip_suffix(";\n"))
+            .unwrap();
+        let entries: Vec<Value> = serde_json::from_str(json).unwrap();
+
+        // What search.js does for the query `Str -> List U8`, with no type variables
+        let matches: Vec<&str> = entries
+            .iter()
+            .filter(|entry| entry["args"] == serde_json::json!(["Str"]))
+            .filter(|entry| entry["output"] == "List U8")
+            .map(|entry| entry["name"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(matches, vec!["toBytes"]);
+
+        let first = entries
+            .iter()
+            .find(|entry| entry["name"] == "first")
+            .unwrap();
+
+        assert_eq!(first["kind"], "value");
+        assert_eq!(first["signature"], "List a, a -> a");
+        assert_eq!(first["args"], serde_json::json!(["List a", "a"]));
+        assert_eq!(first["output"], "a");
+    }
+}
@@ -1,10 +1,144 @@
 (() => {
   let sidebar = document.getElementById("sidebar-nav");
   let searchBox = document.getElementById("module-search");
+  let searchResults = document.getElementById("search-results");
+  let searchIndex = window.rocSearchIndex || [];
+
+  const MAX_RESULTS = 50;
+
+  // Mirrors `normalize_type` in search_index.rs: parens are dropped, so
+  // "(List U8)" and "List U8" are the same thing to search.
+  function normalizeType(text) {
+    return text.replace(/[()]/g, " ").trim().split(/\s+/).filter((word) => word !== "").join(" ");
+  }
+
+  // Type variables (like `a` or `elem`) and `*` in either the query or the
+  // indexed signature match any single word.
+  function isTypeVariable(word) {
+    return word === "*" || /^[a-z]/.test(word);
+  }
+
+  function typesMatch(query, indexed) {
+    let queryWords = query.split(" ");
+    let indexedWords = indexed.split(" ");
+
+    if (queryWords.length !== indexedWords.length) {
+      return false;
+    }
+
+    return queryWords.every((word, index) => {
+      let other = indexedWords[index];
+      return word === other || isTypeVariable(word) || isTypeVariable(other);
+    });
+  }
+
+  // Splits "Str, U8 -> List U8" into { args: ["Str", "U8"], output: "List U8" }.
+  function parseSignatureQuery(text) {
+    let arrow = text.lastIndexOf("->");
+    let args = text.slice(0, arrow).split(",").map(normalizeType).filter((arg) => arg !== "");
+    let output = normalizeType(text.slice(arrow + 2));
+
+    return { args, output };
+  }
+
+  // Every argument in the query has to match a distinct argument of the entry,
+  // in any order, and the output types have to match.
+  function signatureMatches(query, entry) {
+    if (query.output !== "" && !typesMatch(query.output, entry.output)) {
+      return false;
+    }
+
+    if (query.args.length > entry.args.length) {
+      return false;
+    }
+
+    let unused = entry.args.slice();
+
+    return query.args.every((arg) => {
+      let found = unused.findIndex((candidate) => typesMatch(arg, candidate));
+
+      if (found === -1) {
+        return false;
+      }
+
+      unused.splice(found, 1);
+      return true;
+    });
+  }
+
+  function searchIndexEntries(text) {
+    if (text.includes("->")) {
+      let query = parseSignatureQuery(text);
+
+      return searchIndex.filter((entry) => entry.kind !== "type" && entry.kind !== "ability" && signatureMatches(query, entry));
+    }
+
+    let lowerText = text.toLowerCase();
+    let exact = [];
+    let prefix = [];
+    let rest = [];
+
+    searchIndex.forEach((entry) => {
+      let name = entry.name.toLowerCase();
+      let qualified = (entry.module + "." + entry.name).toLowerCase();
+
+      if (name === lowerText || qualified === lowerText) {
+        exact.push(entry);
+      } else if (name.startsWith(lowerText) || qualified.startsWith(lowerText)) {
+        prefix.push(entry);
+      } else if (qualified.includes(lowerText) || normalizeType(entry.signature).toLowerCase().includes(lowerText)) {
+        rest.push(entry);
+      }
+    });
+
+    return exact.concat(prefix, rest);
+  }
+
+  function renderResults(text) {
+    searchResults.textContent = "";
+
+    if (text === "") {
+      searchResults.classList.add("hidden");
+      return;
+    }
+
+    let matches = searchIndexEntries(text).slice(0, MAX_RESULTS);
+
+    matches.forEach((entry) => {
+      let item = document.createElement("li");
+      let link = document.createElement("a");
+      let name = document.createElement("span");
+      let kind = document.createElement("span");
+
+      link.href = entry.url;
+
+      name.className = "search-result-name";
+      name.textContent = entry.module + "." + entry.name;
+      link.appendChild(name);
+
+      if (entry.signature !== "") {
+        let signature = document.createElement("code");
+        signature.className = "search-result-signature";
+        signature.textContent = entry.signature;
+        link.appendChild(signature);
+      }
+
+      kind.className = "search-result-kind";
+      kind.textContent = entry.kind;
+      link.appendChild(kind);
+
+      item.appendChild(link);
+      searchResults.appendChild(item);
+    });
+
+    searchResults.classList.toggle("hidden", matches.length === 0);
+  }
 
   function search() {
     let text = searchBox.value.toLowerCase(); // Search is case-insensitive.
 
+    renderResults(searchBox.value.trim());
+
     if (text === "") {
       // Un-hide everything
       sidebar.querySelectorAll(".sidebar-entry a").forEach((entry) => entry.classList.remove("hidden"));
@@ -1,10 +1,12 @@
 use clap::{Arg, Command};
-use roc_docs::generate_docs_html;
+use roc_docs::{generate_docs, DocsFormat, DEFAULT_BUILD_DIR};
 use std::fs::{self, FileType};
 use std::io;
 use std::path::{Path, PathBuf};
 
 pub const DIRECTORY_OR_FILES: &str = "DIRECTORY_OR_FILES";
+pub const FLAG_OUTPUT: &str = "output";
+pub const FLAG_FORMAT: &str = "format";
 
 fn main() -> io::Result<()> {
     let matches = Command::new("roc-docs")
@@ -16,6 +18,22 @@ fn main() -> io::Result<()> {
                 .help("The directory or files to build documentation for")
                 .allow_invalid_utf8(true),
         )
+        .arg(
+            Arg::new(FLAG_OUTPUT)
+                .long(FLAG_OUTPUT)
+                .help("The directory to write the generated documentation to")
+                .takes_value(true)
+                .default_value(DEFAULT_BUILD_DIR)
+                .allow_invalid_utf8(true),
+        )
+        .arg(
+            Arg::new(FLAG_FORMAT)
+                .long(FLAG_FORMAT)
+                .help("What to generate: html, markdown, or json")
+                .takes_value(true)
+                .possible_values(DocsFormat::NAMES)
+                .default_value("html"),
+        )
         .get_matches();
 
     let mut roc_files = Vec::new();
@@ -26,7 +44,10 @@ fn main() -> io::Result<()> {
         roc_files_recursive(os_str, metadata.file_type(), &mut roc_files)?;
     }
 
-    generate_docs_html(roc_files);
+    let build_dir = PathBuf::from(matches.value_of_os(FLAG_OUTPUT).unwrap());
+    let format: DocsFormat = matches.value_of_t(FLAG_FORMAT).unwrap();
+
+    generate_docs(roc_files, &build_dir, format);
 
     Ok(())
 }
@@ -13,7 +13,16 @@ const DYMAMIC_SECTION: usize = 4;
 
 pub fn generate(target: &Triple, custom_names: &[String]) -> object::read::Result<Vec<u8>> {
     match target.binary_format {
-        target_lexicon::BinaryFormat::Elf => create_dylib_elf64(DUMMY_ELF64, custom_names),
+        target_lexicon::BinaryFormat::Elf => {
+            // The dummy library contains no code, so only the machine needs to match the target.
+            let e_machine = match target.architecture {
+                target_lexicon::Architecture::X86_64 => elf::EM_X86_64,
+                target_lexicon::Architecture::Aarch64(_) => elf::EM_AARCH64,
+                other => unimplemented!("elf dylib creation for {:?}", other),
+            };
+
+            create_dylib_elf64(DUMMY_ELF64, e_machine, custom_names)
+        }
         target_lexicon::BinaryFormat::Macho => todo!("macho dylib creation"),
         target_lexicon::BinaryFormat::Coff => todo!("coff dylib creation"),
         other => unimplemented!("dylib creation for {:?}", other),
@@ -145,7 +154,11 @@ pub const fn round_up_to_alignment(width: usize, alignment: usize) -> usize {
     }
 }
 
-fn create_dylib_elf64(in_data: &[u8], custom_names: &[String]) -> object::read::Result<Vec<u8>> {
+fn create_dylib_elf64(
+    in_data: &[u8],
+    e_machine: u16,
+    custom_names: &[String],
+) -> object::read::Result<Vec<u8>> {
     let in_elf: &elf::FileHeader64<Endianness> = elf::FileHeader64::parse(in_data)?;
     let endian = in_elf.endian()?;
     let in_segments = in_elf.program_headers(endian, in_data)?;
@@ -304,7 +317,7 @@ fn create_dylib_elf64(in_data: &[u8], custom_names: &[String]) -> object::read::
             os_abi: in_elf.e_ident().os_abi,
             abi_version: in_elf.e_ident().abi_version,
             e_type: in_elf.e_type(endian),
-            e_machine: in_elf.e_machine(endian),
+            e_machine,
             e_entry: in_elf.e_entry(endian),
             e_flags: in_elf.e_flags(endian),
         })
@@ -5,9 +5,9 @@ use object::{elf, endian, macho};
 use object::{
     CompressedFileRange, CompressionFormat, LittleEndian, NativeEndian, Object, ObjectSection,
     ObjectSymbol, RelocationKind, RelocationTarget, Section, SectionIndex, SectionKind, Symbol,
-    SymbolIndex, SymbolSection,
+    SymbolIndex, SymbolKind, SymbolSection,
 };
-use roc_build::link::{rebuild_host, LinkType};
+use roc_build::link::{link, rebuild_host, LinkType};
 use roc_collections::all::MutMap;
 use roc_error_macros::{internal_error, user_error};
 use roc_mono::ir::OptLevel;
@@ -18,20 +18,81 @@ use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
 use std::mem;
 use std::os::raw::c_char;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use target_lexicon::Triple;
 
 mod generate_dylib;
+mod linker_error;
 mod metadata;
 use metadata::VirtualOffset;
 
+pub use linker_error::{LinkerError, LinkerResult};
+
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
 
 // TODO: Analyze if this offset is always correct.
 const PLT_ADDRESS_OFFSET: u64 = 0x10;
 const STUB_ADDRESS_OFFSET: u64 = 0x06;
 
+// On aarch64 the first PLT entry (PLT0) is twice the size of the others.
+const AARCH64_PLT0_SIZE: u64 = 0x20;
+const AARCH64_NOP: u32 = 0xd503_201f;
+
+/// The instruction sets we know how to perform surgery on for ELF executables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ElfArch {
+    X86_64,
+    Aarch64,
+}
+
+impl ElfArch {
+    fn from_triple(target: &Triple) -> Self {
+        match target.architecture {
+            target_lexicon::Architecture::X86_64 => ElfArch::X86_64,
+            target_lexicon::Architecture::Aarch64(_) => ElfArch::Aarch64,
+            other => internal_error!("Surgical linking does not support {:?} elf files", other),
+        }
+    }
+
+    fn from_machine(e_machine: u16) -> Self {
+        match e_machine {
+            elf::EM_X86_64 => ElfArch::X86_64,
+            elf::EM_AARCH64 => ElfArch::Aarch64,
+            other => internal_error!("Surgical linking does not support elf machine {}", other),
+        }
+    }
+
+    /// The address of the PLT entry that belongs to the `index`th jump slot relocation.
+    fn plt_entry_address(self, plt_start: u64, index: u64) -> u64 {
+        match self {
+            ElfArch::X86_64 => (index + 1) * PLT_ADDRESS_OFFSET + plt_start,
+            ElfArch::Aarch64 => AARCH64_PLT0_SIZE + index * PLT_ADDRESS_OFFSET + plt_start,
+        }
+    }
+
+    fn jump_slot_reloc(self) -> u32 {
+        match self {
+            ElfArch::X86_64 => elf::R_X86_64_JUMP_SLOT,
+            ElfArch::Aarch64 => elf::R_AARCH64_JUMP_SLOT,
+        }
+    }
+
+    fn glob_dat_reloc(self) -> u32 {
+        match self {
+            ElfArch::X86_64 => elf::R_X86_64_GLOB_DAT,
+            ElfArch::Aarch64 => elf::R_AARCH64_GLOB_DAT,
+        }
+    }
+
+    fn relative_reloc(self) -> u32 {
+        match self {
+            ElfArch::X86_64 => elf::R_X86_64_RELATIVE,
+            ElfArch::Aarch64 => elf::R_AARCH64_RELATIVE,
+        }
+    }
+}
+
 struct ElfDynamicDeps {
     got_app_syms: Vec<(String, usize)>,
     got_sections: Vec<(usize, usize)>,
@@ -54,9 +115,10 @@ pub fn supported(link_type: LinkType, target: &Triple) -> bool {
     matches!(
         (link_type, target),
         (
-            LinkType::Executable,
+            LinkType::Executable | LinkType::Dylib,
             Triple {
-                architecture: target_lexicon::Architecture::X86_64,
+                architecture: target_lexicon::Architecture::X86_64
+                    | target_lexicon::Architecture::Aarch64(_),
                 operating_system: target_lexicon::OperatingSystem::Linux,
                 binary_format: target_lexicon::BinaryFormat::Elf,
                 ..
@@ -76,12 +138,18 @@ pub fn build_and_preprocess_host(
     preprocessed_host_path: &Path,
     exposed_to_host: Vec<String>,
     exported_closure_types: Vec<String>,
-) {
+    link_type: LinkType,
+) -> LinkerResult<()> {
     let dummy_lib = host_input_path.with_file_name("libapp.so");
     generate_dynamic_lib(target, exposed_to_host, exported_closure_types, &dummy_lib);
-    rebuild_host(opt_level, target, host_input_path, Some(&dummy_lib));
-    let dynhost = host_input_path.with_file_name("dynhost");
-    let metadata = host_input_path.with_file_name("metadata");
+    match link_type {
+        LinkType::Dylib => rebuild_host_lib(opt_level, target, host_input_path, &dummy_lib),
+        LinkType::Executable | LinkType::None => {
+            rebuild_host(opt_level, target, host_input_path, Some(&dummy_lib));
+        }
+    };
+    let dynhost = dynhost_path(host_input_path, link_type);
+    let metadata = metadata_path(host_input_path, link_type);
     // let prehost = host_input_path.with_file_name("preprocessedhost");
 
     preprocess(
@@ -95,21 +163,162 @@ pub fn build_and_preprocess_host(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn link_preprocessed_host(
+    opt_level: OptLevel,
     target: &Triple,
     host_input_path: &Path,
+    preprocessed_host_path: &Path,
+    exposed_to_host: Vec<String>,
+    exported_closure_types: Vec<String>,
     roc_app_obj: &Path,
     binary_path: &Path,
+    link_type: LinkType,
+) -> LinkerResult<()> {
+    let metadata = metadata_path(host_input_path, link_type);
+    let run_surgery = || {
+        // A host rebuilt since it was preprocessed can have the same length as before,
+        // so make sure it's the very host the metadata describes.
+        check_host_hash(metadata.to_str().unwrap(), binary_path.to_str().unwrap())?;
+
+        surgery(
+            roc_app_obj.to_str().unwrap(),
+            metadata.to_str().unwrap(),
+            binary_path.to_str().unwrap(),
+            false,
+            false,
+            target,
+        )
+    };
+
+    match run_surgery() {
+        Err(err @ (LinkerError::MetadataOutdated { .. } | LinkerError::HostMismatch { .. })) => {
+            // The metadata is stale. The dynamically linked host left over from the last
+            // rebuild may be just as stale, so if we have the host's source, rebuild it
+            // from that and preprocess it again.
+            if !roc_build::link::host_source_exists(host_input_path) {
+                return Err(err);
+            }
+
+            build_and_preprocess_host(
+                opt_level,
+                target,
+                host_input_path,
+                preprocessed_host_path,
+                exposed_to_host,
+                exported_closure_types,
+                link_type,
+            )?;
+            fs::copy(preprocessed_host_path, binary_path)
+                .unwrap_or_else(|e| internal_error!("{}", e));
+
+            run_surgery()
+        }
+        result => result,
+    }
+}
+
+/// Renders the metadata of a preprocessed host in a human readable form.
+///
+/// `path` can be the metadata file itself, or the platform directory containing it.
+/// A directory can have metadata for both an executable and a shared library host,
+/// in which case both get rendered.
+pub fn inspect_metadata(path: &Path) -> LinkerResult<String> {
+    if !path.is_dir() {
+        return describe_metadata(path);
+    }
+
+    // The metadata goes next to the host, so any host file name in the directory will do.
+    let metadata_files: Vec<PathBuf> = [LinkType::Executable, LinkType::Dylib]
+        .into_iter()
+        .map(|link_type| metadata_path(&path.join("host"), link_type))
+        .filter(|metadata| metadata.exists())
+        .collect();
+
+    match metadata_files.as_slice() {
+        // Report the metadata an executable host would have, as not being there.
+        [] => describe_metadata(&metadata_path(&path.join("host"), LinkType::Executable)),
+        [metadata] => describe_metadata(metadata),
+        _ => {
+            let mut descriptions = Vec::with_capacity(metadata_files.len());
+
+            for metadata in metadata_files.iter() {
+                descriptions.push(format!(
+                    "{}:\n\n{}",
+                    metadata.display(),
+                    describe_metadata(metadata)?
+                ));
+            }
+
+            Ok(descriptions.join("\n\n"))
+        }
+    }
+}
+
+fn describe_metadata(metadata: &Path) -> LinkerResult<String> {
+    let (header, md) = load_metadata(metadata.to_str().unwrap())?;
+
+    Ok(format!(
+        "Metadata format version {}, describing a host with hash {:#018x}\n\n{}",
+        header.version, header.host_hash, md
+    ))
+}
+
+/// Executable and shared library hosts are preprocessed separately, so they need their own metadata.
+fn metadata_path(host_input_path: &Path, link_type: LinkType) -> PathBuf {
+    match link_type {
+        LinkType::Dylib => host_input_path.with_file_name("metadata_lib"),
+        LinkType::Executable | LinkType::None => host_input_path.with_file_name("metadata"),
+    }
+}
+
+/// The host linked against the dummy app library, which is what gets preprocessed.
+fn dynhost_path(host_input_path: &Path, link_type: LinkType) -> PathBuf {
+    match link_type {
+        LinkType::Dylib => host_input_path.with_file_name("dynhost_lib"),
+        LinkType::Executable | LinkType::None => host_input_path.with_file_name("dynhost"),
+    }
+}
+
+/// Shared library hosts get a soname this long, which surgery overwrites with the name of
+/// the library it produces. No file name is longer than this.
+const SONAME_CAPACITY: usize = 255;
+
+/// Builds the host as a plain object file and then links it against the dummy app library,
+/// producing a shared library whose calls into the app go through the plt just like an executable's.
+fn rebuild_host_lib(
+    opt_level: OptLevel,
+    target: &Triple,
+    host_input_path: &Path,
+    dummy_lib: &Path,
 ) {
-    let metadata = host_input_path.with_file_name("metadata");
-    surgery(
-        roc_app_obj.to_str().unwrap(),
-        metadata.to_str().unwrap(),
-        binary_path.to_str().unwrap(),
-        false,
-        false,
+    let host_dest = rebuild_host(opt_level, target, host_input_path, None);
+    let inputs = [host_dest.to_str().unwrap(), dummy_lib.to_str().unwrap()];
+
+    // The linker makes the file name the soname, so pad it out to leave room for any name.
+    let placeholder = format!("{:_<1$}", "dynhost", SONAME_CAPACITY - ".so".len());
+    let (mut child, dynhost) = link(
         target,
+        host_input_path.with_file_name(placeholder),
+        &inputs,
+        LinkType::Dylib,
     )
+    .unwrap_or_else(|e| internal_error!("Failed to spawn the linker for the host: {}", e));
+
+    let exit_status = child
+        .wait()
+        .unwrap_or_else(|e| internal_error!("Failed to link the host: {}", e));
+
+    if !exit_status.success() {
+        internal_error!(
+            "Linking the host as a shared library failed: {}",
+            exit_status
+        );
+    }
+
+    // The system linker picks its own file extension for shared libraries.
+    fs::rename(dynhost, dynhost_path(host_input_path, LinkType::Dylib))
+        .unwrap_or_else(|e| internal_error!("{}", e));
 }
 
 fn generate_dynamic_lib(
@@ -152,7 +361,7 @@ pub fn preprocess(
     shared_lib: &Path,
     verbose: bool,
     time: bool,
-) {
+) -> LinkerResult<()> {
     if verbose {
         println!("Targeting: {}", target);
     }
@@ -263,19 +472,24 @@ pub fn preprocess(
     })
     .collect();
 
+    if app_syms.is_empty() {
+        return Err(LinkerError::NoAppFunctions);
+    }
+
     let mut app_func_addresses: MutMap<u64, &str> = MutMap::default();
     let mut macho_load_so_offset = None;
 
     match target.binary_format {
         target_lexicon::BinaryFormat::Elf => {
+            let elf_arch = ElfArch::from_triple(target);
             let plt_relocs = (match exec_obj.dynamic_relocations() {
                 Some(relocs) => relocs,
                 None => {
-                    internal_error!("Executable does not have any dynamic relocations. No work to do. Probably an invalid input.");
+                    return Err(LinkerError::NoAppFunctions);
                 }
             })
             .filter_map(|(_, reloc)| {
-                if let RelocationKind::Elf(7) = reloc.kind() {
+                if reloc.kind() == RelocationKind::Elf(elf_arch.jump_slot_reloc()) {
                     Some(reloc)
                 } else {
                     None
@@ -284,8 +498,8 @@ pub fn preprocess(
             for (i, reloc) in plt_relocs.enumerate() {
                 for symbol in app_syms.iter() {
                     if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
-                        let func_address = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_address;
-                        let func_offset = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_offset;
+                        let func_address = elf_arch.plt_entry_address(plt_address, i as u64);
+                        let func_offset = elf_arch.plt_entry_address(plt_offset, i as u64);
                         app_func_addresses.insert(func_address, symbol.name().unwrap());
                         md.plt_addresses.insert(
                             symbol.name().unwrap().to_string(),
@@ -499,6 +713,72 @@ pub fn preprocess(
                 internal_error!("Failed to load text section, {:+x?}: {}", sec, err);
             }
         };
+
+        if let target_lexicon::Architecture::Aarch64(_) = target.architecture {
+            // aarch64 instructions are always 4 bytes, so there is nothing to decode.
+            // We only look at direct branches (B and BL); anything else goes through the plt.
+            let mut functions = Vec::new();
+            let mut mapping_symbols = Vec::new();
+            for sym in exec_obj
+                .symbols()
+                .filter(|sym| sym.section_index() == Some(sec.index()))
+            {
+                match sym.name().unwrap_or_default().split('.').next() {
+                    Some("$d") => mapping_symbols.push((sym.address(), true)),
+                    Some("$x") => mapping_symbols.push((sym.address(), false)),
+                    _ if sym.kind() == SymbolKind::Text && sym.size() > 0 => {
+                        functions.push((sym.address(), sym.address() + sym.size()));
+                    }
+                    _ => {}
+                }
+            }
+
+            let section_end = sec.address() + data.len() as u64;
+            let inst_addresses = aarch64_code_ranges(functions, mapping_symbols)
+                .into_iter()
+                .flat_map(|(start, end)| (start..end).step_by(4))
+                .take_while(|address| address + 4 <= section_end);
+
+            for inst_address in inst_addresses {
+                let inst = load_aarch64_inst(&data, (inst_address - sec.address()) as usize);
+                let target = match aarch64_branch_target(inst, inst_address) {
+                    Some(target) => target,
+                    None => continue,
+                };
+
+                if let Some(func_name) = app_func_addresses.get(&target) {
+                    if compressed {
+                        internal_error!(
+                            "Surgical linking does not work with compressed text sections: {:+x?}",
+                            sec
+                        );
+                    }
+
+                    let offset = inst_address - sec.address() + file_offset;
+                    if verbose {
+                        println!(
+                            "Found branch from {:+x} to {:+x}({})",
+                            inst_address, target, func_name
+                        );
+                        println!(
+                            "\tNeed to surgically replace the branch at file offset {:+x}",
+                            offset,
+                        );
+                    }
+                    // aarch64 branches are relative to the branch itself rather than the next instruction.
+                    md.surgeries
+                        .get_mut(*func_name)
+                        .unwrap()
+                        .push(metadata::SurgeryEntry {
+                            file_offset: offset,
+                            virtual_offset: VirtualOffset::Relative(inst_address),
+                            size: 4,
+                        });
+                }
+            }
+            continue;
+        }
+
         let mut decoder = Decoder::with_ip(64, &data, sec.address(), DecoderOptions::NONE);
         let mut inst = Instruction::default();
 
@@ -602,6 +882,7 @@ pub fn preprocess(
         {
             target_lexicon::Endianness::Little => {
                 let scanning_dynamic_deps_start = Instant::now();
+                let elf_arch = ElfArch::from_triple(target);
 
                 let ElfDynamicDeps {
                     got_app_syms,
@@ -609,8 +890,8 @@ pub fn preprocess(
                     dynamic_lib_count,
                     shared_lib_index,
                 } = scan_elf_dynamic_deps(
-                    &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
-                );
+                    &exec_obj, &mut md, &app_syms, shared_lib, exec_data, elf_arch, verbose,
+                )?;
 
                 scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
 
@@ -625,6 +906,7 @@ pub fn preprocess(
                     &got_sections,
                     dynamic_lib_count,
                     shared_lib_index,
+                    elf_arch,
                     verbose,
                 )
             }
@@ -660,10 +942,9 @@ pub fn preprocess(
                     let macho_load_so_offset = match macho_load_so_offset {
                         Some(offset) => offset,
                         None => {
-                            internal_error!(
-                                "Host does not link library `{}`!",
-                                shared_lib.display()
-                            );
+                            return Err(LinkerError::SharedLibNotLinked {
+                                shared_lib: shared_lib.display().to_string(),
+                            });
                         }
                     };
 
@@ -714,9 +995,13 @@ pub fn preprocess(
     let saving_metadata_start = Instant::now();
     // This block ensure that the metadata is fully written and timed before continuing.
     {
+        let header = metadata::Header::new(metadata::hash_host(&out_mmap));
         let output =
             fs::File::create(metadata_filename).unwrap_or_else(|e| internal_error!("{}", e));
-        let output = BufWriter::new(output);
+        let mut output = BufWriter::new(output);
+        if let Err(err) = serialize_into(&mut output, &header) {
+            internal_error!("Failed to serialize metadata header: {}", err);
+        };
         if let Err(err) = serialize_into(output, &md) {
             internal_error!("Failed to serialize metadata: {}", err);
         };
@@ -760,6 +1045,8 @@ pub fn preprocess(
         );
         report_timing("Total", total_duration);
     }
+
+    Ok(())
 }
 
 fn gen_macho_le(
@@ -1326,6 +1613,7 @@ fn gen_elf_le(
     got_sections: &[(usize, usize)],
     dynamic_lib_count: usize,
     shared_lib_index: usize,
+    elf_arch: ElfArch,
     verbose: bool,
 ) -> (MmapMut, File) {
     let exec_header = load_struct_inplace::<elf::FileHeader64<LittleEndian>>(exec_data, 0);
@@ -1505,7 +1793,7 @@ fn gen_elf_le(
                 rel.r_offset = endian::U64::new(LittleEndian, r_offset + md.added_byte_count);
                 // Deal with potential adjusts to absolute jumps.
                 // TODO: Verify other relocation types.
-                if rel.r_type(LittleEndian, false) == elf::R_X86_64_RELATIVE {
+                if rel.r_type(LittleEndian, false) == elf_arch.relative_reloc() {
                     let r_addend = rel.r_addend.get(LittleEndian);
                     rel.r_addend
                         .set(LittleEndian, r_addend + md.added_byte_count as i64);
@@ -1513,11 +1801,11 @@ fn gen_elf_le(
             }
             // If the relocation goes to a roc function, we need to surgically link it and change it to relative.
             let r_type = rel.r_type(NativeEndian, false);
-            if r_type == elf::R_X86_64_GLOB_DAT {
+            if r_type == elf_arch.glob_dat_reloc() {
                 let r_sym = rel.r_sym(NativeEndian, false);
                 for (name, index) in got_app_syms.iter() {
                     if *index as u32 == r_sym {
-                        rel.set_r_info(LittleEndian, false, 0, elf::R_X86_64_RELATIVE);
+                        rel.set_r_info(LittleEndian, false, 0, elf_arch.relative_reloc());
                         let addend_addr = sec_offset as usize
                             + i * mem::size_of::<elf::Rela64<LittleEndian>>()
                             // This 16 skips the first 2 fields and gets to the addend field.
@@ -1662,8 +1950,9 @@ fn scan_elf_dynamic_deps(
     app_syms: &[Symbol],
     shared_lib: &Path,
     exec_data: &[u8],
+    elf_arch: ElfArch,
     verbose: bool,
-) -> ElfDynamicDeps {
+) -> LinkerResult<ElfDynamicDeps> {
     let dyn_sec = match exec_obj.section_by_name(".dynamic") {
         Some(sec) => sec,
         None => {
@@ -1707,16 +1996,22 @@ fn scan_elf_dynamic_deps(
             )
             .unwrap(),
         );
+        let dynstr_off = u64::from_le_bytes(
+            <[u8; 8]>::try_from(
+                &exec_data
+                    [dyn_offset + dyn_lib_index * 16 + 8..dyn_offset + dyn_lib_index * 16 + 16],
+            )
+            .unwrap(),
+        ) as usize;
         if dyn_tag == 0 {
             break;
+        } else if dyn_tag == elf::DT_SONAME as u64 {
+            // Surgery replaces this with the name of the library it produces.
+            let c_buf: *const c_char = dynstr_data[dynstr_off..].as_ptr() as *const i8;
+            let len = unsafe { CStr::from_ptr(c_buf) }.to_bytes().len();
+            let (dynstr_file_offset, _) = dynstr_sec.file_range().unwrap();
+            md.soname = Some((dynstr_file_offset + dynstr_off as u64, len as u64));
         } else if dyn_tag == 1 {
-            let dynstr_off = u64::from_le_bytes(
-                <[u8; 8]>::try_from(
-                    &exec_data
-                        [dyn_offset + dyn_lib_index * 16 + 8..dyn_offset + dyn_lib_index * 16 + 16],
-                )
-                .unwrap(),
-            ) as usize;
             let c_buf: *const c_char = dynstr_data[dynstr_off..].as_ptr() as *const i8;
             let c_str = unsafe { CStr::from_ptr(c_buf) }.to_str().unwrap();
             if Path::new(c_str).file_name() == shared_lib_filename {
@@ -1734,10 +2029,14 @@ fn scan_elf_dynamic_deps(
     }
     let dynamic_lib_count = dyn_lib_index as usize;
 
-    if shared_lib_index.is_none() {
-        panic!("Shared lib not found as a dependency of the executable");
-    }
-    let shared_lib_index = shared_lib_index.unwrap();
+    let shared_lib_index = match shared_lib_index {
+        Some(index) => index,
+        None => {
+            return Err(LinkerError::SharedLibNotLinked {
+                shared_lib: shared_lib.display().to_string(),
+            });
+        }
+    };
 
     let symtab_sec = match exec_obj.section_by_name(".symtab") {
         Some(sec) => sec,
@@ -1799,12 +2098,11 @@ fn scan_elf_dynamic_deps(
     let got_app_syms: Vec<(String, usize)> = (match exec_obj.dynamic_relocations() {
         Some(relocs) => relocs,
         None => {
-            eprintln!("Executable never calls any application functions.");
-            panic!("No work to do. Probably an invalid input.");
+            return Err(LinkerError::NoAppFunctions);
         }
     })
     .filter_map(|(_, reloc)| {
-        if let RelocationKind::Elf(6) = reloc.kind() {
+        if reloc.kind() == RelocationKind::Elf(elf_arch.glob_dat_reloc()) {
             for symbol in app_syms.iter() {
                 if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                     return Some((symbol.name().unwrap().to_string(), symbol.index().0));
@@ -1815,12 +2113,40 @@ fn scan_elf_dynamic_deps(
     })
     .collect();
 
-    ElfDynamicDeps {
+    Ok(ElfDynamicDeps {
         got_app_syms,
         got_sections,
         dynamic_lib_count,
         shared_lib_index,
+    })
+}
+
+/// Overwrite the placeholder soname of a shared library host with the output's file name,
+/// since that's the name anything linking against the library will look for at runtime.
+fn set_soname(
+    exec_mmap: &mut MmapMut,
+    soname_offset: usize,
+    soname_len: usize,
+    out_filename: &str,
+) -> LinkerResult<()> {
+    let name = Path::new(out_filename)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+
+    if name.len() > soname_len {
+        return Err(LinkerError::SonameTooLong {
+            name,
+            capacity: soname_len,
+        });
     }
+
+    let soname = &mut exec_mmap[soname_offset..soname_offset + soname_len];
+    soname.fill(0);
+    soname[..name.len()].copy_from_slice(name.as_bytes());
+
+    Ok(())
 }
 
 pub fn surgery(
@@ -1830,19 +2156,21 @@ pub fn surgery(
     verbose: bool,
     time: bool,
     target: &Triple,
-) {
+) -> LinkerResult<()> {
     let total_start = Instant::now();
     let loading_metadata_start = total_start;
-    let md: metadata::Metadata = {
-        let input = fs::File::open(metadata_filename).unwrap_or_else(|e| internal_error!("{}", e));
-        let input = BufReader::new(input);
-        match deserialize_from(input) {
-            Ok(data) => data,
-            Err(err) => {
-                internal_error!("Failed to deserialize metadata: {}", err);
-            }
-        }
-    };
+    let (_, md) = load_metadata(metadata_filename)?;
+    // Surgery writes past the end of the preprocessed host, so at least make sure it's
+    // that long. `link_preprocessed_host` also checks its hash.
+    let host_len = fs::metadata(out_filename)
+        .unwrap_or_else(|e| internal_error!("{}", e))
+        .len();
+    if host_len != md.exec_len {
+        return Err(LinkerError::HostMismatch {
+            metadata_path: metadata_filename.to_string(),
+            host_path: out_filename.to_string(),
+        });
+    }
     let loading_metadata_duration = loading_metadata_start.elapsed();
 
     let app_parsing_start = Instant::now();
@@ -1876,9 +2204,18 @@ pub fn surgery(
     let out_gen_start = Instant::now();
 
     let mut offset = 0;
-    let output = match target.binary_format {
+    match target.binary_format {
         target_lexicon::BinaryFormat::Elf => {
-            surgery_elf(verbose, &md, &mut exec_mmap, &mut offset, app_obj)
+            surgery_elf(verbose, &md, &mut exec_mmap, &mut offset, app_obj)?;
+
+            if let Some((soname_offset, soname_len)) = md.soname {
+                set_soname(
+                    &mut exec_mmap,
+                    (soname_offset + md.added_byte_count) as usize,
+                    soname_len as usize,
+                    out_filename,
+                )?;
+            }
         }
         target_lexicon::BinaryFormat::Macho => surgery_macho(
             app_filename,
@@ -1946,7 +2283,61 @@ pub fn surgery(
         report_timing("Total", total_duration);
     }
 
-    output
+    Ok(())
+}
+
+/// Fails with `HostMismatch` unless the host hashes to what the metadata says the
+/// preprocessed host did.
+fn check_host_hash(metadata_filename: &str, host_filename: &str) -> LinkerResult<()> {
+    let (header, _) = load_metadata(metadata_filename)?;
+    let host_data = fs::read(host_filename).unwrap_or_else(|e| internal_error!("{}", e));
+
+    if metadata::hash_host(&host_data) != header.host_hash {
+        return Err(LinkerError::HostMismatch {
+            metadata_path: metadata_filename.to_string(),
+            host_path: host_filename.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn load_metadata(metadata_filename: &str) -> LinkerResult<(metadata::Header, metadata::Metadata)> {
+    let unreadable = |reason: String| LinkerError::MetadataUnreadable {
+        path: metadata_filename.to_string(),
+        reason,
+    };
+    let outdated = |reason: String| LinkerError::MetadataOutdated {
+        path: metadata_filename.to_string(),
+        reason,
+    };
+
+    let input = fs::File::open(metadata_filename).map_err(|e| unreadable(e.to_string()))?;
+    let mut input = BufReader::new(input);
+
+    // Metadata from before the header existed starts with the app function list instead,
+    // so it fails the magic check (or doesn't even have enough bytes to be read as a header).
+    let header: metadata::Header = match deserialize_from(&mut input) {
+        Ok(header) if header.magic == metadata::MAGIC => header,
+        _ => {
+            return Err(outdated(
+                "it was written by a compiler from before the metadata format was versioned"
+                    .to_string(),
+            ))
+        }
+    };
+
+    if header.version != metadata::VERSION {
+        return Err(outdated(format!(
+            "it uses metadata format version {}, but this compiler uses version {}",
+            header.version,
+            metadata::VERSION
+        )));
+    }
+
+    let md = deserialize_from(input).map_err(|e| unreadable(e.to_string()))?;
+
+    Ok((header, md))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -2377,13 +2768,14 @@ pub fn surgery_elf(
     exec_mmap: &mut MmapMut,
     offset_ref: &mut usize, // TODO return this instead of taking a mutable reference to it
     app_obj: object::File,
-) {
+) -> LinkerResult<()> {
     let elf64 = exec_mmap[4] == 2;
     let litte_endian = exec_mmap[5] == 1;
     if !elf64 || !litte_endian {
         internal_error!("Only 64bit little endian elf currently supported for surgery");
     }
     let exec_header = load_struct_inplace::<elf::FileHeader64<LittleEndian>>(exec_mmap, 0);
+    let elf_arch = ElfArch::from_machine(exec_header.e_machine.get(NativeEndian));
 
     let ph_offset = exec_header.e_phoff.get(NativeEndian);
     let ph_ent_size = exec_header.e_phentsize.get(NativeEndian);
@@ -2396,6 +2788,7 @@ pub fn surgery_elf(
         println!();
         println!("Is Elf64: {}", elf64);
         println!("Is Little Endian: {}", litte_endian);
+        println!("Architecture: {:?}", elf_arch);
         println!("PH Offset: {:+x}", ph_offset);
         println!("PH Entry Size: {}", ph_ent_size);
         println!("PH Entry Count: {}", ph_num);
@@ -2440,11 +2833,39 @@ pub fn surgery_elf(
 
     // TODO: In the future Roc may use a data section to store memoized toplevel thunks
     // in development builds for caching the results of top-level constants
+    // Constant data holding addresses, like tables of function pointers, goes in .data.rel.ro.
     let rodata_sections: Vec<Section> = app_obj
         .sections()
-        .filter(|sec| sec.name().unwrap_or_default().starts_with(".rodata"))
+        .filter(|sec| {
+            let name = sec.name().unwrap_or_default();
+            name.starts_with(".rodata") || name.starts_with(".data.rel.ro")
+        })
         .collect();
 
+    // Absolute addresses in the app's data only become correct once the loader adds the address
+    // the host was loaded at, so each one needs a dynamic relocation. The loader only reads one
+    // table of those, so the host's table gets copied to the start of the new segment and the
+    // app's relocations are added after it.
+    let app_dynamic_reloc_count = rodata_sections
+        .iter()
+        .flat_map(|sec| sec.relocations())
+        .filter(|(_, rel)| rel.kind() == RelocationKind::Absolute)
+        .count();
+    let host_relocs = if app_dynamic_reloc_count > 0 {
+        host_dynamic_relocations(exec_mmap, md, ph_offset as usize, ph_num as usize)
+    } else {
+        None
+    };
+    let new_rela_offset = offset;
+    let new_rela_vaddr = virt_offset;
+    let mut app_dynamic_relocs: Vec<(u64, i64)> = Vec::with_capacity(app_dynamic_reloc_count);
+    if let Some(host_relocs) = &host_relocs {
+        let table_size = host_relocs.size
+            + app_dynamic_reloc_count * mem::size_of::<elf::Rela64<LittleEndian>>();
+        offset += table_size;
+        virt_offset += table_size;
+    }
+
     // bss section is like rodata section, but it has zero file size and non-zero virtual size.
     let bss_sections: Vec<Section> = app_obj
         .sections()
@@ -2581,6 +3002,46 @@ pub fn surgery_elf(
                     if let Some(target_offset) = target_offset {
                         let virt_base = section_virtual_offset as usize + rel.0 as usize;
                         let base = section_offset as usize + rel.0 as usize;
+
+                        if rel.1.kind() == RelocationKind::Absolute {
+                            // Hosts are position independent, so the loader has to fix up
+                            // absolute addresses. That's only possible in data, since code
+                            // isn't writable, and only if the host has a table to add to.
+                            let is_text = sec.name().unwrap_or_default().starts_with(".text");
+                            if is_text || rel.1.size() != 64 || host_relocs.is_none() {
+                                return Err(LinkerError::AbsoluteRelocation {
+                                    name: app_symbol_name(&app_obj, index),
+                                });
+                            }
+
+                            let address = target_offset + rel.1.addend();
+                            if verbose {
+                                println!(
+                                    "\t\tDynamic relocation at: {:+x} to: {:+x}",
+                                    virt_base, address
+                                );
+                            }
+                            exec_mmap[base..base + 8].copy_from_slice(&address.to_le_bytes());
+                            app_dynamic_relocs.push((virt_base as u64, address));
+                            continue;
+                        }
+
+                        if elf_arch == ElfArch::Aarch64 {
+                            if verbose {
+                                println!(
+                                    "\t\tRelocation base location: {:+x} (virt: {:+x})",
+                                    base, virt_base
+                                );
+                            }
+                            apply_aarch64_relocation(
+                                exec_mmap,
+                                base,
+                                virt_base as i64,
+                                target_offset + rel.1.addend(),
+                                aarch64_reloc_type(&rel.1),
+                            );
+                            continue;
+                        }
                         let target: i64 = match rel.1.kind() {
                             RelocationKind::Relative | RelocationKind::PltRelative => {
                                 target_offset - virt_base as i64 + rel.1.addend()
@@ -2614,11 +3075,16 @@ pub fn surgery_elf(
                         // Explicitly ignore some symbols that are currently always linked.
                         continue;
                     } else {
-                        internal_error!(
-                            "Undefined Symbol in relocation, {:+x?}: {:+x?}",
-                            rel,
-                            app_obj.symbol_by_index(index)
-                        );
+                        if verbose {
+                            println!(
+                                "\t\tUndefined Symbol in relocation, {:+x?}: {:+x?}",
+                                rel,
+                                app_obj.symbol_by_index(index)
+                            );
+                        }
+                        return Err(LinkerError::UndefinedSymbol {
+                            name: app_symbol_name(&app_obj, index),
+                        });
                     }
                 }
 
@@ -2659,10 +3125,35 @@ pub fn surgery_elf(
     let new_text_section_vaddr = new_rodata_section_vaddr as u64 + new_rodata_section_size as u64;
     let new_text_section_size = new_sh_offset as u64 - new_text_section_offset as u64;
 
+    // The loader writes to data with dynamic relocations.
+    let (rodata_section_flags, rodata_segment_flags) = if app_dynamic_relocs.is_empty() {
+        (elf::SHF_ALLOC, elf::PF_R)
+    } else {
+        (elf::SHF_ALLOC | elf::SHF_WRITE, elf::PF_R | elf::PF_W)
+    };
+
+    if let Some(host_relocs) = &host_relocs {
+        // Keep the section headers pointing at the table the loader uses.
+        for section in section_headers.iter_mut() {
+            if section.sh_type.get(NativeEndian) == elf::SHT_RELA
+                && section.sh_addr.get(NativeEndian) == host_relocs.vaddr
+            {
+                section.sh_addr = endian::U64::new(LittleEndian, new_rela_vaddr as u64);
+                section.sh_offset = endian::U64::new(LittleEndian, new_rela_offset as u64);
+                section.sh_size = endian::U64::new(
+                    LittleEndian,
+                    (host_relocs.size
+                        + app_dynamic_relocs.len() * mem::size_of::<elf::Rela64<LittleEndian>>())
+                        as u64,
+                );
+            }
+        }
+    }
+
     let new_rodata_section = &mut section_headers[section_headers.len() - 2];
     new_rodata_section.sh_name = endian::U32::new(LittleEndian, 0);
     new_rodata_section.sh_type = endian::U32::new(LittleEndian, elf::SHT_PROGBITS);
-    new_rodata_section.sh_flags = endian::U64::new(LittleEndian, (elf::SHF_ALLOC) as u64);
+    new_rodata_section.sh_flags = endian::U64::new(LittleEndian, rodata_section_flags as u64);
     new_rodata_section.sh_addr = endian::U64::new(LittleEndian, new_rodata_section_vaddr as u64);
     new_rodata_section.sh_offset = endian::U64::new(LittleEndian, new_rodata_section_offset as u64);
     new_rodata_section.sh_size = endian::U64::new(LittleEndian, new_rodata_section_size);
@@ -2698,7 +3189,7 @@ pub fn surgery_elf(
     );
     let new_rodata_segment = &mut program_headers[program_headers.len() - 2];
     new_rodata_segment.p_type = endian::U32::new(LittleEndian, elf::PT_LOAD);
-    new_rodata_segment.p_flags = endian::U32::new(LittleEndian, elf::PF_R);
+    new_rodata_segment.p_flags = endian::U32::new(LittleEndian, rodata_segment_flags);
     new_rodata_segment.p_offset = endian::U64::new(LittleEndian, new_rodata_section_offset as u64);
     new_rodata_segment.p_vaddr = endian::U64::new(LittleEndian, new_rodata_section_vaddr as u64);
     new_rodata_segment.p_paddr = endian::U64::new(LittleEndian, new_rodata_section_vaddr as u64);
@@ -2723,7 +3214,19 @@ pub fn surgery_elf(
         let func_virt_offset = match app_func_vaddr_map.get(func_name) {
             Some(offset) => *offset as u64,
             None => {
-                internal_error!("Function, {}, was not defined by the app", &func_name);
+                let mut app_defines: Vec<String> = symbols
+                    .iter()
+                    .filter(|sym| sym.is_definition())
+                    .filter_map(|sym| sym.name().ok())
+                    .filter(|name| name.trim_start_matches('_').starts_with("roc__"))
+                    .map(str::to_string)
+                    .collect();
+                app_defines.sort();
+
+                return Err(LinkerError::AppFunctionNotDefined {
+                    name: func_name.clone(),
+                    app_defines,
+                });
             }
         };
         if verbose {
@@ -2742,6 +3245,14 @@ pub fn surgery_elf(
                 VirtualOffset::Absolute => 0,
             };
             match s.size {
+                4 if elf_arch == ElfArch::Aarch64 => {
+                    let base = (s.file_offset + md.added_byte_count) as usize;
+                    let target = func_virt_offset as i64 - surgery_virt_offset;
+                    if verbose {
+                        println!("\tTarget Jump: {:+x}", target);
+                    }
+                    patch_aarch64_branch(exec_mmap, base, target);
+                }
                 4 => {
                     let target = (func_virt_offset as i64 - surgery_virt_offset) as i32;
                     if verbose {
@@ -2773,18 +3284,27 @@ pub fn surgery_elf(
         if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
             let plt_off = (*plt_off + md.added_byte_count) as usize;
             let plt_vaddr = *plt_vaddr + md.added_byte_count;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
-            if verbose {
-                println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
-                println!("\tTarget Jump: {:+x}", target);
-            }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
+            if elf_arch == ElfArch::Aarch64 {
+                let target = func_virt_offset as i64 - plt_vaddr as i64;
+                if verbose {
+                    println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
+                    println!("\tTarget Jump: {:+x}", target);
+                }
+                write_aarch64_plt_stub(exec_mmap, plt_off, target);
+            } else {
+                let jmp_inst_len = 5;
+                let target =
+                    (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+                if verbose {
+                    println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
+                    println!("\tTarget Jump: {:+x}", target);
+                }
+                let data = target.to_le_bytes();
+                exec_mmap[plt_off] = 0xE9;
+                exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
+                for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
+                    exec_mmap[plt_off + i] = 0x90;
+                }
             }
         }
 
@@ -2807,8 +3327,272 @@ pub fn surgery_elf(
         }
     }
 
+    // This comes last so that the host's table is copied with its surgeries applied.
+    if let Some(host_relocs) = host_relocs {
+        write_dynamic_relocations(
+            exec_mmap,
+            elf_arch,
+            md,
+            &host_relocs,
+            new_rela_offset,
+            new_rela_vaddr as u64,
+            &app_dynamic_relocs,
+        );
+    }
+
     // TODO return this instead of accepting a mutable ref!
     *offset_ref = offset;
+
+    Ok(())
+}
+
+/// Where a host's dynamic relocation table is, and which dynamic entries point at it.
+struct HostDynamicRelocations {
+    rela_entry: usize,
+    relasz_entry: usize,
+    vaddr: u64,
+    file_offset: usize,
+    size: usize,
+}
+
+/// Finds the table that `DT_RELA` points to in a host that surgery has started on, if it has one.
+fn host_dynamic_relocations(
+    exec_mmap: &[u8],
+    md: &metadata::Metadata,
+    ph_offset: usize,
+    ph_num: usize,
+) -> Option<HostDynamicRelocations> {
+    let dyn_offset = (md.dynamic_section_offset + md.added_byte_count) as usize;
+    let dyn_size = mem::size_of::<elf::Dyn64<LittleEndian>>();
+
+    let mut rela = None;
+    let mut relasz = None;
+    for i in 0.. {
+        let d =
+            load_struct_inplace::<elf::Dyn64<LittleEndian>>(exec_mmap, dyn_offset + i * dyn_size);
+        match d.d_tag.get(NativeEndian) as u32 {
+            elf::DT_NULL => break,
+            elf::DT_RELA => rela = Some((i, d.d_val.get(NativeEndian))),
+            elf::DT_RELASZ => relasz = Some((i, d.d_val.get(NativeEndian))),
+            _ => {}
+        }
+    }
+    let ((rela_entry, vaddr), (relasz_entry, size)) = (rela?, relasz?);
+
+    // The loader finds the table by address, so look for the segment that maps it.
+    let program_headers =
+        load_structs_inplace::<elf::ProgramHeader64<LittleEndian>>(exec_mmap, ph_offset, ph_num);
+    let file_offset = program_headers.iter().find_map(|ph| {
+        let p_vaddr = ph.p_vaddr.get(NativeEndian);
+        let p_filesz = ph.p_filesz.get(NativeEndian);
+        let contains_table = ph.p_type.get(NativeEndian) == elf::PT_LOAD
+            && p_vaddr <= vaddr
+            && vaddr + size <= p_vaddr + p_filesz;
+        contains_table.then(|| (vaddr - p_vaddr + ph.p_offset.get(NativeEndian)) as usize)
+    })?;
+
+    Some(HostDynamicRelocations {
+        rela_entry,
+        relasz_entry,
+        vaddr,
+        file_offset,
+        size: size as usize,
+    })
+}
+
+/// Writes the host's dynamic relocations followed by a relative relocation for each of the
+/// app's absolute addresses, and points the dynamic section at the new table.
+fn write_dynamic_relocations(
+    exec_mmap: &mut MmapMut,
+    elf_arch: ElfArch,
+    md: &metadata::Metadata,
+    host_relocs: &HostDynamicRelocations,
+    new_rela_offset: usize,
+    new_rela_vaddr: u64,
+    app_dynamic_relocs: &[(u64, i64)],
+) {
+    exec_mmap.copy_within(
+        host_relocs.file_offset..host_relocs.file_offset + host_relocs.size,
+        new_rela_offset,
+    );
+
+    let rela_size = mem::size_of::<elf::Rela64<LittleEndian>>();
+    let relocations = load_structs_inplace_mut::<elf::Rela64<LittleEndian>>(
+        exec_mmap,
+        new_rela_offset + host_relocs.size,
+        app_dynamic_relocs.len(),
+    );
+    for (rel, (vaddr, address)) in relocations.iter_mut().zip(app_dynamic_relocs) {
+        rel.r_offset = endian::U64::new(LittleEndian, *vaddr);
+        rel.set_r_info(LittleEndian, false, 0, elf_arch.relative_reloc());
+        rel.r_addend = endian::I64::new(LittleEndian, *address);
+    }
+
+    let dyn_offset = (md.dynamic_section_offset + md.added_byte_count) as usize;
+    let dyn_size = mem::size_of::<elf::Dyn64<LittleEndian>>();
+    let rela = load_struct_inplace_mut::<elf::Dyn64<LittleEndian>>(
+        exec_mmap,
+        dyn_offset + host_relocs.rela_entry * dyn_size,
+    );
+    rela.d_val = endian::U64::new(LittleEndian, new_rela_vaddr);
+    let relasz = load_struct_inplace_mut::<elf::Dyn64<LittleEndian>>(
+        exec_mmap,
+        dyn_offset + host_relocs.relasz_entry * dyn_size,
+    );
+    relasz.d_val = endian::U64::new(
+        LittleEndian,
+        (host_relocs.size + app_dynamic_relocs.len() * rela_size) as u64,
+    );
+}
+
+fn app_symbol_name(app_obj: &object::File, index: SymbolIndex) -> String {
+    app_obj
+        .symbol_by_index(index)
+        .and_then(|sym| sym.name().map(str::to_string))
+        .unwrap_or_else(|_| format!("<symbol {}>", index.0))
+}
+
+/// The parts of an aarch64 text section which hold instructions, given its functions'
+/// address ranges and its mapping symbols (`$d` starts data, `$x` starts code again).
+///
+/// Only functions are scanned, and the data inside them (like literal pools) is skipped,
+/// since a literal can look exactly like a branch. Missing a branch is fine: it still
+/// reaches the app through the plt, which gets patched too.
+fn aarch64_code_ranges(
+    mut functions: Vec<(u64, u64)>,
+    mut mapping_symbols: Vec<(u64, bool)>,
+) -> Vec<(u64, u64)> {
+    functions.sort_unstable();
+    functions.dedup();
+    mapping_symbols.sort_unstable();
+
+    let mut ranges = Vec::new();
+    for (start, end) in functions {
+        let mut code_start = Some(start);
+        for &(address, is_data) in mapping_symbols
+            .iter()
+            .filter(|(address, _)| (start..end).contains(address))
+        {
+            match (code_start, is_data) {
+                (Some(from), true) => {
+                    if from < address {
+                        ranges.push((from, address));
+                    }
+                    code_start = None;
+                }
+                (None, false) => code_start = Some(address),
+                _ => {}
+            }
+        }
+        if let Some(from) = code_start {
+            ranges.push((from, end));
+        }
+    }
+
+    ranges
+}
+
+fn load_aarch64_inst(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(<[u8; 4]>::try_from(&bytes[offset..offset + 4]).unwrap())
+}
+
+/// If `inst` is a direct B or BL instruction, returns the address it branches to.
+fn aarch64_branch_target(inst: u32, inst_address: u64) -> Option<u64> {
+    if inst & 0x7c00_0000 != 0x1400_0000 {
+        return None;
+    }
+    // Shift the 26 bit immediate to the top to sign extend it, then scale it by 4.
+    let offset = (((inst & 0x03ff_ffff) << 6) as i32 >> 4) as i64;
+    Some((inst_address as i64 + offset) as u64)
+}
+
+/// Rewrites the immediate of the B or BL instruction at `base` to jump by `offset` bytes.
+fn patch_aarch64_branch(bytes: &mut [u8], base: usize, offset: i64) {
+    if offset % 4 != 0 || !(-(1 << 27)..(1 << 27)).contains(&offset) {
+        internal_error!("aarch64 branch offset out of range: {:+x}", offset);
+    }
+    let inst = load_aarch64_inst(bytes, base);
+    let inst = (inst & 0xfc00_0000) | ((offset >> 2) as u32 & 0x03ff_ffff);
+    bytes[base..base + 4].copy_from_slice(&inst.to_le_bytes());
+}
+
+/// Replaces a 16 byte aarch64 plt entry with a direct branch followed by nops.
+fn write_aarch64_plt_stub(bytes: &mut [u8], plt_off: usize, offset: i64) {
+    // Write a B instruction so that patch_aarch64_branch only has to fill in the immediate.
+    bytes[plt_off..plt_off + 4].copy_from_slice(&0x1400_0000u32.to_le_bytes());
+    patch_aarch64_branch(bytes, plt_off, offset);
+    for i in (4..PLT_ADDRESS_OFFSET as usize).step_by(4) {
+        bytes[plt_off + i..plt_off + i + 4].copy_from_slice(&AARCH64_NOP.to_le_bytes());
+    }
+}
+
+/// The raw ELF relocation type, undoing the generic mapping done by the object crate.
+fn aarch64_reloc_type(rel: &object::Relocation) -> u32 {
+    match (rel.kind(), rel.size()) {
+        (RelocationKind::PltRelative, 26) => elf::R_AARCH64_CALL26,
+        (RelocationKind::Relative, 32) => elf::R_AARCH64_PREL32,
+        (RelocationKind::Relative, 64) => elf::R_AARCH64_PREL64,
+        (RelocationKind::Elf(r_type), _) => r_type,
+        (kind, size) => {
+            internal_error!(
+                "Relocation Kind not yet support for aarch64: {:?} ({} bits)",
+                kind,
+                size
+            );
+        }
+    }
+}
+
+/// Applies an aarch64 relocation at file offset `base`.
+/// `pc` is the virtual address being relocated and `target` is the symbol address plus addend.
+/// Absolute relocations like `R_AARCH64_ABS64` never get here: since the host is position
+/// independent, surgery turns them into dynamic relocations or rejects them.
+fn apply_aarch64_relocation(bytes: &mut [u8], base: usize, pc: i64, target: i64, r_type: u32) {
+    // Most loads and stores scale their 12 bit immediate by the access size.
+    let lo12_shift = match r_type {
+        elf::R_AARCH64_ADD_ABS_LO12_NC | elf::R_AARCH64_LDST8_ABS_LO12_NC => Some(0),
+        elf::R_AARCH64_LDST16_ABS_LO12_NC => Some(1),
+        elf::R_AARCH64_LDST32_ABS_LO12_NC => Some(2),
+        elf::R_AARCH64_LDST64_ABS_LO12_NC => Some(3),
+        elf::R_AARCH64_LDST128_ABS_LO12_NC => Some(4),
+        _ => None,
+    };
+    if let Some(shift) = lo12_shift {
+        let imm12 = ((target as u32) & 0xfff) >> shift;
+        let inst = (load_aarch64_inst(bytes, base) & !(0xfff << 10)) | (imm12 << 10);
+        bytes[base..base + 4].copy_from_slice(&inst.to_le_bytes());
+        return;
+    }
+
+    match r_type {
+        elf::R_AARCH64_CALL26 | elf::R_AARCH64_JUMP26 => {
+            patch_aarch64_branch(bytes, base, target - pc);
+        }
+        elf::R_AARCH64_ADR_PREL_PG_HI21 => {
+            let page_delta = ((target & !0xfff) - (pc & !0xfff)) >> 12;
+            if !(-(1 << 20)..(1 << 20)).contains(&page_delta) {
+                internal_error!("aarch64 adrp page offset out of range: {:+x}", page_delta);
+            }
+            let imm = page_delta as u32;
+            let immlo = imm & 0x3;
+            let immhi = (imm >> 2) & 0x7ffff;
+            let inst = (load_aarch64_inst(bytes, base) & !((0x3 << 29) | (0x7ffff << 5)))
+                | (immlo << 29)
+                | (immhi << 5);
+            bytes[base..base + 4].copy_from_slice(&inst.to_le_bytes());
+        }
+        elf::R_AARCH64_PREL32 => {
+            let data = ((target - pc) as i32).to_le_bytes();
+            bytes[base..base + 4].copy_from_slice(&data);
+        }
+        elf::R_AARCH64_PREL64 => {
+            let data = (target - pc).to_le_bytes();
+            bytes[base..base + 8].copy_from_slice(&data);
+        }
+        x => {
+            internal_error!("Relocation type not yet support for aarch64: {}", x);
+        }
+    }
 }
 
 fn align_by_constraint(offset: usize, constraint: usize) -> usize {
@@ -2858,3 +3642,720 @@ fn load_structs_inplace_mut<T>(bytes: &mut [u8], offset: usize, count: usize) ->
     assert!(tail.is_empty(), "End of data was not aligned");
     body
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write;
+    use object::{
+        Architecture, BinaryFormat, Endianness, ObjectSegment, RelocationEncoding, SymbolFlags,
+    };
+
+    const TEXT_VADDR: i64 = 0x40_1000;
+    const RODATA_VADDR: i64 = 0x41_2340;
+    const ROC_ALLOC_VADDR: i64 = 0x40_0400;
+
+    /// An aarch64 app object that loads the address of some rodata and calls into the host.
+    fn aarch64_app_object() -> Vec<u8> {
+        let mut obj =
+            write::Object::new(BinaryFormat::Elf, Architecture::Aarch64, Endianness::Little);
+        let text = obj.section_id(write::StandardSection::Text);
+        let rodata = obj.section_id(write::StandardSection::ReadOnlyData);
+
+        let str_offset = obj.append_section_data(rodata, b"roc!", 8);
+        let str_symbol = obj.add_symbol(write::Symbol {
+            name: b"roc_str".to_vec(),
+            value: str_offset,
+            size: 4,
+            kind: object::SymbolKind::Data,
+            scope: object::SymbolScope::Linkage,
+            weak: false,
+            section: write::SymbolSection::Section(rodata),
+            flags: SymbolFlags::None,
+        });
+        let alloc_symbol = obj.add_symbol(write::Symbol {
+            name: b"roc_alloc".to_vec(),
+            value: 0,
+            size: 0,
+            kind: object::SymbolKind::Text,
+            scope: object::SymbolScope::Dynamic,
+            weak: false,
+            section: write::SymbolSection::Undefined,
+            flags: SymbolFlags::None,
+        });
+
+        // adrp x0, 0; add x0, x0, #0; bl 0; b 0
+        let code: Vec<u8> = [0x9000_0000u32, 0x9100_0000, 0x9400_0000, 0x1400_0000]
+            .iter()
+            .flat_map(|inst| inst.to_le_bytes())
+            .collect();
+        let code_offset = obj.append_section_data(text, &code, 4);
+        obj.add_symbol(write::Symbol {
+            name: b"roc__mainForHost_1_exposed".to_vec(),
+            value: code_offset,
+            size: code.len() as u64,
+            kind: object::SymbolKind::Text,
+            scope: object::SymbolScope::Dynamic,
+            weak: false,
+            section: write::SymbolSection::Section(text),
+            flags: SymbolFlags::None,
+        });
+
+        let relocations = [
+            (0, elf::R_AARCH64_ADR_PREL_PG_HI21, str_symbol),
+            (4, elf::R_AARCH64_ADD_ABS_LO12_NC, str_symbol),
+            (8, elf::R_AARCH64_CALL26, alloc_symbol),
+            (12, elf::R_AARCH64_JUMP26, alloc_symbol),
+        ];
+        for (offset, r_type, symbol) in relocations {
+            obj.add_relocation(
+                text,
+                write::Relocation {
+                    offset: code_offset + offset,
+                    size: 0,
+                    kind: RelocationKind::Elf(r_type),
+                    encoding: RelocationEncoding::Generic,
+                    symbol,
+                    addend: 0,
+                },
+            )
+            .unwrap();
+        }
+
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn aarch64_branch_roundtrip() {
+        let mut bytes = 0x9400_0000u32.to_le_bytes().to_vec();
+        patch_aarch64_branch(&mut bytes, 0, -0x1000);
+        let inst = load_aarch64_inst(&bytes, 0);
+        assert_eq!(inst >> 26, 0x25, "patching must keep the BL opcode");
+        assert_eq!(aarch64_branch_target(inst, 0x5000), Some(0x4000));
+
+        // Not a branch: add x0, x0, #0
+        assert_eq!(aarch64_branch_target(0x9100_0000, 0x5000), None);
+    }
+
+    #[test]
+    fn aarch64_plt_stub() {
+        let mut bytes = vec![0u8; PLT_ADDRESS_OFFSET as usize];
+        write_aarch64_plt_stub(&mut bytes, 0, 0x20);
+
+        assert_eq!(
+            aarch64_branch_target(load_aarch64_inst(&bytes, 0), 0x100),
+            Some(0x120)
+        );
+        for offset in [4, 8, 12] {
+            assert_eq!(load_aarch64_inst(&bytes, offset), AARCH64_NOP);
+        }
+    }
+
+    #[test]
+    fn aarch64_app_relocations() {
+        let app_data = aarch64_app_object();
+        let app_obj = object::File::parse(&*app_data).unwrap();
+        assert_eq!(app_obj.architecture(), Architecture::Aarch64);
+
+        let text = app_obj.section_by_name(".text").unwrap();
+        let mut text_data = text.data().unwrap().to_vec();
+
+        for (offset, rel) in text.relocations() {
+            let index = match rel.target() {
+                RelocationTarget::Symbol(index) => index,
+                other => panic!("unexpected relocation target: {:?}", other),
+            };
+            let sym = app_obj.symbol_by_index(index).unwrap();
+            let target = match sym.name().unwrap() {
+                "roc_alloc" => ROC_ALLOC_VADDR,
+                "roc_str" => RODATA_VADDR + sym.address() as i64,
+                other => panic!("unexpected relocation symbol: {}", other),
+            };
+
+            apply_aarch64_relocation(
+                &mut text_data,
+                offset as usize,
+                TEXT_VADDR + offset as i64,
+                target + rel.addend(),
+                aarch64_reloc_type(&rel),
+            );
+        }
+
+        let adrp = load_aarch64_inst(&text_data, 0);
+        let page_delta = (((adrp >> 5) & 0x7ffff) << 2) | ((adrp >> 29) & 0x3);
+        assert_eq!(
+            page_delta as i64,
+            ((RODATA_VADDR & !0xfff) - (TEXT_VADDR & !0xfff)) >> 12
+        );
+
+        let add = load_aarch64_inst(&text_data, 4);
+        assert_eq!((add >> 10) & 0xfff, (RODATA_VADDR & 0xfff) as u32);
+
+        for offset in [8, 12] {
+            let inst = load_aarch64_inst(&text_data, offset);
+            let pc = (TEXT_VADDR + offset as i64) as u64;
+            assert_eq!(
+                aarch64_branch_target(inst, pc),
+                Some(ROC_ALLOC_VADDR as u64)
+            );
+        }
+    }
+
+    #[test]
+    fn aarch64_code_ranges_skip_data() {
+        // The second function has a literal pool in the middle, and nothing covers 0x160..0x180.
+        let functions = vec![
+            (0x100, 0x120),
+            (0x120, 0x160),
+            (0x180, 0x190),
+            (0x100, 0x120),
+        ];
+        let mapping_symbols = vec![(0x100, false), (0x140, true), (0x150, false), (0x188, true)];
+
+        assert_eq!(
+            aarch64_code_ranges(functions, mapping_symbols),
+            vec![
+                (0x100, 0x120),
+                (0x120, 0x140),
+                (0x150, 0x160),
+                (0x180, 0x188)
+            ]
+        );
+    }
+
+    /// The bytes at a virtual address of a linked executable.
+    fn read_vaddr<'data>(obj: &object::File<'data>, vaddr: u64, len: usize) -> &'data [u8] {
+        let segment = obj
+            .segments()
+            .find(|segment| {
+                (segment.address()..segment.address() + segment.size()).contains(&vaddr)
+            })
+            .unwrap_or_else(|| panic!("{:#x} is not in any segment", vaddr));
+        let offset = (vaddr - segment.address()) as usize;
+
+        &segment.data().unwrap()[offset..offset + len]
+    }
+
+    /// Cross compiles a C host for aarch64 with zig, links the app object into it, and checks
+    /// the host's call lands on the app, and the app's references land on the host.
+    #[test]
+    fn aarch64_link_end_to_end() {
+        let target: Triple = "aarch64-unknown-linux-gnu".parse().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        let dummy_lib = dir.join("libapp.so");
+        generate_dynamic_lib(&target, vec!["mainForHost".to_string()], vec![], &dummy_lib);
+
+        let host_c = dir.join("host.c");
+        fs::write(
+            &host_c,
+            "#include <stddef.h>\n\
+             extern void roc__mainForHost_1_exposed(void);\n\
+             void *roc_alloc(size_t size, unsigned int alignment) { return NULL; }\n\
+             int main(void) { roc__mainForHost_1_exposed(); return 0; }\n",
+        )
+        .unwrap();
+
+        let dynhost = dir.join("dynhost");
+        let output = std::process::Command::new(roc_build::link::zig_executable())
+            .args(&["cc", "-target", "aarch64-linux-gnu", "-fPIE", "-pie", "-o"])
+            .args(&[&dynhost, &host_c, &dummy_lib])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let metadata = dir.join("metadata");
+        let preprocessed = dir.join("preprocessedhost");
+        preprocess(
+            &target,
+            dynhost.to_str().unwrap(),
+            metadata.to_str().unwrap(),
+            preprocessed.to_str().unwrap(),
+            &dummy_lib,
+            false,
+            false,
+        )
+        .unwrap();
+
+        let app_o = dir.join("app.o");
+        let binary = dir.join("app");
+        fs::write(&app_o, aarch64_app_object()).unwrap();
+        fs::copy(&preprocessed, &binary).unwrap();
+        surgery(
+            app_o.to_str().unwrap(),
+            metadata.to_str().unwrap(),
+            binary.to_str().unwrap(),
+            false,
+            false,
+            &target,
+        )
+        .unwrap();
+
+        let (_, md) = load_metadata(metadata.to_str().unwrap()).unwrap();
+        let data = fs::read(&binary).unwrap();
+        let obj = object::File::parse(&*data).unwrap();
+        assert_eq!(obj.architecture(), Architecture::Aarch64);
+
+        let branch_target = |file_offset: u64, vaddr: u64| {
+            let inst = load_aarch64_inst(&data, (file_offset + md.added_byte_count) as usize);
+            aarch64_branch_target(inst, vaddr + md.added_byte_count).unwrap()
+        };
+
+        // main's call and the plt entry both go straight to the app function.
+        let name = "roc__mainForHost_1_exposed";
+        let (plt_offset, plt_vaddr) = md.plt_addresses[name];
+        let app_fn = branch_target(plt_offset, plt_vaddr);
+        let surgeries = &md.surgeries[name];
+        assert!(!surgeries.is_empty(), "the call from main was not found");
+        for entry in surgeries {
+            match entry.virtual_offset {
+                VirtualOffset::Relative(vaddr) => {
+                    assert_eq!(branch_target(entry.file_offset, vaddr), app_fn)
+                }
+                VirtualOffset::Absolute => panic!("aarch64 branches are relative"),
+            }
+        }
+
+        let app_segment = obj
+            .segments()
+            .find(|segment| {
+                (segment.address()..segment.address() + segment.size()).contains(&app_fn)
+            })
+            .unwrap();
+        assert!(
+            matches!(app_segment.flags(), object::SegmentFlags::Elf { p_flags } if p_flags & elf::PF_X != 0),
+            "the app function is not executable"
+        );
+
+        // The app's bl and b go to the host's roc_alloc.
+        let code = read_vaddr(&obj, app_fn, 16);
+        let roc_alloc = md.roc_symbol_vaddresses["roc_alloc"] + md.added_byte_count;
+        for offset in [8, 12] {
+            let inst = load_aarch64_inst(code, offset);
+            assert_eq!(
+                aarch64_branch_target(inst, app_fn + offset as u64),
+                Some(roc_alloc)
+            );
+        }
+
+        // The app's adrp and add point at its string.
+        let adrp = load_aarch64_inst(code, 0);
+        let imm21 = (((adrp >> 5) & 0x7ffff) << 2) | ((adrp >> 29) & 0x3);
+        // Shift the 21 bit immediate to the top to sign extend it.
+        let page_delta = (imm21 << 11) as i32 >> 11;
+        let page = (app_fn & !0xfff) as i64 + ((page_delta as i64) << 12);
+        let add = load_aarch64_inst(code, 4);
+        let str_address = page as u64 + ((add >> 10) & 0xfff) as u64;
+        assert_eq!(read_vaddr(&obj, str_address, 4), b"roc!");
+    }
+
+    /// An x86_64 app object whose code loads a pointer to its string from `.data.rel.ro`.
+    fn x86_64_app_object() -> Vec<u8> {
+        let mut obj =
+            write::Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let text = obj.section_id(write::StandardSection::Text);
+        let rodata = obj.section_id(write::StandardSection::ReadOnlyData);
+        let data_rel_ro = obj.add_section(
+            Vec::new(),
+            b".data.rel.ro".to_vec(),
+            object::SectionKind::Data,
+        );
+
+        let str_offset = obj.append_section_data(rodata, b"roc!", 8);
+        let str_symbol = obj.add_symbol(write::Symbol {
+            name: b"roc_str".to_vec(),
+            value: str_offset,
+            size: 4,
+            kind: object::SymbolKind::Data,
+            scope: object::SymbolScope::Linkage,
+            weak: false,
+            section: write::SymbolSection::Section(rodata),
+            flags: SymbolFlags::None,
+        });
+
+        let ptr_offset = obj.append_section_data(data_rel_ro, &[0; 8], 8);
+        let ptr_symbol = obj.add_symbol(write::Symbol {
+            name: b"roc_str_ptr".to_vec(),
+            value: ptr_offset,
+            size: 8,
+            kind: object::SymbolKind::Data,
+            scope: object::SymbolScope::Linkage,
+            weak: false,
+            section: write::SymbolSection::Section(data_rel_ro),
+            flags: SymbolFlags::None,
+        });
+        obj.add_relocation(
+            data_rel_ro,
+            write::Relocation {
+                offset: ptr_offset,
+                size: 64,
+                kind: RelocationKind::Absolute,
+                encoding: RelocationEncoding::Generic,
+                symbol: str_symbol,
+                addend: 0,
+            },
+        )
+        .unwrap();
+
+        // lea rax, [rip + roc_str_ptr]; ret
+        let code = [0x48, 0x8d, 0x05, 0, 0, 0, 0, 0xc3];
+        let code_offset = obj.append_section_data(text, &code, 16);
+        obj.add_symbol(write::Symbol {
+            name: b"roc__mainForHost_1_exposed".to_vec(),
+            value: code_offset,
+            size: code.len() as u64,
+            kind: object::SymbolKind::Text,
+            scope: object::SymbolScope::Dynamic,
+            weak: false,
+            section: write::SymbolSection::Section(text),
+            flags: SymbolFlags::None,
+        });
+        obj.add_relocation(
+            text,
+            write::Relocation {
+                offset: code_offset + 3,
+                size: 32,
+                kind: RelocationKind::Relative,
+                encoding: RelocationEncoding::Generic,
+                symbol: ptr_symbol,
+                addend: -4,
+            },
+        )
+        .unwrap();
+
+        obj.write().unwrap()
+    }
+
+    /// Links the app object into a C host built as a shared library, and checks the library is
+    /// named after its file, and the loader will relocate the app's pointer to its string.
+    #[test]
+    fn x86_64_shared_library_end_to_end() {
+        let target: Triple = "x86_64-unknown-linux-gnu".parse().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        let dummy_lib = dir.join("libapp.so");
+        generate_dynamic_lib(&target, vec!["mainForHost".to_string()], vec![], &dummy_lib);
+
+        // host_name gives the host a dynamic relocation table, like any host linking libc has.
+        let host_c = dir.join("host.c");
+        fs::write(
+            &host_c,
+            "extern const char **roc__mainForHost_1_exposed(void);\n\
+             const char *host_name = \"host\";\n\
+             const char *host_str(void) { return *roc__mainForHost_1_exposed(); }\n",
+        )
+        .unwrap();
+
+        // Like rebuild_host_lib, leave room for the library's real name.
+        let placeholder = format!("{:_<1$}", "dynhost", SONAME_CAPACITY - ".so".len());
+        let dynhost = dir.join("dynhost.so");
+        let output = std::process::Command::new(roc_build::link::zig_executable())
+            .args(&["cc", "-target", "x86_64-linux-gnu", "-fPIC", "-shared"])
+            .arg(format!("-Wl,-soname,{}.so", placeholder))
+            .arg("-o")
+            .args(&[&dynhost, &host_c, &dummy_lib])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let metadata = dir.join("metadata_lib");
+        let preprocessed = dir.join("preprocessedhost.so");
+        preprocess(
+            &target,
+            dynhost.to_str().unwrap(),
+            metadata.to_str().unwrap(),
+            preprocessed.to_str().unwrap(),
+            &dummy_lib,
+            false,
+            false,
+        )
+        .unwrap();
+
+        let app_o = dir.join("app.o");
+        let library = dir.join("libhello.so");
+        fs::write(&app_o, x86_64_app_object()).unwrap();
+        fs::copy(&preprocessed, &library).unwrap();
+        surgery(
+            app_o.to_str().unwrap(),
+            metadata.to_str().unwrap(),
+            library.to_str().unwrap(),
+            false,
+            false,
+            &target,
+        )
+        .unwrap();
+
+        let data = fs::read(&library).unwrap();
+        let obj = object::File::parse(&*data).unwrap();
+        let exec_header = load_struct_inplace::<elf::FileHeader64<LittleEndian>>(&data, 0);
+        let program_headers = load_structs_inplace::<elf::ProgramHeader64<LittleEndian>>(
+            &data,
+            exec_header.e_phoff.get(NativeEndian) as usize,
+            exec_header.e_phnum.get(NativeEndian) as usize,
+        );
+        let dynamic_header = program_headers
+            .iter()
+            .find(|ph| ph.p_type.get(NativeEndian) == elf::PT_DYNAMIC)
+            .unwrap();
+        let dynamic = load_structs_inplace::<elf::Dyn64<LittleEndian>>(
+            &data,
+            dynamic_header.p_offset.get(NativeEndian) as usize,
+            dynamic_header.p_filesz.get(NativeEndian) as usize
+                / mem::size_of::<elf::Dyn64<LittleEndian>>(),
+        );
+        let dynamic_value = |tag: u32| {
+            dynamic
+                .iter()
+                .find(|d| d.d_tag.get(NativeEndian) == tag as u64)
+                .map(|d| d.d_val.get(NativeEndian))
+                .unwrap()
+        };
+
+        let soname = read_vaddr(
+            &obj,
+            dynamic_value(elf::DT_STRTAB) + dynamic_value(elf::DT_SONAME),
+            SONAME_CAPACITY,
+        );
+        assert_eq!(soname.split(|b| *b == 0).next(), Some(&b"libhello.so"[..]));
+
+        // The app's code finds its pointer through a pc relative address.
+        let app_fn = obj
+            .dynamic_symbols()
+            .find(|sym| sym.name() == Ok("roc__mainForHost_1_exposed"))
+            .unwrap()
+            .address();
+        let code = read_vaddr(&obj, app_fn, 8);
+        assert_eq!(code[..3], [0x48, 0x8d, 0x05]);
+        let pc_offset = i32::from_le_bytes(code[3..7].try_into().unwrap());
+        let ptr_vaddr = (app_fn as i64 + 7 + pc_offset as i64) as u64;
+
+        // The loader adds the load address to the pointer, which points at the string.
+        let rela_size = mem::size_of::<elf::Rela64<LittleEndian>>();
+        let relocations = load_structs_inplace::<elf::Rela64<LittleEndian>>(
+            read_vaddr(
+                &obj,
+                dynamic_value(elf::DT_RELA),
+                dynamic_value(elf::DT_RELASZ) as usize,
+            ),
+            0,
+            dynamic_value(elf::DT_RELASZ) as usize / rela_size,
+        );
+        let relocation = relocations
+            .iter()
+            .find(|rel| rel.r_offset.get(NativeEndian) == ptr_vaddr)
+            .expect("the app's pointer has no dynamic relocation");
+        assert_eq!(
+            relocation.r_type(LittleEndian, false),
+            elf::R_X86_64_RELATIVE
+        );
+        let str_vaddr = relocation.r_addend.get(NativeEndian) as u64;
+        assert_eq!(read_vaddr(&obj, str_vaddr, 4), b"roc!");
+        assert_eq!(read_vaddr(&obj, ptr_vaddr, 8), str_vaddr.to_le_bytes());
+
+        let ptr_segment = obj
+            .segments()
+            .find(|segment| {
+                (segment.address()..segment.address() + segment.size()).contains(&ptr_vaddr)
+            })
+            .unwrap();
+        assert!(
+            matches!(ptr_segment.flags(), object::SegmentFlags::Elf { p_flags } if p_flags & elf::PF_W != 0),
+            "the loader can't write the app's pointer"
+        );
+    }
+
+    #[test]
+    fn describe_metadata() {
+        let name = "roc__mainForHost_1_exposed".to_string();
+        let mut md = metadata::Metadata {
+            app_functions: vec![name.clone()],
+            exec_len: 0x4000,
+            ..Default::default()
+        };
+        md.plt_addresses.insert(name.clone(), (0x1030, 0x401030));
+        md.surgeries.insert(
+            name,
+            vec![metadata::SurgeryEntry {
+                file_offset: 0x1200,
+                virtual_offset: VirtualOffset::Relative(0x401205),
+                size: 4,
+            }],
+        );
+        md.roc_symbol_vaddresses
+            .insert("roc_alloc".to_string(), 0x401500);
+
+        let description = md.to_string();
+
+        assert!(description.contains("    roc__mainForHost_1_exposed\n"));
+        assert!(description.contains("plt slot: file offset 0x1030, virtual address 0x401030"));
+        assert!(description.contains("file offset 0x1200, relative to 0x401205, 4 bytes"));
+        assert!(description.contains("0x00401500  roc_alloc"));
+        assert!(description.contains("executable length:          0x4000"));
+    }
+
+    #[test]
+    fn metadata_header_validation() {
+        let md = metadata::Metadata {
+            app_functions: vec!["roc__mainForHost_1_exposed".to_string()],
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let current = dir.path().join("metadata");
+        let mut bytes = bincode::serialize(&metadata::Header::new(42)).unwrap();
+        bytes.extend(bincode::serialize(&md).unwrap());
+        fs::write(&current, bytes).unwrap();
+
+        let (header, loaded) = load_metadata(current.to_str().unwrap()).unwrap();
+        assert_eq!(header.host_hash, 42);
+        assert_eq!(loaded, md);
+
+        // Metadata written before there was a header.
+        let unversioned = dir.path().join("unversioned");
+        fs::write(&unversioned, bincode::serialize(&md).unwrap()).unwrap();
+        assert!(matches!(
+            load_metadata(unversioned.to_str().unwrap()),
+            Err(LinkerError::MetadataOutdated { .. })
+        ));
+
+        // Metadata written by a compiler using a newer format.
+        let newer = dir.path().join("newer");
+        let mut header = metadata::Header::new(42);
+        header.version += 1;
+        let mut bytes = bincode::serialize(&header).unwrap();
+        bytes.extend(bincode::serialize(&md).unwrap());
+        fs::write(&newer, bytes).unwrap();
+        assert!(matches!(
+            load_metadata(newer.to_str().unwrap()),
+            Err(LinkerError::MetadataOutdated { .. })
+        ));
+    }
+
+    #[test]
+    fn host_mismatch() {
+        let md = metadata::Metadata {
+            app_functions: vec!["roc__mainForHost_1_exposed".to_string()],
+            exec_len: 16,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let metadata = dir.path().join("metadata");
+        let mut bytes = bincode::serialize(&metadata::Header::new(42)).unwrap();
+        bytes.extend(bincode::serialize(&md).unwrap());
+        fs::write(&metadata, bytes).unwrap();
+
+        // A host of a different length than the one which was preprocessed.
+        let binary = dir.path().join("app");
+        fs::write(&binary, [0; 8]).unwrap();
+
+        let result = surgery(
+            dir.path().join("app.o").to_str().unwrap(),
+            metadata.to_str().unwrap(),
+            binary.to_str().unwrap(),
+            false,
+            false,
+            &Triple::host(),
+        );
+
+        assert!(matches!(result, Err(LinkerError::HostMismatch { .. })));
+    }
+
+    #[test]
+    fn host_hash_mismatch() {
+        let md = metadata::Metadata {
+            app_functions: vec!["roc__mainForHost_1_exposed".to_string()],
+            exec_len: 8,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let metadata = dir.path().join("metadata");
+        let preprocessed = [1; 8];
+        let mut bytes =
+            bincode::serialize(&metadata::Header::new(metadata::hash_host(&preprocessed))).unwrap();
+        bytes.extend(bincode::serialize(&md).unwrap());
+        fs::write(&metadata, bytes).unwrap();
+
+        let binary = dir.path().join("app");
+        fs::write(&binary, preprocessed).unwrap();
+
+        assert_eq!(
+            check_host_hash(metadata.to_str().unwrap(), binary.to_str().unwrap()),
+            Ok(())
+        );
+
+        // A host which was rebuilt to the same length, but different contents.
+        fs::write(&binary, [2; 8]).unwrap();
+
+        assert!(matches!(
+            check_host_hash(metadata.to_str().unwrap(), binary.to_str().unwrap()),
+            Err(LinkerError::HostMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn inspect_both_metadata_files() {
+        let md = metadata::Metadata {
+            app_functions: vec!["roc__mainForHost_1_exposed".to_string()],
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        for (name, host_hash) in [("metadata", 1), ("metadata_lib", 2)] {
+            let mut bytes = bincode::serialize(&metadata::Header::new(host_hash)).unwrap();
+            bytes.extend(bincode::serialize(&md).unwrap());
+            fs::write(dir.path().join(name), bytes).unwrap();
+        }
+
+        let description = inspect_metadata(dir.path()).unwrap();
+
+        assert!(description.contains("metadata:\n\n"));
+        assert!(description.contains("metadata_lib:\n\n"));
+        assert!(description.contains("host with hash 0x0000000000000001"));
+        assert!(description.contains("host with hash 0x0000000000000002"));
+
+        // With only one of them, it's rendered on its own.
+        fs::remove_file(dir.path().join("metadata")).unwrap();
+
+        let description = inspect_metadata(dir.path()).unwrap();
+
+        assert!(description.starts_with("Metadata format version"));
+        assert!(description.contains("host with hash 0x0000000000000002"));
+    }
+
+    #[test]
+    fn missing_app_function_error() {
+        let err = LinkerError::AppFunctionNotDefined {
+            name: "roc__mainForHost_1_exposed".to_string(),
+            app_defines: vec!["roc__main_1_exposed".to_string()],
+        };
+        let message = err.to_string();
+
+        assert!(message.contains("The host calls `roc__mainForHost_1_exposed`"));
+        assert!(message.contains("\n    roc__main_1_exposed"));
+    }
+
+    #[test]
+    fn aarch64_dummy_dylib() {
+        let target: Triple = "aarch64-unknown-linux-gnu".parse().unwrap();
+        let names = vec!["roc__mainForHost_1_exposed".to_string()];
+        let bytes = generate_dylib::generate(&target, &names).unwrap();
+
+        let dylib = object::File::parse(&*bytes).unwrap();
+        assert_eq!(dylib.architecture(), Architecture::Aarch64);
+        assert!(dylib
+            .dynamic_symbols()
+            .any(|sym| sym.name() == Ok("roc__mainForHost_1_exposed")));
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt;
+
+/// Problems with the host or application that surgical linking can't work around.
+///
+/// Broken invariants inside the linker itself still use `internal_error!`;
+/// these are the cases where the inputs don't fit together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkerError {
+    /// The metadata written by `preprocess` could not be read back.
+    MetadataUnreadable { path: String, reason: String },
+    /// The metadata was written by a compiler that used a different metadata format.
+    MetadataOutdated { path: String, reason: String },
+    /// The metadata describes a different preprocessed host than the one being linked.
+    HostMismatch {
+        metadata_path: String,
+        host_path: String,
+    },
+    /// The host does not list the dummy app library as a dynamic dependency.
+    SharedLibNotLinked { shared_lib: String },
+    /// The host never calls any `roc__*` function, so there is nothing to link.
+    NoAppFunctions,
+    /// The host calls an application function that the application does not define.
+    AppFunctionNotDefined {
+        name: String,
+        app_defines: Vec<String>,
+    },
+    /// The application refers to a symbol that neither it nor the host defines.
+    UndefinedSymbol { name: String },
+    /// The application needs the absolute address of a symbol somewhere the loader
+    /// can't fix it up once it knows where the position independent host is.
+    AbsoluteRelocation { name: String },
+    /// The shared library's file name doesn't fit in the space the host reserved for its soname.
+    SonameTooLong { name: String, capacity: usize },
+}
+
+pub type LinkerResult<T, E = LinkerError> = std::result::Result<T, E>;
+
+impl fmt::Display for LinkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkerError::MetadataUnreadable { path, reason } => write!(
+                f,
+                "I could not read the preprocessed host metadata at {}: {}\n\nRebuilding the host (without --precompiled-host) should regenerate it.",
+                path, reason
+            ),
+            LinkerError::MetadataOutdated { path, reason } => write!(
+                f,
+                "The preprocessed host metadata at {} is outdated: {}\n\nRebuilding the host (without --precompiled-host) will preprocess it again.",
+                path, reason
+            ),
+            LinkerError::HostMismatch {
+                metadata_path,
+                host_path,
+            } => write!(
+                f,
+                "The preprocessed host metadata at {} does not describe {}. The host was probably rebuilt or replaced after it was preprocessed.\n\nRebuilding the host (without --precompiled-host) will preprocess it again.",
+                metadata_path, host_path
+            ),
+            LinkerError::SharedLibNotLinked { shared_lib } => write!(
+                f,
+                "The host does not dynamically link `{}`, so I can't tell which calls go to the application.\n\nThe host needs to be built against the dummy app library for surgical linking to work. You can use `--linker=legacy` in the meantime.",
+                shared_lib
+            ),
+            LinkerError::NoAppFunctions => write!(
+                f,
+                "The host never calls any `roc__*` application functions, so there is nothing to link.\n\nThis probably means the wrong file was given as the host."
+            ),
+            LinkerError::AppFunctionNotDefined { name, app_defines } => {
+                write!(
+                    f,
+                    "The host calls `{}`, but the application does not define it.",
+                    name
+                )?;
+
+                if app_defines.is_empty() {
+                    write!(f, "\n\nThe application does not define any `roc__*` functions.")?;
+                } else {
+                    write!(f, "\n\nThe application defines these functions instead:\n")?;
+                    for defined in app_defines {
+                        write!(f, "\n    {}", defined)?;
+                    }
+                }
+
+                write!(
+                    f,
+                    "\n\nThis usually means the platform's `provides` list does not match what the host was built for."
+                )
+            }
+            LinkerError::UndefinedSymbol { name } => write!(
+                f,
+                "The application refers to `{}`, but neither the application nor the host defines it.\n\nHosts need to define every `roc_*` function the application calls, like `roc_alloc` and `roc_panic`.",
+                name
+            ),
+            LinkerError::AbsoluteRelocation { name } => write!(
+                f,
+                "The application needs the absolute address of `{}`, but the surgical linker can only link position independent code.\n\nYou can use `--linker=legacy` in the meantime.",
+                name
+            ),
+            LinkerError::SonameTooLong { name, capacity } => write!(
+                f,
+                "The shared library name `{}` is too long; the host only has room for {} bytes.\n\nA shorter output file name will fit.",
+                name, capacity
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LinkerError {}
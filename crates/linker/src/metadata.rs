@@ -1,5 +1,39 @@
-use roc_collections::all::MutMap;
+use roc_collections::all::{MutMap, WyHash};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::Hasher;
+
+/// Every metadata file starts with these bytes, followed by the format version.
+pub const MAGIC: [u8; 8] = *b"rocmeta\0";
+
+/// Bump this whenever `Metadata` or `SurgeryEntry` change shape or meaning,
+/// so that hosts preprocessed by an older compiler get preprocessed again.
+pub const VERSION: u32 = 2;
+
+/// Written before the `Metadata` itself, so it can be validated before we trust the rest.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Header {
+    pub magic: [u8; 8],
+    pub version: u32,
+    /// Hash of the preprocessed host that the metadata describes.
+    pub host_hash: u64,
+}
+
+impl Header {
+    pub fn new(host_hash: u64) -> Self {
+        Header {
+            magic: MAGIC,
+            version: VERSION,
+            host_hash,
+        }
+    }
+}
+
+pub fn hash_host(bytes: &[u8]) -> u64 {
+    let mut hasher = WyHash::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub enum VirtualOffset {
@@ -35,4 +69,101 @@ pub struct Metadata {
     pub symbol_table_section_offset: u64,
     pub symbol_table_size: u64,
     pub macho_cmd_loc: u64,
+    /// File offset and length of a shared library's soname in `.dynstr`.
+    pub soname: Option<(u64, u64)>,
+}
+
+impl fmt::Display for SurgeryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "file offset {:#x}, ", self.file_offset)?;
+        match self.virtual_offset {
+            VirtualOffset::Absolute => write!(f, "absolute")?,
+            VirtualOffset::Relative(vaddr) => write!(f, "relative to {:#x}", vaddr)?,
+        }
+        write!(f, ", {} bytes", self.size)
+    }
+}
+
+impl fmt::Display for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Application functions ({}):", self.app_functions.len())?;
+        for name in self.app_functions.iter() {
+            writeln!(f, "    {}", name)?;
+
+            match self.plt_addresses.get(name) {
+                Some((offset, vaddr)) => writeln!(
+                    f,
+                    "        plt slot: file offset {:#x}, virtual address {:#x}",
+                    offset, vaddr
+                )?,
+                None => writeln!(f, "        plt slot: none")?,
+            }
+
+            if let Some(index) = self.dynamic_symbol_indices.get(name) {
+                writeln!(f, "        dynamic symbol index: {}", index)?;
+            }
+
+            let surgeries = self.surgeries.get(name).map(Vec::as_slice).unwrap_or(&[]);
+            writeln!(f, "        surgery sites ({}):", surgeries.len())?;
+            for surgery in surgeries {
+                writeln!(f, "            {}", surgery)?;
+            }
+        }
+
+        let mut roc_symbols: Vec<_> = self.roc_symbol_vaddresses.iter().collect();
+        roc_symbols.sort();
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Roc symbols defined by the host ({}):",
+            roc_symbols.len()
+        )?;
+        for (name, vaddr) in roc_symbols {
+            writeln!(f, "    {:#010x}  {}", vaddr, name)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Layout:")?;
+        writeln!(f, "    executable length:          {:#x}", self.exec_len)?;
+        writeln!(
+            f,
+            "    load alignment:             {:#x}",
+            self.load_align_constraint
+        )?;
+        writeln!(
+            f,
+            "    added bytes:                {:#x}",
+            self.added_byte_count
+        )?;
+        writeln!(f, "    last virtual address:       {:#x}", self.last_vaddr)?;
+        writeln!(
+            f,
+            "    .dynamic offset:            {:#x}",
+            self.dynamic_section_offset
+        )?;
+        writeln!(
+            f,
+            "    .dynsym offset:             {:#x}",
+            self.dynamic_symbol_table_section_offset
+        )?;
+        writeln!(
+            f,
+            "    .symtab offset:             {:#x} ({:#x} bytes)",
+            self.symbol_table_section_offset, self.symbol_table_size
+        )?;
+        writeln!(
+            f,
+            "    macho load command offset:  {:#x}",
+            self.macho_cmd_loc
+        )?;
+        match self.soname {
+            Some((offset, len)) => write!(
+                f,
+                "    soname:                     {:#x} ({} bytes)",
+                offset, len
+            ),
+            None => write!(f, "    soname:                     none"),
+        }
+    }
 }
@@ -0,0 +1,37 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+const HOST_FILENAME: &str = "repl_host";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let source_path = "src/platform/host.c";
+    println!("cargo:rerun-if-changed={}", source_path);
+
+    // The REPL links this into every dylib it builds, so it has to be position-independent.
+    let mut host_obj = PathBuf::from(env::var("OUT_DIR").unwrap()).join(HOST_FILENAME);
+    host_obj.set_extension("o");
+
+    let output = Command::new(&zig_executable())
+        .args([
+            "build-obj",
+            source_path,
+            "-lc",
+            "-fPIC",
+            "-O",
+            "ReleaseSafe",
+            &format!("-femit-bin={}", host_obj.to_str().unwrap()),
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:#?}", output);
+}
+
+fn zig_executable() -> String {
+    match std::env::var("ROC_ZIG") {
+        Ok(path) => path,
+        Err(_) => "zig".into(),
+    }
+}
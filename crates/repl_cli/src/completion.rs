@@ -0,0 +1,266 @@
+use lazy_static::lazy_static;
+use roc_collections::all::MutMap;
+use roc_repl_eval::gen::{name_types, NameType};
+use roc_repl_eval::state::ReplState;
+use roc_reporting::report::DEFAULT_PALETTE;
+use roc_target::TargetInfo;
+use target_lexicon::Triple;
+
+lazy_static! {
+    /// The types of the builtin modules' values. They're the same in every session, and
+    /// finding them means typechecking all of the builtins, so it's only done once.
+    static ref BUILTIN_TYPES: MutMap<String, NameType> = {
+        let state = ReplState::new();
+        let values = module_values(&state, &ReplState::builtin_module_names());
+
+        name_types(&state, &values, host_target_info(), DEFAULT_PALETTE).unwrap_or_default()
+    };
+}
+
+/// The names the REPL can complete, and their types for hints. Finding types means
+/// type-checking the session, so they get worked out once whenever it changes,
+/// rather than while typing.
+#[derive(Debug, Default)]
+pub(crate) struct Completions {
+    /// The `ReplState::changes` these are up to date with
+    changes: usize,
+    /// Names which can be used on their own: modules, and the session's definitions
+    names: Vec<String>,
+    /// What each module exposes, by module name
+    module_exposes: MutMap<String, Vec<String>>,
+    /// Types of the session's definitions and modules' values, and of their record
+    /// fields, by names like `x`, `Str.concat` or `point.x`
+    types: MutMap<String, NameType>,
+}
+
+impl Completions {
+    pub(crate) fn new(state: &ReplState) -> Self {
+        let mut names = state.module_names();
+        let module_exposes = state
+            .module_names()
+            .into_iter()
+            .map(|module_name| {
+                let exposes = state.module_exposes(&module_name).unwrap_or_default();
+
+                (module_name, exposes)
+            })
+            .collect();
+
+        names.extend(state.defined_names());
+
+        // Only what the session can change needs typechecking again
+        let builtin_module_names = ReplState::builtin_module_names();
+        let other_module_names: Vec<String> = state
+            .module_names()
+            .into_iter()
+            .filter(|module_name| !builtin_module_names.contains(module_name))
+            .collect();
+
+        let mut values = state.defined_names();
+        values.extend(module_values(state, &other_module_names));
+
+        let mut types = BUILTIN_TYPES.clone();
+        types.extend(
+            name_types(state, &values, host_target_info(), DEFAULT_PALETTE).unwrap_or_default(),
+        );
+
+        Completions {
+            changes: state.changes(),
+            names,
+            module_exposes,
+            types,
+        }
+    }
+
+    pub(crate) fn is_up_to_date(&self, state: &ReplState) -> bool {
+        self.changes == state.changes()
+    }
+
+    /// Where the name that ends at `pos` should be replaced from, and what it could be
+    /// completed to
+    pub(crate) fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let start = name_start(line, pos);
+        let name = &line[start..pos];
+
+        match name.rsplit_once('.') {
+            // Str.con -> Str.concat
+            Some((module_name, partial)) if module_name.starts_with(char::is_uppercase) => {
+                let exposes = self
+                    .module_exposes
+                    .get(module_name)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+
+                (pos - partial.len(), matching(exposes, partial))
+            }
+            // point.x -> point.x, point.y
+            Some((record, partial)) => {
+                let fields = self
+                    .types
+                    .get(record)
+                    .map(|name_type| name_type.record_fields.as_slice())
+                    .unwrap_or_default();
+
+                (pos - partial.len(), matching(fields, partial))
+            }
+            None => (start, matching(&self.names, name)),
+        }
+    }
+
+    /// Once the name at the end of the line is complete, its type goes after it.
+    pub(crate) fn hint(&self, line: &str, pos: usize) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+
+        let name = &line[name_start(line, pos)..pos];
+
+        self.types
+            .get(name)
+            .map(|name_type| format!(" : {}", name_type.expr_type))
+    }
+}
+
+fn host_target_info() -> TargetInfo {
+    TargetInfo::from(&Triple::host())
+}
+
+/// The values the modules expose, by names like `Str.concat`
+fn module_values(state: &ReplState, module_names: &[String]) -> Vec<String> {
+    module_names
+        .iter()
+        .flat_map(|module_name| {
+            state
+                .module_exposes(module_name)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|name| name.starts_with(char::is_lowercase))
+                .map(move |name| format!("{}.{}", module_name, name))
+        })
+        .collect()
+}
+
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '.'
+}
+
+/// Where the name (e.g. `Str.con`) that ends at `pos` starts
+fn name_start(line: &str, pos: usize) -> usize {
+    line[..pos]
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| !is_name_char(*ch))
+        .map(|(index, ch)| index + ch.len_utf8())
+        .unwrap_or(0)
+}
+
+/// The candidates which start with what's been typed so far, sorted and without duplicates
+fn matching(candidates: &[String], partial: &str) -> Vec<String> {
+    let mut matches: Vec<String> = candidates
+        .iter()
+        .filter(|candidate| candidate.starts_with(partial))
+        .cloned()
+        .collect();
+
+    matches.sort();
+    matches.dedup();
+
+    matches
+}
+
+#[cfg(test)]
+mod test_completion {
+    use super::*;
+
+    fn strings(strs: &[&str]) -> Vec<String> {
+        strs.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn name_type(expr_type: &str, record_fields: &[&str]) -> NameType {
+        NameType {
+            expr_type: expr_type.to_string(),
+            record_fields: strings(record_fields),
+        }
+    }
+
+    fn completions() -> Completions {
+        let mut module_exposes = MutMap::default();
+        let mut types = MutMap::default();
+
+        module_exposes.insert(
+            "Str".to_string(),
+            strings(&["Utf8Problem", "concat", "countGraphemes", "isEmpty"]),
+        );
+        module_exposes.insert("List".to_string(), strings(&["len", "map"]));
+
+        types.insert("Str.concat".to_string(), name_type("Str, Str -> Str", &[]));
+        types.insert(
+            "point".to_string(),
+            name_type("{ x : Num *, y : Num * }", &["x", "y"]),
+        );
+        types.insert("point.x".to_string(), name_type("Num *", &[]));
+
+        Completions {
+            changes: 0,
+            names: strings(&["Str", "List", "strs", "point", "strs"]),
+            module_exposes,
+            types,
+        }
+    }
+
+    #[test]
+    fn name_starts() {
+        assert_eq!(name_start("Str.con", 7), 0);
+        assert_eq!(name_start("x = Str.con", 11), 4);
+        assert_eq!(name_start("f (point.x", 10), 3);
+        assert_eq!(name_start("foo bar", 3), 0);
+        assert_eq!(name_start("foo ", 4), 4);
+        assert_eq!(name_start("\"é\" ab", 7), 5);
+    }
+
+    #[test]
+    fn matching_candidates() {
+        let candidates = strings(&["map", "len", "mapWithIndex", "map"]);
+
+        assert_eq!(
+            matching(&candidates, "ma"),
+            strings(&["map", "mapWithIndex"])
+        );
+        assert_eq!(
+            matching(&candidates, ""),
+            strings(&["len", "map", "mapWithIndex"])
+        );
+        assert!(matching(&candidates, "x").is_empty());
+    }
+
+    #[test]
+    fn complete_names() {
+        let completions = completions();
+
+        assert_eq!(completions.complete("st", 2), (0, strings(&["strs"])));
+        assert_eq!(completions.complete("1 + S", 5), (4, strings(&["Str"])));
+        assert_eq!(
+            completions.complete("Str.c", 5),
+            (4, strings(&["concat", "countGraphemes"]))
+        );
+        assert_eq!(completions.complete("point.", 6), (6, strings(&["x", "y"])));
+        assert_eq!(completions.complete("Foo.b", 5), (4, Vec::new()));
+        assert_eq!(completions.complete("other.", 6), (6, Vec::new()));
+    }
+
+    #[test]
+    fn hints() {
+        let completions = completions();
+
+        assert_eq!(
+            completions.hint("Str.concat", 10).as_deref(),
+            Some(" : Str, Str -> Str")
+        );
+        assert_eq!(
+            completions.hint("1 + point.x", 11).as_deref(),
+            Some(" : Num *")
+        );
+        assert_eq!(completions.hint("Str.concat", 3), None);
+        assert_eq!(completions.hint("Str.con", 7), None);
+    }
+}
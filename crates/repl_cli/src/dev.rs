@@ -0,0 +1,228 @@
+//! Evaluating with the dev backend, which generates machine code directly rather
+//! than going through LLVM. That makes it much faster for one-off expressions like
+//! the REPL's, but it doesn't support everything yet, so the REPL falls back to
+//! LLVM whenever this can't handle an expression.
+
+use bumpalo::Bump;
+use libloading::Library;
+use roc_build::link::{link, LinkType};
+use roc_builtins::bitcode;
+use roc_collections::all::{MutMap, MutSet};
+use roc_load::MonomorphizedModule;
+use roc_module::symbol::Symbol;
+use roc_mono::ir::ProcLayout;
+use roc_mono::layout::{Layout, LayoutIds};
+use roc_parse::ast::Expr;
+use roc_repl_eval::ReplApp;
+use roc_std::RocStr;
+use roc_target::TargetInfo;
+use std::fs;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::path::{Path, PathBuf};
+use target_lexicon::Triple;
+use tempfile::TempDir;
+
+use crate::CliMemory;
+
+pub(crate) struct DevApp {
+    lib: Library,
+}
+
+impl<'a> ReplApp<'a> for DevApp {
+    type Memory = CliMemory;
+
+    /// Unlike the LLVM backend's main function, the dev backend's returns its value
+    /// directly, using the C calling convention.
+    fn call_function<Return, F>(&mut self, main_fn_name: &str, transform: F) -> Expr<'a>
+    where
+        F: Fn(&'a Self::Memory, Return) -> Expr<'a>,
+        Self::Memory: 'a,
+    {
+        let main: libloading::Symbol<unsafe extern "C" fn() -> Return> =
+            unsafe { self.lib.get(main_fn_name.as_bytes()) }
+                .unwrap_or_else(|_| panic!("Unable to JIT compile `{}`", main_fn_name));
+
+        transform(&CliMemory, unsafe { main() })
+    }
+
+    fn call_function_returns_roc_str<T, F>(
+        &mut self,
+        _target_info: TargetInfo,
+        main_fn_name: &str,
+        transform: F,
+    ) -> T
+    where
+        F: Fn(&'a Self::Memory, usize) -> T,
+        Self::Memory: 'a,
+    {
+        let main: libloading::Symbol<unsafe extern "C" fn() -> RocStr> =
+            unsafe { self.lib.get(main_fn_name.as_bytes()) }
+                .unwrap_or_else(|_| panic!("Unable to JIT compile `{}`", main_fn_name));
+
+        // The string's memory belongs to the dylib, so Rust mustn't free it.
+        let string = ManuallyDrop::new(unsafe { main() });
+
+        transform(&CliMemory, &*string as *const RocStr as usize)
+    }
+
+    fn call_function_dynamic_size<T, F>(
+        &mut self,
+        _main_fn_name: &str,
+        _ret_bytes: usize,
+        _transform: F,
+    ) -> T
+    where
+        F: Fn(&'a Self::Memory, usize) -> T,
+        Self::Memory: 'a,
+    {
+        unreachable!("The dev backend is only used for values with builtin layouts")
+    }
+}
+
+/// Whether the REPL can read a value with this layout from the dev backend. Structs
+/// and unions are returned in registers or memory depending on their size and the
+/// target's calling convention, which only the LLVM path handles so far.
+fn supports_layout(layout: &Layout) -> bool {
+    matches!(layout, Layout::Builtin(_))
+}
+
+/// What the dev backend keeps between inputs. Most of the time it would take to link an
+/// input's code goes on the builtins, so they're linked once per session instead, into a
+/// library along with the host and the allocators. That stays loaded, and each input's
+/// code only gets linked against it.
+pub(crate) struct DevBackend {
+    dir: TempDir,
+    support: Option<(Library, PathBuf)>,
+    inputs: usize,
+}
+
+impl DevBackend {
+    pub(crate) fn new() -> io::Result<Self> {
+        Ok(DevBackend {
+            dir: tempfile::tempdir()?,
+            support: None,
+            inputs: 0,
+        })
+    }
+
+    /// Compiles the module with the dev backend, and links it into a dylib. Returns
+    /// `None` if the dev backend doesn't support something in the module, in which case
+    /// the LLVM backend has to be used.
+    pub(crate) fn mono_module_to_dylib(
+        &mut self,
+        arena: &Bump,
+        target: &Triple,
+        loaded: &MonomorphizedModule,
+        main_fn_symbol: Symbol,
+        main_fn_layout: ProcLayout,
+        host_objects: &[&Path],
+    ) -> Option<(DevApp, String)> {
+        if !supports_layout(&main_fn_layout.result) {
+            return None;
+        }
+
+        roc_gen_dev::check_supported(&loaded.interns, target, &loaded.procedures).ok()?;
+
+        let mut interns = loaded.interns.clone();
+        let main_fn_name = LayoutIds::default()
+            .get_toplevel(main_fn_symbol, &main_fn_layout)
+            .to_exposed_symbol_string(main_fn_symbol, &interns);
+
+        let support_path = self
+            .support_library(arena, target, loaded, host_objects)?
+            .to_path_buf();
+
+        let env = roc_gen_dev::Env {
+            arena,
+            module_id: loaded.module_id,
+            exposed_to_host: loaded.exposed_to_host.values.keys().copied().collect(),
+            lazy_literals: true,
+            generate_allocators: false, // the support library has them
+        };
+
+        let module_out =
+            roc_gen_dev::build_module(&env, &mut interns, target, loaded.procedures.clone())
+                .write()
+                .ok()?;
+
+        // Each input gets a file of its own, so loading it can't find an earlier one.
+        self.inputs += 1;
+
+        let app_o_file = self.dir.path().join("app.o");
+
+        fs::write(&app_o_file, module_out).ok()?;
+
+        let dylib_path = self.link_dylib(
+            self.dir.path().join(format!("app{}", self.inputs)),
+            &[app_o_file.as_path(), support_path.as_path()],
+        )?;
+
+        let lib = unsafe { Library::new(&dylib_path) }.ok()?;
+
+        // It's loaded now, so the file isn't needed any more.
+        let _ = fs::remove_file(dylib_path);
+
+        Some((DevApp { lib }, main_fn_name))
+    }
+
+    /// The library with the builtins, the host, and the allocators, which is built and
+    /// loaded the first time an input needs it.
+    fn support_library(
+        &mut self,
+        arena: &Bump,
+        target: &Triple,
+        loaded: &MonomorphizedModule,
+        host_objects: &[&Path],
+    ) -> Option<&Path> {
+        if self.support.is_none() {
+            let mut interns = loaded.interns.clone();
+            let env = roc_gen_dev::Env {
+                arena,
+                module_id: loaded.module_id,
+                exposed_to_host: MutSet::default(),
+                lazy_literals: true,
+                generate_allocators: true, // the repl has no platform to provide them
+            };
+
+            let allocators_out =
+                roc_gen_dev::build_module(&env, &mut interns, target, MutMap::default())
+                    .write()
+                    .ok()?;
+
+            let allocators_o_file = self.dir.path().join("allocators.o");
+
+            fs::write(&allocators_o_file, allocators_out).ok()?;
+
+            let builtins_obj = bitcode::get_builtins_host_obj_path();
+            let mut input_paths = vec![allocators_o_file.as_path(), Path::new(&builtins_obj)];
+
+            input_paths.extend(host_objects);
+
+            let dylib_path = self.link_dylib(self.dir.path().join("support"), &input_paths)?;
+
+            // Inputs' dylibs find this by its soname, since it's already loaded.
+            let lib = unsafe { Library::new(&dylib_path) }.ok()?;
+
+            self.support = Some((lib, dylib_path));
+        }
+
+        self.support.as_ref().map(|(_, path)| path.as_path())
+    }
+
+    fn link_dylib(&self, output_path: PathBuf, input_paths: &[&Path]) -> Option<PathBuf> {
+        let input_paths = input_paths
+            .iter()
+            .map(|path| path.to_str())
+            .collect::<Option<Vec<_>>>()?;
+
+        let (mut child, dylib_path) =
+            link(&Triple::host(), output_path, &input_paths, LinkType::Dylib).ok()?;
+
+        if child.wait().ok()?.success() {
+            Some(dylib_path)
+        } else {
+            None
+        }
+    }
+}
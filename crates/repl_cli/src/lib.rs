@@ -1,34 +1,50 @@
+mod completion;
+mod dev;
+mod platform;
+
 use bumpalo::Bump;
 use const_format::concatcp;
 use inkwell::context::Context;
 use libloading::Library;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
-use roc_types::subs::Subs;
+use roc_types::subs::{Content, Subs};
+use rustyline::completion::Completer;
 use rustyline::highlight::{Highlighter, PromptInfo};
+use rustyline::hint::Hinter;
 use rustyline::validate::{self, ValidationContext, ValidationResult, Validator};
-use rustyline_derive::{Completer, Helper, Hinter};
+use rustyline_derive::Helper;
 use std::borrow::Cow;
 use std::io;
+use std::path::Path;
 use target_lexicon::Triple;
 
-use roc_build::link::llvm_module_to_dylib;
+use roc_build::link::llvm_module_to_dylib_with_host;
 use roc_collections::all::MutSet;
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_gen_llvm::{run_jit_function, run_jit_function_dynamic_type};
 use roc_load::{EntryPoint, MonomorphizedModule};
 use roc_mono::ir::OptLevel;
 use roc_parse::ast::Expr;
-use roc_parse::parser::{EExpr, ELambda, SyntaxError};
+use roc_parse::parser::{EExpr, ELambda};
 use roc_repl_eval::eval::jit_to_ast;
-use roc_repl_eval::gen::{compile_to_mono, format_answer, ReplOutput};
+use roc_repl_eval::gen::{
+    browse_module, check_syntax, compile_to_mono, format_answer, type_of, typecheck, ReplOutput,
+    SyntaxProblem,
+};
+use roc_repl_eval::state::ReplState;
 use roc_repl_eval::{ReplApp, ReplAppMemory};
 use roc_reporting::report::DEFAULT_PALETTE;
 use roc_std::RocStr;
 use roc_target::TargetInfo;
 use roc_types::pretty_print::{name_and_print_var, DebugPrint};
 
+use crate::completion::Completions;
+use crate::dev::DevBackend;
+use crate::platform::ReplPlatform;
+
 const BLUE: &str = "\u{001b}[36m";
 const PINK: &str = "\u{001b}[35m";
+const DIM: &str = "\u{001b}[2m";
 const END_COL: &str = "\u{001b}[0m";
 
 pub const WELCOME_MESSAGE: &str = concatcp!(
@@ -43,29 +59,79 @@ pub const WELCOME_MESSAGE: &str = concatcp!(
     "\n\n"
 );
 pub const INSTRUCTIONS: &str = "Enter an expression, or :help, or :q to quit.\n";
+const HELP: &str = "  <expr>            Evaluate an expression
+  <name> = <expr>   Define a value; end definitions with a blank line
+  :type <expr>      Show an expression's type without evaluating it
+  :load <path>      Bring the values and types a module exposes into scope
+  :reload           Re-read the modules loaded with :load
+  :browse <module>  List what a module exposes, with types
+  <task>            Run a task, e.g. Stdout.line \"Hi\", and show what it produces
+  :exit or :q       Quit";
 pub const PROMPT: &str = concatcp!("\n", BLUE, "»", END_COL, " ");
 pub const CONT_PROMPT: &str = concatcp!(BLUE, "…", END_COL, " ");
 
-#[derive(Completer, Helper, Hinter)]
+#[derive(Helper)]
 struct ReplHelper {
     validator: InputValidator,
     pending_src: String,
+    state: ReplState,
+    platform: ReplPlatform,
+    dev: DevBackend,
+    completions: Completions,
 }
 
 impl ReplHelper {
-    pub(crate) fn new() -> ReplHelper {
+    pub(crate) fn new(platform: ReplPlatform, dev: DevBackend) -> ReplHelper {
+        let state = ReplState::with_effects(platform.modules_dir().to_path_buf());
+
         ReplHelper {
             validator: InputValidator::new(),
             pending_src: String::new(),
+            completions: Completions::new(&state),
+            state,
+            platform,
+            dev,
+        }
+    }
+
+    /// Catches completions up with the session, e.g. after an input defined something.
+    fn update_completions(&mut self) {
+        if !self.completions.is_up_to_date(&self.state) {
+            self.completions = Completions::new(&self.state);
         }
     }
 }
 
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.completions.complete(line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> Option<String> {
+        self.completions.hint(line, pos)
+    }
+}
+
 impl Highlighter for ReplHelper {
     fn has_continuation_prompt(&self) -> bool {
         true
     }
 
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        format!("{}{}{}", DIM, hint, END_COL).into()
+    }
+
     fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
         &'s self,
         prompt: &'p str,
@@ -109,10 +175,18 @@ impl Validator for InputValidator {
             let state = roc_parse::state::State::new(ctx.input().trim().as_bytes());
 
             match roc_parse::expr::parse_loc_expr(0, &arena, state) {
-                // Special case some syntax errors to allow for multi-line inputs
+                // Definitions on their own get added to the session, but more definitions
+                // or an expression using them may follow, so wait for a blank line.
                 Err((_, EExpr::DefMissingFinalExpr(_), _))
-                | Err((_, EExpr::DefMissingFinalExpr2(_, _), _))
-                | Err((_, EExpr::Lambda(ELambda::Body(_, _), _), _)) => {
+                | Err((_, EExpr::DefMissingFinalExpr2(_, _), _)) => {
+                    if ctx.input().ends_with('\n') {
+                        Ok(ValidationResult::Valid(None))
+                    } else {
+                        Ok(ValidationResult::Incomplete)
+                    }
+                }
+                // Special case some syntax errors to allow for multi-line inputs
+                Err((_, EExpr::Lambda(ELambda::Body(_, _), _), _)) => {
                     Ok(ValidationResult::Incomplete)
                 }
                 _ => Ok(ValidationResult::Valid(None)),
@@ -206,6 +280,7 @@ pub fn mono_module_to_dylib<'a>(
     target: Triple,
     loaded: MonomorphizedModule,
     opt_level: OptLevel,
+    host_objects: &[&Path],
 ) -> Result<(libloading::Library, &'a str, Subs), libloading::Error> {
     let target_info = TargetInfo::from(&target);
 
@@ -242,6 +317,8 @@ pub fn mono_module_to_dylib<'a>(
         mode: LlvmBackendMode::GenTest, // so roc_panic is generated
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        source_info: None,
+        shadow_stack: false,
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
@@ -291,24 +368,73 @@ pub fn mono_module_to_dylib<'a>(
         );
     }
 
-    llvm_module_to_dylib(env.module, &target, opt_level).map(|lib| (lib, main_fn_name, subs))
+    llvm_module_to_dylib_with_host(env.module, &target, opt_level, host_objects)
+        .map(|lib| (lib, main_fn_name, subs))
+}
+
+/// Whether the output is a `Task` from the REPL's platform, which gets run rather than printed
+fn is_task(loaded: &MonomorphizedModule) -> bool {
+    loaded.exposed_to_host.values.values().any(|var| {
+        match loaded.subs.get_content_without_compacting(*var) {
+            Content::Alias(symbol, _, _, _) => {
+                let module_name = symbol.module_string(&loaded.interns).as_str();
+
+                symbol.as_str(&loaded.interns) == "Task"
+                    && (module_name == "Task" || module_name == "InternalTask")
+            }
+            _ => false,
+        }
+    })
 }
 
-fn gen_and_eval_llvm<'a>(
+/// Returns `Err` with a report of the syntax error if the input ends partway through
+/// something, since the lines after it may finish it.
+fn gen_and_eval(
     src: &str,
+    state: &mut ReplState,
+    dev: &mut DevBackend,
+    host: &Path,
     target: Triple,
     opt_level: OptLevel,
-) -> Result<ReplOutput, SyntaxError<'a>> {
+) -> Result<ReplOutput, String> {
+    match check_syntax(src, DEFAULT_PALETTE) {
+        Ok(()) => {}
+        Err(SyntaxProblem::Incomplete(report)) => return Err(report),
+        Err(SyntaxProblem::Invalid(report)) => return Ok(ReplOutput::Problems(vec![report])),
+    }
+
     let arena = Bump::new();
     let target_info = TargetInfo::from(&target);
+    let mut input = state.parse_input(src);
 
-    let mut loaded = match compile_to_mono(&arena, src, target_info, DEFAULT_PALETTE) {
+    let mut loaded = match compile_to_mono(&arena, state, &input, target_info, DEFAULT_PALETTE) {
         Ok(x) => x,
         Err(prob_strings) => {
             return Ok(ReplOutput::Problems(prob_strings));
         }
     };
 
+    if state.has_effects() && input.is_expr() && is_task(&loaded) {
+        // Compile it again to run the task, so what it produces gets printed.
+        input.run_task();
+
+        loaded = match compile_to_mono(&arena, state, &input, target_info, DEFAULT_PALETTE) {
+            Ok(x) => x,
+            Err(prob_strings) => {
+                return Ok(ReplOutput::Problems(prob_strings));
+            }
+        };
+    }
+
+    // Only definitions which compiled make it into the session.
+    let has_output = input.has_output();
+
+    state.add_defs(input);
+
+    if !has_output {
+        return Ok(ReplOutput::Empty);
+    }
+
     debug_assert_eq!(loaded.exposed_to_host.values.len(), 1);
     let (main_fn_symbol, main_fn_var) = loaded.exposed_to_host.values.iter().next().unwrap();
     let main_fn_symbol = *main_fn_symbol;
@@ -336,8 +462,32 @@ fn gen_and_eval_llvm<'a>(
 
     let interns = loaded.interns.clone();
 
+    // Try the dev backend first, since it's much faster than LLVM.
+    if let Some((mut app, main_fn_name)) = dev.mono_module_to_dylib(
+        &arena,
+        &target,
+        &loaded,
+        main_fn_symbol,
+        main_fn_layout,
+        &[host],
+    ) {
+        let res_answer = jit_to_ast(
+            &arena,
+            &mut app,
+            &main_fn_name,
+            main_fn_layout,
+            &content,
+            &loaded.subs,
+            &interns,
+            target_info,
+        );
+
+        return Ok(format_answer(&arena, res_answer, expr_type_str));
+    }
+
     let (lib, main_fn_name, subs) =
-        mono_module_to_dylib(&arena, target, loaded, opt_level).expect("we produce a valid Dylib");
+        mono_module_to_dylib(&arena, target, loaded, opt_level, &[host])
+            .expect("we produce a valid Dylib");
 
     let mut app = CliApp { lib };
 
@@ -356,19 +506,126 @@ fn gen_and_eval_llvm<'a>(
     Ok(formatted)
 }
 
-fn eval_and_format<'a>(src: &str) -> Result<String, SyntaxError<'a>> {
+fn eval_and_format(
+    src: &str,
+    state: &mut ReplState,
+    platform: &ReplPlatform,
+    dev: &mut DevBackend,
+) -> Result<String, String> {
     let format_output = |output| match output {
         ReplOutput::NoProblems { expr, expr_type } => {
             format!("\n{} {}:{} {}", expr, PINK, END_COL, expr_type)
         }
         ReplOutput::Problems(lines) => format!("\n{}\n", lines.join("\n\n")),
+        ReplOutput::Empty => String::new(),
     };
 
-    gen_and_eval_llvm(src, Triple::host(), OptLevel::Normal).map(format_output)
+    gen_and_eval(
+        src,
+        state,
+        dev,
+        &platform.host_path(),
+        Triple::host(),
+        OptLevel::Normal,
+    )
+    .map(format_output)
 }
 
-fn report_parse_error(fail: SyntaxError) {
-    println!("TODO Gracefully report parse error in repl: {:?}", fail);
+/// Runs a command like `:type`, returning what to print.
+fn run_command(line: &str, state: &mut ReplState) -> String {
+    let (command, argument) = match line.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (line, ""),
+    };
+    let target_info = TargetInfo::from(&Triple::host());
+
+    let format_problems = |lines: Vec<String>| format!("\n{}\n", lines.join("\n\n"));
+
+    match command {
+        ":type" | ":t" => {
+            let input = state.parse_input(argument);
+
+            if argument.is_empty() || !input.has_output() {
+                return "\nUse :type with an expression, like :type Str.concat".to_string();
+            }
+
+            if let Err(SyntaxProblem::Incomplete(report) | SyntaxProblem::Invalid(report)) =
+                check_syntax(argument, DEFAULT_PALETTE)
+            {
+                return format_problems(vec![report]);
+            }
+
+            match type_of(state, &input, target_info, DEFAULT_PALETTE) {
+                Ok(expr_type) => format!("\n{} {}:{} {}", argument, PINK, END_COL, expr_type),
+                Err(lines) => format_problems(lines),
+            }
+        }
+        ":load" | ":l" => {
+            if argument.is_empty() {
+                return "\nUse :load with the path to a module, like :load src/Parser.roc"
+                    .to_string();
+            }
+
+            let module_name = match state.load_module(Path::new(argument)) {
+                Ok(module_name) => module_name,
+                Err(problem) => return format!("\n{}", problem),
+            };
+
+            let input = state.parse_input("{}");
+
+            match typecheck(&Bump::new(), state, &input, target_info, DEFAULT_PALETTE) {
+                Ok(_) => format!("\nLoaded {}.", module_name),
+                Err(lines) => {
+                    state.unload_module(&module_name);
+
+                    format_problems(lines)
+                }
+            }
+        }
+        ":reload" | ":r" => {
+            let module_names = match state.reload_modules() {
+                Ok(module_names) if module_names.is_empty() => {
+                    return "\nThere are no modules to reload. Use :load to load one.".to_string();
+                }
+                Ok(module_names) => module_names,
+                Err(problem) => return format!("\n{}", problem),
+            };
+
+            let input = state.parse_input("{}");
+
+            match typecheck(&Bump::new(), state, &input, target_info, DEFAULT_PALETTE) {
+                Ok(_) => format!("\nReloaded {}.", module_names.join(", ")),
+                Err(lines) => format_problems(lines),
+            }
+        }
+        ":browse" | ":b" => {
+            if argument.is_empty() {
+                return "\nUse :browse with a module name, like :browse Str".to_string();
+            }
+
+            match browse_module(state, argument, target_info, DEFAULT_PALETTE) {
+                Ok(exposed) => {
+                    let mut buf = String::new();
+
+                    for (name, expr_type) in exposed {
+                        buf.push('\n');
+                        buf.push_str(name.as_str());
+
+                        if let Some(expr_type) = expr_type {
+                            buf.push_str(format!(" {}:{} {}", PINK, END_COL, expr_type).as_str());
+                        }
+                    }
+
+                    buf
+                }
+                Err(lines) => format_problems(lines),
+            }
+        }
+        _ => format!(
+            "\nI don't know the {} command. Use :help to see the ones I know.",
+            command
+        ),
+    }
 }
 
 pub fn main() -> io::Result<()> {
@@ -382,21 +639,31 @@ pub fn main() -> io::Result<()> {
 
     let mut prev_line_blank = false;
     let mut editor = Editor::<ReplHelper>::new();
-    let repl_helper = ReplHelper::new();
+    let repl_helper = ReplHelper::new(ReplPlatform::new()?, DevBackend::new()?);
     editor.set_helper(Some(repl_helper));
 
     loop {
-        let readline = editor.readline(PROMPT);
+        let is_pending = !editor
+            .helper()
+            .expect("Editor helper was not set")
+            .pending_src
+            .is_empty();
+        let readline = editor.readline(if is_pending { CONT_PROMPT } else { PROMPT });
 
         match readline {
             Ok(line) => {
                 let trim_line = line.trim();
-                editor.add_history_entry(trim_line);
 
-                let pending_src = &mut editor
-                    .helper_mut()
-                    .expect("Editor helper was not set")
-                    .pending_src;
+                let helper = editor.helper_mut().expect("Editor helper was not set");
+                let pending_src = &mut helper.pending_src;
+                let state = &mut helper.state;
+                let platform = &helper.platform;
+                let dev = &mut helper.dev;
+
+                // Inputs which span several lines go into the history as one entry once
+                // they're done, so they can be brought back and edited as a whole, e.g.
+                // to fix them after they fail to compile.
+                let mut history_entry = None;
 
                 match trim_line.to_lowercase().as_str() {
                     "" => {
@@ -405,16 +672,16 @@ pub fn main() -> io::Result<()> {
                         } else if prev_line_blank {
                             // After two blank lines in a row, give up and try parsing it
                             // even though it's going to fail. This way you don't get stuck.
-                            match eval_and_format(pending_src.as_str()) {
+                            match eval_and_format(pending_src.as_str(), state, platform, dev) {
                                 Ok(output) => {
                                     println!("{}", output);
                                 }
-                                Err(fail) => {
-                                    report_parse_error(fail);
+                                Err(report) => {
+                                    println!("\n{}\n", report);
                                 }
                             }
 
-                            pending_src.clear();
+                            history_entry = Some(std::mem::take(pending_src));
                         } else {
                             pending_src.push('\n');
 
@@ -423,37 +690,45 @@ pub fn main() -> io::Result<()> {
                         }
                     }
                     ":help" => {
-                        println!("Use :exit or :quit or :q to exit.");
+                        println!("{}", HELP);
+                        history_entry = Some(trim_line.to_string());
                     }
                     ":exit" | ":quit" | ":q" => {
                         break;
                     }
+                    command if command.starts_with(':') && pending_src.is_empty() => {
+                        println!("{}", run_command(trim_line, state));
+                        history_entry = Some(trim_line.to_string());
+                    }
                     _ => {
-                        let result = if pending_src.is_empty() {
-                            eval_and_format(trim_line)
+                        if pending_src.is_empty() {
+                            pending_src.push_str(trim_line);
                         } else {
+                            // Keep the indentation of the lines after the first.
                             pending_src.push('\n');
-                            pending_src.push_str(trim_line);
-
-                            eval_and_format(pending_src.as_str())
-                        };
+                            pending_src.push_str(line.trim_end());
+                        }
 
-                        match result {
-                            Ok(output) => {
-                                println!("{}", output);
-                                pending_src.clear();
-                            }
-                            //                            Err(Fail {
-                            //                                reason: FailReason::Eof(_),
-                            //                                ..
-                            //                            }) => {}
-                            Err(fail) => {
-                                report_parse_error(fail);
-                                pending_src.clear();
-                            }
+                        // If the input ends partway through something, this keeps it and
+                        // waits for the rest.
+                        if let Ok(output) =
+                            eval_and_format(pending_src.as_str(), state, platform, dev)
+                        {
+                            println!("{}", output);
+                            history_entry = Some(std::mem::take(pending_src));
                         }
                     }
                 }
+
+                if let Some(entry) = history_entry {
+                    editor.add_history_entry(entry);
+                }
+
+                // The input may have defined or loaded things, which changes what names mean.
+                editor
+                    .helper_mut()
+                    .expect("Editor helper was not set")
+                    .update_completions();
             }
             Err(ReadlineError::Interrupted) => {
                 println!("CTRL-C");
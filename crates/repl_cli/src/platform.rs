@@ -0,0 +1,53 @@
+//! The REPL's built-in platform, which lets it run tasks for printing, reading
+//! stdin, environment variables and files.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const MODULES: &[(&str, &str)] = &[
+    ("Effect.roc", include_str!("platform/Effect.roc")),
+    (
+        "InternalTask.roc",
+        include_str!("platform/InternalTask.roc"),
+    ),
+    ("Task.roc", include_str!("platform/Task.roc")),
+    ("Stdout.roc", include_str!("platform/Stdout.roc")),
+    ("Stdin.roc", include_str!("platform/Stdin.roc")),
+    ("Env.roc", include_str!("platform/Env.roc")),
+    ("File.roc", include_str!("platform/File.roc")),
+];
+
+/// Provides the `roc_fx_` functions `Effect.roc` declares. Built by `build.rs`.
+const HOST: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/repl_host.o"));
+
+const HOST_FILENAME: &str = "host.o";
+
+/// The platform's modules and host, written to a temporary directory for the
+/// session, since the loader and linker work with files.
+pub struct ReplPlatform {
+    dir: TempDir,
+}
+
+impl ReplPlatform {
+    pub fn new() -> io::Result<Self> {
+        let dir = tempfile::tempdir()?;
+
+        for (filename, src) in MODULES {
+            fs::write(dir.path().join(filename), src)?;
+        }
+
+        fs::write(dir.path().join(HOST_FILENAME), HOST)?;
+
+        Ok(ReplPlatform { dir })
+    }
+
+    pub fn modules_dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn host_path(&self) -> PathBuf {
+        self.dir.path().join(HOST_FILENAME)
+    }
+}
@@ -0,0 +1,16 @@
+hosted Effect
+    exposes [Effect, after, map, always, forever, loop, run, putLine, getLine, envVar, readFile]
+    imports []
+    generates Effect with [after, map, always, forever, loop]
+
+putLine : Str -> Effect {}
+
+getLine : Effect Str
+
+envVar : Str -> Effect { bytes : List U8, isSet : Bool }
+
+readFile : Str -> Effect { bytes : List U8, error : Str }
+
+## Performs an effect right away. Only the REPL does this, to run the tasks it's given.
+run : Effect a -> a
+run = \@Effect thunk -> thunk {}
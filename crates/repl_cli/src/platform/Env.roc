@@ -0,0 +1,16 @@
+interface Env
+    exposes [var]
+    imports [Effect, Task.{ Task }, InternalTask]
+
+## Reads an environment variable.
+var : Str -> Task Str [VarNotFound Str, VarNotUtf8 Str]* [Read [Env]*]*
+var = \name ->
+    Effect.envVar name
+    |> Effect.map
+        \result ->
+            if result.isSet then
+                Str.fromUtf8 result.bytes
+                |> Result.mapErr \_ -> VarNotUtf8 name
+            else
+                Err (VarNotFound name)
+    |> InternalTask.fromEffect
@@ -0,0 +1,16 @@
+interface File
+    exposes [readUtf8]
+    imports [Effect, Task.{ Task }, InternalTask]
+
+## Reads a whole file, which must be UTF-8. Paths are relative to where the REPL was started.
+readUtf8 : Str -> Task Str [FileReadErr Str Str, FileReadUtf8Err Str]* [Read [File]*]*
+readUtf8 = \path ->
+    Effect.readFile path
+    |> Effect.map
+        \result ->
+            if Str.isEmpty result.error then
+                Str.fromUtf8 result.bytes
+                |> Result.mapErr \_ -> FileReadUtf8Err path
+            else
+                Err (FileReadErr path result.error)
+    |> InternalTask.fromEffect
@@ -0,0 +1,14 @@
+interface InternalTask
+    exposes [Task, fromEffect, toEffect, run]
+    imports [Effect.{ Effect }]
+
+Task ok err fx := Effect (Result ok err)
+
+fromEffect : Effect (Result ok err) -> Task ok err *
+fromEffect = \effect -> @Task effect
+
+toEffect : Task ok err * -> Effect (Result ok err)
+toEffect = \@Task effect -> effect
+
+run : Task ok err * -> Result ok err
+run = \@Task effect -> Effect.run effect
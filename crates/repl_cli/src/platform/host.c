@@ -0,0 +1,209 @@
+#include <errno.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+/*
+    The host for the REPL's platform, providing the effects Effect.roc declares.
+    It gets linked into the dylib of every expression the REPL evaluates, next to
+    the roc_alloc and friends the REPL generates.
+*/
+
+extern void *roc_alloc(size_t size, unsigned int alignment);
+
+struct RocStr
+{
+    char *bytes;
+    size_t len;
+    size_t capacity;
+};
+
+struct RocList
+{
+    char *bytes;
+    size_t len;
+    size_t capacity;
+};
+
+// Both of these are { bytes : List U8, ... } records; record fields are
+// ordered by alignment, then by name.
+struct EnvVarResult
+{
+    struct RocList bytes;
+    bool isSet;
+};
+
+struct ReadFileResult
+{
+    struct RocList bytes;
+    struct RocStr error;
+};
+
+//--------------------------
+
+static bool is_small_str(const struct RocStr *str) { return ((ssize_t)str->capacity) < 0; }
+
+static size_t roc_str_len(const struct RocStr *str)
+{
+    if (is_small_str(str))
+    {
+        char last_byte = ((const char *)str)[sizeof(struct RocStr) - 1];
+
+        return (size_t)(last_byte ^ 0b10000000);
+    }
+    else
+    {
+        return str->len;
+    }
+}
+
+static const char *roc_str_bytes(const struct RocStr *str)
+{
+    return is_small_str(str) ? (const char *)str : str->bytes;
+}
+
+// A copy of the string with a nul terminator, for passing to libc. Free it when done.
+static char *roc_str_to_c(const struct RocStr *str)
+{
+    size_t len = roc_str_len(str);
+    char *c_str = malloc(len + 1);
+
+    memcpy(c_str, roc_str_bytes(str), len);
+    c_str[len] = '\0';
+
+    return c_str;
+}
+
+// Roc keeps a refcount right before the bytes of a heap allocation.
+static char *roc_alloc_refcounted(size_t len)
+{
+    intptr_t *allocation = roc_alloc(sizeof(intptr_t) + len, sizeof(intptr_t));
+
+    allocation[0] = INTPTR_MIN;
+
+    return (char *)(allocation + 1);
+}
+
+static struct RocList roc_list_from_bytes(const char *bytes, size_t len)
+{
+    struct RocList list = {NULL, 0, 0};
+
+    if (len > 0)
+    {
+        list.bytes = roc_alloc_refcounted(len);
+        list.len = len;
+        list.capacity = len;
+
+        memcpy(list.bytes, bytes, len);
+    }
+
+    return list;
+}
+
+static struct RocStr roc_str_from_c(const char *c_str)
+{
+    struct RocList list = roc_list_from_bytes(c_str, strlen(c_str));
+    struct RocStr str = {list.bytes, list.len, list.capacity};
+
+    return str;
+}
+
+//--------------------------
+
+void roc_fx_putLine(const struct RocStr *line)
+{
+    // Write straight to the file descriptor, so this isn't stuck in a buffer
+    // when the REPL prints the answer.
+    write(STDOUT_FILENO, roc_str_bytes(line), roc_str_len(line));
+    write(STDOUT_FILENO, "\n", 1);
+}
+
+//--------------------------
+
+struct RocStr roc_fx_getLine(void)
+{
+    char *line = NULL;
+    size_t buffer_size = 0;
+    ssize_t len = getline(&line, &buffer_size, stdin);
+    struct RocStr str = {NULL, 0, 0};
+
+    if (len > 0)
+    {
+        if (line[len - 1] == '\n')
+        {
+            line[len - 1] = '\0';
+        }
+
+        str = roc_str_from_c(line);
+    }
+
+    free(line);
+
+    return str;
+}
+
+//--------------------------
+
+struct EnvVarResult roc_fx_envVar(const struct RocStr *name)
+{
+    char *c_name = roc_str_to_c(name);
+    const char *value = getenv(c_name);
+    struct EnvVarResult result = {{NULL, 0, 0}, false};
+
+    if (value != NULL)
+    {
+        result.bytes = roc_list_from_bytes(value, strlen(value));
+        result.isSet = true;
+    }
+
+    free(c_name);
+
+    return result;
+}
+
+//--------------------------
+
+struct ReadFileResult roc_fx_readFile(const struct RocStr *path)
+{
+    char *c_path = roc_str_to_c(path);
+    FILE *file = fopen(c_path, "rb");
+    struct ReadFileResult result = {{NULL, 0, 0}, {NULL, 0, 0}};
+
+    free(c_path);
+
+    if (file == NULL)
+    {
+        result.error = roc_str_from_c(strerror(errno));
+
+        return result;
+    }
+
+    char *contents = NULL;
+    size_t len = 0;
+    char buffer[4096];
+    size_t read;
+
+    while ((read = fread(buffer, 1, sizeof(buffer), file)) > 0)
+    {
+        contents = realloc(contents, len + read);
+        memcpy(contents + len, buffer, read);
+        len += read;
+    }
+
+    if (ferror(file))
+    {
+        result.error = roc_str_from_c(strerror(errno));
+    }
+    else
+    {
+        result.bytes = roc_list_from_bytes(contents, len);
+    }
+
+    free(contents);
+    fclose(file);
+
+    return result;
+}
@@ -3,19 +3,87 @@ use roc_load::{ExecutionMode, LoadConfig, Threading};
 use roc_reporting::report::Palette;
 use std::path::PathBuf;
 
+use roc_can::expr::DeclarationTag;
+use roc_collections::all::MutMap;
 use roc_fmt::annotation::Formattable;
 use roc_fmt::annotation::{Newlines, Parens};
-use roc_load::{LoadingProblem, MonomorphizedModule};
+use roc_load::{LoadedModule, LoadingProblem, MonomorphizedModule};
+use roc_module::symbol::{Interns, ModuleId};
 use roc_parse::ast::Expr;
-use roc_region::all::LineInfo;
-use roc_reporting::report::{can_problem, type_problem, RocDocAllocator};
+use roc_parse::parser::{EExpr, FileError, Parser, SourceError, SyntaxError};
+use roc_parse::state::State;
+use roc_problem::can::Problem;
+use roc_region::all::{LineInfo, Position};
+use roc_reporting::report::{can_problem, parse_problem, type_problem, RocDocAllocator};
+use roc_solve_problem::TypeError;
 use roc_target::TargetInfo;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_types::subs::{Content, FlatType, Subs, Variable};
 
 use crate::eval::ToAstProblem;
+use crate::state::{ReplInput, ReplModule, ReplState};
 
 pub enum ReplOutput {
     Problems(Vec<String>),
-    NoProblems { expr: String, expr_type: String },
+    NoProblems {
+        expr: String,
+        expr_type: String,
+    },
+    /// The input only defined types, so there was no value to show
+    Empty,
+}
+
+/// A syntax error in what was typed into the REPL, reported against the lines as
+/// they were typed
+pub enum SyntaxProblem {
+    /// The input ends partway through something, e.g. `1 +`, so more lines may finish it.
+    Incomplete(String),
+    Invalid(String),
+}
+
+/// Parses an input on its own, so a syntax error can be reported with the input's
+/// own line numbers, rather than the ones of the module it gets compiled in.
+pub fn check_syntax(src: &str, palette: Palette) -> Result<(), SyntaxProblem> {
+    let arena = Bump::new();
+    let src = src.trim();
+    let state = State::new(src.as_bytes());
+
+    let (problem, rest) = match roc_parse::expr::parse_loc_expr(0, &arena, state) {
+        Ok(_) => return Ok(()),
+        // Definitions on their own go into the session.
+        Err((_, EExpr::DefMissingFinalExpr(_), _))
+        | Err((_, EExpr::DefMissingFinalExpr2(_, _), _)) => {
+            match roc_parse::module::module_defs().parse(&arena, State::new(src.as_bytes())) {
+                Ok(_) => return Ok(()),
+                Err((_, problem, rest)) => (problem, rest),
+            }
+        }
+        Err((_, problem, rest)) => (SyntaxError::Expr(problem, Position::default()), rest),
+    };
+
+    let src_lines: Vec<&str> = src.split('\n').collect();
+    let line_info = LineInfo::new(src);
+    let mut interns = Interns::default();
+    let home = interns.module_ids.get_or_insert(&"REPL".into());
+    let alloc = RocDocAllocator::new(&src_lines, home, &interns);
+
+    let file_error = FileError {
+        problem: SourceError {
+            problem,
+            bytes: src.as_bytes(),
+        },
+        filename: PathBuf::from(""),
+    };
+    let report = parse_problem(&alloc, &line_info, PathBuf::from(""), 0, file_error);
+    let mut buf = String::new();
+
+    report.render_color_terminal(&mut buf, &alloc, &palette);
+
+    if rest.has_reached_end() {
+        Err(SyntaxProblem::Incomplete(buf))
+    } else {
+        Err(SyntaxProblem::Invalid(buf))
+    }
 }
 
 pub fn format_answer(
@@ -44,21 +112,22 @@ pub fn format_answer(
 
 pub fn compile_to_mono<'a>(
     arena: &'a Bump,
-    src: &str,
+    state: &ReplState,
+    input: &ReplInput,
     target_info: TargetInfo,
     palette: Palette,
 ) -> Result<MonomorphizedModule<'a>, Vec<String>> {
     let filename = PathBuf::from("");
-    let src_dir = PathBuf::from("fake/test/path");
 
-    let module_src = arena.alloc(promote_expr_to_module(src));
+    let module = state.module_src(input);
+    let module_src = arena.alloc(module.src.clone());
 
     let exposed_types = Default::default();
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
         filename,
         module_src,
-        src_dir,
+        state.src_dir(),
         exposed_types,
         LoadConfig {
             target_info,
@@ -79,6 +148,64 @@ pub fn compile_to_mono<'a>(
     };
 
     let MonomorphizedModule {
+        module_id,
+        interns,
+        sources,
+        can_problems,
+        type_problems,
+        ..
+    } = &mut loaded;
+
+    let lines = report_problems(
+        sources,
+        can_problems,
+        type_problems,
+        interns,
+        *module_id,
+        &module,
+        palette,
+    );
+
+    if !lines.is_empty() {
+        Err(lines)
+    } else {
+        Ok(loaded)
+    }
+}
+
+/// Type-checks an input without evaluating it.
+pub fn typecheck(
+    arena: &Bump,
+    state: &ReplState,
+    input: &ReplInput,
+    target_info: TargetInfo,
+    palette: Palette,
+) -> Result<LoadedModule, Vec<String>> {
+    let module = state.module_src(input);
+    let module_src = arena.alloc(module.src.clone());
+
+    let loaded = roc_load::load_and_typecheck_str(
+        arena,
+        PathBuf::from(""),
+        module_src,
+        state.src_dir(),
+        Default::default(),
+        target_info,
+        roc_reporting::report::RenderTarget::ColorTerminal,
+    );
+
+    let mut loaded = match loaded {
+        Ok(v) => v,
+        Err(LoadingProblem::FormattedReport(report)) => {
+            return Err(vec![report]);
+        }
+        Err(e) => {
+            panic!("error while loading module: {:?}", e)
+        }
+    };
+
+    let LoadedModule {
+        module_id,
         interns,
         sources,
         can_problems,
@@ -86,6 +213,203 @@ pub fn compile_to_mono<'a>(
         ..
     } = &mut loaded;
 
+    let lines = report_problems(
+        sources,
+        can_problems,
+        type_problems,
+        interns,
+        *module_id,
+        &module,
+        palette,
+    );
+
+    if !lines.is_empty() {
+        Err(lines)
+    } else {
+        Ok(loaded)
+    }
+}
+
+/// The type of an expression, for `:type`
+pub fn type_of(
+    state: &ReplState,
+    input: &ReplInput,
+    target_info: TargetInfo,
+    palette: Palette,
+) -> Result<String, Vec<String>> {
+    let arena = Bump::new();
+    let mut loaded = typecheck(&arena, state, input, target_info, palette)?;
+    let home = loaded.module_id;
+
+    let (_, var) = loaded
+        .exposed_to_host
+        .iter()
+        .next()
+        .expect("replOutput is always exposed");
+
+    Ok(name_and_print_var(
+        *var,
+        loaded.solved.inner_mut(),
+        home,
+        &loaded.interns,
+        DebugPrint::NOTHING,
+    ))
+}
+
+/// The names a module exposes, along with the types of its values, for `:browse`.
+/// Types and abilities don't have a type of their own, so they're listed by name.
+pub fn browse_module(
+    state: &ReplState,
+    module_name: &str,
+    target_info: TargetInfo,
+    palette: Palette,
+) -> Result<Vec<(String, Option<String>)>, Vec<String>> {
+    let exposes = match state.module_exposes(module_name) {
+        Some(exposes) => exposes,
+        None => {
+            return Err(vec![format!(
+                "There's no module named {}. Builtin modules and ones loaded with :load can be browsed.",
+                module_name
+            )])
+        }
+    };
+
+    let values: Vec<String> = exposes
+        .iter()
+        .filter(|name| name.starts_with(char::is_lowercase))
+        .map(|name| format!("{}.{}", module_name, name))
+        .collect();
+
+    let mut types = name_types(state, &values, target_info, palette)?;
+
+    Ok(exposes
+        .into_iter()
+        .map(|name| {
+            let expr_type = types
+                .remove(&format!("{}.{}", module_name, name))
+                .map(|name_type| name_type.expr_type);
+
+            (name, expr_type)
+        })
+        .collect())
+}
+
+/// The type of a name like `x` or `Str.concat`, and its fields if it's a record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameType {
+    pub expr_type: String,
+    pub record_fields: Vec<String>,
+}
+
+/// The types of the given names, found with one type-check of the session. The fields
+/// of records get included too, with names like `point.x`.
+pub fn name_types(
+    state: &ReplState,
+    names: &[String],
+    target_info: TargetInfo,
+    palette: Palette,
+) -> Result<MutMap<String, NameType>, Vec<String>> {
+    // Look up each name's type by giving it a name of our own, since the session
+    // may already use a module's exposed name for something else.
+    let defs = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let def_name = format!("replName{}", index);
+            let src = format!("{} = {}", def_name, name);
+
+            (def_name, src)
+        })
+        .collect();
+
+    let arena = Bump::new();
+    let input = ReplInput::hidden_defs(defs);
+    let mut loaded = typecheck(&arena, state, &input, target_info, palette)?;
+    let home = loaded.module_id;
+    let mut types = MutMap::default();
+
+    if let Some(declarations) = loaded.declarations_by_id.get(&home) {
+        for (index, tag) in declarations.declarations.iter().enumerate() {
+            use DeclarationTag::*;
+
+            if let Value | Function(_) | Recursive(_) | TailRecursive(_) = tag {
+                let symbol = declarations.symbols[index].value;
+                let name_index = symbol
+                    .as_str(&loaded.interns)
+                    .strip_prefix("replName")
+                    .and_then(|index| index.parse::<usize>().ok());
+
+                if let Some(name_index) = name_index {
+                    add_name_type(
+                        &mut types,
+                        names[name_index].clone(),
+                        declarations.variables[index],
+                        loaded.solved.inner_mut(),
+                        home,
+                        &loaded.interns,
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(types)
+}
+
+fn add_name_type(
+    types: &mut MutMap<String, NameType>,
+    name: String,
+    var: Variable,
+    subs: &mut Subs,
+    home: ModuleId,
+    interns: &Interns,
+) {
+    let fields = record_fields(subs, var);
+
+    for (field, field_var) in fields.iter() {
+        add_name_type(
+            types,
+            format!("{}.{}", name, field),
+            *field_var,
+            subs,
+            home,
+            interns,
+        );
+    }
+
+    let name_type = NameType {
+        expr_type: name_and_print_var(var, subs, home, interns, DebugPrint::NOTHING),
+        record_fields: fields.into_iter().map(|(field, _)| field).collect(),
+    };
+
+    types.insert(name, name_type);
+}
+
+/// The fields of a record type. Anything that isn't a record has none.
+fn record_fields(subs: &Subs, mut var: Variable) -> Vec<(String, Variable)> {
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Content::Alias(_, _, real_var, _) => var = *real_var,
+            Content::Structure(FlatType::Record(fields, ext)) => {
+                return fields
+                    .sorted_iterator(subs, *ext)
+                    .map(|(label, field)| (label.as_str().to_string(), field.into_inner()))
+                    .collect();
+            }
+            _ => return Vec::new(),
+        }
+    }
+}
+
+fn report_problems(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    can_problems: &mut MutMap<ModuleId, Vec<Problem>>,
+    type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    interns: &Interns,
+    repl_module: ModuleId,
+    module: &ReplModule,
+    palette: Palette,
+) -> Vec<String> {
     let mut lines = Vec::new();
 
     for (home, (module_path, src)) in sources.iter() {
@@ -98,13 +422,29 @@ pub fn compile_to_mono<'a>(
             continue;
         }
 
-        let line_info = LineInfo::new(module_src);
+        let line_info = LineInfo::new(src);
         let src_lines: Vec<&str> = src.split('\n').collect();
 
         // Report parsing and canonicalization problems
         let alloc = RocDocAllocator::new(&src_lines, *home, interns);
 
+        // Show the lines which were typed the way they were typed, rather than as part
+        // of the module they get compiled in.
+        let alloc = if *home == repl_module {
+            alloc.with_line_origins(&module.line_origins)
+        } else {
+            alloc
+        };
+
         for problem in can_probs.into_iter() {
+            // Definitions and modules from earlier inputs don't need to be used by this one,
+            // and modules loaded into the session can have unused things of their own.
+            if let Problem::UnusedDef(_, region) | Problem::UnusedImport(_, region) = &problem {
+                if *home != repl_module || (region.start().offset as usize) < module.defs_end {
+                    continue;
+                }
+            }
+
             let report = can_problem(&alloc, &line_info, module_path.clone(), problem);
             let mut buf = String::new();
 
@@ -124,23 +464,5 @@ pub fn compile_to_mono<'a>(
         }
     }
 
-    if !lines.is_empty() {
-        Err(lines)
-    } else {
-        Ok(loaded)
-    }
-}
-
-fn promote_expr_to_module(src: &str) -> String {
-    let mut buffer =
-        String::from("app \"app\" provides [replOutput] to \"./platform\"\n\nreplOutput =\n");
-
-    for line in src.lines() {
-        // indent the body!
-        buffer.push_str("    ");
-        buffer.push_str(line);
-        buffer.push('\n');
-    }
-
-    buffer
+    lines
 }
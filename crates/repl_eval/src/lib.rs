@@ -4,6 +4,7 @@ use roc_target::TargetInfo;
 
 pub mod eval;
 pub mod gen;
+pub mod state;
 
 pub trait ReplApp<'a> {
     type Memory: 'a + ReplAppMemory;
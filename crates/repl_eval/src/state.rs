@@ -0,0 +1,956 @@
+use bumpalo::Bump;
+use roc_collections::all::MutSet;
+use roc_module::symbol::{ModuleId, ModuleIds};
+use roc_parse::ast::{AssignedField, Defs, Expr, Module, Pattern, Spaced, StrLiteral, StrSegment};
+use roc_parse::ast::{TypeDef, ValueDef};
+use roc_parse::header::ExposedName;
+use roc_parse::parser::{EExpr, Parser};
+use roc_parse::state::State;
+use roc_region::all::{Loc, Position, Region};
+use roc_reporting::report::LineOrigin;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Modules of the platform which runs tasks, when the REPL has one
+const EFFECT_MODULES: &[&str] = &["Task", "Stdout", "Stdin", "Env", "File"];
+
+/// Definitions entered into the REPL so far. Each input gets compiled in a module
+/// which has all of them as top-level definitions, so later inputs can use them.
+#[derive(Debug, Default)]
+pub struct ReplState {
+    past_defs: Vec<PastDef>,
+    loaded_modules: Vec<LoadedFile>,
+    /// Where the modules of the platform which runs tasks are
+    effects_dir: Option<PathBuf>,
+    /// How many times definitions or modules have been added or changed
+    changes: usize,
+}
+
+/// A module brought into scope with `:load`
+#[derive(Debug)]
+struct LoadedFile {
+    path: PathBuf,
+    name: String,
+    exposes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct PastDef {
+    kind: PastDefKind,
+    /// The names this definition introduces
+    names: Vec<String>,
+    /// Versions like `xShadowed1` of names it used to introduce before later
+    /// definitions shadowed them, which the definitions using them now refer to
+    shadowed_names: Vec<String>,
+    /// An annotation for it which was entered on its own, before it
+    annotation: Option<String>,
+    src: String,
+    /// The line of its input this definition starts on
+    line: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PastDefKind {
+    /// A type annotation without a body, like `x : Str`
+    Annotation,
+    /// A body without a type annotation, like `x = "Hi"`
+    Body,
+    /// A type alias, opaque type or ability
+    Type,
+    /// An annotated body or an `expect`
+    Other,
+}
+
+impl PastDef {
+    fn all_names(&self) -> impl Iterator<Item = &String> {
+        self.names.iter().chain(self.shadowed_names.iter())
+    }
+
+    fn uses(&self, name: &str) -> bool {
+        !name_edits(&self.src, name).is_empty()
+    }
+
+    fn rename(&mut self, name: &str, new_name: &str) {
+        self.src = rename(&self.src, name, new_name);
+
+        if let Some(annotation) = self.annotation.as_mut() {
+            *annotation = rename(annotation, name, new_name);
+        }
+    }
+
+    /// Its source, along with its annotation if that was entered on its own
+    fn full_src(&self) -> String {
+        match &self.annotation {
+            Some(annotation) => format!("{}\n{}", annotation, self.src),
+            None => self.src.clone(),
+        }
+    }
+}
+
+/// The module an input gets compiled in
+#[derive(Debug)]
+pub struct ReplModule {
+    pub src: String,
+    /// Where the session's definitions end and `replOutput` begins
+    pub defs_end: usize,
+    /// Which of the module's lines were typed in this input, so reports can show
+    /// them the way they were typed
+    pub line_origins: Vec<Option<LineOrigin>>,
+}
+
+impl ReplModule {
+    fn push_generated(&mut self, src: &str) {
+        self.src.push_str(src);
+        self.line_origins.extend(src.matches('\n').map(|_| None));
+    }
+
+    /// Adds each line with the given indentation in front of it. `first_line` is the
+    /// line of the input they start on, if they were typed rather than generated.
+    fn push_lines(&mut self, src: &str, indent: u32, first_line: Option<u32>) {
+        for (index, line) in src.lines().enumerate() {
+            self.src
+                .extend(std::iter::repeat(' ').take(indent as usize));
+            self.src.push_str(line);
+            self.src.push('\n');
+            self.line_origins
+                .push(first_line.map(|first_line| LineOrigin {
+                    line_number: first_line + index as u32,
+                    added_indent: indent,
+                }));
+        }
+    }
+}
+
+/// What the REPL was given: either an expression to evaluate, or definitions to
+/// add to the session.
+#[derive(Debug)]
+pub struct ReplInput {
+    new_defs: Vec<PastDef>,
+    /// The expression whose value gets printed. For definitions, this is the last
+    /// value they define, if any.
+    output_expr: Option<String>,
+    /// Whether `output_expr` is a task to run, printing what it produces
+    run_task: bool,
+}
+
+impl ReplInput {
+    /// Definitions which only get type-checked, e.g. so `:browse` can look up types.
+    pub fn hidden_defs(defs: Vec<(String, String)>) -> Self {
+        ReplInput {
+            new_defs: defs
+                .into_iter()
+                .map(|(name, src)| PastDef {
+                    kind: PastDefKind::Body,
+                    names: vec![name],
+                    shadowed_names: Vec::new(),
+                    annotation: None,
+                    src,
+                    line: 1,
+                })
+                .collect(),
+            output_expr: None,
+            run_task: false,
+        }
+    }
+
+    pub fn has_output(&self) -> bool {
+        self.output_expr.is_some()
+    }
+
+    /// Whether this is an expression on its own, as opposed to definitions
+    pub fn is_expr(&self) -> bool {
+        self.new_defs.is_empty() && self.output_expr.is_some()
+    }
+
+    /// Makes the output what running the expression's task produces.
+    pub fn run_task(&mut self) {
+        self.run_task = true;
+    }
+}
+
+impl ReplState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A session which can run tasks, using the platform modules in the given directory.
+    pub fn with_effects(effects_dir: PathBuf) -> Self {
+        ReplState {
+            effects_dir: Some(effects_dir),
+            ..Self::default()
+        }
+    }
+
+    pub fn has_effects(&self) -> bool {
+        self.effects_dir.is_some()
+    }
+
+    pub fn parse_input(&self, src: &str) -> ReplInput {
+        let arena = Bump::new();
+        let src = src.trim();
+        let state = State::new(src.as_bytes());
+
+        let as_expr = ReplInput {
+            new_defs: Vec::new(),
+            output_expr: Some(src.to_string()),
+            run_task: false,
+        };
+
+        match roc_parse::expr::parse_loc_expr(0, &arena, state) {
+            // Definitions without an expression after them go into the session.
+            Err((_, EExpr::DefMissingFinalExpr(_), _))
+            | Err((_, EExpr::DefMissingFinalExpr2(_, _), _)) => {
+                match roc_parse::module::module_defs().parse(&arena, State::new(src.as_bytes())) {
+                    Ok((_, defs, _)) => defs_input(src, &defs),
+                    // Compiling it as an expression reports the syntax error.
+                    Err(_) => as_expr,
+                }
+            }
+            _ => as_expr,
+        }
+    }
+
+    /// The module to compile for this input
+    pub fn module_src(&self, input: &ReplInput) -> ReplModule {
+        let defs = self.defs_with(&input.new_defs);
+        let mut header = String::from("app \"app\"");
+        let mut imports: Vec<String> = Vec::new();
+
+        if let Some(effects_dir) = &self.effects_dir {
+            // Only the directory of a package's path is used, to find its modules in.
+            let package_path = effects_dir.join("main.roc");
+
+            header.push_str(
+                format!(
+                    " packages {{ pf: {:?} }}",
+                    package_path.display().to_string()
+                )
+                .as_str(),
+            );
+
+            imports.extend(EFFECT_MODULES.iter().map(|name| format!("pf.{}", name)));
+            imports.push("pf.InternalTask".to_string());
+        }
+
+        // A loaded module's names are only brought into scope unqualified when the
+        // session doesn't define them too; otherwise they'd clash with its definitions.
+        let defined_names: MutSet<&str> = defs
+            .iter()
+            .flat_map(|def| def.names.iter().map(String::as_str))
+            .collect();
+
+        imports.extend(self.loaded_modules.iter().map(|module| {
+            let exposes: Vec<&str> = module
+                .exposes
+                .iter()
+                .map(String::as_str)
+                .filter(|name| !defined_names.contains(name))
+                .collect();
+
+            match exposes.as_slice() {
+                [] => module.name.clone(),
+                exposes => format!("{}.{{ {} }}", module.name, exposes.join(", ")),
+            }
+        }));
+
+        if !imports.is_empty() {
+            header.push_str(format!(" imports [{}]", imports.join(", ")).as_str());
+        }
+
+        header.push_str(" provides [replOutput] to \"./platform\"\n\n");
+
+        let mut module = ReplModule {
+            src: String::new(),
+            defs_end: 0,
+            line_origins: Vec::new(),
+        };
+
+        module.push_generated(header.as_str());
+
+        let (past_defs, new_defs) = defs.split_at(defs.len() - input.new_defs.len());
+
+        for def in needed_defs(past_defs, new_defs, input.output_expr.as_deref()) {
+            module.push_generated(def.full_src().as_str());
+            module.push_generated("\n\n");
+        }
+
+        for def in new_defs {
+            if let Some(annotation) = &def.annotation {
+                module.push_generated(annotation.as_str());
+                module.push_generated("\n");
+            }
+
+            module.push_lines(def.src.as_str(), 0, Some(def.line));
+            module.push_generated("\n");
+        }
+
+        module.defs_end = module.src.len();
+
+        if input.run_task {
+            module.push_generated("replTask =\n");
+        } else {
+            module.push_generated("replOutput =\n");
+        }
+
+        // For definitions, the output is the last value they define, which wasn't typed.
+        let expr_line = if input.new_defs.is_empty() {
+            Some(1)
+        } else {
+            None
+        };
+
+        // indent the body!
+        module.push_lines(input.output_expr.as_deref().unwrap_or("{}"), 4, expr_line);
+
+        if input.run_task {
+            module.push_generated("\nreplOutput = InternalTask.run replTask\n");
+        }
+
+        module
+    }
+
+    /// Where imports get loaded from: the directory of the modules loaded with `:load`.
+    pub fn src_dir(&self) -> PathBuf {
+        match self.loaded_modules.first() {
+            Some(module) => module_src_dir(&module.path, &module.name),
+            None => PathBuf::from("fake/test/path"),
+        }
+    }
+
+    /// Brings the values and types a module exposes into scope. Returns the module's name.
+    pub fn load_module(&mut self, path: &Path) -> Result<String, String> {
+        let (name, exposes) = read_interface_header(path)?;
+
+        if let Some(loaded) = self.loaded_modules.iter().find(|m| m.name != name) {
+            let src_dir = module_src_dir(path, &name);
+
+            // The loader finds imported modules by name, relative to one directory.
+            if src_dir != module_src_dir(&loaded.path, &loaded.name) {
+                return Err(format!(
+                    "{} is not in {}, where the other loaded modules are.",
+                    path.display(),
+                    self.src_dir().display()
+                ));
+            }
+        }
+
+        self.loaded_modules.retain(|module| module.name != name);
+        self.loaded_modules.push(LoadedFile {
+            path: path.to_path_buf(),
+            name: name.clone(),
+            exposes,
+        });
+        self.changes += 1;
+
+        Ok(name)
+    }
+
+    /// Re-reads what each loaded module exposes. Returns the names of the modules.
+    pub fn reload_modules(&mut self) -> Result<Vec<String>, String> {
+        for module in self.loaded_modules.iter_mut() {
+            let (_, exposes) = read_interface_header(&module.path)?;
+
+            module.exposes = exposes;
+        }
+
+        self.changes += 1;
+
+        Ok(self
+            .loaded_modules
+            .iter()
+            .map(|module| module.name.clone())
+            .collect())
+    }
+
+    /// Undoes `load_module`, for when a module turns out not to compile.
+    pub fn unload_module(&mut self, name: &str) {
+        self.loaded_modules.retain(|module| module.name != name);
+        self.changes += 1;
+    }
+
+    /// Goes up whenever what the session's names refer to may have changed, so anything
+    /// worked out from them, like their types, can tell when it's out of date.
+    pub fn changes(&self) -> usize {
+        self.changes
+    }
+
+    /// The names a loaded, builtin or platform module exposes
+    pub fn module_exposes(&self, module_name: &str) -> Option<Vec<String>> {
+        if let Some(module) = self
+            .loaded_modules
+            .iter()
+            .find(|module| module.name == module_name)
+        {
+            return Some(module.exposes.clone());
+        }
+
+        if let Some(effects_dir) = &self.effects_dir {
+            if EFFECT_MODULES.contains(&module_name) {
+                let path = effects_dir.join(module_name).with_extension("roc");
+
+                return read_interface_header(&path)
+                    .ok()
+                    .map(|(_, exposes)| exposes);
+            }
+        }
+
+        let (_, module_id) = builtin_modules()
+            .into_iter()
+            .find(|(name, _)| name == module_name)?;
+
+        header_exposes(roc_builtins::roc::module_source(module_id))
+            .ok()
+            .map(|(_, exposes)| exposes)
+    }
+
+    /// The names of every module which can be used: builtins, the platform's modules,
+    /// and modules loaded with `:load`
+    pub fn module_names(&self) -> Vec<String> {
+        let effect_modules = match self.effects_dir {
+            Some(_) => EFFECT_MODULES,
+            None => &[],
+        };
+
+        Self::builtin_module_names()
+            .into_iter()
+            .chain(effect_modules.iter().map(|name| name.to_string()))
+            .chain(self.loaded_modules.iter().map(|module| module.name.clone()))
+            .collect()
+    }
+
+    /// The names of the builtin modules, which are the same in every session
+    pub fn builtin_module_names() -> Vec<String> {
+        builtin_modules()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// The names defined so far in this session
+    pub fn defined_names(&self) -> Vec<String> {
+        self.past_defs
+            .iter()
+            .flat_map(|def| def.names.iter().cloned())
+            .collect()
+    }
+
+    /// Adds an input's definitions to the session, shadowing any earlier
+    /// definitions of the same names.
+    pub fn add_defs(&mut self, input: ReplInput) {
+        if !input.new_defs.is_empty() {
+            self.past_defs = self.defs_with(&input.new_defs);
+            self.changes += 1;
+        }
+    }
+
+    /// The session's definitions once the given ones get added after them.
+    ///
+    /// An earlier definition of a name which gets defined again is dropped, unless other
+    /// definitions use it. Then it gets renamed to a version like `xShadowed1` everywhere,
+    /// so those definitions keep their meaning. An annotation which was entered on its own
+    /// goes with the next definition of its name instead.
+    fn defs_with(&self, new_defs: &[PastDef]) -> Vec<PastDef> {
+        let mut defs = self.past_defs.clone();
+        let mut new_defs = new_defs.to_vec();
+
+        for new_index in 0..new_defs.len() {
+            for name in new_defs[new_index].names.clone() {
+                let old_index = match defs.iter().position(|def| def.names.contains(&name)) {
+                    Some(index) => index,
+                    None => continue,
+                };
+
+                let new_def = &mut new_defs[new_index];
+                let old_def = &defs[old_index];
+
+                if old_def.kind == PastDefKind::Annotation
+                    && old_def.names == [name.as_str()]
+                    && new_def.kind == PastDefKind::Body
+                    && new_def.annotation.is_none()
+                {
+                    new_def.annotation = Some(defs.remove(old_index).src);
+                    continue;
+                }
+
+                let is_used = defs[old_index + 1..].iter().any(|def| def.uses(&name));
+
+                if !is_used && old_def.names.len() == 1 && old_def.shadowed_names.is_empty() {
+                    defs.remove(old_index);
+                    continue;
+                }
+
+                let version = (1..)
+                    .map(|version| format!("{}Shadowed{}", name, version))
+                    .find(|version| {
+                        !defs
+                            .iter()
+                            .chain(new_defs.iter())
+                            .any(|def| def.all_names().any(|existing| existing == version))
+                    })
+                    .unwrap();
+
+                for def in defs[old_index..].iter_mut() {
+                    def.rename(&name, &version);
+                }
+
+                let old_def = &mut defs[old_index];
+
+                old_def.names.retain(|old_name| *old_name != name);
+                old_def.shadowed_names.push(version);
+            }
+        }
+
+        defs.extend(new_defs);
+
+        defs
+    }
+}
+
+/// The earlier definitions an input needs: the ones it uses, the ones those use, and so
+/// on, along with every type definition. Only these get compiled along with the input,
+/// so that what a session has accumulated doesn't slow down every input after it.
+fn needed_defs<'a>(
+    past_defs: &'a [PastDef],
+    new_defs: &'a [PastDef],
+    output_expr: Option<&'a str>,
+) -> Vec<&'a PastDef> {
+    // Telling which names some code uses precisely would mean resolving its scopes, so
+    // this goes by the words in it, which can only bring in more definitions than needed.
+    fn add_words<'a>(words: &mut MutSet<&'a str>, src: &'a str) {
+        words.extend(
+            src.split(|c: char| !c.is_alphanumeric())
+                .filter(|word| word.starts_with(char::is_lowercase)),
+        );
+    }
+
+    let mut words = MutSet::default();
+    let mut is_needed: Vec<bool> = past_defs
+        .iter()
+        .map(|def| def.kind == PastDefKind::Type)
+        .collect();
+
+    for def in new_defs.iter() {
+        add_words(&mut words, &def.src);
+    }
+
+    if let Some(output_expr) = output_expr {
+        add_words(&mut words, output_expr);
+    }
+
+    let mut added_words = true;
+
+    while added_words {
+        added_words = false;
+
+        for (def, is_needed) in past_defs.iter().zip(is_needed.iter_mut()) {
+            if !*is_needed && def.all_names().any(|name| words.contains(name.as_str())) {
+                *is_needed = true;
+            }
+
+            if *is_needed {
+                let len = words.len();
+
+                add_words(&mut words, &def.src);
+                add_words(&mut words, def.annotation.as_deref().unwrap_or(""));
+                added_words |= words.len() > len;
+            }
+        }
+    }
+
+    past_defs
+        .iter()
+        .zip(is_needed)
+        .filter(|(_, is_needed)| *is_needed)
+        .map(|(def, _)| def)
+        .collect()
+}
+
+fn defs_input(src: &str, defs: &Defs) -> ReplInput {
+    let mut new_defs = Vec::with_capacity(defs.len());
+    let mut output_expr = None;
+
+    for (def, region) in defs.defs().zip(defs.regions.iter()) {
+        let mut names = Vec::new();
+
+        let kind = match def {
+            Ok(TypeDef::Alias { header, .. })
+            | Ok(TypeDef::Opaque { header, .. })
+            | Ok(TypeDef::Ability { header, .. }) => {
+                names.push(header.name.value.to_string());
+
+                PastDefKind::Type
+            }
+            Err(ValueDef::Annotation(pattern, _)) => {
+                pattern_names(&pattern.value, &mut names);
+
+                PastDefKind::Annotation
+            }
+            Err(ValueDef::Body(pattern, _)) => {
+                pattern_names(&pattern.value, &mut names);
+
+                PastDefKind::Body
+            }
+            Err(ValueDef::AnnotatedBody { body_pattern, .. }) => {
+                pattern_names(&body_pattern.value, &mut names);
+
+                PastDefKind::Other
+            }
+            Err(ValueDef::Expect { .. }) | Err(ValueDef::ExpectFx { .. }) => PastDefKind::Other,
+        };
+
+        if let PastDefKind::Body | PastDefKind::Other = kind {
+            if let Some(name) = names.last() {
+                output_expr = Some(name.clone());
+            }
+        }
+
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+
+        new_defs.push(PastDef {
+            kind,
+            names,
+            shadowed_names: Vec::new(),
+            annotation: None,
+            src: src[start..end].to_string(),
+            line: src[..start].matches('\n').count() as u32 + 1,
+        });
+    }
+
+    ReplInput {
+        new_defs,
+        output_expr,
+        run_task: false,
+    }
+}
+
+fn pattern_names(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Identifier(name) => names.push(name.to_string()),
+        Pattern::Apply(_, args) => {
+            for arg in args.iter() {
+                pattern_names(&arg.value, names);
+            }
+        }
+        Pattern::RecordDestructure(fields) => {
+            for field in fields.iter() {
+                pattern_names(&field.value, names);
+            }
+        }
+        Pattern::RequiredField(_, nested) => pattern_names(&nested.value, names),
+        Pattern::OptionalField(name, _) => names.push(name.to_string()),
+        Pattern::SpaceBefore(nested, _) | Pattern::SpaceAfter(nested, _) => {
+            pattern_names(nested, names)
+        }
+        _ => {}
+    }
+}
+
+/// Renames a top-level value everywhere in a definition's source, including where the
+/// definition itself introduces it.
+fn rename(src: &str, name: &str, new_name: &str) -> String {
+    let mut renamed = src.to_string();
+
+    // Back to front, so earlier ranges are still accurate after each replacement.
+    for (range, is_shorthand) in name_edits(src, name).into_iter().rev() {
+        let replacement = if is_shorthand {
+            format!("{}: {}", name, new_name)
+        } else {
+            new_name.to_string()
+        };
+
+        renamed.replace_range(range, &replacement);
+    }
+
+    renamed
+}
+
+/// Where a definition's source mentions the given top-level value, in front to back order.
+/// The `bool` is whether it's record field shorthand like `{ x }`, where the field name
+/// needs to stay the same. Since Roc doesn't allow shadowing, every unqualified use of
+/// the name refers to the top-level value.
+fn name_edits(src: &str, name: &str) -> Vec<(Range<usize>, bool)> {
+    let arena = Bump::new();
+    let mut edits = Vec::new();
+
+    let defs = match roc_parse::module::module_defs().parse(&arena, State::new(src.as_bytes())) {
+        Ok((_, defs, _)) => defs,
+        Err(_) => return edits,
+    };
+
+    let mut finder = NameFinder {
+        src,
+        name,
+        edits: &mut edits,
+    };
+
+    for def in defs.defs() {
+        match def {
+            Ok(_) => {}
+            Err(ValueDef::Annotation(pattern, _)) => finder.pattern(pattern, false),
+            Err(ValueDef::Body(pattern, body)) => {
+                finder.pattern(pattern, false);
+                finder.expr(&body.value, body.region);
+            }
+            Err(ValueDef::AnnotatedBody {
+                ann_pattern,
+                body_pattern,
+                body_expr,
+                ..
+            }) => {
+                finder.pattern(ann_pattern, false);
+                finder.pattern(body_pattern, false);
+                finder.expr(&body_expr.value, body_expr.region);
+            }
+            Err(ValueDef::Expect { condition, .. }) | Err(ValueDef::ExpectFx { condition, .. }) => {
+                finder.expr(&condition.value, condition.region)
+            }
+        }
+    }
+
+    edits.sort_by_key(|(range, _)| range.start);
+    edits.dedup();
+
+    edits
+}
+
+struct NameFinder<'s, 'e> {
+    src: &'s str,
+    name: &'s str,
+    edits: &'e mut Vec<(Range<usize>, bool)>,
+}
+
+impl<'s, 'e> NameFinder<'s, 'e> {
+    /// Records the name within the region, which can also have spaces, comments or
+    /// parentheses around it.
+    fn push(&mut self, region: Region, is_shorthand: bool) {
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+
+        let found = self.src[start..end]
+            .match_indices(self.name)
+            .map(|(index, _)| start + index)
+            .find(|&index| {
+                let before = self.src[..index].chars().next_back();
+                let after = self.src[index + self.name.len()..].chars().next();
+
+                !before.map_or(false, char::is_alphanumeric)
+                    && !after.map_or(false, char::is_alphanumeric)
+            });
+
+        if let Some(index) = found {
+            self.edits
+                .push((index..index + self.name.len(), is_shorthand));
+        }
+    }
+
+    fn pattern(&mut self, loc_pattern: &Loc<Pattern>, is_field: bool) {
+        self.pattern_help(&loc_pattern.value, loc_pattern.region, is_field)
+    }
+
+    fn pattern_help(&mut self, pattern: &Pattern, region: Region, is_field: bool) {
+        match pattern {
+            Pattern::Identifier(ident) if *ident == self.name => self.push(region, is_field),
+            Pattern::Apply(_, args) => {
+                for arg in args.iter() {
+                    self.pattern(arg, false);
+                }
+            }
+            Pattern::RecordDestructure(fields) => {
+                for field in fields.iter() {
+                    self.pattern(field, true);
+                }
+            }
+            Pattern::RequiredField(_, nested) => self.pattern(nested, false),
+            Pattern::SpaceBefore(nested, _) | Pattern::SpaceAfter(nested, _) => {
+                self.pattern_help(nested, region, is_field)
+            }
+            _ => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, region: Region) {
+        match expr {
+            Expr::Var { module_name, ident } if module_name.is_empty() && *ident == self.name => {
+                self.push(region, false)
+            }
+            Expr::Access(record, field) => {
+                // The record is everything but the `.field` at the end.
+                let end = region.end().offset - field.len() as u32 - 1;
+                let record_region = Region::new(region.start(), Position::new(end));
+
+                self.expr(record, record_region)
+            }
+            Expr::Str(StrLiteral::Line(segments)) => self.segments(segments),
+            Expr::Str(StrLiteral::Block(lines)) => {
+                for segments in lines.iter() {
+                    self.segments(segments);
+                }
+            }
+            Expr::List(items) => {
+                for item in items.iter() {
+                    self.expr(&item.value, item.region);
+                }
+            }
+            Expr::RecordUpdate { update, fields } => {
+                self.expr(&update.value, update.region);
+
+                for field in fields.iter() {
+                    self.field(&field.value);
+                }
+            }
+            Expr::Record(fields) => {
+                for field in fields.iter() {
+                    self.field(&field.value);
+                }
+            }
+            Expr::Closure(_, body) => self.expr(&body.value, body.region),
+            Expr::Defs(defs, final_expr) => {
+                for def in defs.defs() {
+                    match def {
+                        Err(ValueDef::Body(_, body))
+                        | Err(ValueDef::AnnotatedBody {
+                            body_expr: body, ..
+                        })
+                        | Err(ValueDef::Expect {
+                            condition: body, ..
+                        })
+                        | Err(ValueDef::ExpectFx {
+                            condition: body, ..
+                        }) => self.expr(&body.value, body.region),
+                        Ok(_) | Err(ValueDef::Annotation(_, _)) => {}
+                    }
+                }
+
+                self.expr(&final_expr.value, final_expr.region);
+            }
+            Expr::Backpassing(_, first, second) | Expr::Expect(first, second) => {
+                self.expr(&first.value, first.region);
+                self.expr(&second.value, second.region);
+            }
+            Expr::Apply(function, args, _) => {
+                self.expr(&function.value, function.region);
+
+                for arg in args.iter() {
+                    self.expr(&arg.value, arg.region);
+                }
+            }
+            Expr::BinOps(lefts, right) => {
+                for (left, _) in lefts.iter() {
+                    self.expr(&left.value, left.region);
+                }
+
+                self.expr(&right.value, right.region);
+            }
+            Expr::UnaryOp(operand, _) => self.expr(&operand.value, operand.region),
+            Expr::If(branches, final_else) => {
+                for (condition, then) in branches.iter() {
+                    self.expr(&condition.value, condition.region);
+                    self.expr(&then.value, then.region);
+                }
+
+                self.expr(&final_else.value, final_else.region);
+            }
+            Expr::When(condition, branches) => {
+                self.expr(&condition.value, condition.region);
+
+                for branch in branches.iter() {
+                    if let Some(guard) = &branch.guard {
+                        self.expr(&guard.value, guard.region);
+                    }
+
+                    self.expr(&branch.value.value, branch.value.region);
+                }
+            }
+            Expr::SpaceBefore(nested, _)
+            | Expr::SpaceAfter(nested, _)
+            | Expr::ParensAround(nested) => self.expr(nested, region),
+            Expr::PrecedenceConflict(conflict) => {
+                self.expr(&conflict.expr.value, conflict.expr.region)
+            }
+            _ => {}
+        }
+    }
+
+    fn segments(&mut self, segments: &[StrSegment]) {
+        for segment in segments.iter() {
+            if let StrSegment::Interpolated(interpolated) = segment {
+                self.expr(interpolated.value, interpolated.region);
+            }
+        }
+    }
+
+    fn field(&mut self, field: &AssignedField<Expr>) {
+        match field {
+            AssignedField::RequiredValue(_, _, value)
+            | AssignedField::OptionalValue(_, _, value) => self.expr(&value.value, value.region),
+            AssignedField::LabelOnly(label) if label.value == self.name => {
+                self.push(label.region, true)
+            }
+            AssignedField::SpaceBefore(nested, _) | AssignedField::SpaceAfter(nested, _) => {
+                self.field(nested)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_interface_header(path: &Path) -> Result<(String, Vec<String>), String> {
+    let src = fs::read_to_string(path)
+        .map_err(|err| format!("I couldn't read {}: {}", path.display(), err))?;
+
+    header_exposes(&src).map_err(|problem| format!("{} {}", path.display(), problem))
+}
+
+/// The standard library's modules, which every REPL session can use. The other
+/// builtin modules, like `#Attr`, are only for the compiler's own use.
+fn builtin_modules() -> Vec<(String, ModuleId)> {
+    let module_ids = ModuleIds::default();
+
+    module_ids
+        .available_modules()
+        .filter(|name| !name.as_str().starts_with('#'))
+        .filter_map(|name| Some((name.as_str().to_string(), module_ids.get_id(name)?)))
+        .collect()
+}
+
+fn header_exposes(src: &str) -> Result<(String, Vec<String>), &'static str> {
+    let arena = Bump::new();
+
+    match roc_parse::module::parse_header(&arena, State::new(src.as_bytes())) {
+        Ok((Module::Interface { header }, _)) => {
+            let exposes = header
+                .exposes
+                .iter()
+                .map(|exposed| exposed_name(&exposed.value).to_string())
+                .collect();
+
+            Ok((header.name.value.as_str().to_string(), exposes))
+        }
+        Ok(_) => Err("is not an interface module, so there's nothing to load."),
+        Err(_) => Err("has a syntax error in its header."),
+    }
+}
+
+fn exposed_name<'a>(spaced: &Spaced<'a, ExposedName<'a>>) -> &'a str {
+    match spaced {
+        Spaced::Item(name) => (*name).into(),
+        Spaced::SpaceBefore(inner, _) | Spaced::SpaceAfter(inner, _) => exposed_name(inner),
+    }
+}
+
+/// A module named `Foo.Bar` lives at `Foo/Bar.roc`, relative to the directory imports
+/// get loaded from.
+fn module_src_dir(path: &Path, module_name: &str) -> PathBuf {
+    let mut src_dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+
+    for _ in module_name.matches('.') {
+        src_dir.pop();
+    }
+
+    src_dir
+}
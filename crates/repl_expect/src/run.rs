@@ -182,9 +182,8 @@ fn run_expect_pure<W: std::io::Write>(
         let module_id = expect.symbol.module_id();
         let data = expectations.get_mut(&module_id).unwrap();
 
-        let path = &data.path;
         let filename = data.path.to_owned();
-        let source = std::fs::read_to_string(path).unwrap();
+        let source = data.source.clone();
 
         let renderer = Renderer::new(arena, interns, render_target, module_id, filename, &source);
 
@@ -281,7 +280,7 @@ fn run_expect_fx<W: std::io::Write>(
 
                         let data = expectations.get_mut(&module_id).unwrap();
                         let filename = data.path.to_owned();
-                        let source = std::fs::read_to_string(&data.path).unwrap();
+                        let source = data.source.clone();
 
                         let renderer = Renderer::new(
                             arena,
@@ -325,7 +324,7 @@ pub fn roc_dev_expect(
 
     let data = expectations.get_mut(&module_id).unwrap();
     let filename = data.path.to_owned();
-    let source = std::fs::read_to_string(&data.path).unwrap();
+    let source = data.source.clone();
 
     let renderer = Renderer::new(
         arena,
@@ -509,6 +508,8 @@ pub fn expect_mono_module_to_dylib<'a>(
         mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        source_info: None,
+        shadow_stack: false,
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
@@ -0,0 +1,8 @@
+interface Greeting
+    exposes [greet, greeting]
+    imports []
+
+greeting = "Hi"
+
+greet : Str -> Str
+greet = \name -> Str.concat "Hi, " name
@@ -592,8 +592,8 @@ fn too_few_args() {
 
                 The add function expects 2 arguments, but it got only 1:
 
-                4│      Num.add 2
-                        ^^^^^^^
+                1│  Num.add 2
+                    ^^^^^^^
 
                 Roc does not allow functions to be partially applied. Use a closure to
                 make partial application explicit.
@@ -613,8 +613,8 @@ fn type_problem() {
 
                 This 2nd argument to add has an unexpected type:
 
-                4│      1 + ""
-                            ^^
+                1│  1 + ""
+                        ^^
 
                 The argument is a string of type:
 
@@ -888,6 +888,37 @@ fn print_u8s() {
     )
 }
 
+#[cfg(not(feature = "wasm"))] // TODO: mismatch is due to terminal control codes!
+#[test]
+fn multiline_problem_line_numbers() {
+    expect_failure(
+        indoc!(
+            r#"
+            x = 1
+            y = 1 + "a"
+            "#
+        ),
+        indoc!(
+            r#"
+                ── TYPE MISMATCH ───────────────────────────────────────────────────────────────
+
+                This 2nd argument to add has an unexpected type:
+
+                2│  y = 1 + "a"
+                            ^^^
+
+                The argument is a string of type:
+
+                    Str
+
+                But add needs its 2nd argument to be:
+
+                    Num a
+                "#
+        ),
+    );
+}
+
 #[cfg(not(feature = "wasm"))] // TODO: mismatch is due to terminal control codes!
 #[test]
 fn parse_problem() {
@@ -899,11 +930,8 @@ fn parse_problem() {
 
                 I am partway through parsing a definition, but I got stuck here:
 
-                1│  app "app" provides [replOutput] to "./platform"
-                2│
-                3│  replOutput =
-                4│      add m n = m + n
-                            ^^^
+                1│  add m n = m + n
+                        ^^^
 
                 Looks like you are trying to define a function. In roc, functions are
                 always written as a lambda, like increment = \n -> n + 1.
@@ -931,8 +959,8 @@ fn exhaustiveness_problem() {
 
             This when does not cover all the possibilities:
 
-            7│>      when t is
-            8│>          A -> "a"
+            4│>  when t is
+            5│>      A -> "a"
 
             Other possibilities include:
 
@@ -965,13 +993,13 @@ fn issue_2343_complete_mono_with_shadowed_vars() {
 
                 The b name is first defined here:
 
-                4│      b = False
-                        ^
+                1│  b = False
+                    ^
 
                 But then it's defined a second time here:
 
-                5│      f = \b ->
-                             ^
+                2│  f = \b ->
+                         ^
 
                 Since these variables have the same name, it's easy to use the wrong
                 one on accident. Give one of them a new name.
@@ -1239,3 +1267,214 @@ fn record_of_poly_function_and_string() {
         r#"{ a: <function>, b: "b" } : { a : * -> Str, b : Str }"#,
     );
 }
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn defs_persist_across_inputs() {
+    expect_success(
+        indoc!(
+            r#"
+            x : I64
+            x = 5
+
+            x + 1
+            "#
+        ),
+        "5 : I64\n\n6 : I64",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn redefinition_replaces_earlier_def() {
+    expect_success(
+        indoc!(
+            r#"
+            greeting = "Hi"
+
+            greeting = "Hello"
+
+            Str.concat greeting "!"
+            "#
+        ),
+        "\"Hi\" : Str\n\n\"Hello\" : Str\n\n\"Hello!\" : Str",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn redefinition_keeps_defs_using_earlier_def() {
+    expect_success(
+        indoc!(
+            r#"
+            x = 1
+
+            y = x + 1
+
+            x = "hi"
+
+            y
+            "#
+        ),
+        "1 : Num *\n\n2 : Num *\n\n\"hi\" : Str\n\n2 : Num *",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn repeated_redefinition_keeps_defs_using_each_earlier_def() {
+    expect_success(
+        indoc!(
+            r#"
+            x = 1
+
+            y = x + 1
+
+            x = 10
+
+            z = x + y
+
+            x = "hi"
+
+            z
+            "#
+        ),
+        "1 : Num *\n\n2 : Num *\n\n10 : Num *\n\n12 : Num *\n\n\"hi\" : Str\n\n12 : Num *",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn redefinition_keeps_record_field_shorthand_using_earlier_def() {
+    expect_success(
+        indoc!(
+            r#"
+            x = 1
+
+            r = { x }
+
+            x = 2
+
+            r.x + x
+            "#
+        ),
+        "1 : Num *\n\n{ x: 1 } : { x : Num * }\n\n2 : Num *\n\n3 : Num *",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn annotation_applies_to_def_in_later_input() {
+    expect_success(
+        indoc!(
+            r#"
+            x : U8
+
+            x = 5
+
+            x
+            "#
+        ),
+        "\n5 : U8\n\n5 : U8",
+    );
+}
+
+// The CLI REPL evaluates inputs like these with the dev backend...
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn dev_backend_functions_across_inputs() {
+    expect_success(
+        indoc!(
+            r#"
+            double = \n -> n * 2
+
+            if double 3 < 10 then Str.concat "small" "!" else "big"
+            "#
+        ),
+        "<function> : Num a -> Num a\n\n\"small!\" : Str",
+    );
+}
+
+// ...and falls back to LLVM for inputs like these, which it can go back and forth between.
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn dev_backend_fallback_between_inputs() {
+    expect_success(
+        indoc!(
+            r#"
+            x = 5
+
+            [x, x + 1]
+
+            x * 2
+
+            Num.toStr x
+            "#
+        ),
+        "5 : Num *\n\n[5, 6] : List (Num *)\n\n10 : Num *\n\n\"5\" : Str",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn type_command() {
+    expect_success(":type Str.concat", "Str.concat : Str, Str -> Str");
+}
+
+#[cfg(not(feature = "wasm"))]
+fn greeting_module_path() -> String {
+    format!("{}/fixtures/Greeting.roc", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn load_command() {
+    expect_success(
+        format!(
+            ":load {}\ngreet \"Roc\"\nGreeting.greeting",
+            greeting_module_path()
+        )
+        .as_str(),
+        "Loaded Greeting.\n\n\"Hi, Roc\" : Str\n\n\"Hi\" : Str",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn load_command_with_session_def_of_the_same_name() {
+    expect_success(
+        format!(
+            ":load {}\ngreeting = \"Hello\"\ngreeting\nGreeting.greeting",
+            greeting_module_path()
+        )
+        .as_str(),
+        "Loaded Greeting.\n\n\"Hello\" : Str\n\n\"Hello\" : Str\n\n\"Hi\" : Str",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn reload_command() {
+    expect_success(
+        format!(":load {}\n:reload\ngreeting", greeting_module_path()).as_str(),
+        "Loaded Greeting.\n\nReloaded Greeting.\n\n\"Hi\" : Str",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn browse_command() {
+    expect_success(
+        format!(":load {}\n:browse Greeting", greeting_module_path()).as_str(),
+        "Loaded Greeting.\n\ngreet : Str -> Str\ngreeting : Str",
+    );
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn run_task() {
+    expect_success(
+        r#"Env.var "ROC_REPL_TEST_UNSET_VAR""#,
+        r#"Err (VarNotFound "ROC_REPL_TEST_UNSET_VAR") : Result Str [VarNotFound Str, VarNotUtf8 Str]*"#,
+    );
+}
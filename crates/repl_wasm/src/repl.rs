@@ -1,4 +1,5 @@
 use bumpalo::{collections::vec::Vec, Bump};
+use std::cell::RefCell;
 use std::mem::size_of;
 
 use roc_collections::all::MutSet;
@@ -7,7 +8,8 @@ use roc_load::MonomorphizedModule;
 use roc_parse::ast::Expr;
 use roc_repl_eval::{
     eval::jit_to_ast,
-    gen::{compile_to_mono, format_answer, ReplOutput},
+    gen::{check_syntax, compile_to_mono, format_answer, ReplOutput, SyntaxProblem},
+    state::ReplState,
     ReplApp, ReplAppMemory,
 };
 use roc_reporting::report::DEFAULT_PALETTE_HTML;
@@ -18,6 +20,11 @@ use crate::{js_create_app, js_get_result_and_memory, js_run_app};
 
 const WRAPPER_NAME: &str = "wrapper";
 
+thread_local! {
+    /// Each input comes in through a separate call from JS, so the session lives here.
+    static REPL_STATE: RefCell<ReplState> = RefCell::new(ReplState::new());
+}
+
 pub struct WasmReplApp<'a> {
     arena: &'a Bump,
 }
@@ -173,13 +180,38 @@ pub async fn entrypoint_from_js(src: String) -> Result<String, String> {
 
     let arena = &Bump::new();
 
+    if let Err(SyntaxProblem::Incomplete(report) | SyntaxProblem::Invalid(report)) =
+        check_syntax(&src, DEFAULT_PALETTE_HTML)
+    {
+        return Err(report);
+    }
+
     // Compile the app
     let target_info = TargetInfo::default_wasm32();
-    let mono = match compile_to_mono(arena, &src, target_info, DEFAULT_PALETTE_HTML) {
+    let input = REPL_STATE.with(|state| state.borrow().parse_input(&src));
+    let compiled = REPL_STATE.with(|state| {
+        compile_to_mono(
+            arena,
+            &state.borrow(),
+            &input,
+            target_info,
+            DEFAULT_PALETTE_HTML,
+        )
+    });
+    let mono = match compiled {
         Ok(m) => m,
         Err(messages) => return Err(messages.join("\n\n")),
     };
 
+    // Only definitions which compiled make it into the session.
+    let has_output = input.has_output();
+
+    REPL_STATE.with(|state| state.borrow_mut().add_defs(input));
+
+    if !has_output {
+        return Ok(String::new());
+    }
+
     let MonomorphizedModule {
         module_id,
         procedures,
@@ -272,5 +304,6 @@ pub async fn entrypoint_from_js(src: String) -> Result<String, String> {
     match format_answer(arena, res_answer, expr_type_str) {
         ReplOutput::NoProblems { expr, expr_type } => Ok(format!("{} : {}", expr, expr_type)),
         ReplOutput::Problems(lines) => Err(format!("\n{}\n", lines.join("\n\n"))),
+        ReplOutput::Empty => Ok(String::new()),
     }
 }
@@ -291,12 +291,25 @@ pub const HTML_STYLE_CODES: StyleCodes = StyleCodes {
     color_reset: "</span>",
 };
 
+/// Where a line of a generated source came from, e.g. a line someone typed into the
+/// REPL, which compiles it as part of a module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineOrigin {
+    /// The line number to show for it
+    pub line_number: u32,
+    /// How much indentation the generated source added in front of it
+    pub added_indent: u32,
+}
+
 // define custom allocator struct so we can `impl RocDocAllocator` custom helpers
 pub struct RocDocAllocator<'a> {
     upstream: BoxAllocator,
     pub src_lines: &'a [&'a str],
     pub home: ModuleId,
     pub interns: &'a Interns,
+    /// For generated sources, where each of `src_lines` came from. Lines without an
+    /// origin get shown without a line number.
+    line_origins: Option<&'a [Option<LineOrigin>]>,
 }
 
 pub type RocDocBuilder<'b> = DocBuilder<'b, RocDocAllocator<'b>, Annotation>;
@@ -333,9 +346,38 @@ impl<'a> RocDocAllocator<'a> {
             home,
             src_lines,
             interns,
+            line_origins: None,
         }
     }
 
+    /// Show the lines of a generated source as they were originally written, with their
+    /// original line numbers.
+    pub fn with_line_origins(mut self, line_origins: &'a [Option<LineOrigin>]) -> Self {
+        self.line_origins = Some(line_origins);
+
+        self
+    }
+
+    /// The line number to show for the line at this index, if any, and how much of
+    /// its indentation to leave out.
+    fn line_display(&self, line: u32) -> (Option<u32>, u32) {
+        match self.line_origins {
+            None => (Some(line + 1), 0),
+            Some(origins) => match origins.get(line as usize) {
+                Some(Some(origin)) => (Some(origin.line_number), origin.added_indent),
+                _ => (None, 0),
+            },
+        }
+    }
+
+    fn max_line_number_length(&self, region: LineColumnRegion) -> usize {
+        (region.start().line..=region.end().line)
+            .filter_map(|line| self.line_display(line).0)
+            .map(|line_number| line_number.to_string().len())
+            .max()
+            .unwrap_or(0)
+    }
+
     /// vertical concatenation. Adds a newline between elements
     pub fn vcat<A, I>(&'a self, docs: I) -> DocBuilder<'a, Self, A>
     where
@@ -528,16 +570,17 @@ impl<'a> RocDocAllocator<'a> {
         // where the problem is.
         let error_highlight_line = region.start().line == region.end().line;
 
-        let max_line_number_length = (region.end().line + 1).to_string().len();
+        let max_line_number_length = self.max_line_number_length(region);
         let indent = 2;
 
         let mut result = self.nil();
         for i in region.start().line..=region.end().line {
-            let line_number_string = (i + 1).to_string();
-            let line_number = line_number_string;
+            let (line_number, added_indent) = self.line_display(i);
+            let line_number = line_number.map(|n| n.to_string()).unwrap_or_default();
             let this_line_number_length = line_number.len();
 
             let line = self.src_lines[i as usize];
+            let line = line.get(added_indent as usize..).unwrap_or(line);
 
             let rest_of_line = if !line.trim().is_empty() {
                 self.text(line).indent(indent)
@@ -576,6 +619,7 @@ impl<'a> RocDocAllocator<'a> {
         }
 
         if error_highlight_line {
+            let (_, added_indent) = self.line_display(region.end().line);
             let overlapping = sub_region2.start().column < sub_region1.end().column;
 
             let highlight = if overlapping {
@@ -604,19 +648,21 @@ impl<'a> RocDocAllocator<'a> {
                     .append(self.text(highlight2))
             };
 
-            let highlight_line = self
-                .line()
-                // Omit the gutter bar when we know there are no further
-                // line numbers to be printed after this!
-                .append(self.text(" ".repeat(max_line_number_length + GUTTER_BAR_WIDTH)))
-                .append(if sub_region1.is_empty() && sub_region2.is_empty() {
-                    self.nil()
-                } else {
-                    self.text(" ".repeat(sub_region1.start().column as usize))
+            let highlight_line =
+                self.line()
+                    // Omit the gutter bar when we know there are no further
+                    // line numbers to be printed after this!
+                    .append(self.text(" ".repeat(max_line_number_length + GUTTER_BAR_WIDTH)))
+                    .append(if sub_region1.is_empty() && sub_region2.is_empty() {
+                        self.nil()
+                    } else {
+                        self.text(" ".repeat(
+                            sub_region1.start().column.saturating_sub(added_indent) as usize,
+                        ))
                         .indent(indent)
                         .append(highlight)
                         .annotate(error_annotation)
-                });
+                    });
 
             result = result.append(highlight_line);
         }
@@ -646,16 +692,17 @@ impl<'a> RocDocAllocator<'a> {
         // where the problem is.
         let error_highlight_line = sub_region.start().line == region.end().line;
 
-        let max_line_number_length = (region.end().line + 1).to_string().len();
+        let max_line_number_length = self.max_line_number_length(region);
         let indent = 2;
 
         let mut result = self.nil();
         for i in region.start().line..=region.end().line {
-            let line_number_string = (i + 1).to_string();
-            let line_number = line_number_string;
+            let (line_number, added_indent) = self.line_display(i);
+            let line_number = line_number.map(|n| n.to_string()).unwrap_or_default();
             let this_line_number_length = line_number.len();
 
             let line: &str = self.src_lines.get(i as usize).unwrap_or(&"");
+            let line = line.get(added_indent as usize..).unwrap_or(line);
 
             let rest_of_line = if !line.trim().is_empty() {
                 self.text(line)
@@ -695,6 +742,7 @@ impl<'a> RocDocAllocator<'a> {
         }
 
         if error_highlight_line {
+            let (_, added_indent) = self.line_display(region.end().line);
             let highlight_text = ERROR_UNDERLINE
                 .repeat((sub_region.end().column - sub_region.start().column) as usize);
 
@@ -706,9 +754,11 @@ impl<'a> RocDocAllocator<'a> {
                 .append(if highlight_text.is_empty() {
                     self.nil()
                 } else {
-                    self.text(" ".repeat(sub_region.start().column as usize))
-                        .indent(indent)
-                        .append(self.text(highlight_text).annotate(Annotation::Error))
+                    self.text(
+                        " ".repeat(sub_region.start().column.saturating_sub(added_indent) as usize),
+                    )
+                    .indent(indent)
+                    .append(self.text(highlight_text).annotate(Annotation::Error))
                 });
 
             result = result.append(highlight_line);
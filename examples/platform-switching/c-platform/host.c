@@ -14,10 +14,24 @@ void* roc_realloc(void* ptr, size_t new_size, size_t old_size,
 
 void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
 
+// Only defined when the app was built with `--backtrace`
+extern const char* roc__backtrace_frame(size_t index) __attribute__((weak));
+
 void roc_panic(void* ptr, unsigned int alignment) {
   char* msg = (char*)ptr;
-  fprintf(stderr,
-          "Application crashed with message\n\n    %s\n\nShutting down\n", msg);
+  fprintf(stderr, "Application crashed with message\n\n    %s\n\n", msg);
+
+  if (roc__backtrace_frame) {
+    fprintf(stderr, "Roc backtrace:\n\n");
+
+    for (size_t i = 0; roc__backtrace_frame(i) != NULL; i++) {
+      fprintf(stderr, "    %s\n", roc__backtrace_frame(i));
+    }
+
+    fprintf(stderr, "\n");
+  }
+
+  fprintf(stderr, "Shutting down\n");
   exit(0);
 }
 